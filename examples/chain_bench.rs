@@ -0,0 +1,118 @@
+//! Compares the chains the servants can use for the birthday presents.
+//!
+//! cargo run --release --example chain_bench --features lockfree
+//!
+//! Each chain is first timed on the servants' own run, where every servant alternates between
+//! adding a present and taking the lowest one off. It's then timed with servants adding and
+//! removing presents all along a chain of 1,000 while the same number of threads check whether
+//! presents are on it, as if answering the Minotaur, to show how much checking holds up the
+//! servants changing the chain.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use assignment3::presents::{self, ChainKind, PresentsConfig};
+use rand::Rng;
+
+const SERVANTS: usize = 4;
+const PRESENTS: usize = 100_000;
+
+const CHAIN_LENGTH: usize = 1_000;
+const MIXED_DURATION: Duration = Duration::from_secs(1);
+
+/// Runs `SERVANTS` threads adding and removing random presents and as many checking for them for
+/// `MIXED_DURATION`, returning how many changes and checks were made.
+fn mixed(kind: ChainKind) -> (u64, u64) {
+    let chain = kind.build();
+
+    // Start with every other present on the chain, so about half of each kind of request succeeds
+    for present in (0..CHAIN_LENGTH * 2).step_by(2) {
+        chain.add(present);
+    }
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let changes = Arc::new(AtomicU64::new(0));
+    let checks = Arc::new(AtomicU64::new(0));
+
+    let mut handles = Vec::new();
+    for thread_id in 0..SERVANTS * 2 {
+        let chain = chain.clone();
+        let stop = stop.clone();
+        let counter = if thread_id < SERVANTS {
+            changes.clone()
+        } else {
+            checks.clone()
+        };
+
+        handles.push(thread::spawn(move || {
+            let mut rng = rand::thread_rng();
+            let mut count = 0;
+
+            while !stop.load(Ordering::Relaxed) {
+                let present = rng.gen_range(0..CHAIN_LENGTH * 2);
+
+                if thread_id >= SERVANTS {
+                    chain.contains(present);
+                } else if rng.gen_bool(0.5) {
+                    chain.add(present);
+                } else {
+                    chain.remove_by_id(present);
+                }
+
+                count += 1;
+            }
+
+            counter.fetch_add(count, Ordering::Relaxed);
+        }));
+    }
+
+    thread::sleep(MIXED_DURATION);
+    stop.store(true, Ordering::Relaxed);
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    (
+        changes.load(Ordering::Relaxed),
+        checks.load(Ordering::Relaxed),
+    )
+}
+
+fn main() {
+    println!(
+        "{} servants, {} cores, {} presents\n",
+        SERVANTS,
+        thread::available_parallelism().map_or(1, |x| x.get()),
+        PRESENTS
+    );
+    println!(
+        "{:<16} {:>10} {:>20} {:>20}",
+        "chain", "run", "changes", "checks"
+    );
+
+    for kind in ChainKind::available() {
+        let start = Instant::now();
+        presents::run(PresentsConfig {
+            presents: PRESENTS,
+            servants: SERVANTS,
+            chain: kind,
+            seed: Some(1),
+        })
+        .unwrap();
+        let elapsed = start.elapsed();
+
+        let (changes, checks) = mixed(kind);
+        let seconds = MIXED_DURATION.as_secs_f64();
+
+        println!(
+            "{:<16} {:>9.3}s {:>16.2}M/s {:>18.2}M/s",
+            format!("{:?}", kind),
+            elapsed.as_secs_f64(),
+            changes as f64 / seconds / 1_000_000.0,
+            checks as f64 / seconds / 1_000_000.0
+        );
+    }
+}
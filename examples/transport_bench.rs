@@ -0,0 +1,118 @@
+//! Compares the transports that carry readings from the sensor threads to the report thread. The
+//! slots only hold one reading per sensor, so their senders wait for every reading to be sampled.
+//!
+//! cargo run --release --example transport_bench --features crossbeam
+//!
+//! Throughput is measured with every sensor sending as fast as it can. Latency is measured with
+//! the sensors pausing between readings, so it's the time for one reading to get through an
+//! almost empty queue rather than time spent waiting behind other readings.
+
+use std::sync::mpsc::{TryRecvError, TrySendError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use assignment3::temperature::{sensor_channels, Recording, SystemClock, Temperature, Transport};
+
+const SENSORS: usize = 8;
+const CAPACITY: usize = 1024;
+
+const THROUGHPUT_READINGS: usize = 250_000;
+const LATENCY_READINGS: usize = 2_000;
+const LATENCY_PAUSE: Duration = Duration::from_micros(100);
+
+/// Sends `readings` recordings from each sensor and returns how long after its recording was
+/// taken each one was received.
+fn run(transport: Transport, readings: usize, pause: Option<Duration>) -> Vec<Duration> {
+    let (senders, mut receiver) =
+        sensor_channels(transport, SENSORS, CAPACITY, Arc::new(SystemClock));
+
+    let handles: Vec<_> = senders
+        .into_iter()
+        .enumerate()
+        .map(|(sensor_id, mut sender)| {
+            thread::spawn(move || {
+                for _ in 0..readings {
+                    let mut recording =
+                        Recording::new(sensor_id, Temperature::from_fahrenheit(0.0));
+
+                    loop {
+                        match sender.try_send(recording) {
+                            Ok(()) => break,
+                            Err(TrySendError::Full(x)) => {
+                                recording = x;
+                                thread::yield_now();
+                            }
+                            Err(TrySendError::Disconnected(_)) => return,
+                        }
+                    }
+
+                    if let Some(pause) = pause {
+                        thread::sleep(pause);
+                    }
+                }
+            })
+        })
+        .collect();
+
+    let mut latencies = Vec::with_capacity(SENSORS * readings);
+    loop {
+        match receiver.try_recv() {
+            Ok(recording) => latencies.push(recording.timestamp.elapsed()),
+            Err(TryRecvError::Empty) => thread::yield_now(),
+            Err(TryRecvError::Disconnected) => break,
+        }
+    }
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    latencies
+}
+
+fn percentile(sorted: &[Duration], percentile: f64) -> Duration {
+    let index = ((sorted.len() - 1) as f64 * percentile / 100.0).round() as usize;
+    sorted[index]
+}
+
+fn main() {
+    println!(
+        "{} sensors, {} cores, queue capacity {}\n",
+        SENSORS,
+        thread::available_parallelism().map_or(1, |x| x.get()),
+        CAPACITY
+    );
+    println!(
+        "{:<10} {:>20} {:>16} {:>16}",
+        "transport", "throughput", "median latency", "p99 latency"
+    );
+
+    for transport in Transport::available() {
+        let start = Instant::now();
+        let received = run(transport, THROUGHPUT_READINGS, None).len();
+        let throughput = received as f64 / start.elapsed().as_secs_f64();
+
+        // Readings from the slots are timestamped when they're sampled, so there's no latency to
+        // measure
+        let (median, p99) = if transport == Transport::Slots {
+            ("n/a".to_string(), "n/a".to_string())
+        } else {
+            let mut latencies = run(transport, LATENCY_READINGS, Some(LATENCY_PAUSE));
+            latencies.sort();
+
+            (
+                format!("{:?}", percentile(&latencies, 50.0)),
+                format!("{:?}", percentile(&latencies, 99.0)),
+            )
+        };
+
+        println!(
+            "{:<10} {:>11.2}M reads/s {:>16} {:>16}",
+            format!("{:?}", transport).to_lowercase(),
+            throughput / 1_000_000.0,
+            median,
+            p99
+        );
+    }
+}
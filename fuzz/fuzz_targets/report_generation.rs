@@ -0,0 +1,119 @@
+//! Feeds arbitrary readings into `Report::generate` and checks the report still makes sense:
+//! nothing panics, the largest difference never spans more than the window, and no readings give
+//! no report rather than a broken one.
+//!
+//! ```bash
+//! cargo +nightly fuzz run report_generation
+//! ```
+
+#![no_main]
+
+use std::time::{Duration, Instant};
+
+use arbitrary::Arbitrary;
+use assignment3::temperature::{Recording, Report, SimulationConfig, Temperature};
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    sensor_count: u8,
+    difference_window_minutes: u8,
+    top_n: u8,
+    distinct_extremes: bool,
+
+    // Simulated milliseconds into the period and how far between the lowest and highest
+    // temperature the config allows
+    readings: Vec<(u32, u16)>,
+}
+
+fuzz_target!(|input: Input| {
+    let config = SimulationConfig {
+        sensor_count: input.sensor_count as usize,
+        difference_window_minutes: input.difference_window_minutes as u64,
+        top_n: input.top_n as usize,
+        distinct_extremes: input.distinct_extremes,
+        ..SimulationConfig::default()
+    };
+    if config.validate().is_err() {
+        return;
+    }
+
+    // The report thread only ever hands a report readings from its own period, and each of them
+    // passed validation, so they're in range
+    let start = Instant::now();
+    let period_ms = config.report_interval_minutes * 60 * 1000;
+    let range = config.max_temperature - config.min_temperature;
+    let recordings: Vec<Recording> = input
+        .readings
+        .iter()
+        .enumerate()
+        .map(|(index, &(offset, temperature))| {
+            let offset = Duration::from_millis(offset as u64 % period_ms);
+            let fahrenheit = config.min_temperature + range * temperature as f64 / u16::MAX as f64;
+
+            let mut recording = Recording::new(
+                index % config.sensor_count,
+                Temperature::from_fahrenheit(fahrenheit),
+            );
+            recording.sequence = (offset.as_secs_f64() / config.sampling_period_seconds) as u64;
+            recording.timestamp = start + config.scaled(offset);
+            recording
+        })
+        .collect();
+
+    let count = recordings.len();
+    let Some(report) = Report::generate(0, recordings, &config) else {
+        return;
+    };
+    assert!(count > 0, "a report with no readings");
+
+    // A single reading has nothing to compare with, but still has a summary
+    let summary = report
+        .summary
+        .as_ref()
+        .expect("a report with readings but no summary");
+    assert_eq!(
+        report.largest_temp_difference.is_some(),
+        report.largest_difference_minutes.is_some()
+    );
+
+    // The largest difference is between two readings within the window of each other
+    if let (Some(largest), Some([from, to])) = (
+        &report.largest_temp_difference,
+        report.largest_difference_minutes,
+    ) {
+        let (earlier, later) = if largest.start.timestamp <= largest.end.timestamp {
+            (largest.start.timestamp, largest.end.timestamp)
+        } else {
+            (largest.end.timestamp, largest.start.timestamp)
+        };
+        assert!(later - earlier <= config.scaled_difference_window());
+        assert!((to - from).abs() <= config.difference_window_minutes as f64 + 1e-6);
+
+        let difference =
+            largest.end.temperature.fahrenheit() - largest.start.temperature.fahrenheit();
+        assert!(largest.difference >= 0.0);
+        assert!((largest.difference - difference.abs()).abs() < 1e-9);
+    }
+
+    // Everything reported stays within the readings that went in
+    let fahrenheit = |x: &Recording| x.temperature.fahrenheit();
+    let lowest = config.min_temperature - 1e-9;
+    let highest = config.max_temperature + 1e-9;
+    let mean = summary.mean.fahrenheit();
+    assert!(
+        (lowest..=highest).contains(&mean),
+        "mean {} out of range",
+        mean
+    );
+    assert!(report.lowest_temps.len() <= config.top_n);
+    assert!(report.highest_temps.len() <= config.top_n);
+    assert!(report
+        .lowest_temps
+        .iter()
+        .chain(report.highest_temps.iter())
+        .all(|x| (lowest..=highest).contains(&fahrenheit(x))));
+
+    let readings: usize = report.sensor_stats.iter().map(|x| x.readings).sum();
+    assert!(readings <= count);
+});
@@ -0,0 +1,56 @@
+use std::process::exit;
+
+use assignment3::presents::{self, ChainKind, PresentsConfig};
+use clap::Parser;
+
+#[derive(Parser, Debug)]
+struct Args {
+    /// Number of presents in the bag [default: 500000]
+    #[arg(long)]
+    presents: Option<usize>,
+
+    /// Number of servant threads [default: 4]
+    #[arg(long)]
+    servants: Option<usize>,
+
+    /// What the chain is made of: coarse, hand_over_hand, lock_free, optimistic or lazy [default: coarse]
+    #[arg(long)]
+    chain: Option<ChainKind>,
+
+    /// Seed for shuffling the bag, so runs can be repeated. Random by default
+    #[arg(long)]
+    seed: Option<u64>,
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let mut config = PresentsConfig::default();
+
+    if let Some(presents) = args.presents {
+        config.presents = presents;
+    }
+
+    if let Some(servants) = args.servants {
+        config.servants = servants;
+    }
+
+    if let Some(chain) = args.chain {
+        if !chain.is_available() {
+            eprintln!("the lock_free chain isn't available in this build, it needs the `lockfree` feature");
+            exit(1);
+        }
+
+        config.chain = chain;
+    }
+
+    config.seed = args.seed;
+
+    if let Err(err) = config.validate() {
+        eprintln!("{}", err);
+        exit(1);
+    }
+
+    let summary = presents::run(config).expect("the config has already been validated");
+    print!("{}", summary);
+}
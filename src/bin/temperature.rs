@@ -0,0 +1,1557 @@
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::process::exit;
+
+use assignment3::temperature::timestamp;
+use assignment3::temperature::{
+    rover_name, AnomalyConfig, ConsoleSink, CsvRecordingSink, DecimationConfig, DocumentSink,
+    FileSource, Fleet, HotSwapHandle, InfluxSink, JsonSink, LineFormat, LiveState, OutDirSink,
+    OverflowPolicy, PauseHandle, PriorityConfig, RecordSink, RecordedRun, Recording, ReportBuilder,
+    ReportSection, ReportSink, ReportTrigger, SensorSource, SimulationConfig, SimulationError,
+    SpeedHandle, StdinSource, StopHandle, TemperatureUnit, Transport, WALL_TIME_FORMAT,
+};
+use assignment3::TemperatureSimulation;
+use clap::{Parser, ValueEnum};
+
+/// Simulates the Mars Rover temperature sensors and prints a report every simulated hour
+#[derive(Parser, Clone, Debug)]
+struct Args {
+    /// TOML file to load the simulation settings from. Flags override values from the file
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// Number of sensor threads to spawn [default: 8]
+    #[arg(long)]
+    sensors: Option<usize>,
+
+    /// Simulated minutes covered by each report [default: 60]
+    #[arg(long)]
+    report_interval: Option<u64>,
+
+    /// Number of rovers to run, each with its own sensors, queue and reports [default: 1]
+    #[arg(long)]
+    rovers: Option<usize>,
+
+    /// With several rovers, also prints a summary combining every rover's report for a period
+    #[arg(long)]
+    fleet_summary: bool,
+
+    /// Exit after this many simulated hours instead of running until Ctrl-C
+    #[arg(long)]
+    hours: Option<u64>,
+
+    /// How many times faster than real time the simulation runs [default: 250]
+    #[arg(long)]
+    speedup: Option<u64>,
+
+    /// Seeds the sensors' RNGs so the same readings are produced every run
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Unit to display temperatures in: fahrenheit, celsius or kelvin [default: fahrenheit]
+    #[arg(long)]
+    unit: Option<TemperatureUnit>,
+
+    /// Simulated minutes apart two readings can be and still count towards the largest
+    /// temperature difference [default: 10]
+    #[arg(long)]
+    difference_window: Option<u64>,
+
+    /// Simulated seconds each report waits after its period ends for readings taken during the
+    /// period that haven't arrived yet [default: 30]
+    #[arg(long)]
+    late_grace: Option<f64>,
+
+    /// Simulated minutes from the start whose readings are left out of the first report while
+    /// the sensors settle [default: 0]
+    #[arg(long)]
+    warm_up: Option<f64>,
+
+    /// Which readings go into the reports when the sensors sample faster than the reports need:
+    /// off, every:<n> (every nth reading from each sensor) or min_max (each sensor's lowest &
+    /// highest reading of every simulated minute) [default: off]
+    #[arg(long)]
+    decimate: Option<DecimationConfig>,
+
+    /// Simulated seconds each reading is held so any taken before it can catch up, putting
+    /// readings back in the order they were taken. Can't be longer than the late grace [default:
+    /// 0]
+    #[arg(long)]
+    reorder_window: Option<f64>,
+
+    /// How many of the lowest & highest temps each report lists [default: 5]
+    #[arg(long)]
+    top_n: Option<usize>,
+
+    /// Lists the lowest & highest distinct temps, each with how many readings hit it, instead of
+    /// one entry per reading
+    #[arg(long)]
+    distinct_extremes: bool,
+
+    /// Comma separated percentiles to include in each report, e.g. `5,50,95` [default: 5,95]
+    #[arg(long, value_delimiter = ',')]
+    percentiles: Option<Vec<f64>>,
+
+    /// Comma separated report sections to leave out, so they aren't worked out at all: top,
+    /// percentiles, histogram and suspects (spotting faulty sensors)
+    #[arg(long, value_delimiter = ',')]
+    skip_sections: Vec<ReportSection>,
+
+    /// Prints an alert whenever a reading is more than this many degrees Fahrenheit from its
+    /// sensor's recent readings
+    #[arg(long)]
+    anomaly_threshold: Option<f64>,
+
+    /// Alerts straight away, ahead of the queued readings, when a reading is colder than this
+    /// many degrees Fahrenheit [default: -90]
+    #[arg(long, allow_hyphen_values = true)]
+    priority_below: Option<f64>,
+
+    /// Alerts straight away, ahead of the queued readings, when a reading is hotter than this
+    /// many degrees Fahrenheit [default: 60]
+    #[arg(long, allow_hyphen_values = true)]
+    priority_above: Option<f64>,
+
+    /// What carries readings to the report thread: ring, mpsc or crossbeam (needs the
+    /// `crossbeam` feature) [default: ring]
+    #[arg(long)]
+    transport: Option<Transport>,
+
+    /// How many readings each sensor's queue to the report thread holds [default: 1024]
+    #[arg(long)]
+    queue_capacity: Option<usize>,
+
+    /// What a sensor does when its queue is full: block, drop_oldest or drop_newest
+    /// [default: block]
+    #[arg(long)]
+    queue_overflow: Option<OverflowPolicy>,
+
+    /// Lets sensors sample every few milliseconds: one that wakes up late takes every reading it
+    /// owes in a burst rather than skipping ahead
+    #[arg(long)]
+    high_frequency: bool,
+
+    /// Up to how many waiting readings the report thread takes off the queues at once
+    /// [default: 1024]
+    #[arg(long)]
+    ingest_batch: Option<usize>,
+
+    /// Makes every sensor wait for the others before each reading, so they all take it at the
+    /// same instant
+    #[arg(long)]
+    synchronized_sampling: bool,
+
+    /// Up to how many simulated seconds late each sensor wakes up for a reading, picked at
+    /// random every time [default: 0]
+    #[arg(long)]
+    sampling_jitter: Option<f64>,
+
+    /// Puts each sensor's clock off from the rover's by a random amount, up to this many
+    /// simulated seconds either way [default: 0]
+    #[arg(long)]
+    clock_skew: Option<f64>,
+
+    /// Works out how far each sensor's clock is off and takes it back out of its timestamps
+    #[arg(long)]
+    correct_skew: bool,
+
+    /// Pins each sensor thread and the report thread to a core, see `[affinity]` in
+    /// `sim.example.toml` to pick the cores. Needs the `affinity` feature
+    #[arg(long)]
+    pin_threads: bool,
+
+    /// Where readings come from: `random`, `stdin`, `serial:<device>` (needs the `serial`
+    /// feature), or the path of a CSV/JSON lines file. Anything other than `random` is read by a
+    /// single sensor thread
+    #[arg(long, default_value = "random")]
+    source: String,
+
+    /// Baud rate of a `serial:<device>` source [default: 9600]
+    #[cfg(all(feature = "serial", unix))]
+    #[arg(long)]
+    baud_rate: Option<u32>,
+
+    /// Runs each sensor as its own process, sending its readings to this one through a ring in
+    /// a memory-mapped file. Needs the `multiprocess` feature
+    #[cfg(all(feature = "multiprocess", unix))]
+    #[arg(long)]
+    processes: bool,
+
+    /// Runs as the sensor process for this sensor, started by `--processes`
+    #[cfg(all(feature = "multiprocess", unix))]
+    #[arg(long, hide = true, requires = "shared_rings")]
+    sensor_process: Option<usize>,
+
+    /// The memory-mapped file a sensor process sends its readings through
+    #[cfg(all(feature = "multiprocess", unix))]
+    #[arg(long, hide = true)]
+    shared_rings: Option<PathBuf>,
+
+    /// Directory to write each report to as a JSON file
+    #[arg(long)]
+    json_out: Option<PathBuf>,
+
+    /// Directory to write each report to as a Markdown document, with the top temps and
+    /// per-sensor statistics as tables
+    #[arg(long)]
+    markdown_out: Option<PathBuf>,
+
+    /// Writes each report to `--markdown-out` as an HTML page too
+    #[arg(long, requires = "markdown_out")]
+    html: bool,
+
+    /// Directory to write each report to as text and JSON, along with the raw readings it was
+    /// worked out from, instead of only printing it
+    #[arg(long)]
+    out_dir: Option<PathBuf>,
+
+    /// How many hours of raw readings to keep in `--out-dir`, deleting older ones as new ones are
+    /// written. Keeps them all by default
+    #[arg(long, requires = "out_dir")]
+    retain_hours: Option<NonZeroUsize>,
+
+    /// CSV file to append every raw recording to
+    #[arg(long)]
+    csv_out: Option<PathBuf>,
+
+    /// Unix domain socket to stream every reading to as newline-delimited JSON
+    #[cfg(unix)]
+    #[arg(long)]
+    socket: Option<PathBuf>,
+
+    /// File to record every reading and report boundary to, so the run's reports can be
+    /// reproduced later with `--replay`
+    #[arg(long)]
+    record: Option<PathBuf>,
+
+    /// Builds the reports from a run recorded with `--record` instead of running the sensors.
+    /// The recorded run's speedup, sensors, report interval and sampling periods are used,
+    /// everything else about the reports comes from the flags & config file as usual
+    #[arg(long, conflicts_with_all = ["record", "source", "rovers"])]
+    replay: Option<PathBuf>,
+
+    /// SQLite database to store every recording and report in
+    #[cfg(feature = "sqlite")]
+    #[arg(long)]
+    sqlite: Option<PathBuf>,
+
+    /// Directory to draw a PNG chart of each report's readings in, next to the report as text
+    #[cfg(feature = "charts")]
+    #[arg(long)]
+    charts: Option<PathBuf>,
+
+    /// Directory to write each report's raw readings to as a gzip-compressed CSV file
+    #[cfg(feature = "archive")]
+    #[arg(long)]
+    archive: Option<PathBuf>,
+
+    /// Directory to write the raw readings to as Parquet files, partitioned by simulated hour
+    #[cfg(feature = "parquet")]
+    #[arg(long)]
+    parquet: Option<PathBuf>,
+
+    /// Address to serve the reports on as JSON, e.g. `127.0.0.1:8080`
+    #[cfg(feature = "http")]
+    #[arg(long)]
+    http: Option<String>,
+
+    /// MQTT broker to publish readings and reports to, as `host` or `host:port` (port 1883 by
+    /// default)
+    #[cfg(feature = "mqtt")]
+    #[arg(long)]
+    mqtt: Option<String>,
+
+    /// Kafka brokers to produce readings and reports to, as comma separated `host:port`s
+    #[cfg(feature = "kafka")]
+    #[arg(long)]
+    kafka: Option<String>,
+
+    /// URL to POST every report to, e.g. a Slack or Discord webhook
+    #[cfg(feature = "webhook")]
+    #[arg(long)]
+    webhook: Option<String>,
+
+    /// What's posted to `--webhook`: json (the whole report), slack or discord (a one-line
+    /// summary) [default: json]
+    #[cfg(feature = "webhook")]
+    #[arg(long)]
+    webhook_format: Option<assignment3::temperature::WebhookFormat>,
+
+    /// File to append readings and reports to as InfluxDB line protocol, or the URL of an
+    /// InfluxDB write endpoint to POST them to (needs the `influx` feature)
+    #[arg(long)]
+    influx: Option<String>,
+
+    /// API token sent with every write to an `--influx` URL
+    #[cfg(feature = "influx")]
+    #[arg(long)]
+    influx_token: Option<String>,
+
+    /// Format of the readings from `--source`. Files default to their extension, stdin to CSV
+    #[arg(long, value_enum)]
+    source_format: Option<SourceFormat>,
+
+    /// Runs the sensors and the report loop as tokio tasks instead of threads. `--transport` is
+    /// ignored and `--queue-overflow drop_oldest` isn't supported
+    #[cfg(feature = "async")]
+    #[arg(long = "async")]
+    use_async: bool,
+
+    /// Prints tracing spans & events from the sensor and report threads to stderr. `RUST_LOG`
+    /// picks what's printed [default: assignment3=trace]
+    #[cfg(feature = "tracing")]
+    #[arg(long)]
+    trace: bool,
+
+    /// Reads `pause`, `resume`, `speed <speedup>`, `add` and `retire <sensor>` commands from
+    /// stdin while the simulation runs. Paused time doesn't count towards any report
+    #[arg(long)]
+    commands: bool,
+
+    /// Runs in the background, detached from the terminal, with everything it prints written to
+    /// `--log-file`. SIGTERM stops it cleanly, with a partial report for the unfinished hour
+    #[cfg(unix)]
+    #[arg(long)]
+    daemon: bool,
+
+    /// File to append the output of `--daemon` to [default: temperature.log]
+    #[cfg(unix)]
+    #[arg(long, requires = "daemon")]
+    log_file: Option<PathBuf>,
+
+    // Set on the background process `--daemon` starts
+    #[cfg(unix)]
+    #[arg(long, hide = true)]
+    detached: bool,
+
+    /// Shows a live dashboard in the terminal instead of printing each report
+    #[cfg(feature = "tui")]
+    #[arg(long)]
+    tui: bool,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum SourceFormat {
+    Csv,
+    Jsonl,
+}
+
+impl From<SourceFormat> for LineFormat {
+    fn from(format: SourceFormat) -> LineFormat {
+        match format {
+            SourceFormat::Csv => LineFormat::Csv,
+            SourceFormat::Jsonl => LineFormat::JsonLines,
+        }
+    }
+}
+
+fn open_serial(device: &str, format: LineFormat, args: &Args) -> Box<dyn SensorSource> {
+    #[cfg(all(feature = "serial", unix))]
+    {
+        let baud_rate = args.baud_rate.unwrap_or(9600);
+        let source = assignment3::temperature::SerialSource::serial(device, baud_rate, format)
+            .unwrap_or_else(|err| {
+                eprintln!("couldn't open {}: {}", device, err);
+                exit(1);
+            });
+
+        Box::new(source)
+    }
+
+    #[cfg(not(all(feature = "serial", unix)))]
+    {
+        let _ = (device, format, args);
+        eprintln!("reading from a serial port needs the `serial` feature (on Unix)");
+        exit(1);
+    }
+}
+
+/// Builds the single source used in place of the random sensors, or `None` for random readings.
+fn build_source(args: &Args) -> Option<Box<dyn SensorSource>> {
+    let format = args.source_format.map(LineFormat::from);
+
+    match args.source.as_str() {
+        "random" => None,
+        "stdin" => Some(Box::new(StdinSource::stdin(
+            format.unwrap_or(LineFormat::Csv),
+        ))),
+        source if source.starts_with("serial:") => Some(open_serial(
+            &source["serial:".len()..],
+            format.unwrap_or(LineFormat::Csv),
+            args,
+        )),
+        path => {
+            let mut source = FileSource::open(path).unwrap_or_else(|err| {
+                eprintln!("couldn't open {}: {}", path, err);
+                exit(1);
+            });
+
+            if let Some(format) = format {
+                source = FileSource::new(source.into_reader(), format);
+            }
+
+            Some(Box::new(source))
+        }
+    }
+}
+
+/// Builds every sink asked for on the command line, exiting if one can't be created.
+fn build_sinks(args: &Args, config: &SimulationConfig) -> Vec<Box<dyn ReportSink>> {
+    let mut sinks: Vec<Box<dyn ReportSink>> = Vec::new();
+
+    // The dashboard shows the reports itself
+    if !use_tui(args) {
+        sinks.push(Box::new(ConsoleSink::new(config.unit)));
+    }
+
+    if let Some(dir) = &args.json_out {
+        let sink = JsonSink::new(dir).unwrap_or_else(|err| {
+            eprintln!("couldn't create {}: {}", dir.display(), err);
+            exit(1);
+        });
+
+        sinks.push(Box::new(sink));
+    }
+
+    if let Some(dir) = &args.out_dir {
+        let sink = OutDirSink::new(dir, config, args.retain_hours).unwrap_or_else(|err| {
+            eprintln!("couldn't create {}: {}", dir.display(), err);
+            exit(1);
+        });
+
+        sinks.push(Box::new(sink));
+    }
+
+    if let Some(dir) = &args.markdown_out {
+        let sink = DocumentSink::new(dir, config.unit, args.html).unwrap_or_else(|err| {
+            eprintln!("couldn't create {}: {}", dir.display(), err);
+            exit(1);
+        });
+
+        sinks.push(Box::new(sink));
+    }
+
+    #[cfg(feature = "sqlite")]
+    if let Some(path) = &args.sqlite {
+        let sink = assignment3::temperature::SqliteSink::open(path).unwrap_or_else(|err| {
+            eprintln!("couldn't open {}: {}", path.display(), err);
+            exit(1);
+        });
+
+        sinks.push(Box::new(sink));
+    }
+
+    if let Some(path) = &args.record {
+        let sink = RecordSink::create(path, config).unwrap_or_else(|err| {
+            eprintln!("couldn't create {}: {}", path.display(), err);
+            exit(1);
+        });
+
+        sinks.push(Box::new(sink));
+    }
+
+    if let Some(path) = &args.csv_out {
+        let sink = CsvRecordingSink::new(path).unwrap_or_else(|err| {
+            eprintln!("couldn't open {}: {}", path.display(), err);
+            exit(1);
+        });
+
+        sinks.push(Box::new(sink));
+    }
+
+    #[cfg(unix)]
+    if let Some(path) = &args.socket {
+        let sink = assignment3::temperature::SocketSink::bind(path).unwrap_or_else(|err| {
+            eprintln!("couldn't listen on {}: {}", path.display(), err);
+            exit(1);
+        });
+
+        sinks.push(Box::new(sink));
+    }
+
+    #[cfg(feature = "charts")]
+    if let Some(dir) = &args.charts {
+        let sink = assignment3::temperature::ChartSink::new(dir, config).unwrap_or_else(|err| {
+            eprintln!("couldn't create {}: {}", dir.display(), err);
+            exit(1);
+        });
+
+        sinks.push(Box::new(sink));
+    }
+
+    #[cfg(feature = "archive")]
+    if let Some(dir) = &args.archive {
+        let sink = assignment3::temperature::ArchiveSink::new(dir, config).unwrap_or_else(|err| {
+            eprintln!("couldn't create {}: {}", dir.display(), err);
+            exit(1);
+        });
+
+        sinks.push(Box::new(sink));
+    }
+
+    #[cfg(feature = "parquet")]
+    if let Some(dir) = &args.parquet {
+        let sink = assignment3::temperature::ParquetSink::new(dir, config).unwrap_or_else(|err| {
+            eprintln!("couldn't create {}: {}", dir.display(), err);
+            exit(1);
+        });
+
+        sinks.push(Box::new(sink));
+    }
+
+    #[cfg(feature = "http")]
+    if let Some(address) = &args.http {
+        let sink = assignment3::temperature::HttpSink::bind(address).unwrap_or_else(|err| {
+            eprintln!("couldn't listen on {}: {}", address, err);
+            exit(1);
+        });
+
+        if let Some(address) = sink.local_addr() {
+            println!("Serving reports on http://{}/report/latest", address);
+        }
+
+        sinks.push(Box::new(sink));
+    }
+
+    #[cfg(feature = "mqtt")]
+    if let Some(broker) = &args.mqtt {
+        let (host, port) = match broker.rsplit_once(':') {
+            Some((host, port)) => (
+                host,
+                port.parse().unwrap_or_else(|_| {
+                    eprintln!("`{}` isn't a valid port", port);
+                    exit(1);
+                }),
+            ),
+            None => (broker.as_str(), 1883),
+        };
+
+        sinks.push(Box::new(assignment3::temperature::MqttSink::connect(
+            host, port,
+        )));
+    }
+
+    #[cfg(feature = "kafka")]
+    if let Some(brokers) = &args.kafka {
+        let sink = assignment3::temperature::KafkaSink::connect(brokers).unwrap_or_else(|err| {
+            eprintln!("Couldn't create a Kafka producer for {}: {}", brokers, err);
+            exit(1);
+        });
+
+        sinks.push(Box::new(sink));
+    }
+
+    #[cfg(feature = "webhook")]
+    if let Some(url) = &args.webhook {
+        let format = args.webhook_format.unwrap_or_default();
+        let sink = assignment3::temperature::WebhookSink::new(url, format, config.unit)
+            .unwrap_or_else(|err| {
+                eprintln!("`{}` isn't a valid webhook URL: {}", url, err);
+                exit(1);
+            });
+
+        sinks.push(Box::new(sink));
+    }
+
+    if let Some(target) = &args.influx {
+        sinks.push(Box::new(influx_sink(target, args)));
+    }
+
+    sinks
+}
+
+/// Whether `--influx` is a URL rather than a file.
+fn is_url(target: &str) -> bool {
+    target.starts_with("http://") || target.starts_with("https://")
+}
+
+fn influx_sink(target: &str, args: &Args) -> InfluxSink {
+    if !is_url(target) {
+        return InfluxSink::file(target).unwrap_or_else(|err| {
+            eprintln!("couldn't open {}: {}", target, err);
+            exit(1);
+        });
+    }
+
+    #[cfg(feature = "influx")]
+    {
+        InfluxSink::http(target, args.influx_token.clone()).unwrap_or_else(|err| {
+            eprintln!("`{}` isn't a valid InfluxDB URL: {}", target, err);
+            exit(1);
+        })
+    }
+
+    #[cfg(not(feature = "influx"))]
+    {
+        let _ = args;
+        eprintln!("writing to an InfluxDB server needs the `influx` feature");
+        exit(1);
+    }
+}
+
+/// Whether `--tui` was passed.
+fn use_tui(args: &Args) -> bool {
+    #[cfg(feature = "tui")]
+    {
+        args.tui
+    }
+
+    #[cfg(not(feature = "tui"))]
+    {
+        let _ = args;
+        false
+    }
+}
+
+/// Prints every span & event the filter lets through to stderr, along with how long each span
+/// was busy for when it closes.
+#[cfg(feature = "tracing")]
+fn init_tracing() {
+    use tracing_subscriber::fmt::format::FmtSpan;
+    use tracing_subscriber::EnvFilter;
+
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("assignment3=trace"));
+
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_writer(std::io::stderr)
+        .init();
+}
+
+const COMMANDS_HELP: &str = "Commands: pause, resume, speed [<speedup>], add [<sensor>], \
+    retire <sensor>, min, max, last <sensor>, report, r (report on the period so far), help";
+
+/// What `--commands` can pause and ask about for one rover. The live state is fed by a sink of
+/// its own, so asking never holds up the report thread.
+struct Controls {
+    name: Option<String>,
+    pause: PauseHandle,
+    speed: SpeedHandle,
+    swap: HotSwapHandle,
+    trigger: ReportTrigger,
+    live: LiveState,
+}
+
+impl Controls {
+    /// Adds the sink the answers come from, so it has to be called before the simulation starts.
+    fn new(simulation: &mut TemperatureSimulation, name: Option<String>) -> Controls {
+        let live = LiveState::new(simulation.config());
+        simulation.add_sink(live.sink());
+
+        Controls {
+            name,
+            pause: simulation.pause_handle(),
+            speed: simulation.speed_handle(),
+            swap: simulation.hot_swap_handle(),
+            trigger: simulation.report_trigger(),
+            live,
+        }
+    }
+
+    // Answers from different rovers are told apart by the rover's name
+    fn prefix(&self) -> String {
+        self.name
+            .as_ref()
+            .map(|x| format!("{}: ", x))
+            .unwrap_or_default()
+    }
+}
+
+/// Reads commands from stdin, one per line, on a thread of its own for as long as the program
+/// runs. With several rovers every one of them is paused and resumed together, and answers
+/// come from each of them.
+fn read_commands(controls: Vec<Controls>, unit: TemperatureUnit) {
+    println!("{}", COMMANDS_HELP);
+
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lines() {
+            let Ok(line) = line else {
+                return;
+            };
+
+            run_command(line.trim(), &controls, unit);
+        }
+    });
+}
+
+fn run_command(command: &str, controls: &[Controls], unit: TemperatureUnit) {
+    let show = |recording: &Recording| {
+        format!(
+            "{} from sensor {} at {}",
+            recording.temperature.display(unit),
+            recording.sensor_id,
+            timestamp::wall_time(recording.timestamp).format(WALL_TIME_FORMAT)
+        )
+    };
+
+    match command.split_whitespace().collect::<Vec<_>>()[..] {
+        [] => {}
+        ["pause"] => {
+            if controls.iter().filter(|x| x.pause.pause()).count() > 0 {
+                println!("Paused, type `resume` to carry on");
+            }
+        }
+        ["resume"] => {
+            if controls.iter().filter(|x| x.pause.resume()).count() > 0 {
+                println!("Resumed");
+            }
+        }
+        ["speed"] => {
+            for control in controls {
+                println!(
+                    "{}Running at {}x",
+                    control.prefix(),
+                    control.speed.speedup()
+                );
+            }
+        }
+        ["speed", speedup] => {
+            let Some(speedup) = speedup.parse::<f64>().ok().filter(|x| *x > 0.0) else {
+                eprintln!("`{}` isn't a speedup, expected a positive number", speedup);
+                return;
+            };
+
+            if controls.iter().all(|x| x.speed.set_speedup(speedup)) {
+                println!("Running at {}x", speedup);
+            }
+        }
+        ["add"] => {
+            for control in controls {
+                match control.swap.add() {
+                    Some(sensor_id) => println!("{}Sensor {} added", control.prefix(), sensor_id),
+                    None => println!("{}No empty slot, retire a sensor first", control.prefix()),
+                }
+            }
+        }
+        [action @ ("add" | "retire"), sensor_id] => {
+            let Ok(sensor_id) = sensor_id.parse::<usize>() else {
+                eprintln!("`{}` isn't a sensor ID", sensor_id);
+                return;
+            };
+
+            for control in controls {
+                match action {
+                    "add" if control.swap.add_at(sensor_id) => {
+                        println!("{}Sensor {} added", control.prefix(), sensor_id)
+                    }
+                    "add" => println!("{}Slot {} isn't empty", control.prefix(), sensor_id),
+                    _ if control.swap.retire(sensor_id) => {
+                        println!("{}Sensor {} retired", control.prefix(), sensor_id)
+                    }
+                    _ => println!(
+                        "{}There's no sensor {} to retire",
+                        control.prefix(),
+                        sensor_id
+                    ),
+                }
+            }
+        }
+        [extreme @ ("min" | "max")] => {
+            for control in controls {
+                let recording = match extreme {
+                    "min" => control.live.lowest(),
+                    _ => control.live.highest(),
+                };
+                let which = if extreme == "min" {
+                    "Lowest"
+                } else {
+                    "Highest"
+                };
+
+                match recording {
+                    Some(x) => println!("{}{} this period: {}", control.prefix(), which, show(&x)),
+                    None => println!("{}No readings yet this period", control.prefix()),
+                }
+            }
+        }
+        ["last", sensor_id] => {
+            let Ok(sensor_id) = sensor_id.parse::<usize>() else {
+                eprintln!("`{}` isn't a sensor ID", sensor_id);
+                return;
+            };
+
+            for control in controls {
+                match control.live.latest(sensor_id) {
+                    Some(x) => println!("{}Last reading: {}", control.prefix(), show(&x)),
+                    None => println!(
+                        "{}No readings from sensor {} yet",
+                        control.prefix(),
+                        sensor_id
+                    ),
+                }
+            }
+        }
+        ["report"] => {
+            for control in controls {
+                match control.live.last_report() {
+                    Some(report) => print!("{}", report.display(unit)),
+                    None => println!("{}No report has been generated yet", control.prefix()),
+                }
+            }
+        }
+        ["r"] => {
+            for control in controls {
+                control.trigger.trigger();
+            }
+        }
+        ["help"] => println!("{}", COMMANDS_HELP),
+        _ => eprintln!("unknown command `{}`. {}", command, COMMANDS_HELP),
+    }
+}
+
+/// The first Ctrl-C stops the sensors and lets the report thread publish a partial report for
+/// the unfinished hour, a second one exits straight away. With several rovers every one of them
+/// is stopped.
+fn handle_ctrl_c(stop_handles: Vec<StopHandle>) {
+    let handler = ctrlc::set_handler(move || {
+        if stop_handles.iter().all(|x| x.is_stopped()) {
+            exit(130);
+        }
+
+        println!("\nStopping, press Ctrl-C again to exit immediately");
+        for stop_handle in stop_handles.iter() {
+            stop_handle.stop();
+        }
+    });
+
+    if let Err(err) = handler {
+        eprintln!("couldn't set the Ctrl-C handler: {}", err);
+    }
+}
+
+/// SIGHUP asks every rover for a report on its period so far, without stopping anything.
+#[cfg(unix)]
+fn handle_sighup(triggers: Vec<ReportTrigger>) {
+    use signal_hook::consts::SIGHUP;
+    use signal_hook::iterator::Signals;
+
+    let mut signals = match Signals::new([SIGHUP]) {
+        Ok(signals) => signals,
+        Err(err) => {
+            eprintln!("couldn't set the SIGHUP handler: {}", err);
+            return;
+        }
+    };
+
+    std::thread::spawn(move || {
+        for _ in signals.forever() {
+            for trigger in triggers.iter() {
+                trigger.trigger();
+            }
+        }
+    });
+}
+
+#[cfg(not(unix))]
+fn handle_sighup(_triggers: Vec<ReportTrigger>) {}
+
+/// SIGTERM stops every rover the same as Ctrl-C, so a service manager stopping the program still
+/// gets the partial report and the run's summary. A second one exits straight away.
+#[cfg(unix)]
+fn handle_sigterm(stop_handles: Vec<StopHandle>) {
+    use signal_hook::consts::SIGTERM;
+    use signal_hook::iterator::Signals;
+
+    let mut signals = match Signals::new([SIGTERM]) {
+        Ok(signals) => signals,
+        Err(err) => {
+            eprintln!("couldn't set the SIGTERM handler: {}", err);
+            return;
+        }
+    };
+
+    std::thread::spawn(move || {
+        for _ in signals.forever() {
+            if stop_handles.iter().all(|x| x.is_stopped()) {
+                exit(143);
+            }
+
+            println!("Received SIGTERM, stopping");
+            for stop_handle in stop_handles.iter() {
+                stop_handle.stop();
+            }
+        }
+    });
+}
+
+#[cfg(not(unix))]
+fn handle_sigterm(_stop_handles: Vec<StopHandle>) {}
+
+/// Starts this program again with the same flags as a background process in a session of its own,
+/// without a controlling terminal, so Ctrl-C and closing the terminal don't reach it, with its
+/// output appended to `log`. Exits once it's running.
+#[cfg(unix)]
+fn daemonize(log: &Path) -> ! {
+    use std::fs::OpenOptions;
+    use std::os::unix::process::CommandExt;
+    use std::process::{Command, Stdio};
+
+    let open_log = || {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(log)
+            .unwrap_or_else(|err| {
+                eprintln!("couldn't open {}: {}", log.display(), err);
+                exit(1);
+            })
+    };
+
+    let exe = std::env::current_exe().unwrap_or_else(|err| {
+        eprintln!(
+            "couldn't find this program to start it in the background: {}",
+            err
+        );
+        exit(1);
+    });
+
+    let mut command = Command::new(exe);
+    command
+        .args(std::env::args_os().skip(1))
+        .arg("--detached")
+        .stdin(Stdio::null())
+        .stdout(open_log())
+        .stderr(open_log());
+
+    // A new session also gets a process group of its own, and leaves the terminal behind so a
+    // SIGHUP when it closes doesn't reach the child
+    // SAFETY: setsid is async-signal-safe, and nothing else runs between fork and exec
+    unsafe {
+        command.pre_exec(|| {
+            if libc::setsid() == -1 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+
+    let child = command.spawn().unwrap_or_else(|err| {
+        eprintln!("couldn't start the background process: {}", err);
+        exit(1);
+    });
+
+    println!(
+        "Running in the background as process {}, logging to {}. Stop it with `kill {}`",
+        child.id(),
+        log.display(),
+        child.id()
+    );
+    exit(0);
+}
+
+#[cfg(feature = "async")]
+fn run_async(config: SimulationConfig, source: Option<Box<dyn SensorSource>>, args: &Args) {
+    use assignment3::temperature::AsyncTemperatureSimulation;
+
+    if config.queue_overflow == OverflowPolicy::DropOldest {
+        eprintln!("queue_overflow = \"drop_oldest\" isn't supported with --async");
+        exit(1);
+    }
+
+    if config.affinity.is_some() {
+        eprintln!(
+            "tokio tasks can't be pinned to cores, --pin-threads isn't supported with --async"
+        );
+        exit(1);
+    }
+
+    if config.synchronized_sampling {
+        eprintln!("synchronized sampling needs sensor threads, it isn't supported with --async");
+        exit(1);
+    }
+
+    if args.commands {
+        eprintln!("tokio's timers can't be paused, --commands isn't supported with --async");
+        exit(1);
+    }
+
+    #[cfg(all(feature = "multiprocess", unix))]
+    if args.processes {
+        eprintln!("--processes can't be used with --async");
+        exit(1);
+    }
+
+    let sinks = build_sinks(args, &config);
+
+    let mut simulation = match source {
+        Some(source) => AsyncTemperatureSimulation::with_sources(config, vec![source]),
+        None => AsyncTemperatureSimulation::with_config(config),
+    };
+    for sink in sinks {
+        simulation.add_sink(sink);
+    }
+
+    handle_ctrl_c(vec![simulation.stop_handle()]);
+    handle_sigterm(vec![simulation.stop_handle()]);
+
+    let runtime = tokio::runtime::Runtime::new().unwrap_or_else(|err| {
+        eprintln!("couldn't start the tokio runtime: {}", err);
+        exit(1);
+    });
+
+    println!("The sensor tasks are being spawned and will send recordings over the channel");
+    println!("The report task is processing recordings from the channel");
+
+    exit_on_error(runtime.block_on(simulation.run()));
+}
+
+/// The flags that can't be split up between rovers, if any were passed.
+fn rover_conflicts(args: &Args) -> Vec<&'static str> {
+    let mut conflicts = Vec::new();
+
+    if args.source != "random" {
+        conflicts.push("--source");
+    }
+
+    #[cfg(all(feature = "multiprocess", unix))]
+    if args.processes {
+        conflicts.push("--processes");
+    }
+
+    #[cfg(feature = "async")]
+    if args.use_async {
+        conflicts.push("--async");
+    }
+
+    #[cfg(feature = "tui")]
+    if args.tui {
+        conflicts.push("--tui");
+    }
+
+    #[cfg(feature = "sqlite")]
+    if args.sqlite.is_some() {
+        conflicts.push("--sqlite");
+    }
+
+    #[cfg(feature = "charts")]
+    if args.charts.is_some() {
+        conflicts.push("--charts");
+    }
+
+    #[cfg(feature = "http")]
+    if args.http.is_some() {
+        conflicts.push("--http");
+    }
+
+    #[cfg(feature = "mqtt")]
+    if args.mqtt.is_some() {
+        conflicts.push("--mqtt");
+    }
+
+    #[cfg(feature = "kafka")]
+    if args.kafka.is_some() {
+        conflicts.push("--kafka");
+    }
+
+    // Readings aren't tagged with the rover they came from
+    if args.influx.as_deref().is_some_and(is_url) {
+        conflicts.push("--influx");
+    }
+
+    conflicts
+}
+
+/// `path` with the rover's name added to the end of the file name, e.g. `readings-rover-2.csv`.
+fn rover_path(path: &Path, name: &str) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let file_name = match path.extension() {
+        Some(extension) => format!("{}-{}.{}", stem, name, extension.to_string_lossy()),
+        None => format!("{}-{}", stem, name),
+    };
+
+    path.with_file_name(file_name)
+}
+
+/// Runs every rover with its own sinks. Each rover's JSON reports go in their own subdirectory of
+/// `--json-out`, and its recordings, recorded run and line protocol in their own `--csv-out`,
+/// `--record` and `--influx` files, e.g. `readings-rover-2.csv`.
+fn run_fleet(config: SimulationConfig, args: &Args) {
+    let conflicts = rover_conflicts(args);
+    if !conflicts.is_empty() {
+        eprintln!("{} can't be used with --rovers", conflicts.join(", "));
+        exit(1);
+    }
+
+    let unit = config.unit;
+    let fleet_summary = config.fleet_summary;
+    let mut fleet = Fleet::new(config);
+
+    for (index, rover) in fleet.rovers_mut().iter_mut().enumerate() {
+        let name = rover_name(index);
+
+        let mut rover_args = args.clone();
+        rover_args.json_out = args.json_out.as_ref().map(|dir| dir.join(&name));
+        rover_args.markdown_out = args.markdown_out.as_ref().map(|dir| dir.join(&name));
+        rover_args.out_dir = args.out_dir.as_ref().map(|dir| dir.join(&name));
+        #[cfg(feature = "archive")]
+        {
+            rover_args.archive = args.archive.as_ref().map(|dir| dir.join(&name));
+        }
+        #[cfg(feature = "parquet")]
+        {
+            rover_args.parquet = args.parquet.as_ref().map(|dir| dir.join(&name));
+        }
+        rover_args.csv_out = args.csv_out.as_ref().map(|path| rover_path(path, &name));
+        rover_args.record = args.record.as_ref().map(|path| rover_path(path, &name));
+        #[cfg(unix)]
+        {
+            rover_args.socket = args.socket.as_ref().map(|path| rover_path(path, &name));
+        }
+        rover_args.influx = args
+            .influx
+            .as_ref()
+            .map(|path| rover_path(Path::new(path), &name).display().to_string());
+
+        for sink in build_sinks(&rover_args, rover.config()) {
+            rover.add_sink(sink);
+        }
+    }
+
+    if fleet_summary {
+        fleet.on_summary(move |summary| {
+            println!("\nA new fleet summary has been generated\n");
+            print!("{}", summary.display(unit));
+        });
+    }
+
+    handle_ctrl_c(fleet.stop_handles());
+    handle_sigterm(fleet.stop_handles());
+    handle_sighup(
+        fleet
+            .rovers()
+            .iter()
+            .map(TemperatureSimulation::report_trigger)
+            .collect(),
+    );
+    if args.commands {
+        let controls = fleet
+            .rovers_mut()
+            .iter_mut()
+            .enumerate()
+            .map(|(index, rover)| Controls::new(rover, Some(rover_name(index))))
+            .collect();
+        read_commands(controls, unit);
+    }
+
+    fleet.start();
+
+    println!(
+        "{} rovers have been started, each with its own sensor threads, queue and report thread",
+        fleet.rovers().len()
+    );
+
+    exit_on_error(fleet.wait());
+}
+
+/// Builds the reports from a recorded run and hands them to the sinks, without starting any
+/// threads.
+fn run_replay(path: &Path, config: SimulationConfig, args: &Args) {
+    if use_tui(args) {
+        eprintln!("--tui can't be used with --replay");
+        exit(1);
+    }
+
+    let run = RecordedRun::open(path).unwrap_or_else(|err| {
+        eprintln!("couldn't read {}: {}", path.display(), err);
+        exit(1);
+    });
+
+    let config = run.config(config);
+    if let Err(err) = config.validate() {
+        eprintln!("{}", err);
+        exit(1);
+    }
+
+    println!("Replaying {} readings from {}", run.len(), path.display());
+
+    let mut sinks = build_sinks(args, &config);
+    run.replay(&config, &mut sinks);
+}
+
+/// The sensor processes started by `--processes` and the rings they send their readings
+/// through. Dropping it stops them and waits for them to exit.
+#[cfg(all(feature = "multiprocess", unix))]
+struct SensorProcesses {
+    rings: assignment3::temperature::SharedRings,
+    reapers: Vec<std::thread::JoinHandle<()>>,
+}
+
+#[cfg(all(feature = "multiprocess", unix))]
+impl SensorProcesses {
+    /// Starts this program again for each sensor, with the same flags, as that sensor's process.
+    fn spawn(config: &SimulationConfig) -> SensorProcesses {
+        use std::process::{Command, Stdio};
+
+        let path = std::env::temp_dir().join(format!("temperature-{}.rings", std::process::id()));
+        let rings = assignment3::temperature::SharedRings::create(
+            &path,
+            config.sensor_count,
+            config.queue_capacity,
+        )
+        .unwrap_or_else(|err| {
+            eprintln!("couldn't create {}: {}", path.display(), err);
+            exit(1);
+        });
+
+        let exe = std::env::current_exe().unwrap_or_else(|err| {
+            eprintln!(
+                "couldn't find this program to start the sensor processes: {}",
+                err
+            );
+            exit(1);
+        });
+
+        let reapers = (0..config.sensor_count)
+            .map(|sensor_id| {
+                let mut child = Command::new(&exe)
+                    .args(std::env::args_os().skip(1))
+                    .arg("--sensor-process")
+                    .arg(sensor_id.to_string())
+                    .arg("--shared-rings")
+                    .arg(&path)
+                    .stdin(Stdio::null())
+                    .spawn()
+                    .unwrap_or_else(|err| {
+                        eprintln!(
+                            "couldn't start the process for sensor {}: {}",
+                            sensor_id, err
+                        );
+                        rings.stop();
+                        exit(1);
+                    });
+
+                // A process that exits without closing its ring (e.g. it was killed) would
+                // leave its sensor thread waiting for readings forever
+                let rings = rings.clone();
+                std::thread::spawn(move || {
+                    let _ = child.wait();
+                    rings.close(sensor_id);
+                })
+            })
+            .collect();
+
+        SensorProcesses { rings, reapers }
+    }
+
+    /// A source for each sensor's thread that receives the readings its process sends.
+    fn sources(&self) -> Vec<Box<dyn SensorSource>> {
+        (0..self.rings.sensor_count())
+            .map(|sensor_id| Box::new(self.rings.source(sensor_id)) as Box<dyn SensorSource>)
+            .collect()
+    }
+}
+
+#[cfg(all(feature = "multiprocess", unix))]
+impl Drop for SensorProcesses {
+    fn drop(&mut self) {
+        self.rings.stop();
+        for reaper in self.reapers.drain(..) {
+            let _ = reaper.join();
+        }
+    }
+}
+
+/// Runs as one sensor's process until the report process stops it.
+#[cfg(all(feature = "multiprocess", unix))]
+fn run_sensor_process(sensor_id: usize, config: &SimulationConfig, args: &Args) {
+    let Some(path) = &args.shared_rings else {
+        return;
+    };
+
+    let rings = assignment3::temperature::SharedRings::open(path).unwrap_or_else(|err| {
+        eprintln!("couldn't open {}: {}", path.display(), err);
+        exit(1);
+    });
+
+    // Ctrl-C reaches every process in the terminal, but it's up to the report process to stop
+    // the sensors so the last readings still make it into the partial report
+    let _ = ctrlc::set_handler(|| {});
+
+    assignment3::temperature::run_sensor_process(&rings, sensor_id, config);
+}
+
+fn main() {
+    let args = Args::parse();
+
+    #[cfg(feature = "tracing")]
+    if args.trace {
+        init_tracing();
+    }
+
+    let mut config = match &args.config {
+        Some(path) => SimulationConfig::from_toml_file(path).unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            exit(1);
+        }),
+        None => SimulationConfig::default(),
+    };
+
+    if let Some(sensors) = args.sensors {
+        config.sensor_count = sensors;
+    }
+
+    if let Some(report_interval) = args.report_interval {
+        config.report_interval_minutes = report_interval;
+    }
+
+    if let Some(rovers) = args.rovers {
+        config.rovers = rovers;
+    }
+
+    if args.fleet_summary {
+        config.fleet_summary = true;
+    }
+
+    if let Some(hours) = args.hours {
+        config.hours = Some(hours);
+    }
+
+    if let Some(speedup) = args.speedup {
+        config.speedup = speedup;
+    }
+
+    if let Some(seed) = args.seed {
+        config.seed = Some(seed);
+    }
+
+    if let Some(unit) = args.unit {
+        config.unit = unit;
+    }
+
+    if let Some(difference_window) = args.difference_window {
+        config.difference_window_minutes = difference_window;
+    }
+
+    if let Some(late_grace) = args.late_grace {
+        config.late_grace_seconds = late_grace;
+    }
+
+    if let Some(warm_up) = args.warm_up {
+        config.warm_up_minutes = warm_up;
+    }
+
+    if let Some(decimation) = args.decimate {
+        config.decimation = decimation;
+    }
+
+    if let Some(reorder_window) = args.reorder_window {
+        config.reorder_window_seconds = reorder_window;
+    }
+
+    if let Some(top_n) = args.top_n {
+        config.top_n = top_n;
+    }
+
+    if args.distinct_extremes {
+        config.distinct_extremes = true;
+    }
+
+    if let Some(percentiles) = args.percentiles.clone() {
+        config.percentiles = percentiles;
+    }
+
+    if !args.skip_sections.is_empty() {
+        let builder = args
+            .skip_sections
+            .iter()
+            .fold(ReportBuilder::from_config(&config), |builder, section| {
+                builder.skip(*section)
+            });
+        builder.apply(&mut config);
+    }
+
+    if let Some(threshold) = args.anomaly_threshold {
+        config.anomaly = Some(AnomalyConfig {
+            threshold,
+            ..config.anomaly.unwrap_or_default()
+        });
+    }
+
+    if let Some(below) = args.priority_below {
+        config.priority = Some(PriorityConfig {
+            below,
+            ..config.priority.unwrap_or_default()
+        });
+    }
+
+    if let Some(above) = args.priority_above {
+        config.priority = Some(PriorityConfig {
+            above,
+            ..config.priority.unwrap_or_default()
+        });
+    }
+
+    if let Some(transport) = args.transport {
+        config.transport = transport;
+    }
+
+    if let Some(queue_capacity) = args.queue_capacity {
+        config.queue_capacity = queue_capacity;
+    }
+
+    if let Some(queue_overflow) = args.queue_overflow {
+        config.queue_overflow = queue_overflow;
+    }
+
+    if args.high_frequency {
+        config.high_frequency = true;
+    }
+
+    if let Some(ingest_batch) = args.ingest_batch {
+        config.ingest_batch = ingest_batch;
+    }
+
+    if args.synchronized_sampling {
+        config.synchronized_sampling = true;
+    }
+
+    if let Some(sampling_jitter) = args.sampling_jitter {
+        config.sampling_jitter_seconds = sampling_jitter;
+    }
+
+    if let Some(max_offset) = args.clock_skew {
+        config.clock_skew.max_offset = max_offset;
+    }
+
+    if args.correct_skew {
+        config.clock_skew.correct = true;
+    }
+
+    if args.pin_threads {
+        config.affinity = Some(config.affinity.unwrap_or_default());
+    }
+
+    if let Err(err) = config.validate() {
+        eprintln!("{}", err);
+        exit(1);
+    }
+
+    // A background process has no terminal to read commands or readings from, or draw on
+    #[cfg(unix)]
+    if args.daemon && (args.commands || args.source == "stdin" || use_tui(&args)) {
+        eprintln!("--daemon can't be used with --commands, --source stdin or --tui");
+        exit(1);
+    }
+
+    #[cfg(unix)]
+    if args.daemon && !args.detached {
+        let log = args
+            .log_file
+            .as_deref()
+            .unwrap_or(Path::new("temperature.log"));
+        daemonize(log);
+    }
+
+    #[cfg(all(feature = "multiprocess", unix))]
+    if let Some(sensor_id) = args.sensor_process {
+        run_sensor_process(sensor_id, &config, &args);
+        return;
+    }
+
+    if let Some(path) = &args.replay {
+        run_replay(path, config, &args);
+        return;
+    }
+
+    // Commands and the readings or the dashboard can't both have the terminal
+    if args.commands && (args.source == "stdin" || use_tui(&args)) {
+        eprintln!("--commands can't be used with --source stdin or --tui");
+        exit(1);
+    }
+
+    #[cfg(all(feature = "multiprocess", unix))]
+    if args.processes && args.source != "random" {
+        eprintln!("--processes can't be used with --source");
+        exit(1);
+    }
+
+    if config.rovers > 1 {
+        run_fleet(config, &args);
+        return;
+    }
+
+    let source = build_source(&args);
+
+    #[cfg(feature = "async")]
+    if args.use_async {
+        if use_tui(&args) {
+            eprintln!("--tui can't be used with --async");
+            exit(1);
+        }
+
+        run_async(config, source, &args);
+        return;
+    }
+
+    let sources = source.map(|x| vec![x]);
+
+    // Each sensor's thread only passes on the readings its process sends
+    #[cfg(all(feature = "multiprocess", unix))]
+    let processes = args.processes.then(|| SensorProcesses::spawn(&config));
+    #[cfg(all(feature = "multiprocess", unix))]
+    let sources = processes.as_ref().map(SensorProcesses::sources).or(sources);
+
+    let sinks = build_sinks(&args, &config);
+
+    let mut simulation = match sources {
+        Some(sources) => TemperatureSimulation::with_sources(config, sources),
+        None => TemperatureSimulation::with_config(config),
+    };
+    for sink in sinks {
+        simulation.add_sink(sink);
+    }
+
+    #[cfg(feature = "tui")]
+    let dashboard = args
+        .tui
+        .then(|| assignment3::temperature::Dashboard::new(simulation.config()));
+    #[cfg(feature = "tui")]
+    if let Some(dashboard) = &dashboard {
+        simulation.add_sink(dashboard.sink());
+    }
+
+    handle_ctrl_c(vec![simulation.stop_handle()]);
+    handle_sigterm(vec![simulation.stop_handle()]);
+    handle_sighup(vec![simulation.report_trigger()]);
+    if args.commands {
+        let unit = simulation.config().unit;
+        read_commands(vec![Controls::new(&mut simulation, None)], unit);
+    }
+
+    simulation.start();
+
+    // The last report is printed once the dashboard has given the terminal back. If the
+    // dashboard can't run, the simulation carries on without any output until it finishes
+    #[cfg(feature = "tui")]
+    if let Some(dashboard) = dashboard {
+        match dashboard.run(&simulation) {
+            Ok(Some(report)) => print!("{}", report.display(simulation.config().unit)),
+            Ok(None) => {}
+            Err(err) => eprintln!("couldn't run the dashboard: {}", err),
+        }
+
+        let result = simulation.wait();
+        #[cfg(all(feature = "multiprocess", unix))]
+        drop(processes);
+        exit_on_error(result);
+        return;
+    }
+
+    println!("The sensor threads have been created and are pushing recordings onto the queue");
+    println!("The report thread has been created and is processing recordings from the queue");
+
+    let result = simulation.wait();
+    #[cfg(all(feature = "multiprocess", unix))]
+    drop(processes);
+    exit_on_error(result);
+}
+
+/// Exits with an error if a thread or task failed, since reports may be missing.
+fn exit_on_error(result: Result<(), SimulationError>) {
+    if let Err(err) = result {
+        eprintln!("the simulation didn't finish cleanly: {}", err);
+        exit(1);
+    }
+}
@@ -0,0 +1,4 @@
+pub mod presents;
+pub mod temperature;
+
+pub use temperature::TemperatureSimulation;
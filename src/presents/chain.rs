@@ -0,0 +1,103 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+#[cfg(feature = "lockfree")]
+use super::LockFreeChain;
+use super::{CoarseChain, HandOverHandChain, LazyChain, OptimisticChain};
+
+/// The chain the servants hang the presents on, in order of their ID. Every method can be called
+/// from any number of servants at once.
+pub trait PresentChain: Send + Sync {
+    /// Adds a present in its place on the chain. Returns false if it was already on it.
+    fn add(&self, present: usize) -> bool;
+
+    /// Takes the present with the lowest ID off the chain, or `None` if it's empty.
+    fn remove_min(&self) -> Option<usize>;
+
+    /// Takes a present off the chain. Returns false if it wasn't on it.
+    fn remove_by_id(&self, present: usize) -> bool;
+
+    /// Whether a present is on the chain.
+    fn contains(&self, present: usize) -> bool;
+
+    /// How many presents are on the chain. With servants changing it at the same time this is
+    /// only a snapshot.
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Which `PresentChain` the servants use.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ChainKind {
+    /// A `LinkedList` behind one `RwLock`, so only one servant can change it at a time
+    #[default]
+    Coarse,
+
+    /// A list with a lock on every node, so servants can change different parts of it at once
+    HandOverHand,
+
+    /// Harris's lock-free list, where nobody waits for anyone. Needs the `lockfree` feature
+    LockFree,
+
+    /// A list that's walked without locks, locking and checking just the spot being changed
+    Optimistic,
+
+    /// Like `Optimistic`, but taken off nodes are marked first, so checking for a present never
+    /// locks anything
+    Lazy,
+}
+
+impl ChainKind {
+    /// Every chain that was compiled in.
+    pub fn available() -> Vec<ChainKind> {
+        [
+            ChainKind::Coarse,
+            ChainKind::HandOverHand,
+            ChainKind::LockFree,
+            ChainKind::Optimistic,
+            ChainKind::Lazy,
+        ]
+        .into_iter()
+        .filter(|x| x.is_available())
+        .collect()
+    }
+
+    pub fn is_available(self) -> bool {
+        self != ChainKind::LockFree || cfg!(feature = "lockfree")
+    }
+
+    /// Creates an empty chain of this kind.
+    pub fn build(self) -> Arc<dyn PresentChain> {
+        match self {
+            ChainKind::Coarse => Arc::new(CoarseChain::new()),
+            ChainKind::HandOverHand => Arc::new(HandOverHandChain::new()),
+            #[cfg(feature = "lockfree")]
+            ChainKind::LockFree => Arc::new(LockFreeChain::new()),
+            #[cfg(not(feature = "lockfree"))]
+            ChainKind::LockFree => panic!("the lock-free chain needs the `lockfree` feature"),
+            ChainKind::Optimistic => Arc::new(OptimisticChain::new()),
+            ChainKind::Lazy => Arc::new(LazyChain::new()),
+        }
+    }
+}
+
+impl FromStr for ChainKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "coarse" => Ok(ChainKind::Coarse),
+            "hand_over_hand" => Ok(ChainKind::HandOverHand),
+            "lock_free" => Ok(ChainKind::LockFree),
+            "optimistic" => Ok(ChainKind::Optimistic),
+            "lazy" => Ok(ChainKind::Lazy),
+            _ => Err(format!(
+                "unknown chain `{}`, expected coarse, hand_over_hand, lock_free, optimistic or lazy",
+                s
+            )),
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use std::collections::LinkedList;
+use std::sync::RwLock;
+
+use super::PresentChain;
+
+/// The chain as a `LinkedList` behind one `RwLock`. Servants checking for a present can share
+/// it, but adding or removing one locks the whole chain, so only one servant changes it at a time.
+#[derive(Debug, Default)]
+pub struct CoarseChain {
+    list: RwLock<LinkedList<usize>>,
+}
+
+impl CoarseChain {
+    pub fn new() -> CoarseChain {
+        CoarseChain::default()
+    }
+}
+
+impl PresentChain for CoarseChain {
+    fn add(&self, present: usize) -> bool {
+        let mut chain = self.list.write().unwrap();
+        let mut insertion_index = None;
+
+        // Find the position of the present to add
+        for (index, &item) in chain.iter().enumerate() {
+            if present == item {
+                return false;
+            }
+
+            if present < item {
+                insertion_index = Some(index);
+                break;
+            }
+        }
+
+        match insertion_index {
+            Some(index) => {
+                // Split the list & insert at the right position
+                let mut split = chain.split_off(index);
+                chain.push_back(present);
+                chain.append(&mut split);
+            }
+            None => chain.push_back(present),
+        }
+
+        true
+    }
+
+    fn remove_min(&self) -> Option<usize> {
+        self.list.write().unwrap().pop_front()
+    }
+
+    fn remove_by_id(&self, present: usize) -> bool {
+        let mut chain = self.list.write().unwrap();
+
+        // Find the position of the present to remove
+        let Some(removal_index) = chain.iter().position(|&x| x == present) else {
+            return false;
+        };
+
+        let mut split = chain.split_off(removal_index);
+        split.pop_front(); // This removes the present
+        chain.append(&mut split);
+        true
+    }
+
+    fn contains(&self, present: usize) -> bool {
+        self.list.read().unwrap().iter().any(|&x| x == present)
+    }
+
+    fn len(&self) -> usize {
+        self.list.read().unwrap().len()
+    }
+}
@@ -0,0 +1,152 @@
+use std::ptr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+use super::PresentChain;
+
+/// A pointer to the next node on the chain, or null at the end of it.
+type Link = *mut Node;
+
+struct Node {
+    present: usize,
+
+    /// Each node's own lock, guarding which node comes after it
+    next: Mutex<Link>,
+}
+
+/// A sorted linked list where every node has its own lock. Servants walk the chain hand over
+/// hand, locking the next node before letting go of the one they're on, so servants working on
+/// different parts of the chain don't wait for each other. One servant can't overtake another,
+/// though, so everyone behind a servant near the front moves at its pace.
+pub struct HandOverHandChain {
+    head: Mutex<Link>,
+    len: AtomicUsize,
+}
+
+// SAFETY: a node is only reached through the lock on the link pointing at it, and it's only
+// freed by a servant holding that lock and its own, so nobody else can be using it at the time
+unsafe impl Send for HandOverHandChain {}
+unsafe impl Sync for HandOverHandChain {}
+
+impl HandOverHandChain {
+    pub fn new() -> HandOverHandChain {
+        HandOverHandChain {
+            head: Mutex::new(ptr::null_mut()),
+            len: AtomicUsize::new(0),
+        }
+    }
+
+    /// Walks the chain to where `present` belongs, returning the locked link that points at the
+    /// first node whose present isn't lower than it (or is null if there isn't one).
+    fn lock_position(&self, present: usize) -> MutexGuard<'_, Link> {
+        let mut link = self.head.lock().unwrap();
+
+        loop {
+            if link.is_null() {
+                return link;
+            }
+
+            // SAFETY: the node can't be freed while the link pointing at it is locked
+            let node = unsafe { &**link };
+
+            if node.present >= present {
+                return link;
+            }
+
+            // Lock the next link before the current one is let go of by the assignment
+            link = node.next.lock().unwrap();
+        }
+    }
+
+    /// Takes the node `link` points at off the chain, returning its present. `link` mustn't be
+    /// null.
+    fn unlink(&self, link: &mut MutexGuard<'_, Link>) -> usize {
+        let current = **link;
+
+        // SAFETY: `link` is locked, so the node is still on the chain
+        let node = unsafe { &*current };
+
+        // Wait for anyone still on the node to move on, then skip over it
+        let next = node.next.lock().unwrap();
+        **link = *next;
+        drop(next);
+
+        self.len.fetch_sub(1, Ordering::Relaxed);
+
+        // SAFETY: the node isn't on the chain any more, and reaching it would mean locking
+        // `link`, so nobody else can have hold of it
+        let node = unsafe { Box::from_raw(current) };
+        node.present
+    }
+}
+
+impl Default for HandOverHandChain {
+    fn default() -> Self {
+        HandOverHandChain::new()
+    }
+}
+
+impl Drop for HandOverHandChain {
+    fn drop(&mut self) {
+        let mut current = *self.head.get_mut().unwrap();
+
+        while !current.is_null() {
+            // SAFETY: the chain is being dropped, so nobody else can have hold of its nodes
+            let mut node = unsafe { Box::from_raw(current) };
+            current = *node.next.get_mut().unwrap();
+        }
+    }
+}
+
+impl PresentChain for HandOverHandChain {
+    fn add(&self, present: usize) -> bool {
+        let mut link = self.lock_position(present);
+
+        // SAFETY: the node can't be freed while the link pointing at it is locked
+        if !link.is_null() && unsafe { (**link).present } == present {
+            return false;
+        }
+
+        let node = Box::new(Node {
+            present,
+            next: Mutex::new(*link),
+        });
+        *link = Box::into_raw(node);
+        self.len.fetch_add(1, Ordering::Relaxed);
+
+        true
+    }
+
+    fn remove_min(&self) -> Option<usize> {
+        let mut head = self.head.lock().unwrap();
+
+        if head.is_null() {
+            return None;
+        }
+
+        Some(self.unlink(&mut head))
+    }
+
+    fn remove_by_id(&self, present: usize) -> bool {
+        let mut link = self.lock_position(present);
+
+        // SAFETY: the node can't be freed while the link pointing at it is locked
+        if link.is_null() || unsafe { (**link).present } != present {
+            return false;
+        }
+
+        self.unlink(&mut link);
+        true
+    }
+
+    fn contains(&self, present: usize) -> bool {
+        let link = self.lock_position(present);
+
+        // SAFETY: the node can't be freed while the link pointing at it is locked
+        !link.is_null() && unsafe { (**link).present } == present
+    }
+
+    fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,65 @@
+use std::sync::atomic::Ordering;
+
+use super::node_list::{Node, NodeList};
+use super::PresentChain;
+
+/// The lazy list from The Art of Multiprocessor Programming. Like `OptimisticChain`, servants
+/// find their place without locks and then lock the two nodes either side of it, but a node is
+/// marked before it's unlinked, so checking the locked nodes are still on the chain is just
+/// looking at their marks rather than walking the chain again. Checking whether a present is on
+/// the chain takes no locks at all and never retries, so it never waits for a servant changing
+/// the chain or holds one up.
+///
+/// As with `OptimisticChain`, taken off nodes are kept until the chain is dropped, since a servant
+/// can still be walking through one.
+pub struct LazyChain {
+    list: NodeList,
+}
+
+impl LazyChain {
+    pub fn new() -> LazyChain {
+        LazyChain {
+            list: NodeList::new(validate),
+        }
+    }
+}
+
+/// Unmarked nodes are on the chain, so they just have to still be next to each other.
+fn validate(_: &NodeList, pred: &Node, curr: *mut Node) -> bool {
+    // SAFETY: nodes aren't freed until the chain is dropped
+    !pred.marked.load(Ordering::Acquire)
+        && unsafe { curr.as_ref() }.is_none_or(|x| !x.marked.load(Ordering::Acquire))
+        && pred.next() == curr
+}
+
+impl Default for LazyChain {
+    fn default() -> Self {
+        LazyChain::new()
+    }
+}
+
+impl PresentChain for LazyChain {
+    fn add(&self, present: usize) -> bool {
+        self.list.add(present)
+    }
+
+    fn remove_min(&self) -> Option<usize> {
+        self.list.remove_min()
+    }
+
+    fn remove_by_id(&self, present: usize) -> bool {
+        self.list.remove_by_id(present)
+    }
+
+    fn contains(&self, present: usize) -> bool {
+        let (_, curr) = self.list.find(present);
+
+        // SAFETY: as in `validate`
+        unsafe { curr.as_ref() }
+            .is_some_and(|x| x.present == present && !x.marked.load(Ordering::Acquire))
+    }
+
+    fn len(&self) -> usize {
+        self.list.len()
+    }
+}
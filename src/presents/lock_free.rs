@@ -0,0 +1,216 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crossbeam_epoch::{self as epoch, Atomic, Guard, Owned, Shared};
+
+use super::PresentChain;
+
+/// The tag on a node's `next` pointer that says the node has been taken off the chain.
+const REMOVED: usize = 1;
+
+struct Node {
+    present: usize,
+    next: Atomic<Node>,
+}
+
+/// Harris's lock-free sorted list. Nodes are linked in and out with compare-and-swap on the
+/// `next` pointers, so a servant that's held up never holds anyone else up. A present is taken off
+/// in two steps: its node is first marked as removed by tagging its `next` pointer, which stops
+/// anything being linked in after it, and then unlinked by whoever gets there first. Unlinked
+/// nodes are freed by `crossbeam-epoch` once no servant can still be looking at them.
+pub struct LockFreeChain {
+    head: Atomic<Node>,
+    len: AtomicUsize,
+}
+
+impl LockFreeChain {
+    pub fn new() -> LockFreeChain {
+        LockFreeChain {
+            head: Atomic::null(),
+            len: AtomicUsize::new(0),
+        }
+    }
+
+    /// Finds where `present` belongs, returning the pointer to the first node whose present isn't
+    /// lower than it (which is null if there isn't one) and that node. Removed nodes passed on the
+    /// way are unlinked, so the returned node wasn't removed when it was reached.
+    fn find<'g>(
+        &'g self,
+        present: usize,
+        guard: &'g Guard,
+    ) -> (&'g Atomic<Node>, Shared<'g, Node>) {
+        'retry: loop {
+            let mut prev = &self.head;
+            let mut curr = prev.load(Ordering::Acquire, guard);
+
+            // SAFETY: the guard stops any node reached from the chain being freed
+            while let Some(node) = unsafe { curr.as_ref() } {
+                let next = node.next.load(Ordering::Acquire, guard);
+
+                if next.tag() == REMOVED {
+                    // Help whoever removed it by unlinking it. If `prev` has changed (or has been
+                    // removed itself) start again from the head
+                    let next = next.with_tag(0);
+
+                    match prev.compare_exchange(
+                        curr,
+                        next,
+                        Ordering::AcqRel,
+                        Ordering::Acquire,
+                        guard,
+                    ) {
+                        // SAFETY: the node is off the chain, so only servants that already had
+                        // hold of it can see it, and it's only freed once they've all let go
+                        Ok(_) => unsafe { guard.defer_destroy(curr) },
+                        Err(_) => continue 'retry,
+                    }
+
+                    curr = next;
+                    continue;
+                }
+
+                if node.present >= present {
+                    break;
+                }
+
+                prev = &node.next;
+                curr = next;
+            }
+
+            return (prev, curr);
+        }
+    }
+
+    /// Takes `curr`, which `prev` pointed to, off the chain. Returns false if another servant
+    /// removed it first.
+    fn remove_node<'g>(
+        &'g self,
+        prev: &'g Atomic<Node>,
+        curr: Shared<'g, Node>,
+        guard: &'g Guard,
+    ) -> bool {
+        // SAFETY: the guard stops any node reached from the chain being freed
+        let node = unsafe { curr.deref() };
+
+        // Marking the node is what removes the present. Only the servant that marks it owns the
+        // removal
+        let next = node.next.fetch_or(REMOVED, Ordering::AcqRel, guard);
+
+        if next.tag() == REMOVED {
+            return false;
+        }
+
+        self.len.fetch_sub(1, Ordering::Relaxed);
+
+        match prev.compare_exchange(curr, next, Ordering::AcqRel, Ordering::Acquire, guard) {
+            // SAFETY: as in `find`
+            Ok(_) => unsafe { guard.defer_destroy(curr) },
+            // Something changed before the node, so leave unlinking it to `find`
+            Err(_) => {
+                self.find(node.present, guard);
+            }
+        }
+
+        true
+    }
+}
+
+impl Default for LockFreeChain {
+    fn default() -> Self {
+        LockFreeChain::new()
+    }
+}
+
+impl Drop for LockFreeChain {
+    fn drop(&mut self) {
+        // SAFETY: the chain is being dropped, so nobody else can have hold of its nodes. Nodes
+        // that were unlinked are freed by the epoch collector instead
+        unsafe {
+            let guard = epoch::unprotected();
+            let mut curr = self.head.load(Ordering::Relaxed, guard);
+
+            while !curr.is_null() {
+                let node = curr.into_owned();
+                curr = node.next.load(Ordering::Relaxed, guard).with_tag(0);
+            }
+        }
+    }
+}
+
+impl PresentChain for LockFreeChain {
+    fn add(&self, present: usize) -> bool {
+        let guard = &epoch::pin();
+        let mut node = Owned::new(Node {
+            present,
+            next: Atomic::null(),
+        });
+
+        loop {
+            let (prev, curr) = self.find(present, guard);
+
+            // SAFETY: the guard stops any node reached from the chain being freed
+            if unsafe { curr.as_ref() }.is_some_and(|x| x.present == present) {
+                return false;
+            }
+
+            node.next.store(curr, Ordering::Relaxed);
+
+            match prev.compare_exchange(curr, node, Ordering::AcqRel, Ordering::Acquire, guard) {
+                Ok(_) => {
+                    self.len.fetch_add(1, Ordering::Relaxed);
+                    return true;
+                }
+                Err(err) => node = err.new,
+            }
+        }
+    }
+
+    fn remove_min(&self) -> Option<usize> {
+        let guard = &epoch::pin();
+
+        loop {
+            let (prev, curr) = self.find(usize::MIN, guard);
+
+            // SAFETY: the guard stops any node reached from the chain being freed
+            let present = unsafe { curr.as_ref() }?.present;
+
+            if self.remove_node(prev, curr, guard) {
+                return Some(present);
+            }
+        }
+    }
+
+    fn remove_by_id(&self, present: usize) -> bool {
+        let guard = &epoch::pin();
+        let (prev, curr) = self.find(present, guard);
+
+        // SAFETY: the guard stops any node reached from the chain being freed
+        match unsafe { curr.as_ref() } {
+            // If another servant removes it first it's gone either way
+            Some(node) if node.present == present => self.remove_node(prev, curr, guard),
+            _ => false,
+        }
+    }
+
+    fn contains(&self, present: usize) -> bool {
+        let guard = &epoch::pin();
+        let mut curr = self.head.load(Ordering::Acquire, guard);
+
+        // Walks straight past removed nodes without unlinking them, so it never has to retry
+        // SAFETY: the guard stops any node reached from the chain being freed
+        while let Some(node) = unsafe { curr.as_ref() } {
+            let next = node.next.load(Ordering::Acquire, guard);
+
+            if node.present >= present {
+                return node.present == present && next.tag() != REMOVED;
+            }
+
+            curr = next.with_tag(0);
+        }
+
+        false
+    }
+
+    fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,282 @@
+//! Problem 1: the Minotaur's servants sorting birthday presents. The presents start out shuffled
+//! in a bag, and each servant alternates between taking one out of the bag and adding it to the
+//! chain in order, and taking the first present off the chain and writing its thank you card,
+//! until the bag and the chain are both empty.
+
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::spawn;
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use thiserror::Error;
+
+mod chain;
+mod coarse;
+mod hand_over_hand;
+mod lazy;
+#[cfg(feature = "lockfree")]
+mod lock_free;
+mod node_list;
+mod optimistic;
+
+pub use chain::{ChainKind, PresentChain};
+pub use coarse::CoarseChain;
+pub use hand_over_hand::HandOverHandChain;
+pub use lazy::LazyChain;
+#[cfg(feature = "lockfree")]
+pub use lock_free::LockFreeChain;
+pub use optimistic::OptimisticChain;
+
+/// How many presents there are and how many servants sort them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PresentsConfig {
+    /// Number of presents in the bag, numbered from 1
+    pub presents: usize,
+
+    /// Number of servant threads
+    pub servants: usize,
+
+    /// What the chain is made of
+    pub chain: ChainKind,
+
+    /// Seed for shuffling the bag, so the same seed puts the presents in the same order.
+    /// Random if `None`
+    pub seed: Option<u64>,
+}
+
+impl Default for PresentsConfig {
+    fn default() -> Self {
+        PresentsConfig {
+            presents: 500_000,
+            servants: 4,
+            chain: ChainKind::default(),
+            seed: None,
+        }
+    }
+}
+
+impl PresentsConfig {
+    /// Checks that every value makes sense, naming the first one that doesn't.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.presents == 0 {
+            return Err(ConfigError::invalid("presents", "must be at least 1"));
+        }
+
+        if self.servants == 0 {
+            return Err(ConfigError::invalid("servants", "must be at least 1"));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ConfigError {
+    /// A value the servants can't run with
+    #[error("invalid `{key}`: {message}")]
+    Invalid { key: String, message: String },
+}
+
+impl ConfigError {
+    fn invalid(key: impl Into<String>, message: impl Into<String>) -> ConfigError {
+        ConfigError::Invalid {
+            key: key.into(),
+            message: message.into(),
+        }
+    }
+}
+
+/// What one servant did.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ServantStats {
+    pub servant_id: usize,
+
+    /// Presents taken out of the bag and added to the chain
+    pub presents_added: u64,
+
+    /// Presents taken off the chain and thanked for
+    pub thank_you_cards: u64,
+}
+
+/// What a run did, returned by `run` once every servant has finished.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RunSummary {
+    /// Number of presents that were in the bag
+    pub presents: usize,
+
+    /// Thank you cards written by all of the servants, which should equal `presents`
+    pub thank_you_cards: u64,
+
+    /// How long the servants took, from being started to the last one finishing
+    pub elapsed: Duration,
+
+    /// Indexed by servant ID
+    pub servants: Vec<ServantStats>,
+}
+
+impl fmt::Display for RunSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "The servants have processed {} presents and written {} thank you notes in {:.3}s",
+            self.presents,
+            self.thank_you_cards,
+            self.elapsed.as_secs_f64()
+        )?;
+
+        for servant in self.servants.iter() {
+            writeln!(
+                f,
+                "Servant {}: {} presents added, {} thank you notes",
+                servant.servant_id, servant.presents_added, servant.thank_you_cards
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+enum ServantAction {
+    /// Take a present from the bag and add it to the chain in the correct location
+    AddPresentToChain,
+
+    /// Remove a present from the chain and write a thank you card to the guest
+    /// who gave the present.
+    WriteThankYouCard,
+
+    /// Check if a present with a given ID is on the chain or not.
+    #[allow(dead_code)]
+    CheckIfPresentOnChain(usize),
+}
+
+// Notes
+// - Each servant needs to alternate between adding a gift and writing a thank you card
+// - The servants should only stop when the bag and chain are both empty
+
+/// Runs the servants until every present has been thanked for, blocking until they're done.
+/// Returns an error without starting if `config` doesn't validate. Panics if `config.chain` isn't
+/// available in this build.
+pub fn run(config: PresentsConfig) -> Result<RunSummary, ConfigError> {
+    let chain = config.chain.build();
+    run_with(config, chain)
+}
+
+/// Runs the servants on a chain of the caller's own, ignoring `config.chain`. The chain should
+/// start out empty. Returns an error without starting if `config` doesn't validate.
+pub fn run_with(
+    config: PresentsConfig,
+    chain_of_presents: Arc<dyn PresentChain>,
+) -> Result<RunSummary, ConfigError> {
+    config.validate()?;
+
+    // "Initially all of the presents were thrown into a large bag with no particular order."
+    let mut large_bag: Vec<usize> = (1..config.presents + 1).collect();
+
+    // Mix up the bag
+    let mut rng = match config.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    large_bag.shuffle(&mut rng);
+
+    let large_bag = Arc::new(Mutex::new(large_bag));
+
+    // Should be equal to the number of presents when the servants are finished
+    let thank_you_counter = Arc::new(AtomicU64::new(0));
+
+    let start = Instant::now();
+
+    // Spawn the servant threads
+    let mut servant_handles = Vec::new();
+
+    for servant_id in 0..config.servants {
+        let local_bag = large_bag.clone();
+        let local_chain = chain_of_presents.clone();
+        let local_counter = thank_you_counter.clone();
+
+        let join_handle = spawn(move || {
+            let mut stats = ServantStats {
+                servant_id,
+                ..ServantStats::default()
+            };
+            let mut current_action = ServantAction::AddPresentToChain;
+
+            loop {
+                // Set the next action for the servant based on what the servant just did
+                current_action = match current_action {
+                    ServantAction::AddPresentToChain => ServantAction::WriteThankYouCard,
+                    ServantAction::WriteThankYouCard => ServantAction::AddPresentToChain,
+                    ServantAction::CheckIfPresentOnChain(_) => ServantAction::AddPresentToChain,
+                };
+
+                match current_action {
+                    ServantAction::AddPresentToChain => {
+                        let mut bag = local_bag.lock().unwrap();
+                        let maybe_present = bag.pop();
+                        drop(bag);
+
+                        let present_to_add = if let Some(present) = maybe_present {
+                            present
+                        } else {
+                            // If the bag is empty check to see if the chain is empty as well. If it is then the
+                            // servant's job is done and it can return.
+                            if local_chain.is_empty() {
+                                return stats;
+                            } else {
+                                continue;
+                            }
+                        };
+
+                        local_chain.add(present_to_add);
+                        stats.presents_added += 1;
+                    }
+                    ServantAction::WriteThankYouCard => {
+                        if local_chain.remove_min().is_none() {
+                            // If the chain is empty check to see if the bag is empty as well. If it is then the
+                            // servant's job is done and it can return.
+                            let bag = local_bag.lock().unwrap();
+                            let is_empty = bag.is_empty();
+                            drop(bag);
+
+                            if is_empty {
+                                return stats;
+                            } else {
+                                continue;
+                            }
+                        }
+
+                        // Writing a thank you card is represented as adding 1 to the thank you counter
+                        local_counter.fetch_add(1, Ordering::Relaxed);
+                        stats.thank_you_cards += 1;
+                    }
+                    ServantAction::CheckIfPresentOnChain(present_id) => {
+                        if local_chain.contains(present_id) {
+                            println!("The present with ID {} is on the chain", present_id);
+                        } else {
+                            println!("The present with ID {} is not on the chain", present_id);
+                        }
+                    }
+                }
+            }
+        });
+
+        servant_handles.push(join_handle);
+    }
+
+    // Wait for the servants to finish
+    let servants = servant_handles
+        .into_iter()
+        .map(|x| x.join().unwrap())
+        .collect();
+
+    Ok(RunSummary {
+        presents: config.presents,
+        thank_you_cards: thank_you_counter.load(Ordering::Relaxed),
+        elapsed: start.elapsed(),
+        servants,
+    })
+}
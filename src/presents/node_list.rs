@@ -0,0 +1,185 @@
+use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+pub(super) struct Node {
+    /// Ignored on the head, which is always there and comes before every present
+    pub(super) present: usize,
+    next: AtomicPtr<Node>,
+
+    /// Set once the node has been taken off the chain, before it's unlinked. Only `LazyChain`
+    /// looks at it
+    pub(super) marked: AtomicBool,
+    lock: Mutex<()>,
+}
+
+impl Node {
+    fn new(present: usize, next: *mut Node) -> Node {
+        Node {
+            present,
+            next: AtomicPtr::new(next),
+            marked: AtomicBool::new(false),
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// The node after this one, or null if it's the last.
+    pub(super) fn next(&self) -> *mut Node {
+        self.next.load(Ordering::Acquire)
+    }
+}
+
+/// Checks that `pred` and `curr`, which are both locked, are still on the chain and next to each
+/// other.
+pub(super) type Validate = fn(&NodeList, &Node, *mut Node) -> bool;
+
+/// The sorted list with a lock on every node that `OptimisticChain` and `LazyChain` share. Finding
+/// where a present belongs doesn't lock anything, so once the two nodes either side of it are
+/// locked they're checked with the chain's `Validate`, starting over if they've changed.
+///
+/// A servant can still be walking through a node after it's been taken off the list, which the
+/// book leaves to Java's garbage collector. Here taken off nodes are kept until the list is
+/// dropped instead.
+pub(super) struct NodeList {
+    head: Box<Node>,
+    len: AtomicUsize,
+    validate: Validate,
+
+    /// Nodes that have been taken off the list, freed when it's dropped
+    retired: Mutex<Vec<*mut Node>>,
+}
+
+// SAFETY: nodes are only changed with their lock held or through atomics, and aren't freed until
+// the list is dropped, so a servant can follow a pointer to any of them at any time
+unsafe impl Send for NodeList {}
+unsafe impl Sync for NodeList {}
+
+impl NodeList {
+    pub(super) fn new(validate: Validate) -> NodeList {
+        NodeList {
+            head: Box::new(Node::new(0, ptr::null_mut())),
+            len: AtomicUsize::new(0),
+            validate,
+            retired: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub(super) fn head(&self) -> &Node {
+        &self.head
+    }
+
+    /// Walks the list without locking anything to where `present` belongs, returning the node
+    /// before it and the first node whose present isn't lower than it (or null if there isn't
+    /// one). Either of them may have been taken off by the time they're returned.
+    pub(super) fn find(&self, present: usize) -> (&Node, *mut Node) {
+        let mut pred = &*self.head;
+        let mut curr = pred.next();
+
+        // SAFETY: nodes aren't freed until the list is dropped
+        while let Some(node) = unsafe { curr.as_ref() } {
+            if node.present >= present {
+                break;
+            }
+
+            pred = node;
+            curr = node.next();
+        }
+
+        (pred, curr)
+    }
+
+    /// Finds where `present` belongs and calls `f` with the two nodes either side of it locked,
+    /// once they've been validated.
+    pub(super) fn with_locked<R>(
+        &self,
+        present: usize,
+        f: impl FnOnce(&Node, *mut Node) -> R,
+    ) -> R {
+        loop {
+            let (pred, curr) = self.find(present);
+
+            // Nodes are always locked in the order they're on the list, so servants can't
+            // deadlock
+            let _pred = pred.lock.lock().unwrap();
+            // SAFETY: as in `find`
+            let _curr = unsafe { curr.as_ref() }.map(|x| x.lock.lock().unwrap());
+
+            if (self.validate)(self, pred, curr) {
+                return f(pred, curr);
+            }
+        }
+    }
+
+    /// Takes `curr` off the list. Both it and `pred`, which points at it, must be locked.
+    fn unlink(&self, pred: &Node, curr: *mut Node) -> usize {
+        // SAFETY: as in `find`
+        let node = unsafe { &*curr };
+
+        // For `LazyChain`, marking it is what takes the present off, unlinking it just tidies up
+        node.marked.store(true, Ordering::Release);
+        pred.next.store(node.next(), Ordering::Release);
+        self.len.fetch_sub(1, Ordering::Relaxed);
+        self.retired.lock().unwrap().push(curr);
+
+        node.present
+    }
+
+    pub(super) fn add(&self, present: usize) -> bool {
+        self.with_locked(present, |pred, curr| {
+            // SAFETY: as in `find`
+            if unsafe { curr.as_ref() }.is_some_and(|x| x.present == present) {
+                return false;
+            }
+
+            let node = Box::into_raw(Box::new(Node::new(present, curr)));
+            pred.next.store(node, Ordering::Release);
+            self.len.fetch_add(1, Ordering::Relaxed);
+
+            true
+        })
+    }
+
+    pub(super) fn remove_min(&self) -> Option<usize> {
+        self.with_locked(usize::MIN, |pred, curr| {
+            if curr.is_null() {
+                None
+            } else {
+                Some(self.unlink(pred, curr))
+            }
+        })
+    }
+
+    pub(super) fn remove_by_id(&self, present: usize) -> bool {
+        self.with_locked(present, |pred, curr| {
+            // SAFETY: as in `find`
+            if unsafe { curr.as_ref() }.is_some_and(|x| x.present == present) {
+                self.unlink(pred, curr);
+                true
+            } else {
+                false
+            }
+        })
+    }
+
+    pub(super) fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for NodeList {
+    fn drop(&mut self) {
+        let mut current = *self.head.next.get_mut();
+
+        while !current.is_null() {
+            // SAFETY: the list is being dropped, so nobody else can have hold of its nodes, and
+            // nodes still on the list were never retired
+            let mut node = unsafe { Box::from_raw(current) };
+            current = *node.next.get_mut();
+        }
+
+        for &node in self.retired.get_mut().unwrap().iter() {
+            // SAFETY: as above, and a node is only retired once
+            drop(unsafe { Box::from_raw(node) });
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use std::ptr;
+
+use super::node_list::{Node, NodeList};
+use super::PresentChain;
+
+/// The optimistic list from The Art of Multiprocessor Programming. Servants find their place on
+/// the chain without taking any locks, then lock just the two nodes either side of it and check
+/// they're still on the chain and next to each other, starting again if they aren't. Walking the
+/// chain never waits for anyone, but everything has to be checked by walking it a second time.
+///
+/// Taken off nodes are kept until the chain is dropped, since a servant can still be walking
+/// through one.
+pub struct OptimisticChain {
+    list: NodeList,
+}
+
+impl OptimisticChain {
+    pub fn new() -> OptimisticChain {
+        OptimisticChain {
+            list: NodeList::new(validate),
+        }
+    }
+}
+
+/// Whether `pred` is still on the chain and still points at `curr`. Both are locked, so neither
+/// can change while this walks the chain again to check.
+fn validate(list: &NodeList, pred: &Node, curr: *mut Node) -> bool {
+    let mut node = list.head();
+
+    loop {
+        if ptr::eq(node, pred) {
+            return pred.next() == curr;
+        }
+
+        // SAFETY: nodes aren't freed until the chain is dropped
+        match unsafe { node.next().as_ref() } {
+            // Past where `pred` would be, so it's been taken off
+            Some(next) if next.present > pred.present => return false,
+            Some(next) => node = next,
+            None => return false,
+        }
+    }
+}
+
+impl Default for OptimisticChain {
+    fn default() -> Self {
+        OptimisticChain::new()
+    }
+}
+
+impl PresentChain for OptimisticChain {
+    fn add(&self, present: usize) -> bool {
+        self.list.add(present)
+    }
+
+    fn remove_min(&self) -> Option<usize> {
+        self.list.remove_min()
+    }
+
+    fn remove_by_id(&self, present: usize) -> bool {
+        self.list.remove_by_id(present)
+    }
+
+    fn contains(&self, present: usize) -> bool {
+        // SAFETY: as in `validate`
+        self.list.with_locked(present, |_, curr| {
+            unsafe { curr.as_ref() }.is_some_and(|x| x.present == present)
+        })
+    }
+
+    fn len(&self) -> usize {
+        self.list.len()
+    }
+}
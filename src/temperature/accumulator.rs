@@ -0,0 +1,768 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use super::decimation::Decimator;
+use super::spill::RecordingBuffer;
+use super::{
+    find_suspect_sensors, timestamp, Alert, Extremes, Histogram, QuantileEstimator, QueueLatency,
+    Recording, RejectReason, RepeatRun, Report, SensorStats, SimulationConfig, StallReason,
+    StalledSensor, SummaryStats, Temperature, TemperatureDifference,
+};
+
+/// Builds a report one recording at a time. Every statistic is updated as the recording arrives,
+/// so generating the report at the end of the hour only has to copy the results out instead of
+/// processing the whole hour's recordings in one burst.
+#[derive(Clone, Debug)]
+pub struct ReportAccumulator {
+    sensor_count: usize,
+    extremes: Extremes,
+    sensors: BTreeMap<usize, SensorAccumulator>,
+    difference: DifferenceTracker,
+    histogram: Histogram,
+    moments: Moments,
+    quantiles: QuantileEstimator,
+
+    // How long readings waited in the queue, in milliseconds
+    latencies: QuantileEstimator,
+
+    // When the report's period began, if it's known. Otherwise the earliest recording is used
+    period_start: Option<Instant>,
+
+    // Timestamps of the earliest & latest recordings
+    earliest: Option<Instant>,
+    latest: Option<Instant>,
+
+    // Only kept when suspect sensors are detected, so the extremes & largest difference can be
+    // worked out again without them. Past `max_recordings_in_memory` the oldest go to a file
+    recordings: Option<RecordingBuffer>,
+
+    // Sensors the watchdog flagged during the period
+    stalled: BTreeMap<usize, StallReason>,
+
+    // How many alerts each alert rule raised
+    rule_alerts: BTreeMap<String, usize>,
+
+    // How many readings the sensors threw away as invalid, by why
+    rejected: BTreeMap<RejectReason, usize>,
+
+    // How many readings were taken while the sensors were warming up
+    warm_up: usize,
+
+    // Thins out the readings pushed with `push_decimated`, and how many it's left out
+    decimator: Decimator,
+    decimated: usize,
+}
+
+impl ReportAccumulator {
+    pub fn new(config: &SimulationConfig) -> ReportAccumulator {
+        ReportAccumulator {
+            sensor_count: config.sensor_count,
+            extremes: Extremes::for_config(config),
+            sensors: BTreeMap::new(),
+            difference: DifferenceTracker::new(config.scaled_difference_window()),
+            histogram: config.histogram(),
+            moments: Moments::default(),
+            quantiles: config.quantiles.build(),
+            latencies: config.quantiles.build(),
+            period_start: None,
+            earliest: None,
+            latest: None,
+            recordings: config
+                .suspect_sensors
+                .enabled
+                .then(|| RecordingBuffer::new(config.max_recordings_in_memory)),
+            stalled: BTreeMap::new(),
+            rule_alerts: BTreeMap::new(),
+            rejected: BTreeMap::new(),
+            warm_up: 0,
+            decimator: Decimator::new(config),
+            decimated: 0,
+        }
+    }
+
+    /// Adds a recording to every statistic. Recordings are expected in (roughly) timestamp order.
+    pub fn push(&mut self, recording: &Recording) {
+        self.moments.push(recording.temperature.fahrenheit());
+        self.quantiles.push(recording.temperature.fahrenheit());
+        self.extremes.push(recording);
+        self.difference.push(recording);
+        self.histogram.push(recording.temperature);
+
+        let timestamp = recording.timestamp;
+        self.earliest = Some(self.earliest.map_or(timestamp, |x| x.min(timestamp)));
+        self.latest = Some(self.latest.map_or(timestamp, |x| x.max(timestamp)));
+
+        if let Some(recordings) = &mut self.recordings {
+            recordings.push(recording.clone());
+        }
+
+        self.sensors
+            .entry(recording.sensor_id)
+            .or_default()
+            .push(recording);
+    }
+
+    /// Adds a recording if `decimation` keeps it. One that's left out still counts as received, so
+    /// it isn't missed. Min/max decimation holds each sensor's readings until its minute is over,
+    /// so they only show up in the statistics then (or when the report is finished).
+    pub fn push_decimated(&mut self, recording: &Recording) {
+        self.sensors
+            .entry(recording.sensor_id)
+            .or_default()
+            .sequences
+            .push(recording.sequence);
+
+        let origin = self
+            .period_start
+            .or(self.earliest)
+            .unwrap_or(recording.timestamp);
+        let mut decimator = std::mem::take(&mut self.decimator);
+        self.decimated += decimator.push(recording, origin, |x| self.push(x));
+        self.decimator = decimator;
+    }
+
+    /// Puts whatever min/max decimation is still holding on to into the statistics.
+    fn flush_decimated(&mut self) {
+        let mut decimator = std::mem::take(&mut self.decimator);
+        self.decimated += decimator.flush(|x| self.push(x));
+        self.decimator = decimator;
+    }
+
+    /// Pushes every recording in order.
+    pub fn from_recordings(
+        recordings: &[Recording],
+        config: &SimulationConfig,
+    ) -> ReportAccumulator {
+        let mut accumulator = ReportAccumulator::new(config);
+        for recording in recordings {
+            accumulator.push(recording);
+        }
+
+        accumulator
+    }
+
+    /// Counts an alert raised by the anomaly detector, the priority limits or a rule against the
+    /// report.
+    pub fn push_alert(&mut self, alert: &Alert) {
+        self.sensors
+            .entry(alert.recording.sensor_id)
+            .or_default()
+            .alerts += 1;
+
+        if let Some(rule) = &alert.rule {
+            *self.rule_alerts.entry(rule.clone()).or_default() += 1;
+        }
+    }
+
+    /// Notes how long a reading waited between being taken and the report thread using it.
+    pub fn push_latency(&mut self, latency: Duration) {
+        self.latencies.push(latency.as_secs_f64() * 1000.0);
+    }
+
+    /// Counts a reading taken while the sensors were warming up. It's left out of every statistic,
+    /// but it was received, so it isn't missed.
+    pub fn push_warm_up(&mut self, recording: &Recording) {
+        self.warm_up += 1;
+        self.sensors
+            .entry(recording.sensor_id)
+            .or_default()
+            .sequences
+            .push(recording.sequence);
+    }
+
+    /// Counts readings the sensors threw away because they were invalid.
+    pub fn push_rejected(&mut self, reason: RejectReason, count: usize) {
+        if count > 0 {
+            *self.rejected.entry(reason).or_default() += count;
+        }
+    }
+
+    /// Counts readings a sensor took that were dropped because its queue was full.
+    pub fn push_dropped(&mut self, sensor_id: usize, count: usize) {
+        if count > 0 {
+            self.sensors.entry(sensor_id).or_default().dropped += count;
+        }
+    }
+
+    /// Counts readings that arrived after the report for the period they were taken in.
+    pub fn push_late(&mut self, sensor_id: usize, count: usize) {
+        if count > 0 {
+            self.sensors.entry(sensor_id).or_default().late += count;
+        }
+    }
+
+    /// Counts the readings a sensor was due to take during the period (the sequence numbers in
+    /// `owed`) that haven't been pushed, whatever happened to them, and notes where the gaps in
+    /// its sequence numbers are.
+    pub fn count_missed(&mut self, sensor_id: usize, owed: Range<u64>) {
+        let sensor = self.sensors.entry(sensor_id).or_default();
+        sensor.sequences.sort_unstable();
+        sensor.sequences.dedup();
+
+        let mut gaps = Vec::new();
+        let mut next = owed.start;
+        for &sequence in sensor.sequences.iter().filter(|x| owed.contains(x)) {
+            if sequence > next {
+                gaps.push(next..sequence);
+            }
+            next = sequence + 1;
+        }
+        if next < owed.end {
+            gaps.push(next..owed.end);
+        }
+
+        let due = owed.end.saturating_sub(owed.start) as usize;
+        sensor.expected = Some(sensor.expected.unwrap_or(0) + due);
+        sensor.missed += gaps
+            .iter()
+            .map(|x| (x.end - x.start) as usize)
+            .sum::<usize>();
+        sensor.gaps.extend(gaps);
+    }
+
+    /// Counts readings a sensor was due to take that are known to be missing.
+    pub fn push_missed(&mut self, sensor_id: usize, count: usize) {
+        if count > 0 {
+            self.sensors.entry(sensor_id).or_default().missed += count;
+        }
+    }
+
+    /// Sets how many readings a sensor was due to take and the gaps in its sequence numbers, when
+    /// they're already known.
+    pub fn push_gaps(&mut self, sensor_id: usize, expected: usize, gaps: &[Range<u64>]) {
+        let sensor = self.sensors.entry(sensor_id).or_default();
+        sensor.expected = Some(sensor.expected.unwrap_or(0) + expected);
+        sensor.gaps.extend_from_slice(gaps);
+    }
+
+    /// Sets how far a sensor's clock is estimated to be off, in simulated seconds. The latest
+    /// estimate is the one reported.
+    pub fn push_clock_offset(&mut self, sensor_id: usize, seconds: f64) {
+        self.sensors.entry(sensor_id).or_default().clock_offset = Some(seconds);
+    }
+
+    /// Flags a sensor the watchdog noticed had stopped sending readings.
+    pub fn push_stalled(&mut self, stalled: &StalledSensor) {
+        let reason = match self.stalled.remove(&stalled.sensor_id) {
+            Some(reason) => reason.worst(stalled.reason.clone()),
+            None => stalled.reason.clone(),
+        };
+        self.stalled.insert(stalled.sensor_id, reason);
+    }
+
+    /// How many recordings have been added since the last report.
+    pub fn len(&self) -> usize {
+        self.moments.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moments.count == 0 && self.decimator.is_empty()
+    }
+
+    /// Sets when the report's period began, which the largest difference's offsets and the
+    /// report's start time are measured from.
+    pub fn start_period(&mut self, start: Instant) {
+        self.period_start = Some(start);
+    }
+
+    /// Produces the `index`th report from everything added so far and starts over for the next
+    /// one. A period with no readings (e.g. every sensor stalled) still gets a report, without the
+    /// statistics that need readings. Returns `None` only if there's no period and no readings to
+    /// report on.
+    pub fn finish(&mut self, index: usize, config: &SimulationConfig) -> Option<Report> {
+        let started = Instant::now();
+        self.flush_decimated();
+        let mut finished = std::mem::replace(self, ReportAccumulator::new(config));
+
+        let summary = SummaryStats::new(
+            finished.moments.mean,
+            finished.moments.std_dev(),
+            &mut finished.quantiles,
+            &config.percentiles,
+        );
+
+        let mut sensors = finished.sensors;
+        for sensor_id in 0..finished.sensor_count {
+            sensors.entry(sensor_id).or_default();
+        }
+
+        let queue_latency = QueueLatency::new(&mut finished.latencies);
+
+        let alert_count = sensors.values().map(|x| x.alerts).sum();
+        let dropped_readings = sensors.values().map(|x| x.dropped).sum();
+        let late_readings = sensors.values().map(|x| x.late).sum();
+        let missed_readings = sensors.values().map(|x| x.missed).sum();
+        let stalled_sensors = finished
+            .stalled
+            .into_iter()
+            .map(|(sensor_id, reason)| StalledSensor { sensor_id, reason })
+            .collect();
+
+        let sensor_stats: Vec<SensorStats> = sensors
+            .into_iter()
+            .map(|(sensor_id, sensor)| sensor.stats(sensor_id))
+            .collect();
+
+        let suspect_sensors = if config.suspect_sensors.enabled {
+            find_suspect_sensors(&sensor_stats, &config.suspect_sensors)
+        } else {
+            Vec::new()
+        };
+
+        // Readings can't be taken back out of the extremes & difference tracker, so those are
+        // worked out again from the kept recordings without the suspect sensors
+        if let (false, Some(recordings)) = (suspect_sensors.is_empty(), finished.recordings.take())
+        {
+            let mut extremes = Extremes::for_config(config);
+            let mut difference = DifferenceTracker::new(config.scaled_difference_window());
+
+            for recording in recordings
+                .into_recordings()
+                .filter(|x| !suspect_sensors.iter().any(|s| s.sensor_id == x.sensor_id))
+            {
+                extremes.push(&recording);
+                difference.push(&recording);
+            }
+
+            // If every reading came from a suspect sensor there'd be nothing left to report on
+            if difference.largest.is_some() {
+                finished.extremes = extremes;
+                finished.difference = difference;
+            }
+        }
+
+        let largest_temp_difference = finished.difference.largest;
+        let period_start = finished.period_start.or(finished.earliest)?;
+
+        let minutes_into_period = |recording: &Recording| {
+            config.simulated_minutes(recording.timestamp.saturating_duration_since(period_start))
+        };
+        let largest_difference_minutes = largest_temp_difference
+            .as_ref()
+            .map(|x| [minutes_into_period(&x.start), minutes_into_period(&x.end)]);
+
+        Some(Report {
+            index,
+            rover: None,
+            start_minute: index as u64 * config.report_interval_minutes,
+            period_start: timestamp::wall_time(period_start),
+            period_end: timestamp::wall_time(finished.latest.unwrap_or(period_start)),
+            partial: false,
+            on_demand: false,
+            lowest_temps: finished.extremes.lowest(),
+            highest_temps: finished.extremes.highest(),
+            lowest_temp_counts: finished.extremes.lowest_counts(),
+            highest_temp_counts: finished.extremes.highest_counts(),
+            largest_temp_difference,
+            largest_difference_minutes,
+            difference_window_minutes: config.difference_window_minutes,
+            summary,
+            histogram: finished.histogram,
+            alert_count,
+            rule_alerts: finished.rule_alerts,
+            dropped_readings,
+            rejected_readings: finished.rejected,
+            late_readings,
+            missed_readings,
+            warm_up_readings: finished.warm_up,
+            decimated_readings: finished.decimated,
+            suspect_sensors,
+            stalled_sensors,
+            sensor_stats,
+            queue_latency,
+            generation_seconds: started.elapsed().as_secs_f64(),
+            trend: None,
+            records: None,
+        })
+    }
+}
+
+/// How many recordings each rayon task handles when accumulating in parallel.
+#[cfg(feature = "parallel")]
+pub const PARALLEL_CHUNK_SIZE: usize = 4096;
+
+#[cfg(feature = "parallel")]
+impl ReportAccumulator {
+    /// Gives the same result as pushing every recording in order, but splits the recordings into
+    /// chunks that are accumulated on rayon's thread pool and merged afterwards. `recordings` has
+    /// to be sorted by timestamp.
+    pub fn from_recordings_parallel(
+        recordings: &[Recording],
+        config: &SimulationConfig,
+    ) -> ReportAccumulator {
+        let window = config.scaled_difference_window();
+
+        recordings
+            .par_chunks(PARALLEL_CHUNK_SIZE)
+            .enumerate()
+            .map(|(chunk_index, chunk)| {
+                let mut accumulator = ReportAccumulator::new(config);
+
+                // Differences can span two chunks, so the recordings from just before the chunk
+                // are replayed into the tracker without counting any differences that end there
+                let chunk_start = chunk_index * PARALLEL_CHUNK_SIZE;
+                let first = &chunk[0];
+                let overlap_start = recordings[..chunk_start].partition_point(|x| {
+                    first.timestamp.saturating_duration_since(x.timestamp) > window
+                });
+
+                for recording in recordings[overlap_start..chunk_start].iter() {
+                    accumulator.difference.push(recording);
+                }
+                accumulator.difference.largest = None;
+
+                for recording in chunk {
+                    accumulator.push(recording);
+                }
+
+                accumulator
+            })
+            .reduce(
+                || ReportAccumulator::new(config),
+                |mut earlier, later| {
+                    earlier.merge(later);
+                    earlier
+                },
+            )
+    }
+
+    /// Folds in the statistics from `later`, whose recordings were all taken after this one's.
+    fn merge(&mut self, mut later: ReportAccumulator) {
+        self.flush_decimated();
+        later.flush_decimated();
+
+        self.moments.merge(&later.moments);
+        self.quantiles.merge(later.quantiles);
+        self.latencies.merge(later.latencies);
+        self.earliest = self.earliest.into_iter().chain(later.earliest).min();
+        self.latest = self.latest.into_iter().chain(later.latest).max();
+
+        if let (Some(recordings), Some(later_recordings)) = (&mut self.recordings, later.recordings)
+        {
+            recordings.append(later_recordings);
+        }
+        self.histogram.merge(&later.histogram);
+
+        self.extremes.merge(&later.extremes);
+
+        for (sensor_id, sensor) in later.sensors {
+            self.sensors.entry(sensor_id).or_default().merge(sensor);
+        }
+
+        for (sensor_id, reason) in later.stalled {
+            self.push_stalled(&StalledSensor { sensor_id, reason });
+        }
+
+        for (rule, count) in later.rule_alerts {
+            *self.rule_alerts.entry(rule).or_default() += count;
+        }
+
+        for (reason, count) in later.rejected {
+            self.push_rejected(reason, count);
+        }
+        self.warm_up += later.warm_up;
+        self.decimated += later.decimated;
+
+        // On a tie the earlier difference wins, same as when the recordings are pushed in order
+        if let Some(difference) = later.difference.largest {
+            if self
+                .difference
+                .largest
+                .as_ref()
+                .is_none_or(|x| difference.difference > x.difference)
+            {
+                self.difference.largest = Some(difference);
+            }
+        }
+    }
+}
+
+/// Running mean & variance (Welford's algorithm), so the readings themselves don't need to be kept.
+#[derive(Clone, Debug, Default)]
+struct Moments {
+    count: usize,
+    mean: f64,
+
+    // Sum of squared differences from the mean
+    m2: f64,
+}
+
+impl Moments {
+    fn push(&mut self, value: f64) {
+        self.count += 1;
+
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+    }
+
+    /// Population standard deviation
+    fn std_dev(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            (self.m2 / self.count as f64).sqrt()
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    fn merge(&mut self, other: &Moments) {
+        if other.count == 0 {
+            return;
+        }
+
+        let count = self.count + other.count;
+        let delta = other.mean - self.mean;
+
+        self.mean += delta * other.count as f64 / count as f64;
+        self.m2 += other.m2 + delta * delta * (self.count * other.count) as f64 / count as f64;
+        self.count = count;
+    }
+}
+
+/// Running statistics for one sensor.
+#[derive(Clone, Debug, Default)]
+struct SensorAccumulator {
+    moments: Moments,
+    min: Option<Temperature>,
+    max: Option<Temperature>,
+    alerts: usize,
+    dropped: usize,
+    late: usize,
+    missed: usize,
+    clock_offset: Option<f64>,
+
+    // Sequence numbers of the sensor's readings, to tell which ones are missing
+    sequences: Vec<u64>,
+    expected: Option<usize>,
+    gaps: Vec<Range<u64>>,
+
+    // Runs of identical readings, used to spot stuck sensors. The first run is tracked separately
+    // so runs that cross a chunk boundary can be joined up when merging
+    first: Option<Temperature>,
+    last: Option<Temperature>,
+    leading_run: usize,
+    current_run: usize,
+    longest_run: Option<RepeatRun>,
+}
+
+impl SensorAccumulator {
+    fn push(&mut self, recording: &Recording) {
+        let temperature = recording.temperature;
+        self.moments.push(temperature.fahrenheit());
+        self.sequences.push(recording.sequence);
+
+        self.min = Some(self.min.map_or(temperature, |x| x.min(temperature)));
+        self.max = Some(self.max.map_or(temperature, |x| x.max(temperature)));
+
+        if self.last == Some(temperature) {
+            self.current_run += 1;
+        } else {
+            self.current_run = 1;
+        }
+
+        // The leading run only grows while every reading so far has been the same
+        if self.leading_run == self.moments.count - 1 && self.first.is_none_or(|x| x == temperature)
+        {
+            self.leading_run += 1;
+        }
+
+        self.first.get_or_insert(temperature);
+        self.last = Some(temperature);
+        self.extend_longest_run(temperature, self.current_run);
+    }
+
+    fn extend_longest_run(&mut self, temperature: Temperature, readings: usize) {
+        if self
+            .longest_run
+            .as_ref()
+            .is_none_or(|x| readings > x.readings)
+        {
+            self.longest_run = Some(RepeatRun {
+                temperature,
+                readings,
+            });
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    fn merge(&mut self, later: SensorAccumulator) {
+        if later.moments.count == 0 {
+            self.alerts += later.alerts;
+            self.dropped += later.dropped;
+            self.late += later.late;
+            self.missed += later.missed;
+            self.clock_offset = later.clock_offset.or(self.clock_offset);
+            self.merge_gaps(later.expected, later.gaps);
+            return;
+        }
+
+        if self.moments.count == 0 {
+            let (alerts, dropped, late, missed, clock_offset) = (
+                self.alerts,
+                self.dropped,
+                self.late,
+                self.missed,
+                self.clock_offset,
+            );
+            let (expected, gaps) = (self.expected, std::mem::take(&mut self.gaps));
+            *self = later;
+            self.alerts += alerts;
+            self.dropped += dropped;
+            self.late += late;
+            self.missed += missed;
+            self.clock_offset = self.clock_offset.or(clock_offset);
+            self.merge_gaps(expected, gaps);
+            return;
+        }
+
+        let joined = self.last == later.first;
+        let self_uniform = self.leading_run == self.moments.count;
+        let later_uniform = later.leading_run == later.moments.count;
+
+        if let (true, Some(last)) = (joined, self.last) {
+            self.extend_longest_run(last, self.current_run + later.leading_run);
+
+            if self_uniform {
+                self.leading_run += later.leading_run;
+            }
+        }
+
+        self.current_run = if joined && later_uniform {
+            self.current_run + later.current_run
+        } else {
+            later.current_run
+        };
+
+        if let Some(run) = later.longest_run {
+            self.extend_longest_run(run.temperature, run.readings);
+        }
+        self.last = later.last;
+
+        self.moments.merge(&later.moments);
+        self.min = self.min.min(later.min);
+        self.max = self.max.max(later.max);
+        self.alerts += later.alerts;
+        self.dropped += later.dropped;
+        self.late += later.late;
+        self.missed += later.missed;
+        self.clock_offset = later.clock_offset.or(self.clock_offset);
+        self.sequences.extend(later.sequences);
+        self.merge_gaps(later.expected, later.gaps);
+    }
+
+    #[cfg(feature = "parallel")]
+    fn merge_gaps(&mut self, expected: Option<usize>, mut gaps: Vec<Range<u64>>) {
+        if let Some(expected) = expected {
+            self.expected = Some(self.expected.unwrap_or(0) + expected);
+        }
+        self.gaps.append(&mut gaps);
+        self.gaps.sort_unstable_by_key(|x| x.start);
+    }
+
+    fn stats(&self, sensor_id: usize) -> SensorStats {
+        let has_readings = self.moments.count > 0;
+
+        SensorStats {
+            sensor_id,
+            readings: self.moments.count,
+            min: self.min,
+            max: self.max,
+            mean: has_readings.then(|| Temperature::from_fahrenheit(self.moments.mean)),
+            std_dev: has_readings.then(|| self.moments.std_dev()),
+            longest_repeat: self.longest_run.clone(),
+            alerts: self.alerts,
+            dropped: self.dropped,
+            late: self.late,
+            missed: self.missed,
+            clock_offset: self.clock_offset,
+            expected: self.expected,
+            gaps: self.gaps.clone(),
+        }
+    }
+}
+
+/// Finds the largest difference between two recordings no more than `window` apart as the
+/// recordings arrive. Two monotonic deques hold the candidates for the lowest and highest
+/// temperature in the current window, so each new recording only has to be compared against the
+/// front of each deque.
+#[derive(Clone, Debug)]
+struct DifferenceTracker {
+    window: Duration,
+
+    // Temperatures increase from front to back, so the front is the window's minimum
+    min_candidates: VecDeque<Recording>,
+
+    // Temperatures decrease from front to back, so the front is the window's maximum
+    max_candidates: VecDeque<Recording>,
+
+    largest: Option<TemperatureDifference>,
+}
+
+impl DifferenceTracker {
+    fn new(window: Duration) -> DifferenceTracker {
+        DifferenceTracker {
+            window,
+            min_candidates: VecDeque::new(),
+            max_candidates: VecDeque::new(),
+            largest: None,
+        }
+    }
+
+    fn push(&mut self, recording: &Recording) {
+        // Drop anything that's too old to be compared with the new recording
+        let is_expired = |x: &Recording| {
+            recording.timestamp.saturating_duration_since(x.timestamp) > self.window
+        };
+
+        while self.min_candidates.front().is_some_and(is_expired) {
+            self.min_candidates.pop_front();
+        }
+
+        while self.max_candidates.front().is_some_and(is_expired) {
+            self.max_candidates.pop_front();
+        }
+
+        // The biggest difference ending at this recording is against the window's min or max
+        for start in [self.min_candidates.front(), self.max_candidates.front()]
+            .into_iter()
+            .flatten()
+        {
+            let difference = recording.temperature.difference(start.temperature);
+
+            if self
+                .largest
+                .as_ref()
+                .is_none_or(|x| difference > x.difference)
+            {
+                self.largest = Some(TemperatureDifference {
+                    start: start.clone(),
+                    end: recording.clone(),
+                    difference,
+                });
+            }
+        }
+
+        while self
+            .min_candidates
+            .back()
+            .is_some_and(|x| x.temperature >= recording.temperature)
+        {
+            self.min_candidates.pop_back();
+        }
+        self.min_candidates.push_back(recording.clone());
+
+        while self
+            .max_candidates
+            .back()
+            .is_some_and(|x| x.temperature <= recording.temperature)
+        {
+            self.max_candidates.pop_back();
+        }
+        self.max_candidates.push_back(recording.clone());
+    }
+}
@@ -0,0 +1,69 @@
+//! Pinning the sensor threads and the report thread to cores, so the OS moving threads between
+//! cores doesn't get mixed up with what the simulation itself is doing. Needs the `affinity`
+//! feature.
+
+use serde::Deserialize;
+
+/// Which cores the threads are pinned to, written as an `[affinity]` table in the config file.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AffinityConfig {
+    /// The core the report thread runs on
+    pub report_core: usize,
+
+    /// The cores the sensor threads take in turn, sensor `i` running on
+    /// `sensor_cores[i % sensor_cores.len()]`. When empty the sensors take turns on every core
+    /// except the report thread's, unless there's only one core
+    pub sensor_cores: Vec<usize>,
+}
+
+impl AffinityConfig {
+    /// The core sensor `sensor_id` runs on, out of `core_count` cores.
+    pub fn sensor_core(&self, sensor_id: usize, core_count: usize) -> usize {
+        if !self.sensor_cores.is_empty() {
+            return self.sensor_cores[sensor_id % self.sensor_cores.len()];
+        }
+
+        if core_count <= 1 {
+            return self.report_core;
+        }
+
+        // Counts through the cores as if the report thread's wasn't there
+        let core = sensor_id % (core_count - 1);
+        if core >= self.report_core {
+            core + 1
+        } else {
+            core
+        }
+    }
+}
+
+/// Whether threads can be pinned in this build.
+pub fn is_available() -> bool {
+    cfg!(feature = "affinity")
+}
+
+/// How many cores threads can be pinned to, 0 if they can't be.
+pub fn core_count() -> usize {
+    #[cfg(feature = "affinity")]
+    {
+        core_affinity::get_core_ids().map_or(0, |x| x.len())
+    }
+
+    #[cfg(not(feature = "affinity"))]
+    {
+        0
+    }
+}
+
+/// Pins the calling thread to `core`. A thread that can't be pinned carries on wherever the OS
+/// puts it.
+pub(crate) fn pin_current_thread(core: usize) {
+    #[cfg(feature = "affinity")]
+    if !core_affinity::set_for_current(core_affinity::CoreId { id: core }) {
+        eprintln!("Couldn't pin a thread to core {}", core);
+    }
+
+    #[cfg(not(feature = "affinity"))]
+    let _ = core;
+}
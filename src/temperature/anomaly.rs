@@ -0,0 +1,212 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use super::rules::RuleEngine;
+use super::{Recording, SimulationConfig, Temperature};
+
+/// Settings for flagging sudden temperature spikes, written as an `[anomaly]` table in the config
+/// file.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AnomalyConfig {
+    /// How many degrees Fahrenheit a reading can be from its sensor's baseline before it's flagged
+    pub threshold: f64,
+
+    /// How many of the sensor's previous readings the baseline is the mean of. No readings are
+    /// flagged until a sensor has recorded this many
+    pub baseline_readings: usize,
+}
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        AnomalyConfig {
+            threshold: 50.0,
+            baseline_readings: 10,
+        }
+    }
+}
+
+/// Settings for sending dangerously extreme readings to the report thread ahead of everything
+/// else, written as a `[priority]` table in the config file.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PriorityConfig {
+    /// Readings colder than this many degrees Fahrenheit raise an alert straight away
+    pub below: f64,
+
+    /// Readings hotter than this many degrees Fahrenheit raise an alert straight away
+    pub above: f64,
+}
+
+impl Default for PriorityConfig {
+    fn default() -> Self {
+        PriorityConfig {
+            below: -90.0,
+            above: 60.0,
+        }
+    }
+}
+
+impl PriorityConfig {
+    /// Returns an alert if the recording is beyond either limit.
+    pub fn check(&self, recording: &Recording) -> Option<Alert> {
+        let value = recording.temperature.fahrenheit();
+        let limit = if value < self.below {
+            self.below
+        } else if value > self.above {
+            self.above
+        } else {
+            return None;
+        };
+
+        Some(Alert {
+            recording: recording.clone(),
+            kind: AlertKind::Extreme,
+            baseline: Temperature::from_fahrenheit(limit),
+            deviation: value - limit,
+            rule: None,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    /// The reading is too far from its sensor's recent readings
+    #[default]
+    Spike,
+
+    /// The reading is beyond one of the `[priority]` limits
+    Extreme,
+
+    /// The reading set off one of the `[[alert_rule]]`s
+    Rule,
+}
+
+/// A reading that deviated too far from its sensor's recent readings, went past one of the
+/// priority limits, or set off an alert rule.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Alert {
+    pub recording: Recording,
+
+    /// Alerts from before there were priority limits were always spikes
+    #[serde(default)]
+    pub kind: AlertKind,
+
+    /// Mean of the sensor's readings before this one, the limit an extreme reading or a rule's
+    /// streak went past, or the other end of a rule's swing
+    pub baseline: Temperature,
+
+    /// How far the reading is from the baseline, in degrees Fahrenheit. Negative if it's colder
+    pub deviation: f64,
+
+    /// Name of the rule that raised the alert, if a rule did
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+}
+
+/// Keeps a rolling baseline per sensor and checks every new reading against it.
+#[derive(Clone, Debug)]
+pub struct AnomalyDetector {
+    config: AnomalyConfig,
+
+    // The most recent readings from each sensor, in degrees Fahrenheit
+    baselines: HashMap<usize, VecDeque<f64>>,
+}
+
+impl AnomalyDetector {
+    pub fn new(config: AnomalyConfig) -> AnomalyDetector {
+        AnomalyDetector {
+            config,
+            baselines: HashMap::new(),
+        }
+    }
+
+    /// Returns an alert if the recording is too far from its sensor's baseline, then adds the
+    /// recording to the baseline.
+    pub fn check(&mut self, recording: &Recording) -> Option<Alert> {
+        let window = self.config.baseline_readings;
+        let baseline = self.baselines.entry(recording.sensor_id).or_default();
+        let value = recording.temperature.fahrenheit();
+
+        let alert = if window > 0 && baseline.len() == window {
+            let mean = baseline.iter().sum::<f64>() / window as f64;
+            let deviation = value - mean;
+
+            if deviation.abs() > self.config.threshold {
+                Some(Alert {
+                    recording: recording.clone(),
+                    kind: AlertKind::Spike,
+                    baseline: Temperature::from_fahrenheit(mean),
+                    deviation,
+                    rule: None,
+                })
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+
+        baseline.push_back(value);
+        if baseline.len() > window {
+            baseline.pop_front();
+        }
+
+        alert
+    }
+}
+
+/// How many alerts for extreme readings can wait to be taken off the priority channel. An alert
+/// that doesn't fit is raised when its reading arrives instead.
+pub(crate) const PRIORITY_CAPACITY: usize = 64;
+
+/// Everything that raises alerts about readings as they arrive: the anomaly detector, the
+/// priority limits and the alert rules.
+pub(crate) struct Alerts {
+    detector: Option<AnomalyDetector>,
+    priority: Option<PriorityConfig>,
+    rules: RuleEngine,
+
+    // Sensor & sequence number of extreme readings whose alert went ahead of them, so it isn't
+    // raised again when the reading itself arrives
+    raised_ahead: HashSet<(usize, u64)>,
+}
+
+impl Alerts {
+    pub(crate) fn new(config: &SimulationConfig) -> Alerts {
+        Alerts {
+            detector: config.anomaly.clone().map(AnomalyDetector::new),
+            priority: config.priority.clone(),
+            rules: RuleEngine::new(config),
+            raised_ahead: HashSet::new(),
+        }
+    }
+
+    /// Remembers an alert that was raised before its reading arrived.
+    pub(crate) fn raise_ahead(&mut self, alert: &Alert) {
+        self.raised_ahead
+            .insert((alert.recording.sensor_id, alert.recording.sequence));
+    }
+
+    /// The alerts a reading raises as it arrives. An extreme reading only raises one here if its
+    /// alert couldn't go ahead of it (e.g. the priority channel was full).
+    pub(crate) fn check(&mut self, recording: &Recording) -> Vec<Alert> {
+        let mut alerts = Vec::new();
+
+        if let Some(alert) = self.detector.as_mut().and_then(|x| x.check(recording)) {
+            alerts.push(alert);
+        }
+
+        if let Some(alert) = self.priority.as_ref().and_then(|x| x.check(recording)) {
+            let key = (recording.sensor_id, recording.sequence);
+            if !self.raised_ahead.remove(&key) {
+                alerts.push(alert);
+            }
+        }
+
+        alerts.extend(self.rules.check(recording));
+        alerts
+    }
+}
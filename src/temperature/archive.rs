@@ -0,0 +1,96 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+use super::schedule::SensorSchedules;
+use super::{timestamp, Recording, Report, ReportSink, SimulationConfig};
+
+/// Writes each report's raw readings to a gzip-compressed CSV file once the report has been
+/// generated, `readings-001.csv.gz` for the first report and so on, with the columns
+/// `sensor_id,sequence,timestamp,temperature` (timestamps and temperatures as in the
+/// `CsvRecordingSink`). A reading is written with the report for the period it was due in, the
+/// same as it's counted, and let go of once it's written, so a long run doesn't hold on to more
+/// than a period or so of readings.
+pub struct ArchiveSink {
+    dir: PathBuf,
+    config: SimulationConfig,
+    schedules: Option<SensorSchedules>,
+    start: Option<Instant>,
+    readings: Vec<Recording>,
+}
+
+impl ArchiveSink {
+    /// Creates the output directory if it doesn't exist yet.
+    pub fn new(dir: impl Into<PathBuf>, config: &SimulationConfig) -> io::Result<ArchiveSink> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+
+        Ok(ArchiveSink {
+            dir,
+            config: config.clone(),
+            schedules: None,
+            start: None,
+            readings: Vec::new(),
+        })
+    }
+
+    /// Takes the readings that were due before the end of the `index`th report's period, or
+    /// every reading if the report is partial, leaving the next period's behind.
+    fn take_period(&mut self, index: usize, partial: bool) -> Vec<Recording> {
+        let (Some(schedules), Some(start), false) = (&self.schedules, self.start, partial) else {
+            return mem::take(&mut self.readings);
+        };
+
+        let period_end = start + self.config.scaled_report_interval() * (index as u32 + 1);
+        schedules.take_due_before(&mut self.readings, period_end)
+    }
+}
+
+fn write_readings(path: &Path, readings: &[Recording]) -> io::Result<()> {
+    let file = BufWriter::new(File::create(path)?);
+    let mut writer = GzEncoder::new(file, Compression::default());
+    writeln!(writer, "sensor_id,sequence,timestamp,temperature")?;
+
+    for recording in readings {
+        writeln!(
+            writer,
+            "{},{},{},{}",
+            recording.sensor_id,
+            recording.sequence,
+            timestamp::to_nanos(recording.timestamp),
+            recording.temperature.fahrenheit()
+        )?;
+    }
+
+    writer.finish()?.flush()
+}
+
+impl ReportSink for ArchiveSink {
+    fn handle_start(&mut self, start: Instant) {
+        self.start = Some(start);
+        self.schedules = Some(SensorSchedules::new(start, &self.config));
+    }
+
+    fn handle_recording(&mut self, recording: &Recording) {
+        self.readings.push(recording.clone());
+    }
+
+    fn handle_report(&mut self, report: &Report) {
+        let readings = self.take_period(report.index, report.partial);
+        let path = self
+            .dir
+            .join(format!("readings-{:03}.csv.gz", report.index + 1));
+
+        if let Err(err) = write_readings(&path, &readings) {
+            eprintln!("Couldn't archive readings to {}: {}", path.display(), err);
+        }
+
+        // The buffer would otherwise stay as big as the busiest period so far
+        self.readings.shrink_to(self.readings.len() * 2);
+    }
+}
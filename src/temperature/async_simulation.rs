@@ -0,0 +1,613 @@
+//! The same simulation as `TemperatureSimulation`, built from tokio tasks instead of threads. Each
+//! sensor is a task that sleeps until its next reading is due, every reading goes over one bounded
+//! `tokio::sync::mpsc` channel, and the report task waits for whichever comes first: the next
+//! reading or the end of the period.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio::task::{block_in_place, spawn_blocking, JoinError};
+use tokio::time::{interval_at, sleep_until, Instant};
+use tracing::Instrument;
+
+use super::anomaly::{Alerts, PRIORITY_CAPACITY};
+use super::broadcast::{Broadcaster, OutputEvent};
+use super::callbacks::{OnAlert, OnReading, OnReport};
+use super::error::{self, SimulationError};
+use super::reorder::ReorderBuffer;
+use super::schedule::{SamplingJitter, SensorSchedules};
+use super::simulation::{count_missed, lock_history, sensor_sources};
+use super::skew::{self, SkewCorrector};
+use super::validation::{ReadingFilter, RejectionCounts};
+use super::watchdog::{SensorLiveness, Watchdog};
+use super::{
+    timestamp, Alert, OverflowPolicy, Recording, Report, ReportAccumulator, ReportHistory,
+    ReportSink, RunSummary, SensorSource, SimulationConfig, StopHandle,
+};
+
+/// Runs the sensors and the report loop as tokio tasks. Time always comes from tokio rather than
+/// a `Clock`, and `transport` is ignored since readings always go over a tokio channel. That
+/// channel can't have readings taken back out of it, so `queue_overflow = "drop_oldest"` is
+/// treated like `"block"`. A sensor task that panics isn't restarted, whatever `watchdog.restart`
+/// says, and `synchronized_sampling` is ignored.
+pub struct AsyncTemperatureSimulation {
+    config: SimulationConfig,
+    sources: Option<Vec<Box<dyn SensorSource>>>,
+    sinks: Vec<Box<dyn ReportSink>>,
+    running: Arc<AtomicBool>,
+
+    // Set by `run` and never cleared, so it stays set once the simulation has finished
+    started: bool,
+
+    history: Arc<Mutex<ReportHistory>>,
+
+    // The most readings that were ever queued when the report loop took one
+    peak_queue_depth: usize,
+}
+
+impl AsyncTemperatureSimulation {
+    pub fn new() -> AsyncTemperatureSimulation {
+        AsyncTemperatureSimulation::with_config(SimulationConfig::default())
+    }
+
+    pub fn with_config(config: SimulationConfig) -> AsyncTemperatureSimulation {
+        AsyncTemperatureSimulation {
+            config,
+            sources: None,
+            sinks: Vec::new(),
+            peak_queue_depth: 0,
+            running: Arc::new(AtomicBool::new(false)),
+            started: false,
+            history: Arc::new(Mutex::new(ReportHistory::new())),
+        }
+    }
+
+    /// Creates a simulation with one sensor task per source instead of the random sensors
+    /// described by the config. `config.sensor_count` is set to the number of sources.
+    pub fn with_sources(
+        mut config: SimulationConfig,
+        sources: Vec<Box<dyn SensorSource>>,
+    ) -> AsyncTemperatureSimulation {
+        config.sensor_count = sources.len();
+
+        let mut simulation = AsyncTemperatureSimulation::with_config(config);
+        simulation.sources = Some(sources);
+        simulation
+    }
+
+    /// Adds a sink that every generated report gets handed to. Sinks have to be added before
+    /// the simulation is run.
+    pub fn add_sink(&mut self, sink: impl ReportSink + 'static) {
+        self.sinks.push(Box::new(sink));
+    }
+
+    /// Calls `handler` with every report the simulation generates, on its own output thread
+    /// like any other sink. Has to be called before the simulation is run.
+    pub fn on_report(&mut self, handler: impl FnMut(&Report) + Send + 'static) {
+        self.add_sink(OnReport(handler));
+    }
+
+    /// Returns a channel that every report is sent down as it's generated, so another task can
+    /// `recv` them while the simulation runs. `recv` returns `None` once the simulation has
+    /// finished and the last report has been taken. Fails once the simulation has been run.
+    pub fn subscribe_reports(
+        &mut self,
+    ) -> Result<mpsc::UnboundedReceiver<Report>, SimulationError> {
+        if self.started {
+            return Err(SimulationError::AlreadyStarted);
+        }
+
+        let (sender, receiver) = mpsc::unbounded_channel();
+        self.on_report(move |report| {
+            let _ = sender.send(report.clone());
+        });
+
+        Ok(receiver)
+    }
+
+    /// Calls `handler` with every reading as the report thread receives it. Has to be called
+    /// before the simulation is run.
+    pub fn on_reading(&mut self, handler: impl FnMut(&Recording) + Send + 'static) {
+        self.add_sink(OnReading(handler));
+    }
+
+    /// Calls `handler` with every alert as soon as it's raised. Has to be called before the
+    /// simulation is started.
+    pub fn on_alert(&mut self, handler: impl FnMut(&Alert) + Send + 'static) {
+        self.add_sink(OnAlert(handler));
+    }
+
+    /// Returns a handle that can stop the simulation from another thread, e.g. a Ctrl-C handler.
+    pub fn stop_handle(&self) -> StopHandle {
+        StopHandle::new(self.running.clone(), None)
+    }
+
+    /// Returns a copy of every report generated so far, oldest first.
+    pub fn all_reports(&self) -> Vec<Report> {
+        lock_history(&self.history).all_reports().to_vec()
+    }
+
+    /// Returns a copy of the whole report history.
+    pub fn history(&self) -> ReportHistory {
+        lock_history(&self.history).clone()
+    }
+
+    /// Summarises every report generated so far, along with the deepest the queue got. Once the
+    /// simulation has finished this is the summary the sinks were given.
+    pub fn summary(&self) -> RunSummary {
+        RunSummary::from_reports(
+            lock_history(&self.history).all_reports(),
+            Some(self.peak_queue_depth),
+        )
+    }
+
+    pub fn config(&self) -> &SimulationConfig {
+        &self.config
+    }
+
+    /// Spawns the sensor tasks and runs the report loop until the simulation is stopped (e.g.
+    /// through a `StopHandle`), every sensor goes offline, or `config.hours` have been reported
+    /// on. Has to be run on a multi-threaded tokio runtime, since sources are read with
+    /// `block_in_place` in case they block. Does nothing if the simulation is already running.
+    /// Fails if a task panicked or the sinks stopped early, in which case some reports may be
+    /// missing.
+    pub async fn run(&mut self) -> Result<(), SimulationError> {
+        if self.running.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+        self.started = true;
+
+        let config = self.config.clone();
+        let scaled_report_interval = config.scaled_report_interval();
+        let late_grace = config.scaled_late_grace();
+
+        let sources = sensor_sources(&config, self.sources.take());
+        let sensor_count = sources.len();
+
+        // Unlike the threaded transports there's one channel shared by every sensor, so it gets
+        // room for every sensor's queue
+        let (temperature_sender, mut temperature_receiver) =
+            mpsc::channel::<Recording>(config.queue_capacity * sensor_count);
+        let dropped_counts: Arc<Vec<AtomicUsize>> =
+            Arc::new((0..sensor_count).map(|_| AtomicUsize::new(0)).collect());
+        let filter = ReadingFilter::new(&config);
+        let rejections = Arc::new(RejectionCounts::default());
+
+        let start = Instant::now();
+        let mut sensor_handles = Vec::new();
+        let liveness = Arc::new(SensorLiveness::new(sensor_count, start.into_std()));
+        let schedules = SensorSchedules::new(start.into_std(), &config);
+
+        // Alerts for extreme readings skip the queue, like the threaded sensors'
+        let (priority_sender, mut priority_receiver) = mpsc::channel::<Alert>(PRIORITY_CAPACITY);
+
+        for (sensor_id, mut source) in sources.into_iter().enumerate() {
+            let local_sender = temperature_sender.clone();
+            let local_running = self.running.clone();
+            let local_dropped_counts = dropped_counts.clone();
+            let local_rejections = rejections.clone();
+            let queue_overflow = config.queue_overflow;
+            let high_frequency = config.high_frequency;
+            let local_liveness = liveness.clone();
+            let schedule = *schedules.sensor(sensor_id);
+            let mut jitter = SamplingJitter::for_sensor(&config, sensor_id);
+            let clock_offset = skew::clock_offset(&config, sensor_id);
+            let local_priority = config
+                .priority
+                .clone()
+                .map(|x| (x, priority_sender.clone()));
+
+            let span = tracing::info_span!("sensor", sensor_id);
+            sensor_handles.push(tokio::spawn(
+                async move {
+                    // Readings are due a fixed sampling period apart from the start, like the
+                    // threaded sensors'
+                    let mut next_sequence = 0;
+
+                    while local_running.load(Ordering::Relaxed) {
+                        let wake_at = schedule.due(next_sequence) + jitter.delay();
+                        if !(high_frequency && wake_at <= Instant::now().into_std()) {
+                            sleep_until(Instant::from_std(wake_at)).await;
+                        }
+
+                        // Sources can block (e.g. waiting on stdin), which mustn't hold up the other
+                        // tasks on the same worker
+                        let mut recording = match block_in_place(|| {
+                            filter.next_reading(source.as_mut(), &local_rejections)
+                        }) {
+                            Some(recording) => recording,
+                            None => return,
+                        };
+                        recording.timestamp = Instant::now().into_std();
+                        if high_frequency {
+                            // Caught up in a burst, counted as taken when it was due
+                            recording.timestamp = recording.timestamp.min(wake_at);
+                        }
+                        local_liveness.reading(sensor_id, recording.timestamp);
+                        recording.sequence = schedule.catch_up(next_sequence, recording.timestamp);
+                        next_sequence = recording.sequence + 1;
+                        recording.timestamp = skew::shift(recording.timestamp, clock_offset);
+                        if let Some((limits, sender)) = &local_priority {
+                            if let Some(alert) = limits.check(&recording) {
+                                let _ = sender.try_send(alert);
+                            }
+                        }
+                        tracing::trace!(
+                            temperature = recording.temperature.fahrenheit(),
+                            "reading produced"
+                        );
+
+                        // Either way a closed channel means the report loop has finished
+                        match queue_overflow {
+                            OverflowPolicy::DropNewest => match local_sender.try_send(recording) {
+                                Ok(()) => {}
+                                Err(TrySendError::Full(_)) => {
+                                    local_dropped_counts[sensor_id].fetch_add(1, Ordering::Relaxed);
+                                    tracing::debug!("newest reading dropped");
+                                }
+                                Err(TrySendError::Closed(_)) => return,
+                            },
+                            OverflowPolicy::Block | OverflowPolicy::DropOldest => {
+                                if local_sender.send(recording).await.is_err() {
+                                    return;
+                                }
+                            }
+                        }
+                    }
+
+                    // Told to stop, so it doesn't owe any more readings
+                    local_liveness.stopped(sensor_id, next_sequence);
+                }
+                .instrument(span),
+            ));
+        }
+
+        // The channels close once every sensor task has dropped its senders
+        drop(temperature_sender);
+        drop(priority_sender);
+
+        // Sinks write to stdout & files, so each gets a blocking thread instead of a task
+        let (output_sender, workers) = Broadcaster::new(std::mem::take(&mut self.sinks));
+        let output_handles: Vec<_> = workers
+            .into_iter()
+            .map(|worker| spawn_blocking(move || worker.run(start.into_std())))
+            .collect();
+
+        let mut accumulator = ReportAccumulator::new(&config);
+        accumulator.start_period(start.into_std());
+        let mut alerts = Alerts::new(&config);
+        let mut reports_generated = 0;
+        let report_limit = config.report_limit();
+        let warm_up_end = start.into_std() + config.scaled_warm_up();
+
+        let receive =
+            |recording: Recording, accumulator: &mut ReportAccumulator, alerts: &mut Alerts| {
+                let latency = recording.timestamp.elapsed();
+                tracing::trace!(
+                    sensor_id = recording.sensor_id,
+                    lag_ms = latency.as_secs_f64() * 1000.0,
+                    "reading consumed"
+                );
+                output_sender.send(OutputEvent::Recording(recording.clone()));
+                accumulator.push_latency(latency);
+
+                if schedules.due(&recording) < warm_up_end {
+                    accumulator.push_warm_up(&recording);
+                    return;
+                }
+                accumulator.push_decimated(&recording);
+
+                for alert in alerts.check(&recording) {
+                    accumulator.push_alert(&alert);
+                    output_sender.send(OutputEvent::Alert(alert));
+                }
+            };
+
+        let take_discarded = |accumulator: &mut ReportAccumulator| {
+            for (sensor_id, count) in dropped_counts.iter().enumerate() {
+                accumulator.push_dropped(sensor_id, count.swap(0, Ordering::Relaxed));
+            }
+            for (reason, count) in rejections.take() {
+                accumulator.push_rejected(reason, count);
+            }
+        };
+
+        let publish = |mut report: Report| {
+            tracing::debug!(
+                index = report.index,
+                partial = report.partial,
+                generation_ms = report.generation_seconds * 1000.0,
+                "report finished"
+            );
+
+            let mut history = lock_history(&self.history);
+            report.trend = history.trend(&report);
+            report.records = history.records(&report);
+            history.push(report.clone());
+            drop(history);
+
+            output_sender.send(OutputEvent::Report(Box::new(report)));
+        };
+
+        // Periods are a fixed length from the start, like the threaded report loop's
+        let mut periods = interval_at(start + scaled_report_interval, scaled_report_interval);
+        let mut period_start = start.into_std();
+
+        // A panicked sensor task can't be told apart from one that's stopped sending readings, so
+        // both are flagged as silent
+        let mut watchdog = config
+            .watchdog
+            .enabled
+            .then(|| Watchdog::new(liveness.clone(), &config, None));
+        let shortest_sampling_period = (0..sensor_count)
+            .map(|sensor_id| config.scaled_sampling_period_for(sensor_id))
+            .min()
+            .unwrap_or_else(|| config.scaled_sampling_period());
+        let mut checks = interval_at(start, shortest_sampling_period);
+
+        // The period that just ended, waiting `late_grace` for readings taken before it ended
+        let mut closing: Option<(ReportAccumulator, usize, Instant)> = None;
+        let mut limit_reached = false;
+        let mut peak_queue_depth = 0;
+
+        // Alerts that went ahead of their readings count for the period the reading was due in
+        let raise_ahead = |alert: Alert,
+                           accumulator: &mut ReportAccumulator,
+                           closing: &mut Option<(ReportAccumulator, usize, Instant)>,
+                           alerts: &mut Alerts| {
+            tracing::debug!(sensor_id = alert.recording.sensor_id, "extreme reading");
+            alerts.raise_ahead(&alert);
+
+            match closing {
+                Some((closing, _, period_end))
+                    if schedules.due(&alert.recording) < period_end.into_std() =>
+                {
+                    closing.push_alert(&alert)
+                }
+                _ => accumulator.push_alert(&alert),
+            }
+            output_sender.send(OutputEvent::Alert(alert));
+        };
+
+        let mut skew_corrector = config
+            .clock_skew
+            .correct
+            .then(|| SkewCorrector::new(schedules.clone(), &config));
+
+        let mut reorder = ReorderBuffer::new(config.scaled_reorder_window());
+
+        // Queued readings are taken off in batches, into a buffer that's only allocated once
+        let ingest_batch = config.ingest_batch;
+        let mut batch = Vec::with_capacity(ingest_batch);
+
+        // Skew is corrected for and readings are put back in order before they're used, like the
+        // threaded report loop's
+        let mut arrive = |mut recording: Recording,
+                          accumulator: &mut ReportAccumulator,
+                          reorder: &mut ReorderBuffer| {
+            if let Some(offset) = skew_corrector
+                .as_mut()
+                .and_then(|x| x.correct(&mut recording))
+            {
+                accumulator.push_clock_offset(recording.sensor_id, offset);
+            }
+            reorder.push(recording);
+        };
+
+        // Readings go in the period they were due in
+        let route = |recording: Recording,
+                     accumulator: &mut ReportAccumulator,
+                     closing: &mut Option<(ReportAccumulator, usize, Instant)>,
+                     alerts: &mut Alerts,
+                     period_start: std::time::Instant| {
+            let due = schedules.due(&recording);
+
+            match closing {
+                Some((closing, _, period_end)) if due < period_end.into_std() => {
+                    receive(recording, closing, alerts)
+                }
+                _ if due < period_start => {
+                    tracing::debug!(sensor_id = recording.sensor_id, "late reading");
+                    accumulator.push_late(recording.sensor_id, 1);
+                }
+                _ => receive(recording, accumulator, alerts),
+            }
+        };
+
+        async {
+            loop {
+                while let Some(recording) = reorder.pop_ready(Instant::now().into_std()) {
+                    route(
+                        recording,
+                        &mut accumulator,
+                        &mut closing,
+                        &mut alerts,
+                        period_start,
+                    );
+                }
+
+                let close_at = closing
+                    .as_ref()
+                    .map(|(_, _, period_end)| *period_end + late_grace);
+                let release_at = reorder.next_release().map(Instant::from_std);
+
+                tokio::select! {
+                    // The end of a period is checked first so a steady stream of readings can't
+                    // hold up a report
+                    biased;
+
+                    _ = sleep_until(close_at.unwrap_or(start)), if close_at.is_some() => {
+                        // Whatever's already queued was sent before the grace period ran out,
+                        // and nothing's held back from the report
+                        while let Ok(recording) = temperature_receiver.try_recv() {
+                            arrive(recording, &mut accumulator, &mut reorder);
+                        }
+                        while let Ok(alert) = priority_receiver.try_recv() {
+                            raise_ahead(alert, &mut accumulator, &mut closing, &mut alerts);
+                        }
+                        while let Some(recording) = reorder.pop() {
+                            route(
+                                recording,
+                                &mut accumulator,
+                                &mut closing,
+                                &mut alerts,
+                                period_start,
+                            );
+                        }
+
+                        let Some((mut ended, index, period_end)) = closing.take() else {
+                            continue;
+                        };
+                        tracing::debug!(index, "report started");
+                        let period = (period_end - scaled_report_interval).into_std()
+                            ..period_end.into_std();
+                        count_missed(&mut ended, &schedules, &liveness, period);
+                        if let Some(mut report) = ended.finish(index, &config) {
+                            report.period_end = timestamp::wall_time(period_end.into_std());
+                            publish(report);
+                        }
+
+                        if limit_reached {
+                            break;
+                        }
+                    }
+
+                    period_end = periods.tick(), if !limit_reached && closing.is_none() => {
+                        take_discarded(&mut accumulator);
+                        tracing::debug!(index = reports_generated, "period ended");
+
+                        // Readings from the next period go into a new accumulator while this one
+                        // waits for stragglers
+                        let ended =
+                            std::mem::replace(&mut accumulator, ReportAccumulator::new(&config));
+                        closing = Some((ended, reports_generated, period_end));
+                        reports_generated += 1;
+
+                        if report_limit == Some(reports_generated) {
+                            self.running.store(false, Ordering::SeqCst);
+                            limit_reached = true;
+                        }
+
+                        period_start = period_end.into_std();
+                        accumulator.start_period(period_start);
+                    }
+
+                    _ = checks.tick(), if watchdog.is_some() => {
+                        if let Some(watchdog) = &mut watchdog {
+                            for stalled in watchdog.check(Instant::now().into_std()) {
+                                accumulator.push_stalled(&stalled);
+                            }
+                        }
+                    }
+
+                    _ = sleep_until(release_at.unwrap_or(start)), if release_at.is_some() => {}
+
+                    Some(alert) = priority_receiver.recv() => {
+                        raise_ahead(alert, &mut accumulator, &mut closing, &mut alerts)
+                    }
+
+                    received = temperature_receiver
+                        .recv_many(&mut batch, ingest_batch) => match received {
+                        1.. => {
+                            peak_queue_depth =
+                                peak_queue_depth.max(temperature_receiver.len() + received);
+
+                            // Anything else that's already queued may have been taken before it,
+                            // so it all goes in before any are used
+                            for recording in batch.drain(..) {
+                                arrive(recording, &mut accumulator, &mut reorder);
+                            }
+                            while let Ok(recording) = temperature_receiver.try_recv() {
+                                arrive(recording, &mut accumulator, &mut reorder);
+                            }
+
+                            // An alert is always sent before its reading, so however far behind
+                            // the queue is, any alert that went ahead is raised first
+                            while let Ok(alert) = priority_receiver.try_recv() {
+                                raise_ahead(alert, &mut accumulator, &mut closing, &mut alerts);
+                            }
+                        }
+                        0 => {
+                            while let Some(recording) = reorder.pop() {
+                                route(
+                                    recording,
+                                    &mut accumulator,
+                                    &mut closing,
+                                    &mut alerts,
+                                    period_start,
+                                );
+                            }
+
+                            // Nothing else can arrive for the period waiting on stragglers
+                            if let Some((mut ended, index, period_end)) = closing.take() {
+                                tracing::debug!(index, "report started");
+                                let period = (period_end - scaled_report_interval).into_std()
+                                    ..period_end.into_std();
+                                count_missed(&mut ended, &schedules, &liveness, period);
+                                if let Some(mut report) = ended.finish(index, &config) {
+                                    report.period_end = timestamp::wall_time(period_end.into_std());
+                                    publish(report);
+                                }
+                            }
+
+                            // Every sensor has stopped, so report on the unfinished period
+                            if !limit_reached && !accumulator.is_empty() {
+                                take_discarded(&mut accumulator);
+                                tracing::debug!(index = reports_generated, "report started");
+                                let now = Instant::now().into_std();
+                                count_missed(
+                                    &mut accumulator,
+                                    &schedules,
+                                    &liveness,
+                                    period_start..now,
+                                );
+                                let report = accumulator.finish(reports_generated, &config);
+                                if let Some(mut report) = report {
+                                    report.partial = true;
+                                    report.period_end = timestamp::wall_time(now);
+                                    publish(report);
+                                }
+                            }
+
+                            break;
+                        }
+                    },
+                }
+            }
+        }
+        .await;
+
+        // Every report has been published, so the summary goes out after the last one
+        self.peak_queue_depth = peak_queue_depth;
+        output_sender.send(OutputEvent::Summary(Box::new(self.summary())));
+
+        // Closing the channel stops any sensor that's waiting to send
+        self.running.store(false, Ordering::SeqCst);
+        drop(temperature_receiver);
+        let mut results = Vec::new();
+        for sensor_handle in sensor_handles {
+            results.push(sensor_handle.await.map_err(|x| task_error("sensor", x)));
+        }
+
+        drop(output_sender);
+        for output_handle in output_handles {
+            results.push(output_handle.await.map_err(|x| task_error("output", x)));
+        }
+        error::first_cause(results)
+    }
+}
+
+/// A task that didn't finish either panicked or was cancelled because the runtime shut down.
+fn task_error(task: &'static str, err: JoinError) -> SimulationError {
+    match err.try_into_panic() {
+        Ok(payload) => SimulationError::panicked(task, payload),
+        Err(_) => SimulationError::Disconnected(task),
+    }
+}
+
+impl Default for AsyncTemperatureSimulation {
+    fn default() -> Self {
+        AsyncTemperatureSimulation::new()
+    }
+}
@@ -0,0 +1,98 @@
+//! With `synchronized_sampling` every sensor waits at a barrier before each reading, so they all
+//! take it at the same instant instead of whenever their own thread happens to wake up. Unlike
+//! `std::sync::Barrier` a sensor can leave, since a sensor that's stopped or gone offline would
+//! otherwise hold the rest up forever.
+
+use std::sync::{Arc, Condvar, Mutex};
+
+use super::Clock;
+
+pub(crate) struct SamplingBarrier {
+    clock: Arc<dyn Clock>,
+    state: Mutex<BarrierState>,
+    released: Condvar,
+}
+
+struct BarrierState {
+    // Sensors that haven't left yet
+    parties: usize,
+    waiting: usize,
+
+    // Counts up each time the waiting sensors are let go
+    generation: u64,
+}
+
+impl SamplingBarrier {
+    pub(crate) fn new(parties: usize, clock: Arc<dyn Clock>) -> SamplingBarrier {
+        SamplingBarrier {
+            clock,
+            state: Mutex::new(BarrierState {
+                parties,
+                waiting: 0,
+                generation: 0,
+            }),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Blocks until every sensor that hasn't left is waiting too.
+    ///
+    /// A waiting sensor isn't asleep on the clock, so it stops being a participant until it's
+    /// let go, otherwise a virtual clock couldn't move on for a sensor that's still sleeping
+    /// (e.g. waiting for room in its queue). The last one to arrive makes the others participants
+    /// again before it lets them go, so time can't skip ahead while they're waking up.
+    pub(crate) fn wait(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.waiting += 1;
+
+        if state.waiting >= state.parties {
+            // This sensor never stopped being a participant
+            let others = state.waiting - 1;
+            self.release(&mut state, others);
+            return;
+        }
+
+        self.clock.remove_participant();
+        let generation = state.generation;
+        while state.generation == generation {
+            state = self.released.wait(state).unwrap();
+        }
+    }
+
+    /// Waits for one more sensor from its next reading on, e.g. one that's been added while the
+    /// simulation runs.
+    pub(crate) fn join(&self) {
+        self.state.lock().unwrap().parties += 1;
+    }
+
+    /// Stops waiting for this sensor, letting the others go if they were only waiting on it.
+    pub(crate) fn leave(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.parties -= 1;
+
+        if state.waiting > 0 && state.waiting >= state.parties {
+            let waiting = state.waiting;
+            self.release(&mut state, waiting);
+        }
+    }
+
+    // `waiting` is how many of the sensors being let go stopped being participants
+    fn release(&self, state: &mut BarrierState, waiting: usize) {
+        for _ in 0..waiting {
+            self.clock.add_participant();
+        }
+
+        state.waiting = 0;
+        state.generation += 1;
+        self.released.notify_all();
+    }
+}
+
+/// Leaves the barrier when it's dropped, however the sensor's thread ends.
+pub(crate) struct BarrierSeat(pub(crate) Arc<SamplingBarrier>);
+
+impl Drop for BarrierSeat {
+    fn drop(&mut self) {
+        self.0.leave();
+    }
+}
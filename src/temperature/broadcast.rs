@@ -0,0 +1,72 @@
+//! Every sink gets every reading, alert and report on its own thread, from its own queue. A sink
+//! that's slow (e.g. writing to a file or posting to a server) only holds itself up, and a sink
+//! that panics only misses what comes after, rather than the sinks taking turns on one thread.
+
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::time::Instant;
+
+use super::{Alert, Recording, Report, ReportSink, RunSummary};
+
+pub(crate) enum OutputEvent {
+    Recording(Recording),
+    Alert(Alert),
+    // Reports are much bigger than recordings, which go through the queues far more often
+    Report(Box<Report>),
+    OnDemandReport(Box<Report>),
+    Summary(Box<RunSummary>),
+}
+
+/// Hands each event to every sink's queue. Clones share the same sinks.
+#[derive(Clone)]
+pub(crate) struct Broadcaster {
+    senders: Vec<Sender<Arc<OutputEvent>>>,
+}
+
+impl Broadcaster {
+    /// Creates a queue for each sink. The sinks only see anything once their `SinkWorker` is
+    /// run, and stop once every clone of the broadcaster has been dropped.
+    pub(crate) fn new(sinks: Vec<Box<dyn ReportSink>>) -> (Broadcaster, Vec<SinkWorker>) {
+        let (senders, workers) = sinks
+            .into_iter()
+            .map(|sink| {
+                let (sender, receiver) = mpsc::channel();
+                (sender, SinkWorker { sink, receiver })
+            })
+            .unzip();
+
+        (Broadcaster { senders }, workers)
+    }
+
+    /// Sinks share the one copy of the event. A sink whose thread has panicked is skipped, its
+    /// panic comes out when the thread is joined.
+    pub(crate) fn send(&self, event: OutputEvent) {
+        let event = Arc::new(event);
+        for sender in self.senders.iter() {
+            let _ = sender.send(event.clone());
+        }
+    }
+}
+
+/// A sink and the queue of events waiting for it.
+pub(crate) struct SinkWorker {
+    sink: Box<dyn ReportSink>,
+    receiver: Receiver<Arc<OutputEvent>>,
+}
+
+impl SinkWorker {
+    /// Hands the sink every event in the order it was sent, until the broadcaster is dropped.
+    pub(crate) fn run(mut self, start: Instant) {
+        self.sink.handle_start(start);
+
+        for event in self.receiver {
+            match &*event {
+                OutputEvent::Recording(recording) => self.sink.handle_recording(recording),
+                OutputEvent::Alert(alert) => self.sink.handle_alert(alert),
+                OutputEvent::Report(report) => self.sink.handle_report(report),
+                OutputEvent::OnDemandReport(report) => self.sink.handle_on_demand_report(report),
+                OutputEvent::Summary(summary) => self.sink.handle_summary(summary),
+            }
+        }
+    }
+}
@@ -0,0 +1,32 @@
+//! Sinks that just call a closure, for embedders that want to react to one kind of event without
+//! writing a whole `ReportSink`.
+
+use super::{Alert, Recording, Report, ReportSink};
+
+pub(crate) struct OnReport<F>(pub(crate) F);
+
+impl<F: FnMut(&Report) + Send> ReportSink for OnReport<F> {
+    fn handle_report(&mut self, report: &Report) {
+        (self.0)(report)
+    }
+}
+
+pub(crate) struct OnReading<F>(pub(crate) F);
+
+impl<F: FnMut(&Recording) + Send> ReportSink for OnReading<F> {
+    fn handle_report(&mut self, _report: &Report) {}
+
+    fn handle_recording(&mut self, recording: &Recording) {
+        (self.0)(recording)
+    }
+}
+
+pub(crate) struct OnAlert<F>(pub(crate) F);
+
+impl<F: FnMut(&Alert) + Send> ReportSink for OnAlert<F> {
+    fn handle_report(&mut self, _report: &Report) {}
+
+    fn handle_alert(&mut self, alert: &Alert) {
+        (self.0)(alert)
+    }
+}
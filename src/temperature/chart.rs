@@ -0,0 +1,172 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use plotters::prelude::*;
+
+use super::{Recording, Report, ReportSink, SimulationConfig};
+
+const CHART_SIZE: (u32, u32) = (1280, 720);
+
+/// Draws a PNG chart for every report in a directory, next to the report as text. Each sensor's
+/// readings during the report's period are drawn as a line, the lowest & highest temps are
+/// marked, and the window the largest difference happened in is shaded. Files are named
+/// `report-001.png` and `report-001.txt`, counting from 1.
+pub struct ChartSink {
+    dir: PathBuf,
+    config: SimulationConfig,
+
+    // Readings that haven't been charted yet. They can arrive before the report for the
+    // previous period, so each report only takes the ones from its own period
+    recordings: Vec<Recording>,
+}
+
+impl ChartSink {
+    /// Creates the directory if it doesn't exist. Temperatures are drawn in `config.unit`.
+    pub fn new(dir: impl Into<PathBuf>, config: &SimulationConfig) -> io::Result<ChartSink> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+
+        Ok(ChartSink {
+            dir,
+            config: config.clone(),
+            recordings: Vec::new(),
+        })
+    }
+
+    fn draw(
+        &self,
+        path: &Path,
+        report: &Report,
+        recordings: &[Recording],
+    ) -> Result<(), Box<dyn Error>> {
+        let unit = self.config.unit;
+
+        // Simulated minutes into the period & temperature in the chosen unit
+        let point = |recording: &Recording| {
+            let offset = (recording.wall_time() - report.period_start)
+                .to_std()
+                .unwrap_or_default();
+
+            (
+                self.config.simulated_minutes(offset),
+                recording.temperature.in_unit(unit),
+            )
+        };
+
+        let mut series: BTreeMap<usize, Vec<(f64, f64)>> = BTreeMap::new();
+        for recording in recordings {
+            series
+                .entry(recording.sensor_id)
+                .or_default()
+                .push(point(recording));
+        }
+
+        let points = series.values().flatten();
+        let (mut low, mut high) = points.fold((f64::INFINITY, f64::NEG_INFINITY), |acc, x| {
+            (acc.0.min(x.1), acc.1.max(x.1))
+        });
+        if low > high {
+            (low, high) = (0.0, 1.0);
+        }
+        let padding = ((high - low) * 0.05).max(1.0);
+        let (low, high) = (low - padding, high + padding);
+
+        let last_minute = series.values().flatten().map(|x| x.0).fold(0.0, f64::max);
+        let minutes = last_minute.max(self.config.report_interval_minutes as f64);
+
+        let root = BitMapBackend::new(path, CHART_SIZE).into_drawing_area();
+        root.fill(&WHITE)?;
+
+        let mut chart = ChartBuilder::on(&root)
+            .caption(
+                format!("Report {} (hour {})", report.index + 1, report.hour()),
+                ("sans-serif", 24),
+            )
+            .margin(16)
+            .x_label_area_size(40)
+            .y_label_area_size(60)
+            .build_cartesian_2d(0.0..minutes, low..high)?;
+
+        chart
+            .configure_mesh()
+            .x_desc("Minutes into the period")
+            .y_desc(format!("Temperature ({})", unit.symbol()))
+            .draw()?;
+
+        // Drawn first so the readings go over the top of it
+        if let Some([start, end]) = report.largest_difference_minutes {
+            let window = YELLOW.mix(0.3).filled();
+            chart
+                .draw_series(std::iter::once(Rectangle::new(
+                    [(start, low), (end, high)],
+                    window,
+                )))?
+                .label("Largest difference")
+                .legend(move |(x, y)| Rectangle::new([(x, y - 5), (x + 16, y + 5)], window));
+        }
+
+        for (sensor_id, points) in series {
+            let color = Palette99::pick(sensor_id).mix(0.8);
+
+            chart
+                .draw_series(LineSeries::new(points, color))?
+                .label(format!("Sensor {}", sensor_id))
+                .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 16, y)], color));
+        }
+
+        let extremes = [
+            ("lowest", &report.lowest_temps, BLUE),
+            ("highest", &report.highest_temps, RED),
+        ];
+        for (label, recordings, color) in extremes {
+            let label = format!("{} {}", recordings.len(), label);
+            chart
+                .draw_series(
+                    recordings
+                        .iter()
+                        .map(|x| Circle::new(point(x), 5, color.filled())),
+                )?
+                .label(label)
+                .legend(move |(x, y)| Circle::new((x + 8, y), 5, color.filled()));
+        }
+
+        chart
+            .configure_series_labels()
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .position(SeriesLabelPosition::UpperRight)
+            .draw()?;
+
+        root.present()?;
+        Ok(())
+    }
+}
+
+impl ReportSink for ChartSink {
+    fn handle_report(&mut self, report: &Report) {
+        // A partial report is the last one, so it gets everything that's left
+        let (recordings, later) = std::mem::take(&mut self.recordings)
+            .into_iter()
+            .partition(|x| report.partial || x.wall_time() < report.period_end);
+        self.recordings = later;
+
+        let name = format!("report-{:03}", report.index + 1);
+
+        let path = self.dir.join(format!("{}.png", name));
+        if let Err(err) = self.draw(&path, report, &recordings) {
+            eprintln!("Couldn't draw chart {}: {}", path.display(), err);
+        }
+
+        let path = self.dir.join(format!("{}.txt", name));
+        if let Err(err) = fs::write(&path, report.display(self.config.unit).to_string()) {
+            eprintln!("Couldn't write report to {}: {}", path.display(), err);
+        }
+    }
+
+    fn handle_recording(&mut self, recording: &Recording) {
+        self.recordings.push(recording.clone());
+    }
+}
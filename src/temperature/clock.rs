@@ -0,0 +1,135 @@
+use std::sync::{Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Where the sensor and report threads get the time from and how they wait. The simulation uses
+/// `SystemClock` unless it's given another one.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+
+    fn sleep(&self, duration: Duration);
+
+    /// Called before a thread that sleeps on the clock is spawned.
+    fn add_participant(&self) {}
+
+    /// Called once a thread that sleeps on the clock has finished.
+    fn remove_participant(&self) {}
+}
+
+/// Real time, used by the binary.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        thread::sleep(duration);
+    }
+}
+
+/// Simulated time that jumps forward instead of passing. Once every participating thread is
+/// asleep the clock skips straight to the earliest wake up time, so a whole simulated hour takes
+/// as long as the work done in it rather than a real hour.
+///
+/// Every thread that sleeps on the clock has to be a participant, otherwise time can skip ahead
+/// while it's busy. Threads that block on something else (like reading stdin) stop time.
+#[derive(Debug)]
+pub struct VirtualClock {
+    start: Instant,
+    state: Mutex<VirtualState>,
+    woken: Condvar,
+}
+
+#[derive(Debug, Default)]
+struct VirtualState {
+    elapsed: Duration,
+    participants: usize,
+
+    // When each sleeping thread wants to wake up, as time since `start`
+    wake_times: Vec<Duration>,
+}
+
+impl VirtualState {
+    // Skips ahead to the earliest wake up once nobody is left running. Returns false if some
+    // thread is already due to wake up, since it'll be running again soon.
+    fn advance_if_idle(&mut self) -> bool {
+        if self.wake_times.len() < self.participants {
+            return false;
+        }
+
+        match self.wake_times.iter().min() {
+            Some(&earliest) if earliest > self.elapsed => {
+                self.elapsed = earliest;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl VirtualClock {
+    pub fn new() -> VirtualClock {
+        VirtualClock {
+            start: Instant::now(),
+            state: Mutex::new(VirtualState::default()),
+            woken: Condvar::new(),
+        }
+    }
+
+    /// How much simulated (scaled) time has passed since the clock was created.
+    pub fn elapsed(&self) -> Duration {
+        self.state.lock().unwrap().elapsed
+    }
+}
+
+impl Default for VirtualClock {
+    fn default() -> Self {
+        VirtualClock::new()
+    }
+}
+
+impl Clock for VirtualClock {
+    fn now(&self) -> Instant {
+        self.start + self.elapsed()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        let mut state = self.state.lock().unwrap();
+        let wake_time = state.elapsed + duration;
+        state.wake_times.push(wake_time);
+
+        loop {
+            if state.elapsed >= wake_time {
+                if let Some(index) = state.wake_times.iter().position(|&x| x == wake_time) {
+                    state.wake_times.swap_remove(index);
+                }
+
+                return;
+            }
+
+            if state.advance_if_idle() {
+                self.woken.notify_all();
+                continue;
+            }
+
+            state = self.woken.wait(state).unwrap();
+        }
+    }
+
+    fn add_participant(&self) {
+        self.state.lock().unwrap().participants += 1;
+    }
+
+    fn remove_participant(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.participants = state.participants.saturating_sub(1);
+
+        // The threads left might all be waiting on the one that just finished
+        if state.advance_if_idle() {
+            self.woken.notify_all();
+        }
+    }
+}
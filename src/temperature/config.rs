@@ -0,0 +1,842 @@
+use std::f64::consts::TAU;
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::Deserialize;
+use thiserror::Error;
+
+use super::{
+    affinity, AffinityConfig, AlertRuleConfig, AnomalyConfig, ClockSkewConfig, DecimationConfig,
+    Histogram, ModelConfig, OverflowPolicy, PriorityConfig, QuantileConfig, SensorFaultConfig,
+    SensorModelConfig, SensorPositionConfig, SensorSamplingConfig, SuspectConfig, Temperature,
+    TemperatureUnit, Transport, WatchdogConfig, ONE_MINUTE_MS, SPEEDUP_FACTOR,
+};
+
+// Mixed into the seed for fault RNGs so they don't produce the same numbers as the sensors
+const FAULT_SEED_SALT: u64 = 0x5EED_FA17;
+
+// Mixed into the seed for jitter & clock skew RNGs, for the same reason
+const JITTER_SEED_SALT: u64 = 0x5EED_7177;
+const SKEW_SEED_SALT: u64 = 0x5EED_5CE3;
+const FIELD_SEED_SALT: u64 = 0x5EED_F1E1;
+
+// Meters from the middle of the rover that sensors without a position are spaced around
+const DEFAULT_POSITION_RADIUS: f64 = 1.0;
+
+/// Settings for a `TemperatureSimulation`. Every interval is measured in simulated time and
+/// gets scaled down by `speedup` when the simulation runs.
+///
+/// The field names in a TOML config file are the ones in the `rename` attributes, e.g.
+/// `sensors = 8` or `sampling_period = 60`. Missing keys fall back to the defaults.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SimulationConfig {
+    /// How many sensor threads to spawn
+    #[serde(rename = "sensors")]
+    pub sensor_count: usize,
+
+    /// How many simulated seconds each sensor waits between recordings, unless overridden in
+    /// `sensor_sampling`
+    #[serde(rename = "sampling_period")]
+    pub sampling_period_seconds: f64,
+
+    /// Makes every sensor wait for the others before each reading, so they all take it at the
+    /// same instant
+    pub synchronized_sampling: bool,
+
+    /// Up to how many simulated seconds late a sensor wakes up for each reading, picked at
+    /// random every time like a real sensor's timer. Has to be shorter than every sampling period
+    #[serde(rename = "sampling_jitter")]
+    pub sampling_jitter_seconds: f64,
+
+    /// Puts each sensor's clock off from the rover's, and whether the report thread corrects
+    /// for it
+    pub clock_skew: ClockSkewConfig,
+
+    /// How many simulated minutes each report covers
+    #[serde(rename = "report_interval")]
+    pub report_interval_minutes: u64,
+
+    /// How many rovers to run, each with `sensor_count` sensors and its own queue and reports
+    pub rovers: usize,
+
+    /// Whether to print a summary combining every rover's report for a period once they've all
+    /// been generated
+    pub fleet_summary: bool,
+
+    /// How many simulated hours to run for before stopping on its own. Runs until stopped when
+    /// `None`
+    pub hours: Option<u64>,
+
+    /// The lowest temperature a sensor can record, in degrees Fahrenheit
+    pub min_temperature: f64,
+
+    /// The highest temperature a sensor can record, in degrees Fahrenheit
+    pub max_temperature: f64,
+
+    /// Unit temperatures are displayed in. Readings are always stored in Fahrenheit
+    pub unit: TemperatureUnit,
+
+    /// How much faster than real time the simulation runs
+    pub speedup: u64,
+
+    /// Seeds every sensor's RNG so runs produce the same readings. Uses entropy when `None`
+    pub seed: Option<u64>,
+
+    /// How the sensors come up with temperatures, unless overridden in `sensor_models`
+    pub model: ModelConfig,
+
+    /// How many of the lowest & highest temperatures each report lists, 0 leaves them out
+    pub top_n: usize,
+
+    /// Whether the lowest & highest temperatures are distinct, each listed once with how many
+    /// readings hit it, rather than one entry per reading
+    pub distinct_extremes: bool,
+
+    /// How many simulated minutes apart two readings can be and still count towards the largest
+    /// temperature difference
+    #[serde(rename = "difference_window")]
+    pub difference_window_minutes: u64,
+
+    /// How many simulated seconds a report waits after its period ends for readings taken
+    /// during the period that haven't arrived yet. Ones that arrive later are only counted
+    #[serde(rename = "late_grace")]
+    pub late_grace_seconds: f64,
+
+    /// How many simulated seconds the report thread holds each reading so that any taken before
+    /// it can catch up, putting readings back in the order they were taken. 0 uses them in the
+    /// order they arrive
+    #[serde(rename = "reorder_window")]
+    pub reorder_window_seconds: f64,
+
+    /// How many simulated minutes from the start the sensors take to settle. Their readings
+    /// during that time are passed on to the sinks but left out of the first report's statistics
+    #[serde(rename = "warm_up")]
+    pub warm_up_minutes: f64,
+
+    /// Which readings go into the reports when the sensors sample faster than the reports need.
+    /// The sinks get every reading either way
+    pub decimation: DecimationConfig,
+
+    /// Percentiles (between 0 and 100) included in each report's summary statistics
+    pub percentiles: Vec<f64>,
+
+    /// How the median & percentiles are worked out
+    pub quantiles: QuantileConfig,
+
+    /// Width of each report histogram bucket in degrees Fahrenheit. The buckets cover
+    /// `min_temperature` to `max_temperature`, 0 leaves the histogram out
+    pub histogram_bucket_width: f64,
+
+    /// Flags readings that are far from their sensor's recent readings. Off when `None`
+    pub anomaly: Option<AnomalyConfig>,
+
+    /// Readings beyond these limits go to the report thread ahead of any backlog and raise an
+    /// alert straight away. Off when `None`
+    pub priority: Option<PriorityConfig>,
+
+    /// What kind of queue carries readings from the sensors to the report thread
+    pub transport: Transport,
+
+    /// How many readings each sensor's queue to the report thread holds
+    pub queue_capacity: usize,
+
+    /// What a sensor does with a reading when its queue is full
+    pub queue_overflow: OverflowPolicy,
+
+    /// Lets sensors sample every few milliseconds without losing readings. A sensor that wakes
+    /// up late takes every reading it owes in a burst, timestamped when each was due, rather than
+    /// skipping ahead to the latest
+    pub high_frequency: bool,
+
+    /// Up to how many waiting readings the report thread takes off the queues at once
+    pub ingest_batch: usize,
+
+    /// Pins the sensor threads and the report thread to cores. Not pinned when `None`
+    pub affinity: Option<AffinityConfig>,
+
+    /// Spotting sensors that look faulty and leaving them out of the extremes
+    pub suspect_sensors: SuspectConfig,
+
+    /// How many of a period's readings are kept in memory so its report can be worked out again
+    /// without the suspect sensors. Past this the oldest are written to a temporary file until
+    /// the report is made. Unlimited when `None`
+    #[serde(rename = "recordings_in_memory")]
+    pub max_recordings_in_memory: Option<usize>,
+
+    /// Noticing sensors that have stopped sending readings
+    pub watchdog: WatchdogConfig,
+
+    /// Per-sensor models, written as `[[sensor_model]]` tables
+    #[serde(rename = "sensor_model")]
+    pub sensor_models: Vec<SensorModelConfig>,
+
+    /// Where sensors are on the rover for the spatial model, written as `[[sensor_position]]`
+    /// tables
+    #[serde(rename = "sensor_position")]
+    pub sensor_positions: Vec<SensorPositionConfig>,
+
+    /// Per-sensor sampling periods, written as `[[sensor_sampling]]` tables
+    pub sensor_sampling: Vec<SensorSamplingConfig>,
+
+    /// Faults injected into individual sensors, written as `[[sensor_fault]]` tables
+    #[serde(rename = "sensor_fault")]
+    pub sensor_faults: Vec<SensorFaultConfig>,
+
+    /// Conditions that raise an alert, written as `[[alert_rule]]` tables
+    #[serde(rename = "alert_rule")]
+    pub alert_rules: Vec<AlertRuleConfig>,
+}
+
+impl SimulationConfig {
+    /// Loads a config from a TOML file and validates it.
+    pub fn from_toml_file(path: impl AsRef<Path>) -> Result<SimulationConfig, ConfigError> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .map_err(|err| ConfigError::Io(path.display().to_string(), err))?;
+
+        SimulationConfig::from_toml_str(&contents)
+    }
+
+    /// Parses a config from TOML text and validates it.
+    pub fn from_toml_str(contents: &str) -> Result<SimulationConfig, ConfigError> {
+        let config: SimulationConfig = toml::from_str(contents).map_err(ConfigError::Parse)?;
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    /// Checks that every value makes sense, naming the first key that doesn't.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.sensor_count == 0 {
+            return Err(ConfigError::invalid("sensors", "must be at least 1"));
+        }
+
+        if !(self.sampling_period_seconds.is_finite() && self.sampling_period_seconds > 0.0) {
+            return Err(ConfigError::invalid(
+                "sampling_period",
+                "must be a positive number of seconds",
+            ));
+        }
+
+        for (index, sampling) in self.sensor_sampling.iter().enumerate() {
+            if sampling.sensor >= self.sensor_count {
+                return Err(ConfigError::invalid(
+                    format!("sensor_sampling[{}].sensor", index),
+                    format!(
+                        "must be below the number of sensors ({})",
+                        self.sensor_count
+                    ),
+                ));
+            }
+
+            if !(sampling.period.is_finite() && sampling.period > 0.0) {
+                return Err(ConfigError::invalid(
+                    format!("sensor_sampling[{}].period", index),
+                    "must be a positive number of seconds",
+                ));
+            }
+        }
+
+        // Jitter any longer and a sensor could wake up after its next reading was due
+        let shortest_sampling_period = (0..self.sensor_count)
+            .map(|sensor_id| self.sampling_period_for(sensor_id))
+            .fold(f64::INFINITY, f64::min);
+        if !(self.sampling_jitter_seconds.is_finite()
+            && self.sampling_jitter_seconds >= 0.0
+            && self.sampling_jitter_seconds < shortest_sampling_period)
+        {
+            return Err(ConfigError::invalid(
+                "sampling_jitter",
+                "must be a non-negative number of seconds shorter than every sampling period",
+            ));
+        }
+
+        if !(self.clock_skew.max_offset.is_finite() && self.clock_skew.max_offset >= 0.0) {
+            return Err(ConfigError::invalid(
+                "clock_skew.max_offset",
+                "must be a non-negative number of seconds",
+            ));
+        }
+
+        if self.report_interval_minutes == 0 {
+            return Err(ConfigError::invalid(
+                "report_interval",
+                "must be at least 1",
+            ));
+        }
+
+        if self.rovers == 0 {
+            return Err(ConfigError::invalid("rovers", "must be at least 1"));
+        }
+
+        if self.hours == Some(0) {
+            return Err(ConfigError::invalid("hours", "must be at least 1"));
+        }
+
+        if !self.min_temperature.is_finite() {
+            return Err(ConfigError::invalid(
+                "min_temperature",
+                "must be a finite number",
+            ));
+        }
+
+        if !self.max_temperature.is_finite() {
+            return Err(ConfigError::invalid(
+                "max_temperature",
+                "must be a finite number",
+            ));
+        }
+
+        // The sensors can't read anything outside of the rover's range
+        let valid_range = Temperature::MIN_FAHRENHEIT..=Temperature::MAX_FAHRENHEIT;
+        for (key, value) in [
+            ("min_temperature", self.min_temperature),
+            ("max_temperature", self.max_temperature),
+        ] {
+            if !valid_range.contains(&value) {
+                return Err(ConfigError::invalid(
+                    key,
+                    format!(
+                        "must be between {} and {}, the range the rover's sensors can read",
+                        Temperature::MIN_FAHRENHEIT,
+                        Temperature::MAX_FAHRENHEIT
+                    ),
+                ));
+            }
+        }
+
+        if self.min_temperature > self.max_temperature {
+            return Err(ConfigError::invalid(
+                "min_temperature",
+                format!(
+                    "must not be above max_temperature ({})",
+                    self.max_temperature
+                ),
+            ));
+        }
+
+        if self.speedup == 0 {
+            return Err(ConfigError::invalid("speedup", "must be at least 1"));
+        }
+
+        if self.difference_window_minutes == 0 {
+            return Err(ConfigError::invalid(
+                "difference_window",
+                "must be at least 1",
+            ));
+        }
+
+        if !(self.late_grace_seconds.is_finite()
+            && self.late_grace_seconds >= 0.0
+            && self.late_grace_seconds < self.report_interval_minutes as f64 * 60.0)
+        {
+            return Err(ConfigError::invalid(
+                "late_grace",
+                "must be a non-negative number of seconds shorter than report_interval",
+            ));
+        }
+
+        if !(self.reorder_window_seconds.is_finite()
+            && self.reorder_window_seconds >= 0.0
+            && self.reorder_window_seconds <= self.late_grace_seconds)
+        {
+            return Err(ConfigError::invalid(
+                "reorder_window",
+                "must be a non-negative number of seconds no longer than late_grace",
+            ));
+        }
+
+        if !(self.warm_up_minutes.is_finite()
+            && self.warm_up_minutes >= 0.0
+            && self.warm_up_minutes < self.report_interval_minutes as f64)
+        {
+            return Err(ConfigError::invalid(
+                "warm_up",
+                "must be a non-negative number of minutes shorter than report_interval",
+            ));
+        }
+
+        if let Some((key, message)) = self.decimation.invalid_parameter() {
+            return Err(ConfigError::invalid(format!("decimation.{}", key), message));
+        }
+
+        let readings_per_report = self.readings_per_report();
+        if self.top_n > readings_per_report {
+            return Err(ConfigError::invalid(
+                "top_n",
+                format!(
+                    "must not be more than the {} readings each report covers",
+                    readings_per_report
+                ),
+            ));
+        }
+
+        for (index, percentile) in self.percentiles.iter().enumerate() {
+            if !(0.0..=100.0).contains(percentile) {
+                return Err(ConfigError::invalid(
+                    format!("percentiles[{}]", index),
+                    "must be between 0 and 100",
+                ));
+            }
+        }
+
+        if let Some((key, message)) = self.quantiles.invalid_parameter() {
+            return Err(ConfigError::invalid(format!("quantiles.{}", key), message));
+        }
+
+        if !(self.histogram_bucket_width.is_finite() && self.histogram_bucket_width >= 0.0) {
+            return Err(ConfigError::invalid(
+                "histogram_bucket_width",
+                "must be a non-negative number",
+            ));
+        }
+
+        if let Some(anomaly) = &self.anomaly {
+            if !(anomaly.threshold.is_finite() && anomaly.threshold >= 0.0) {
+                return Err(ConfigError::invalid(
+                    "anomaly.threshold",
+                    "must be a non-negative number",
+                ));
+            }
+
+            if anomaly.baseline_readings == 0 {
+                return Err(ConfigError::invalid(
+                    "anomaly.baseline_readings",
+                    "must be at least 1",
+                ));
+            }
+        }
+
+        if let Some(priority) = &self.priority {
+            if !priority.below.is_finite() {
+                return Err(ConfigError::invalid(
+                    "priority.below",
+                    "must be a finite number",
+                ));
+            }
+
+            if !(priority.above.is_finite() && priority.above > priority.below) {
+                return Err(ConfigError::invalid(
+                    "priority.above",
+                    "must be a finite number above priority.below",
+                ));
+            }
+        }
+
+        if !self.transport.is_available() {
+            return Err(ConfigError::invalid(
+                "transport",
+                "isn't available in this build, it needs the `crossbeam` feature",
+            ));
+        }
+
+        if let Some(affinity) = &self.affinity {
+            if !affinity::is_available() {
+                return Err(ConfigError::invalid(
+                    "affinity",
+                    "isn't available in this build, it needs the `affinity` feature",
+                ));
+            }
+
+            let core_count = affinity::core_count();
+            let cores = std::iter::once(&affinity.report_core).chain(&affinity.sensor_cores);
+            for core in cores {
+                if *core >= core_count {
+                    return Err(ConfigError::invalid(
+                        "affinity",
+                        format!(
+                            "core {} doesn't exist, cores have to be less than {}",
+                            core, core_count
+                        ),
+                    ));
+                }
+            }
+        }
+
+        if self.queue_capacity < 2 {
+            return Err(ConfigError::invalid("queue_capacity", "must be at least 2"));
+        }
+
+        if self.ingest_batch == 0 {
+            return Err(ConfigError::invalid("ingest_batch", "must be at least 1"));
+        }
+
+        if self.max_recordings_in_memory == Some(0) {
+            return Err(ConfigError::invalid(
+                "recordings_in_memory",
+                "must be at least 1",
+            ));
+        }
+
+        if self.queue_overflow == OverflowPolicy::DropOldest && !self.transport.can_drop_oldest() {
+            return Err(ConfigError::invalid(
+                "queue_overflow",
+                "drop_oldest isn't supported by the mpsc transport",
+            ));
+        }
+
+        if !(self.suspect_sensors.max_mean_deviation.is_finite()
+            && self.suspect_sensors.max_mean_deviation >= 0.0)
+        {
+            return Err(ConfigError::invalid(
+                "suspect_sensors.max_mean_deviation",
+                "must be a non-negative number",
+            ));
+        }
+
+        if !(self.suspect_sensors.max_spread_ratio.is_finite()
+            && self.suspect_sensors.max_spread_ratio >= 1.0)
+        {
+            return Err(ConfigError::invalid(
+                "suspect_sensors.max_spread_ratio",
+                "must be a number of at least 1",
+            ));
+        }
+
+        if !(self.watchdog.stall_periods.is_finite() && self.watchdog.stall_periods >= 1.0) {
+            return Err(ConfigError::invalid(
+                "watchdog.stall_periods",
+                "must be a number of at least 1",
+            ));
+        }
+
+        if let Some((key, message)) = self.model.invalid_parameter() {
+            return Err(ConfigError::invalid(format!("model.{}", key), message));
+        }
+
+        for (index, sensor_model) in self.sensor_models.iter().enumerate() {
+            if sensor_model.sensor >= self.sensor_count {
+                return Err(ConfigError::invalid(
+                    format!("sensor_model[{}].sensor", index),
+                    format!(
+                        "must be below the number of sensors ({})",
+                        self.sensor_count
+                    ),
+                ));
+            }
+
+            if let Some((key, message)) = sensor_model.model.invalid_parameter() {
+                return Err(ConfigError::invalid(
+                    format!("sensor_model[{}].{}", index, key),
+                    message,
+                ));
+            }
+        }
+
+        for (index, position) in self.sensor_positions.iter().enumerate() {
+            if position.sensor >= self.sensor_count {
+                return Err(ConfigError::invalid(
+                    format!("sensor_position[{}].sensor", index),
+                    format!(
+                        "must be below the number of sensors ({})",
+                        self.sensor_count
+                    ),
+                ));
+            }
+
+            for (key, value) in [("x", position.x), ("y", position.y)] {
+                if !value.is_finite() {
+                    return Err(ConfigError::invalid(
+                        format!("sensor_position[{}].{}", index, key),
+                        "must be a finite number",
+                    ));
+                }
+            }
+        }
+
+        for (index, sensor_fault) in self.sensor_faults.iter().enumerate() {
+            if sensor_fault.sensor >= self.sensor_count {
+                return Err(ConfigError::invalid(
+                    format!("sensor_fault[{}].sensor", index),
+                    format!(
+                        "must be below the number of sensors ({})",
+                        self.sensor_count
+                    ),
+                ));
+            }
+
+            if let Some((key, message)) = sensor_fault.invalid_parameter() {
+                return Err(ConfigError::invalid(
+                    format!("sensor_fault[{}].{}", index, key),
+                    message,
+                ));
+            }
+        }
+
+        for (index, alert_rule) in self.alert_rules.iter().enumerate() {
+            // Reports count alerts by rule name, so two rules can't share one
+            if alert_rule.name.is_empty()
+                || self.alert_rules[..index]
+                    .iter()
+                    .any(|x| x.name == alert_rule.name)
+            {
+                return Err(ConfigError::invalid(
+                    format!("alert_rule[{}].name", index),
+                    "must be a name no other rule has",
+                ));
+            }
+
+            if let Some((key, message)) = alert_rule.rule.invalid_parameter() {
+                return Err(ConfigError::invalid(
+                    format!("alert_rule[{}].{}", index, key),
+                    message,
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Converts a simulated duration into the real duration the simulation waits for.
+    pub fn scaled(&self, simulated: Duration) -> Duration {
+        simulated.div_f64(self.speedup as f64)
+    }
+
+    /// Converts a real duration back into the number of simulated minutes it stands for.
+    pub fn simulated_minutes(&self, real: Duration) -> f64 {
+        real.as_secs_f64() * self.speedup as f64 / 60.0
+    }
+
+    /// Converts a number of simulated minutes into the real duration the simulation waits for.
+    pub fn scaled_minutes(&self, minutes: u64) -> Duration {
+        self.scaled(Duration::from_millis(minutes * ONE_MINUTE_MS))
+    }
+
+    pub fn scaled_minute(&self) -> Duration {
+        self.scaled_minutes(1)
+    }
+
+    pub fn scaled_sampling_period(&self) -> Duration {
+        self.scaled(Duration::from_secs_f64(self.sampling_period_seconds))
+    }
+
+    pub fn scaled_sampling_period_for(&self, sensor_id: usize) -> Duration {
+        self.scaled(Duration::from_secs_f64(self.sampling_period_for(sensor_id)))
+    }
+
+    pub fn scaled_sampling_jitter(&self) -> Duration {
+        self.scaled(Duration::from_secs_f64(self.sampling_jitter_seconds))
+    }
+
+    pub fn scaled_report_interval(&self) -> Duration {
+        self.scaled_minutes(self.report_interval_minutes)
+    }
+
+    pub fn scaled_difference_window(&self) -> Duration {
+        self.scaled_minutes(self.difference_window_minutes)
+    }
+
+    pub fn scaled_late_grace(&self) -> Duration {
+        self.scaled(Duration::from_secs_f64(self.late_grace_seconds))
+    }
+
+    pub fn scaled_reorder_window(&self) -> Duration {
+        self.scaled(Duration::from_secs_f64(self.reorder_window_seconds))
+    }
+
+    pub fn scaled_warm_up(&self) -> Duration {
+        self.scaled(Duration::from_secs_f64(self.warm_up_minutes * 60.0))
+    }
+
+    /// Creates the empty histogram each report fills in.
+    pub fn histogram(&self) -> Histogram {
+        Histogram::new(
+            self.min_temperature,
+            self.max_temperature,
+            self.histogram_bucket_width,
+        )
+    }
+
+    /// Returns the model a sensor should use, taking the last matching override.
+    pub fn model_for(&self, sensor_id: usize) -> &ModelConfig {
+        self.sensor_models
+            .iter()
+            .rev()
+            .find(|x| x.sensor == sensor_id)
+            .map(|x| &x.model)
+            .unwrap_or(&self.model)
+    }
+
+    /// Returns where a sensor is on the rover in meters, taking the last matching entry. Sensors
+    /// without one are spaced evenly around a circle in the middle of the rover.
+    pub fn position_for(&self, sensor_id: usize) -> (f64, f64) {
+        match self
+            .sensor_positions
+            .iter()
+            .rev()
+            .find(|x| x.sensor == sensor_id)
+        {
+            Some(position) => (position.x, position.y),
+            None => {
+                let angle = TAU * sensor_id as f64 / self.sensor_count.max(1) as f64;
+                (
+                    DEFAULT_POSITION_RADIUS * angle.cos(),
+                    DEFAULT_POSITION_RADIUS * angle.sin(),
+                )
+            }
+        }
+    }
+
+    /// Returns how many simulated seconds a sensor waits between recordings, taking the last
+    /// matching override.
+    pub fn sampling_period_for(&self, sensor_id: usize) -> f64 {
+        self.sensor_sampling
+            .iter()
+            .rev()
+            .find(|x| x.sensor == sensor_id)
+            .map_or(self.sampling_period_seconds, |x| x.period)
+    }
+
+    /// How many readings a report covers when every sensor reads on schedule.
+    pub fn readings_per_report(&self) -> usize {
+        (0..self.sensor_count)
+            .map(|sensor_id| {
+                (self.report_interval_minutes as f64 * 60.0 / self.sampling_period_for(sensor_id))
+                    .floor() as usize
+            })
+            .sum()
+    }
+
+    /// How many reports cover `hours`, rounding up when the report interval doesn't divide it.
+    pub fn report_limit(&self) -> Option<usize> {
+        self.hours
+            .map(|hours| (hours * 60).div_ceil(self.report_interval_minutes) as usize)
+    }
+
+    /// Returns the fault injected into a sensor, taking the last matching entry.
+    pub fn fault_for(&self, sensor_id: usize) -> Option<&SensorFaultConfig> {
+        self.sensor_faults
+            .iter()
+            .rev()
+            .find(|x| x.sensor == sensor_id)
+    }
+
+    /// Creates the RNG that decides when a sensor's fault starts. It's a separate stream from the
+    /// sensor's readings so adding a fault doesn't change the readings before it.
+    pub fn fault_rng(&self, sensor_id: usize) -> StdRng {
+        match self.seed {
+            Some(seed) => {
+                StdRng::seed_from_u64((seed ^ FAULT_SEED_SALT).wrapping_add(sensor_id as u64))
+            }
+            None => StdRng::from_entropy(),
+        }
+    }
+
+    /// Creates the RNG that decides how late a sensor wakes up for each reading, a separate
+    /// stream from the sensor's readings like the fault RNG.
+    pub fn jitter_rng(&self, sensor_id: usize) -> StdRng {
+        match self.seed {
+            Some(seed) => {
+                StdRng::seed_from_u64((seed ^ JITTER_SEED_SALT).wrapping_add(sensor_id as u64))
+            }
+            None => StdRng::from_entropy(),
+        }
+    }
+
+    /// Creates the RNG that decides how far off a sensor's clock is.
+    pub fn skew_rng(&self, sensor_id: usize) -> StdRng {
+        match self.seed {
+            Some(seed) => {
+                StdRng::seed_from_u64((seed ^ SKEW_SEED_SALT).wrapping_add(sensor_id as u64))
+            }
+            None => StdRng::from_entropy(),
+        }
+    }
+
+    /// Creates the RNG the spatial model's field is drawn from. Every sensor has to draw the same
+    /// field, so without a seed one is picked at random once and used by every simulation in the
+    /// process.
+    pub fn field_rng(&self) -> StdRng {
+        static UNSEEDED: OnceLock<u64> = OnceLock::new();
+
+        let seed = self
+            .seed
+            .unwrap_or_else(|| *UNSEEDED.get_or_init(rand::random));
+        StdRng::seed_from_u64(seed ^ FIELD_SEED_SALT)
+    }
+
+    /// Creates the RNG for a sensor. With a seed set every sensor gets its own deterministic
+    /// stream, otherwise the RNG is seeded from entropy.
+    pub fn sensor_rng(&self, sensor_id: usize) -> StdRng {
+        match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed.wrapping_add(sensor_id as u64)),
+            None => StdRng::from_entropy(),
+        }
+    }
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        SimulationConfig {
+            sensor_count: 8,
+            sampling_period_seconds: 60.0,
+            synchronized_sampling: false,
+            sampling_jitter_seconds: 0.0,
+            clock_skew: ClockSkewConfig::default(),
+            report_interval_minutes: 60,
+            rovers: 1,
+            fleet_summary: false,
+            hours: None,
+            min_temperature: Temperature::MIN_FAHRENHEIT,
+            max_temperature: Temperature::MAX_FAHRENHEIT,
+            unit: TemperatureUnit::Fahrenheit,
+            speedup: SPEEDUP_FACTOR,
+            seed: None,
+            model: ModelConfig::default(),
+            top_n: 5,
+            distinct_extremes: false,
+            difference_window_minutes: 10,
+            late_grace_seconds: 30.0,
+            reorder_window_seconds: 0.0,
+            warm_up_minutes: 0.0,
+            decimation: DecimationConfig::Off,
+            percentiles: vec![5.0, 95.0],
+            quantiles: QuantileConfig::default(),
+            histogram_bucket_width: 10.0,
+            anomaly: None,
+            priority: None,
+            transport: Transport::Ring,
+            queue_capacity: 1024,
+            queue_overflow: OverflowPolicy::Block,
+            high_frequency: false,
+            ingest_batch: 1024,
+            affinity: None,
+            suspect_sensors: SuspectConfig::default(),
+            max_recordings_in_memory: None,
+            watchdog: WatchdogConfig::default(),
+            sensor_models: Vec::new(),
+            sensor_positions: Vec::new(),
+            sensor_sampling: Vec::new(),
+            sensor_faults: Vec::new(),
+            alert_rules: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    /// The config file couldn't be read
+    #[error("couldn't read config file {0}: {1}")]
+    Io(String, #[source] std::io::Error),
+
+    /// The config file isn't valid TOML or a key has the wrong type
+    #[error("invalid config file: {0}")]
+    Parse(#[source] toml::de::Error),
+
+    /// A key has a value the simulation can't run with
+    #[error("invalid `{key}`: {message}")]
+    Invalid { key: String, message: String },
+}
+
+impl ConfigError {
+    fn invalid(key: impl Into<String>, message: impl Into<String>) -> ConfigError {
+        ConfigError::Invalid {
+            key: key.into(),
+            message: message.into(),
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use std::collections::VecDeque;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::widgets::{Block, Paragraph, Sparkline};
+use ratatui::{DefaultTerminal, Frame};
+
+use super::{
+    Recording, Report, ReportSink, SimulationConfig, Temperature, TemperatureSimulation,
+    TemperatureUnit,
+};
+
+/// How many of the most recent readings (from every sensor) the sparkline shows.
+const RECENT_READINGS: usize = 240;
+
+/// A terminal dashboard for a running `TemperatureSimulation`, showing each sensor's latest
+/// reading, how many readings are waiting in the queues, the last report and a sparkline of
+/// recent readings. It's redrawn every simulated minute.
+///
+/// The dashboard finds out about readings & reports through its sink, which has to be added to
+/// the simulation before it's started.
+pub struct Dashboard {
+    state: Arc<Mutex<DashboardState>>,
+    unit: TemperatureUnit,
+    refresh: Duration,
+
+    // Readings are shifted up by the lowest possible temperature for the sparkline, which only
+    // takes unsigned values
+    min_temperature: f64,
+    max_temperature: f64,
+}
+
+#[derive(Default)]
+struct DashboardState {
+    latest: Vec<Option<Temperature>>,
+    recent: VecDeque<f64>,
+    last_report: Option<Report>,
+}
+
+/// Feeds a `Dashboard` with everything the simulation produces.
+pub struct DashboardSink {
+    state: Arc<Mutex<DashboardState>>,
+}
+
+impl Dashboard {
+    pub fn new(config: &SimulationConfig) -> Dashboard {
+        let state = DashboardState {
+            latest: vec![None; config.sensor_count],
+            ..DashboardState::default()
+        };
+
+        Dashboard {
+            state: Arc::new(Mutex::new(state)),
+            unit: config.unit,
+            refresh: config.scaled_minute(),
+            min_temperature: config.min_temperature,
+            max_temperature: config.max_temperature,
+        }
+    }
+
+    pub fn sink(&self) -> DashboardSink {
+        DashboardSink {
+            state: self.state.clone(),
+        }
+    }
+
+    /// Takes over the terminal until the simulation finishes or `q`, `Esc` or Ctrl-C is
+    /// pressed, which stops the simulation. `r` asks for a report on the period so far, which is
+    /// shown in place of the last report until the next one. Returns the last report so it can be printed once
+    /// the terminal is back to normal.
+    pub fn run(&self, simulation: &TemperatureSimulation) -> io::Result<Option<Report>> {
+        let mut terminal = ratatui::try_init()?;
+        let result = self.draw_until_finished(&mut terminal, simulation);
+        ratatui::try_restore()?;
+        result?;
+
+        Ok(self.state.lock().unwrap().last_report.clone())
+    }
+
+    fn draw_until_finished(
+        &self,
+        terminal: &mut DefaultTerminal,
+        simulation: &TemperatureSimulation,
+    ) -> io::Result<()> {
+        let stop_handle = simulation.stop_handle();
+        let report_trigger = simulation.report_trigger();
+
+        while !simulation.is_finished() {
+            let queue_depth = simulation.queue_depth();
+            terminal.draw(|frame| self.draw(frame, queue_depth))?;
+
+            // Waiting for a key is what paces the redraws
+            if !event::poll(self.refresh)? {
+                continue;
+            }
+
+            if let Event::Key(key) = event::read()? {
+                let ctrl_c =
+                    key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+                let quit = matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) || ctrl_c;
+
+                // Stopping lets the report thread publish a partial report before it finishes
+                if key.kind == KeyEventKind::Press && quit {
+                    stop_handle.stop();
+                }
+
+                if key.kind == KeyEventKind::Press && key.code == KeyCode::Char('r') {
+                    report_trigger.trigger();
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn draw(&self, frame: &mut Frame, queue_depth: Option<usize>) {
+        let state = self.state.lock().unwrap();
+
+        let [top, bottom] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(8)]).areas(frame.area());
+        let [sensors, report] =
+            Layout::horizontal([Constraint::Length(32), Constraint::Min(0)]).areas(top);
+
+        let mut lines: Vec<String> = state
+            .latest
+            .iter()
+            .enumerate()
+            .map(|(sensor_id, temperature)| match temperature {
+                Some(temperature) => {
+                    format!("Sensor {}: {}", sensor_id, temperature.display(self.unit))
+                }
+                None => format!("Sensor {}: no readings yet", sensor_id),
+            })
+            .collect();
+
+        lines.push(String::new());
+        lines.push(match queue_depth {
+            Some(depth) => format!("Readings queued: {}", depth),
+            None => "Readings queued: unknown".to_string(),
+        });
+
+        frame.render_widget(
+            Paragraph::new(lines.join("\n")).block(Block::bordered().title(" Sensors ")),
+            sensors,
+        );
+
+        let report_text = match &state.last_report {
+            Some(report) => report.display(self.unit).to_string(),
+            None => "Waiting for the first report".to_string(),
+        };
+        frame.render_widget(
+            Paragraph::new(report_text).block(Block::bordered().title(" Last report ")),
+            report,
+        );
+
+        // The newest readings are on the right, so the ones that fit are taken from the end
+        let width = bottom.width.saturating_sub(2) as usize;
+        let skip = state.recent.len().saturating_sub(width);
+        let data: Vec<u64> = state
+            .recent
+            .iter()
+            .skip(skip)
+            .map(|x| (x - self.min_temperature).max(0.0).round() as u64)
+            .collect();
+
+        frame.render_widget(
+            Sparkline::default()
+                .block(Block::bordered().title(" Recent readings "))
+                .data(&data)
+                .max((self.max_temperature - self.min_temperature).round() as u64),
+            bottom,
+        );
+    }
+}
+
+impl ReportSink for DashboardSink {
+    fn handle_report(&mut self, report: &Report) {
+        self.state.lock().unwrap().last_report = Some(report.clone());
+    }
+
+    fn handle_on_demand_report(&mut self, report: &Report) {
+        self.state.lock().unwrap().last_report = Some(report.clone());
+    }
+
+    fn handle_recording(&mut self, recording: &Recording) {
+        let mut state = self.state.lock().unwrap();
+
+        if let Some(latest) = state.latest.get_mut(recording.sensor_id) {
+            *latest = Some(recording.temperature);
+        }
+
+        if state.recent.len() == RECENT_READINGS {
+            state.recent.pop_front();
+        }
+        state.recent.push_back(recording.temperature.fahrenheit());
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+use super::{Recording, SimulationConfig};
+
+/// Thins out the readings that go into a report when the sensors sample faster than the report
+/// needs, so working it out stays bounded however high the sampling rate goes. Written in the
+/// config file as e.g. `decimation = { kind = "every", n = 10 }` or
+/// `decimation = { kind = "min_max" }`. The sinks still get every reading.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)]
+pub enum DecimationConfig {
+    /// Every reading goes into the report
+    #[default]
+    Off,
+
+    /// Only every `n`th reading from each sensor goes into the report, by sequence number
+    Every { n: u64 },
+
+    /// Only the lowest & highest reading each sensor took in each simulated minute go into the
+    /// report, so the extremes are kept but the mean & percentiles lean towards them
+    MinMax,
+}
+
+impl DecimationConfig {
+    /// Returns the first key that doesn't make sense and why, if there is one.
+    pub fn invalid_parameter(&self) -> Option<(&'static str, &'static str)> {
+        match *self {
+            DecimationConfig::Every { n: 0 } => Some(("n", "must be at least 1")),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for DecimationConfig {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.to_lowercase().replace('-', "_");
+
+        match s.split_once(':') {
+            Some(("every", n)) => n
+                .parse()
+                .map(|n| DecimationConfig::Every { n })
+                .map_err(|_| format!("`{}` isn't a number of readings", n)),
+            None if s == "off" => Ok(DecimationConfig::Off),
+            None if s == "min_max" => Ok(DecimationConfig::MinMax),
+            _ => Err(format!(
+                "unknown decimation `{}`, expected off, every:<n> or min_max",
+                s
+            )),
+        }
+    }
+}
+
+/// The lowest & highest reading a sensor has taken so far in a minute.
+#[derive(Clone, Debug)]
+struct MinuteExtremes {
+    minute: u64,
+    lowest: Recording,
+    highest: Recording,
+    taken: usize,
+}
+
+impl MinuteExtremes {
+    /// Hands the readings that are kept to `keep`, earliest first, and returns how many were left
+    /// out.
+    fn close(self, keep: &mut impl FnMut(&Recording)) -> usize {
+        if self.lowest.sequence == self.highest.sequence {
+            keep(&self.lowest);
+            return self.taken - 1;
+        }
+
+        let (first, second) = if self.lowest.timestamp <= self.highest.timestamp {
+            (&self.lowest, &self.highest)
+        } else {
+            (&self.highest, &self.lowest)
+        };
+        keep(first);
+        keep(second);
+        self.taken - 2
+    }
+}
+
+/// Decides which of a period's readings go into its report, following a `DecimationConfig`.
+/// Min/max decimation holds on to each sensor's current minute until a reading from a later one
+/// arrives or the decimator is flushed.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Decimator {
+    config: DecimationConfig,
+    minute: Duration,
+    pending: BTreeMap<usize, MinuteExtremes>,
+}
+
+impl Decimator {
+    pub fn new(config: &SimulationConfig) -> Decimator {
+        Decimator {
+            config: config.decimation,
+            minute: config.scaled_minute(),
+            pending: BTreeMap::new(),
+        }
+    }
+
+    /// Hands whichever readings are kept to `keep` and returns how many were left out. Minutes
+    /// are counted from `origin`.
+    pub fn push(
+        &mut self,
+        recording: &Recording,
+        origin: Instant,
+        mut keep: impl FnMut(&Recording),
+    ) -> usize {
+        match self.config {
+            DecimationConfig::Off => {
+                keep(recording);
+                0
+            }
+            DecimationConfig::Every { n } => {
+                if recording.sequence.is_multiple_of(n) {
+                    keep(recording);
+                    0
+                } else {
+                    1
+                }
+            }
+            DecimationConfig::MinMax => {
+                let since = recording.timestamp.saturating_duration_since(origin);
+                let minute = (since.as_nanos() / self.minute.as_nanos().max(1)) as u64;
+
+                let mut left_out = 0;
+                if let Some(current) = self.pending.get_mut(&recording.sensor_id) {
+                    if current.minute == minute {
+                        if recording.temperature < current.lowest.temperature {
+                            current.lowest = recording.clone();
+                        }
+                        if recording.temperature > current.highest.temperature {
+                            current.highest = recording.clone();
+                        }
+                        current.taken += 1;
+                        return 0;
+                    }
+
+                    // A reading from a later minute closes the one before it
+                    if let Some(closed) = self.pending.remove(&recording.sensor_id) {
+                        left_out = closed.close(&mut keep);
+                    }
+                }
+
+                self.pending.insert(
+                    recording.sensor_id,
+                    MinuteExtremes {
+                        minute,
+                        lowest: recording.clone(),
+                        highest: recording.clone(),
+                        taken: 1,
+                    },
+                );
+                left_out
+            }
+        }
+    }
+
+    /// Closes every minute that's still open, handing the kept readings to `keep`, and returns
+    /// how many were left out.
+    pub fn flush(&mut self, mut keep: impl FnMut(&Recording)) -> usize {
+        std::mem::take(&mut self.pending)
+            .into_values()
+            .map(|x| x.close(&mut keep))
+            .sum()
+    }
+
+    /// Whether no readings are being held back.
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
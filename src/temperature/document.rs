@@ -0,0 +1,370 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use super::{Recording, Report, ReportSink, TemperatureUnit, WALL_TIME_FORMAT};
+
+/// What a report is written out as by `Report::to_document`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DocumentFormat {
+    /// GitHub flavoured Markdown, with the lists as tables
+    #[default]
+    Markdown,
+
+    /// A standalone HTML page with the same headings & tables
+    Html,
+}
+
+impl DocumentFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            DocumentFormat::Markdown => "md",
+            DocumentFormat::Html => "html",
+        }
+    }
+}
+
+impl FromStr for DocumentFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "markdown" | "md" => Ok(DocumentFormat::Markdown),
+            "html" => Ok(DocumentFormat::Html),
+            _ => Err(format!(
+                "unknown document format `{}`, expected markdown or html",
+                s
+            )),
+        }
+    }
+}
+
+/// A piece of a report document, which is the same whatever it's written out as.
+enum Block {
+    Heading(usize, String),
+    Paragraph(String),
+    Table {
+        header: Vec<&'static str>,
+        rows: Vec<Vec<String>>,
+    },
+}
+
+impl Report {
+    /// Writes the report out as a document for a write-up, with its top temps & per-sensor
+    /// statistics as tables.
+    pub fn to_document(&self, unit: TemperatureUnit, format: DocumentFormat) -> String {
+        let blocks = blocks(self, unit);
+
+        match format {
+            DocumentFormat::Markdown => markdown(&blocks),
+            DocumentFormat::Html => html(&title(self), &blocks),
+        }
+    }
+}
+
+fn title(report: &Report) -> String {
+    let rover = report
+        .rover
+        .as_ref()
+        .map(|x| format!("{}: ", x))
+        .unwrap_or_default();
+
+    format!(
+        "{}Report {} (hour {})",
+        rover,
+        report.index + 1,
+        report.hour()
+    )
+}
+
+fn blocks(report: &Report, unit: TemperatureUnit) -> Vec<Block> {
+    let difference = |fahrenheit: f64| {
+        format!(
+            "{:.1}{}",
+            unit.convert_difference(fahrenheit),
+            unit.symbol()
+        )
+    };
+    let time = |recording: &Recording| recording.wall_time().format(WALL_TIME_FORMAT).to_string();
+
+    let mut blocks = vec![Block::Heading(1, title(report))];
+
+    let mut period = format!(
+        "{} to {}.",
+        report.period_start.format(WALL_TIME_FORMAT),
+        report.period_end.format(WALL_TIME_FORMAT)
+    );
+    if report.on_demand {
+        period.push_str(" On-demand report, covering the period so far.");
+    } else if report.partial {
+        period.push_str(" Partial report, the simulation stopped before the period ended.");
+    }
+    blocks.push(Block::Paragraph(period));
+
+    let mut rows = Vec::new();
+    match &report.summary {
+        Some(summary) => {
+            let approximate = if summary.approximate {
+                " (approx.)"
+            } else {
+                ""
+            };
+            rows.push(vec![
+                "Mean".to_string(),
+                summary.mean.display(unit).to_string(),
+            ]);
+            rows.push(vec![
+                format!("Median{}", approximate),
+                summary.median.display(unit).to_string(),
+            ]);
+            rows.push(vec![
+                "Standard deviation".to_string(),
+                difference(summary.std_dev),
+            ]);
+            for percentile in summary.percentiles.iter() {
+                rows.push(vec![
+                    format!("p{}{}", percentile.percentile, approximate),
+                    percentile.temperature.display(unit).to_string(),
+                ]);
+            }
+        }
+        None => rows.push(vec!["Readings received".to_string(), "0".to_string()]),
+    }
+    rows.push(vec![
+        "Alerts raised".to_string(),
+        report.alert_count.to_string(),
+    ]);
+    rows.push(vec![
+        "Readings missed".to_string(),
+        report.missed_readings.to_string(),
+    ]);
+    blocks.push(Block::Heading(2, "Summary".to_string()));
+    blocks.push(Block::Table {
+        header: vec!["Statistic", "Value"],
+        rows,
+    });
+
+    let extremes = [
+        ("lowest", &report.lowest_temps, &report.lowest_temp_counts),
+        (
+            "highest",
+            &report.highest_temps,
+            &report.highest_temp_counts,
+        ),
+    ];
+    for (label, recordings, counts) in extremes {
+        if recordings.is_empty() {
+            continue;
+        }
+
+        // Distinct temps come with how many readings hit each, instead of the one reading's time
+        let (heading, header) = if counts.is_empty() {
+            (
+                format!("Top {} {} temps", recordings.len(), label),
+                vec!["#", "Temperature", "Sensor", "Time"],
+            )
+        } else {
+            (
+                format!("Top {} {} distinct temps", recordings.len(), label),
+                vec!["#", "Temperature", "Sensor", "Readings"],
+            )
+        };
+
+        let rows = recordings
+            .iter()
+            .enumerate()
+            .map(|(index, recording)| {
+                vec![
+                    (index + 1).to_string(),
+                    recording.temperature.display(unit).to_string(),
+                    recording.sensor_id.to_string(),
+                    match counts.get(index) {
+                        Some(count) => count.to_string(),
+                        None => time(recording),
+                    },
+                ]
+            })
+            .collect();
+
+        blocks.push(Block::Heading(2, heading));
+        blocks.push(Block::Table { header, rows });
+    }
+
+    blocks.push(Block::Heading(
+        2,
+        "Largest temperature difference".to_string(),
+    ));
+    match (
+        &report.largest_temp_difference,
+        report.largest_difference_minutes,
+    ) {
+        (Some(largest), Some([start, end])) => {
+            blocks.push(Block::Paragraph(format!(
+                "{} within {} minutes, from {:.1} to {:.1} minutes into the period.",
+                difference(largest.difference),
+                report.difference_window_minutes,
+                start,
+                end
+            )));
+            blocks.push(Block::Table {
+                header: vec!["", "Temperature", "Sensor", "Time"],
+                rows: [("Start", &largest.start), ("End", &largest.end)]
+                    .into_iter()
+                    .map(|(label, recording)| {
+                        vec![
+                            label.to_string(),
+                            recording.temperature.display(unit).to_string(),
+                            recording.sensor_id.to_string(),
+                            time(recording),
+                        ]
+                    })
+                    .collect(),
+            });
+        }
+        _ => blocks.push(Block::Paragraph(
+            "Not enough readings were received to compare.".to_string(),
+        )),
+    }
+
+    let optional = |x: Option<String>| x.unwrap_or_else(|| "-".to_string());
+    blocks.push(Block::Heading(2, "Per-sensor statistics".to_string()));
+    blocks.push(Block::Table {
+        header: vec![
+            "Sensor", "Readings", "Min", "Max", "Mean", "Std dev", "Missed", "Dropped", "Alerts",
+        ],
+        rows: report
+            .sensor_stats
+            .iter()
+            .map(|stats| {
+                vec![
+                    stats.sensor_id.to_string(),
+                    stats.readings.to_string(),
+                    optional(stats.min.map(|x| x.display(unit).to_string())),
+                    optional(stats.max.map(|x| x.display(unit).to_string())),
+                    optional(stats.mean.map(|x| x.display(unit).to_string())),
+                    optional(stats.std_dev.map(difference)),
+                    stats.missed.to_string(),
+                    stats.dropped.to_string(),
+                    stats.alerts.to_string(),
+                ]
+            })
+            .collect(),
+    });
+
+    blocks
+}
+
+fn markdown(blocks: &[Block]) -> String {
+    let mut out = String::new();
+
+    for block in blocks {
+        match block {
+            Block::Heading(level, text) => {
+                let _ = writeln!(out, "{} {}\n", "#".repeat(*level), text);
+            }
+            Block::Paragraph(text) => {
+                let _ = writeln!(out, "{}\n", text);
+            }
+            Block::Table { header, rows } => {
+                let _ = writeln!(out, "| {} |", header.join(" | "));
+                let _ = writeln!(out, "|{}", "---|".repeat(header.len()));
+                for row in rows {
+                    let _ = writeln!(out, "| {} |", row.join(" | "));
+                }
+                out.push('\n');
+            }
+        }
+    }
+
+    out
+}
+
+fn html(title: &str, blocks: &[Block]) -> String {
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n\
+         <style>table {{ border-collapse: collapse; }} th, td {{ border: 1px solid #ccc; \
+         padding: 2px 8px; text-align: right; }}</style>\n</head>\n<body>\n",
+        escape(title)
+    );
+
+    for block in blocks {
+        match block {
+            Block::Heading(level, text) => {
+                let _ = writeln!(out, "<h{0}>{1}</h{0}>", level, escape(text));
+            }
+            Block::Paragraph(text) => {
+                let _ = writeln!(out, "<p>{}</p>", escape(text));
+            }
+            Block::Table { header, rows } => {
+                out.push_str("<table>\n<tr>");
+                for cell in header {
+                    let _ = write!(out, "<th>{}</th>", escape(cell));
+                }
+                out.push_str("</tr>\n");
+                for row in rows {
+                    out.push_str("<tr>");
+                    for cell in row {
+                        let _ = write!(out, "<td>{}</td>", escape(cell));
+                    }
+                    out.push_str("</tr>\n");
+                }
+                out.push_str("</table>\n");
+            }
+        }
+    }
+
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Writes each report to its own document in a directory, named `report-001.md`,
+/// `report-002.md` and so on, and also as `report-001.html` etc. when HTML is asked for.
+pub struct DocumentSink {
+    dir: PathBuf,
+    unit: TemperatureUnit,
+    formats: Vec<DocumentFormat>,
+}
+
+impl DocumentSink {
+    /// Creates the output directory if it doesn't exist yet.
+    pub fn new(
+        dir: impl Into<PathBuf>,
+        unit: TemperatureUnit,
+        html: bool,
+    ) -> io::Result<DocumentSink> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+
+        let mut formats = vec![DocumentFormat::Markdown];
+        if html {
+            formats.push(DocumentFormat::Html);
+        }
+
+        Ok(DocumentSink { dir, unit, formats })
+    }
+}
+
+impl ReportSink for DocumentSink {
+    fn handle_report(&mut self, report: &Report) {
+        for &format in self.formats.iter() {
+            let path = self.dir.join(format!(
+                "report-{:03}.{}",
+                report.index + 1,
+                format.extension()
+            ));
+
+            if let Err(err) = fs::write(&path, report.to_document(self.unit, format)) {
+                eprintln!("Couldn't write report to {}: {}", path.display(), err);
+            }
+        }
+    }
+}
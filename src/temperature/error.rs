@@ -0,0 +1,56 @@
+use std::any::Any;
+
+use thiserror::Error;
+
+/// Why a simulation couldn't finish cleanly. Every thread is still waited for before one of these
+/// is returned, so nothing is left running.
+#[derive(Debug, Error)]
+pub enum SimulationError {
+    /// A thread (or task) panicked. The message is the panic's, if it had a readable one
+    #[error("the {thread} thread panicked: {message}")]
+    Panicked {
+        thread: &'static str,
+        message: String,
+    },
+
+    /// A thread stopped taking what it was sent before the simulation finished, which means it
+    /// panicked or returned early. Whatever it was sent is lost
+    #[error("the {0} thread stopped before the simulation finished")]
+    Disconnected(&'static str),
+
+    /// Something that has to be done before the simulation is started was tried afterwards
+    #[error("the simulation has already been started")]
+    AlreadyStarted,
+}
+
+impl SimulationError {
+    /// Turns what `JoinHandle::join` returns for a panicked thread into an error.
+    pub(crate) fn panicked(thread: &'static str, payload: Box<dyn Any + Send>) -> SimulationError {
+        let message = match payload.downcast::<String>() {
+            Ok(message) => *message,
+            Err(payload) => match payload.downcast::<&str>() {
+                Ok(message) => message.to_string(),
+                Err(_) => "no message".to_string(),
+            },
+        };
+
+        SimulationError::Panicked { thread, message }
+    }
+
+    /// Whether this is where the problem started, rather than a thread noticing another one had
+    /// gone away. A panic is what makes other threads disconnect.
+    pub(crate) fn is_cause(&self) -> bool {
+        matches!(self, SimulationError::Panicked { .. })
+    }
+}
+
+/// Keeps the error most likely to explain what went wrong out of every thread's result.
+pub(crate) fn first_cause(
+    results: impl IntoIterator<Item = Result<(), SimulationError>>,
+) -> Result<(), SimulationError> {
+    let mut errors: Vec<SimulationError> = results.into_iter().filter_map(Result::err).collect();
+    match errors.iter().position(|x| x.is_cause()) {
+        Some(index) => Err(errors.swap_remove(index)),
+        None => errors.into_iter().next().map_or(Ok(()), Err),
+    }
+}
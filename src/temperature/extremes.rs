@@ -0,0 +1,309 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, BinaryHeap};
+use std::time::Duration;
+
+use super::{Recording, SimulationConfig, Temperature};
+
+// A sensor and the default sampling period one of its readings was due in
+type Group = (usize, u64);
+
+/// Orders recordings by temperature, then by timestamp so ties come out in the order they were
+/// taken. The group plays no part in the order.
+#[derive(Clone, Debug)]
+struct ByTemperature(Recording, Option<Group>);
+
+impl PartialEq for ByTemperature {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for ByTemperature {}
+
+impl PartialOrd for ByTemperature {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ByTemperature {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .temperature
+            .cmp(&other.0.temperature)
+            .then(self.0.timestamp.cmp(&other.0.timestamp))
+    }
+}
+
+/// Keeps track of the `k` lowest and `k` highest recordings as they arrive using two bounded
+/// binary heaps, so finding them at report time doesn't need a sort of every recording.
+///
+/// A sensor that samples faster than the default sampling period would otherwise fill the lists
+/// just by taking more readings, so its readings are grouped by the default sampling period they
+/// were due in and only the lowest (or highest) of each group can be listed.
+///
+/// With `distinct` set each temperature is listed once instead, by the first reading that hit
+/// it, along with how many readings did. Grouping doesn't apply then, since a group's readings
+/// are all counted anyway.
+#[derive(Clone, Debug)]
+pub struct Extremes {
+    k: usize,
+    distinct: bool,
+
+    // Each faster sensor's sampling period and the default one, in nanoseconds. Empty when no
+    // readings are grouped
+    periods: Vec<u128>,
+    default_period: u128,
+
+    // Max-heap holding the k lowest recordings, so the top is the one to evict
+    lowest: BinaryHeap<ByTemperature>,
+
+    // Min-heap holding the k highest recordings
+    highest: BinaryHeap<Reverse<ByTemperature>>,
+
+    // The k lowest & highest distinct temperatures when `distinct` is set, each with its first
+    // reading and how many readings hit it. A temperature that's been evicted can't come back,
+    // so the counts of the ones listed are exact
+    lowest_distinct: BTreeMap<Temperature, (Recording, usize)>,
+    highest_distinct: BTreeMap<Temperature, (Recording, usize)>,
+}
+
+impl Extremes {
+    pub fn new(k: usize) -> Extremes {
+        Extremes {
+            k,
+            distinct: false,
+            periods: Vec::new(),
+            default_period: 0,
+            lowest: BinaryHeap::with_capacity(k + 1),
+            highest: BinaryHeap::with_capacity(k + 1),
+            lowest_distinct: BTreeMap::new(),
+            highest_distinct: BTreeMap::new(),
+        }
+    }
+
+    /// Keeps the `k` lowest & highest distinct temperatures, with how many readings hit each.
+    pub fn distinct(k: usize) -> Extremes {
+        Extremes {
+            distinct: true,
+            ..Extremes::new(k)
+        }
+    }
+
+    /// Keeps the `top_n` lowest & highest recordings, grouping the readings of every sensor that
+    /// samples faster than the default sampling period, or the `top_n` distinct temperatures if
+    /// `distinct_extremes` is set.
+    pub fn for_config(config: &SimulationConfig) -> Extremes {
+        if config.distinct_extremes {
+            return Extremes::distinct(config.top_n);
+        }
+
+        let nanos = |seconds: f64| Duration::from_secs_f64(seconds).as_nanos();
+        let default_period = nanos(config.sampling_period_seconds);
+        let mut periods: Vec<u128> = (0..config.sensor_count)
+            .map(|sensor_id| nanos(config.sampling_period_for(sensor_id)))
+            .collect();
+        if periods.iter().all(|x| *x >= default_period) {
+            periods.clear();
+        }
+
+        Extremes {
+            periods,
+            default_period,
+            ..Extremes::new(config.top_n)
+        }
+    }
+
+    // Readings from sensors sampling at the default period or slower are never grouped
+    fn group(&self, recording: &Recording) -> Option<Group> {
+        let period = *self.periods.get(recording.sensor_id)?;
+        (period < self.default_period).then(|| {
+            let due = recording.sequence as u128 * period;
+            (recording.sensor_id, (due / self.default_period) as u64)
+        })
+    }
+
+    /// Adds a recording in O(log k), or O(k) if it's in a group.
+    pub fn push(&mut self, recording: &Recording) {
+        if self.k == 0 {
+            return;
+        }
+
+        if self.distinct {
+            count_distinct(
+                &mut self.lowest_distinct,
+                self.k,
+                recording,
+                1,
+                Ordering::Less,
+            );
+            count_distinct(
+                &mut self.highest_distinct,
+                self.k,
+                recording,
+                1,
+                Ordering::Greater,
+            );
+            return;
+        }
+
+        let group = self.group(recording);
+        let entry = ByTemperature(recording.clone(), group);
+
+        // A group is only ever listed once, by whichever of its readings is the most extreme
+        if let Some(group) = group {
+            let same_group = |x: &ByTemperature| x.1 == Some(group);
+
+            match self.lowest.iter().find(|x| same_group(x)) {
+                Some(listed) if entry < *listed => {
+                    self.lowest.retain(|x| !same_group(x));
+                    self.lowest.push(entry.clone());
+                }
+                Some(_) => {}
+                None => self.push_lowest(entry.clone()),
+            }
+
+            match self.highest.iter().find(|x| same_group(&x.0)) {
+                Some(listed) if entry > listed.0 => {
+                    self.highest.retain(|x| !same_group(&x.0));
+                    self.highest.push(Reverse(entry));
+                }
+                Some(_) => {}
+                None => self.push_highest(entry),
+            }
+
+            return;
+        }
+
+        self.push_lowest(entry.clone());
+        self.push_highest(entry);
+    }
+
+    fn push_lowest(&mut self, entry: ByTemperature) {
+        if self.lowest.len() < self.k {
+            self.lowest.push(entry.clone());
+        } else if self.lowest.peek().is_some_and(|top| entry < *top) {
+            self.lowest.pop();
+            self.lowest.push(entry);
+        }
+    }
+
+    fn push_highest(&mut self, entry: ByTemperature) {
+        if self.highest.len() < self.k {
+            self.highest.push(Reverse(entry));
+        } else if self.highest.peek().is_some_and(|top| entry > top.0) {
+            self.highest.pop();
+            self.highest.push(Reverse(entry));
+        }
+    }
+
+    /// Folds in the extremes of `later`, whose recordings were all taken after this one's.
+    pub fn merge(&mut self, later: &Extremes) {
+        if self.distinct {
+            for (recording, count) in later.lowest_distinct.values() {
+                count_distinct(
+                    &mut self.lowest_distinct,
+                    self.k,
+                    recording,
+                    *count,
+                    Ordering::Less,
+                );
+            }
+            for (recording, count) in later.highest_distinct.values() {
+                count_distinct(
+                    &mut self.highest_distinct,
+                    self.k,
+                    recording,
+                    *count,
+                    Ordering::Greater,
+                );
+            }
+            return;
+        }
+
+        for recording in later.lowest().iter().chain(later.highest().iter()) {
+            self.push(recording);
+        }
+    }
+
+    /// The lowest recordings, lowest first.
+    pub fn lowest(&self) -> Vec<Recording> {
+        if self.distinct {
+            return self.lowest_distinct.values().map(|x| x.0.clone()).collect();
+        }
+
+        let mut lowest: Vec<ByTemperature> = self.lowest.iter().cloned().collect();
+        lowest.sort();
+        lowest.into_iter().map(|x| x.0).collect()
+    }
+
+    /// The highest recordings, highest first.
+    pub fn highest(&self) -> Vec<Recording> {
+        if self.distinct {
+            return self
+                .highest_distinct
+                .values()
+                .rev()
+                .map(|x| x.0.clone())
+                .collect();
+        }
+
+        let mut highest: Vec<ByTemperature> = self.highest.iter().map(|x| x.0.clone()).collect();
+        highest.sort_by(|a, b| b.cmp(a));
+        highest.into_iter().map(|x| x.0).collect()
+    }
+
+    /// How many readings hit each of `lowest`, in the same order. Empty unless the temperatures
+    /// are distinct.
+    pub fn lowest_counts(&self) -> Vec<usize> {
+        self.lowest_distinct.values().map(|x| x.1).collect()
+    }
+
+    /// How many readings hit each of `highest`, in the same order. Empty unless the temperatures
+    /// are distinct.
+    pub fn highest_counts(&self) -> Vec<usize> {
+        self.highest_distinct.values().rev().map(|x| x.1).collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.lowest.clear();
+        self.highest.clear();
+        self.lowest_distinct.clear();
+        self.highest_distinct.clear();
+    }
+}
+
+/// Adds `count` readings at `recording`'s temperature to the `k` distinct temperatures listed,
+/// which are the lowest if `keep` is `Less` and the highest if it's `Greater`.
+fn count_distinct(
+    listed: &mut BTreeMap<Temperature, (Recording, usize)>,
+    k: usize,
+    recording: &Recording,
+    count: usize,
+    keep: Ordering,
+) {
+    let temperature = recording.temperature;
+
+    if let Some((first, listed_count)) = listed.get_mut(&temperature) {
+        *listed_count += count;
+        if recording.timestamp < first.timestamp {
+            *first = recording.clone();
+        }
+        return;
+    }
+
+    if listed.len() >= k {
+        let least_extreme = match keep {
+            Ordering::Less => listed.last_key_value(),
+            _ => listed.first_key_value(),
+        };
+        match least_extreme {
+            Some((&evict, _)) if temperature.cmp(&evict) == keep => {
+                listed.remove(&evict);
+            }
+            _ => return,
+        }
+    }
+
+    listed.insert(temperature, (recording.clone(), count));
+}
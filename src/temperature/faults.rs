@@ -0,0 +1,125 @@
+use rand::rngs::StdRng;
+use rand::Rng;
+use rand_distr::{Distribution, Normal};
+use serde::Deserialize;
+
+use super::{Recording, SensorSource, SimulationConfig, Temperature};
+
+/// Makes one sensor misbehave after a random delay, written in the config file as e.g.
+/// `[[sensor_fault]]` with `sensor = 2`, `kind = "stuck"` and `after_minutes = [30, 120]`.
+#[derive(Clone, Debug, Deserialize)]
+pub struct SensorFaultConfig {
+    pub sensor: usize,
+
+    /// The fault starts at a random simulated minute in this range (inclusive)
+    #[serde(default = "default_after_minutes")]
+    pub after_minutes: [f64; 2],
+
+    #[serde(flatten)]
+    pub fault: FaultKind,
+}
+
+fn default_after_minutes() -> [f64; 2] {
+    [0.0, 180.0]
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum FaultKind {
+    /// The sensor stops sending readings, like it went offline
+    Dropout,
+
+    /// The sensor keeps sending the last temperature it read before the fault
+    Stuck,
+
+    /// Normally distributed noise with `std_dev` is added to every reading
+    Noise { std_dev: f64 },
+}
+
+impl SensorFaultConfig {
+    /// Returns the name of the first parameter that isn't usable along with what's wrong with it.
+    pub fn invalid_parameter(&self) -> Option<(&'static str, &'static str)> {
+        let [earliest, latest] = self.after_minutes;
+
+        if !(earliest.is_finite() && latest.is_finite() && 0.0 <= earliest && earliest <= latest) {
+            return Some((
+                "after_minutes",
+                "must be two non-negative numbers with the earliest first",
+            ));
+        }
+
+        match self.fault {
+            FaultKind::Noise { std_dev } if !(std_dev.is_finite() && std_dev >= 0.0) => {
+                Some(("std_dev", "must be a non-negative number"))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Wraps another source and injects a fault into its readings once the fault's start time comes
+/// around. Time is tracked by counting readings, like `RandomSource` does.
+pub struct FaultySource {
+    inner: Box<dyn SensorSource>,
+    fault: FaultKind,
+    rng: StdRng,
+
+    /// Readings taken before the fault starts
+    healthy_readings: u64,
+    readings_taken: u64,
+    last_healthy: Option<Temperature>,
+}
+
+impl FaultySource {
+    pub fn new(
+        inner: Box<dyn SensorSource>,
+        fault: &SensorFaultConfig,
+        mut rng: StdRng,
+        config: &SimulationConfig,
+    ) -> FaultySource {
+        let [earliest, latest] = fault.after_minutes;
+        let start_minute = rng.gen_range(earliest..=latest);
+        let sampling_period_minutes = config.sampling_period_for(fault.sensor) / 60.0;
+
+        FaultySource {
+            inner,
+            fault: fault.fault.clone(),
+            rng,
+            healthy_readings: (start_minute / sampling_period_minutes).ceil() as u64,
+            readings_taken: 0,
+            last_healthy: None,
+        }
+    }
+}
+
+impl SensorSource for FaultySource {
+    fn next_reading(&mut self) -> Option<Recording> {
+        let mut recording = self.inner.next_reading()?;
+        self.readings_taken += 1;
+
+        if self.readings_taken <= self.healthy_readings {
+            self.last_healthy = Some(recording.temperature);
+            return Some(recording);
+        }
+
+        match self.fault {
+            FaultKind::Dropout => return None,
+            FaultKind::Stuck => {
+                // A sensor that failed before its first reading is stuck on that reading
+                let stuck = *self.last_healthy.get_or_insert(recording.temperature);
+                recording.temperature = stuck;
+            }
+            FaultKind::Noise { std_dev } => {
+                let noise = Normal::new(0.0, std_dev).unwrap().sample(&mut self.rng);
+                recording.temperature =
+                    Temperature::from_fahrenheit(recording.temperature.fahrenheit() + noise);
+            }
+        }
+
+        Some(recording)
+    }
+
+    fn take_malformed(&mut self) -> usize {
+        self.inner.take_malformed()
+    }
+}
@@ -0,0 +1,329 @@
+//! Several rovers running in one process. Each rover is its own `TemperatureSimulation`, with its
+//! own sensors, queue and report thread, and labels its reports with its name.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use serde::Serialize;
+
+use super::error::{self, SimulationError};
+use super::{
+    PauseHandle, Recording, Report, ReportSink, SimulationConfig, StopHandle, Temperature,
+    TemperatureSimulation, TemperatureUnit,
+};
+
+/// Runs `config.rovers` simulations side by side.
+pub struct Fleet {
+    rovers: Vec<TemperatureSimulation>,
+}
+
+impl Fleet {
+    /// Creates the rovers, named `rover-1`, `rover-2` and so on, each with `config.sensor_count`
+    /// sensors. With a seed set every rover's sensors get seeds no other rover's sensors use.
+    pub fn new(config: SimulationConfig) -> Fleet {
+        let rovers = (0..config.rovers)
+            .map(|index| {
+                let mut rover_config = config.clone();
+                rover_config.seed = config
+                    .seed
+                    .map(|seed| seed.wrapping_add((index * config.sensor_count) as u64));
+
+                let mut simulation = TemperatureSimulation::with_config(rover_config);
+                simulation.set_name(rover_name(index));
+                simulation
+            })
+            .collect();
+
+        Fleet { rovers }
+    }
+
+    /// The rovers in order, e.g. to add sinks to each one before the fleet is started.
+    pub fn rovers(&self) -> &[TemperatureSimulation] {
+        &self.rovers
+    }
+
+    pub fn rovers_mut(&mut self) -> &mut [TemperatureSimulation] {
+        &mut self.rovers
+    }
+
+    /// Calls `handler` with a summary of every rover's report for a period once all of them have
+    /// generated one. Has to be called before the fleet is started.
+    pub fn on_summary(&mut self, handler: impl FnMut(&FleetSummary) + Send + 'static) {
+        let collector = Arc::new(Mutex::new(SummaryCollector {
+            rovers: self.rovers.len(),
+            pending: BTreeMap::new(),
+            handler: Box::new(handler),
+        }));
+
+        for (rover, simulation) in self.rovers.iter_mut().enumerate() {
+            simulation.add_sink(FleetSink {
+                rover,
+                collector: collector.clone(),
+            });
+        }
+    }
+
+    /// One handle per rover. Stopping the fleet means stopping every one of them.
+    pub fn stop_handles(&self) -> Vec<StopHandle> {
+        self.rovers.iter().map(|x| x.stop_handle()).collect()
+    }
+
+    /// One handle per rover, to pause and resume them together.
+    pub fn pause_handles(&self) -> Vec<PauseHandle> {
+        self.rovers.iter().map(|x| x.pause_handle()).collect()
+    }
+
+    pub fn start(&mut self) {
+        for rover in self.rovers.iter_mut() {
+            rover.start();
+        }
+    }
+
+    /// Stops every rover, even if stopping an earlier one failed.
+    pub fn stop(&mut self) -> Result<(), SimulationError> {
+        let results: Vec<_> = self.rovers.iter_mut().map(|x| x.stop()).collect();
+        error::first_cause(results)
+    }
+
+    /// Blocks until every rover has finished.
+    pub fn wait(&mut self) -> Result<(), SimulationError> {
+        let results: Vec<_> = self.rovers.iter_mut().map(|x| x.wait()).collect();
+        error::first_cause(results)
+    }
+}
+
+/// The name the `index`th rover (counting from 0) labels its reports with.
+pub fn rover_name(index: usize) -> String {
+    format!("rover-{}", index + 1)
+}
+
+struct SummaryCollector {
+    rovers: usize,
+
+    // Reports waiting for the other rovers' reports for the same period, by report index then
+    // rover
+    pending: BTreeMap<usize, Vec<Option<Report>>>,
+
+    handler: Box<dyn FnMut(&FleetSummary) + Send>,
+}
+
+/// Hands one rover's reports to the shared collector.
+struct FleetSink {
+    rover: usize,
+    collector: Arc<Mutex<SummaryCollector>>,
+}
+
+impl ReportSink for FleetSink {
+    fn handle_report(&mut self, report: &Report) {
+        let mut collector = self
+            .collector
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let rovers = collector.rovers;
+
+        let reports = collector
+            .pending
+            .entry(report.index)
+            .or_insert_with(|| vec![None; rovers]);
+        reports[self.rover] = Some(report.clone());
+
+        if !reports.iter().all(|x| x.is_some()) {
+            return;
+        }
+
+        if let Some(reports) = collector.pending.remove(&report.index) {
+            let reports: Vec<Report> = reports.into_iter().flatten().collect();
+            if let Some(summary) = FleetSummary::from_reports(&reports) {
+                (collector.handler)(&summary);
+            }
+        }
+    }
+}
+
+/// Every rover's report for the same period, combined.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct FleetSummary {
+    /// Index of the reports that were combined
+    pub index: usize,
+
+    /// The simulated hour (counting from 0) the period begins in
+    pub hour: u64,
+
+    /// Set if any of the rovers stopped before the period ended
+    pub partial: bool,
+
+    /// How many readings the rovers took between them
+    pub readings: usize,
+
+    /// Mean of every rover's readings together, `None` if none of them took any
+    pub mean: Option<Temperature>,
+
+    /// The lowest & highest temps any rover recorded, and which rover recorded them
+    pub lowest: Option<(String, Recording)>,
+    pub highest: Option<(String, Recording)>,
+
+    /// The largest difference any rover saw, in degrees Fahrenheit, and which rover saw it
+    pub largest_difference: Option<(String, f64)>,
+
+    /// One entry per rover, in the order the rovers were created
+    pub rovers: Vec<RoverSummary>,
+}
+
+/// The headline numbers from one rover's report.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RoverSummary {
+    pub rover: String,
+    pub readings: usize,
+    pub mean: Option<Temperature>,
+    pub lowest: Option<Recording>,
+    pub highest: Option<Recording>,
+
+    /// In degrees Fahrenheit
+    pub largest_difference: Option<f64>,
+}
+
+impl FleetSummary {
+    /// Combines reports for the same period from different rovers. Reports without a rover are
+    /// labelled by their position. Returns `None` if there are no reports.
+    pub fn from_reports(reports: &[Report]) -> Option<FleetSummary> {
+        let first = reports.first()?;
+
+        let rovers: Vec<RoverSummary> = reports
+            .iter()
+            .enumerate()
+            .map(|(index, report)| RoverSummary {
+                rover: report.rover.clone().unwrap_or_else(|| rover_name(index)),
+                readings: report.sensor_stats.iter().map(|x| x.readings).sum(),
+                mean: report.summary.as_ref().map(|x| x.mean),
+                lowest: report.lowest_temps.first().cloned(),
+                highest: report.highest_temps.first().cloned(),
+                largest_difference: report
+                    .largest_temp_difference
+                    .as_ref()
+                    .map(|x| x.difference),
+            })
+            .collect();
+
+        let readings: usize = rovers.iter().map(|x| x.readings).sum();
+        let total: f64 = rovers
+            .iter()
+            .filter_map(|x| Some(x.mean?.fahrenheit() * x.readings as f64))
+            .sum();
+        let mean = (readings > 0).then(|| Temperature::from_fahrenheit(total / readings as f64));
+
+        let extreme = |pick: fn(&RoverSummary) -> &Option<Recording>, lowest: bool| {
+            rovers
+                .iter()
+                .filter_map(|x| pick(x).as_ref().map(|recording| (x, recording)))
+                .reduce(|a, b| {
+                    let a_is_lower = a.1.temperature.fahrenheit() <= b.1.temperature.fahrenheit();
+                    if a_is_lower == lowest {
+                        a
+                    } else {
+                        b
+                    }
+                })
+                .map(|(rover, recording)| (rover.rover.clone(), recording.clone()))
+        };
+
+        let largest_difference = rovers
+            .iter()
+            .filter_map(|x| Some((x.rover.clone(), x.largest_difference?)))
+            .reduce(|a, b| if b.1 > a.1 { b } else { a });
+
+        Some(FleetSummary {
+            index: first.index,
+            hour: first.hour(),
+            partial: reports.iter().any(|x| x.partial),
+            readings,
+            mean,
+            lowest: extreme(|x| &x.lowest, true),
+            highest: extreme(|x| &x.highest, false),
+            largest_difference,
+            rovers,
+        })
+    }
+
+    /// Formats the summary with temperatures in the given unit.
+    pub fn display(&self, unit: TemperatureUnit) -> DisplayFleetSummary<'_> {
+        DisplayFleetSummary {
+            summary: self,
+            unit,
+        }
+    }
+}
+
+pub struct DisplayFleetSummary<'a> {
+    summary: &'a FleetSummary,
+    unit: TemperatureUnit,
+}
+
+impl fmt::Display for DisplayFleetSummary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let summary = self.summary;
+        let unit = self.unit;
+
+        writeln!(
+            f,
+            "Fleet summary for report {} (hour {}), {} rovers and {} readings{}\n",
+            summary.index + 1,
+            summary.hour,
+            summary.rovers.len(),
+            summary.readings,
+            if summary.partial { ", partial" } else { "" }
+        )?;
+
+        if let Some(mean) = summary.mean {
+            writeln!(f, "Mean: {}", mean.display(unit))?;
+        }
+        for (label, extreme) in [("Lowest", &summary.lowest), ("Highest", &summary.highest)] {
+            if let Some((rover, recording)) = extreme {
+                writeln!(
+                    f,
+                    "{}: {} ({}, sensor {})",
+                    label,
+                    recording.temperature.display(unit),
+                    rover,
+                    recording.sensor_id
+                )?;
+            }
+        }
+        match &summary.largest_difference {
+            Some((rover, difference)) => writeln!(
+                f,
+                "Largest difference: {:.1}{} ({})\n",
+                unit.convert_difference(*difference),
+                unit.symbol(),
+                rover
+            )?,
+            None => writeln!(f)?,
+        }
+
+        for rover in summary.rovers.iter() {
+            write!(f, "{}: {} readings", rover.rover, rover.readings)?;
+            if let Some(mean) = rover.mean {
+                write!(f, ", mean {}", mean.display(unit))?;
+            }
+            if let (Some(lowest), Some(highest)) = (&rover.lowest, &rover.highest) {
+                write!(
+                    f,
+                    ", lowest {}, highest {}",
+                    lowest.temperature.display(unit),
+                    highest.temperature.display(unit)
+                )?;
+            }
+            if let Some(difference) = rover.largest_difference {
+                write!(
+                    f,
+                    ", largest difference {:.1}{}",
+                    unit.convert_difference(difference),
+                    unit.symbol()
+                )?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
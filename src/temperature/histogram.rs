@@ -0,0 +1,87 @@
+use serde::{Deserialize, Serialize};
+
+use super::Temperature;
+
+/// Counts how many readings fall into each fixed-width temperature bucket, so a report shows the
+/// distribution of readings and not just the extremes.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Histogram {
+    /// Buckets in ascending order. Each one covers `low` (inclusive) to `high` (exclusive),
+    /// except the last which also includes `high`
+    pub buckets: Vec<HistogramBucket>,
+
+    /// Readings below the first bucket
+    pub below: usize,
+
+    /// Readings above the last bucket
+    pub above: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HistogramBucket {
+    pub low: Temperature,
+    pub high: Temperature,
+    pub count: usize,
+}
+
+impl Histogram {
+    /// Creates empty buckets `bucket_width` degrees Fahrenheit wide covering `min` to `max`. The
+    /// last bucket is cut short if the range doesn't divide evenly. A width of 0 means no buckets.
+    pub fn new(min: f64, max: f64, bucket_width: f64) -> Histogram {
+        let bucket_count = if bucket_width > 0.0 {
+            (((max - min) / bucket_width).ceil() as usize).max(1)
+        } else {
+            0
+        };
+
+        let buckets = (0..bucket_count)
+            .map(|index| HistogramBucket {
+                low: Temperature::from_fahrenheit(min + index as f64 * bucket_width),
+                high: Temperature::from_fahrenheit(
+                    (min + (index + 1) as f64 * bucket_width).min(max),
+                ),
+                count: 0,
+            })
+            .collect();
+
+        Histogram {
+            buckets,
+            below: 0,
+            above: 0,
+        }
+    }
+
+    pub fn push(&mut self, temperature: Temperature) {
+        let (first, last) = match (self.buckets.first(), self.buckets.last()) {
+            (Some(first), Some(last)) => (first.low, last.high),
+            _ => return,
+        };
+
+        if temperature < first {
+            self.below += 1;
+        } else if temperature > last {
+            self.above += 1;
+        } else {
+            // Every bucket is the same width apart from the last one
+            let width = self.buckets[0].high.fahrenheit() - first.fahrenheit();
+            let index = ((temperature.fahrenheit() - first.fahrenheit()) / width) as usize;
+            let index = index.min(self.buckets.len() - 1);
+
+            self.buckets[index].count += 1;
+        }
+    }
+
+    /// Adds the counts from a histogram with the same buckets.
+    pub fn merge(&mut self, other: &Histogram) {
+        for (bucket, other_bucket) in self.buckets.iter_mut().zip(other.buckets.iter()) {
+            bucket.count += other_bucket.count;
+        }
+
+        self.below += other.below;
+        self.above += other.above;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buckets.is_empty()
+    }
+}
@@ -0,0 +1,150 @@
+use super::{
+    BrokenRecord, MissionRecords, RecordKind, Recording, Report, ReportTrend, TrendChange,
+};
+
+/// Every report generated during a run, in the order they were generated.
+#[derive(Clone, Debug, Default)]
+pub struct ReportHistory {
+    reports: Vec<Report>,
+
+    // The run's all-time records, kept up to date as reports are pushed
+    records: Option<MissionRecords>,
+}
+
+impl ReportHistory {
+    pub fn new() -> ReportHistory {
+        ReportHistory::default()
+    }
+
+    pub fn push(&mut self, report: Report) {
+        self.records = match self.records.take() {
+            Some(records) => Some(break_records(records, &report)),
+            None => first_records(&report),
+        };
+        self.reports.push(report);
+    }
+
+    pub fn all_reports(&self) -> &[Report] {
+        &self.reports
+    }
+
+    /// Returns the reports whose period starts during simulated hour `hour` (counting from 0).
+    /// With the default hourly interval that's exactly one report.
+    pub fn reports_for_hour(&self, hour: u64) -> Vec<&Report> {
+        self.reports.iter().filter(|x| x.hour() == hour).collect()
+    }
+
+    pub fn latest(&self) -> Option<&Report> {
+        self.reports.last()
+    }
+
+    /// Compares `report` with the latest report and with the average of every report so far,
+    /// which should all be from before it. Reports without any readings to compare are skipped.
+    /// Returns `None` if there aren't any reports to compare with yet.
+    pub fn trend(&self, report: &Report) -> Option<ReportTrend> {
+        let current = trend_values(report)?;
+        let earlier: Vec<[f64; 4]> = self.reports.iter().filter_map(trend_values).collect();
+        let previous = *earlier.last()?;
+
+        let mut average = [0.0; 4];
+        for values in earlier.iter() {
+            for (total, value) in average.iter_mut().zip(values) {
+                *total += value / earlier.len() as f64;
+            }
+        }
+
+        Some(ReportTrend {
+            previous: trend_change(current, previous),
+            average: trend_change(current, average),
+            reports_averaged: earlier.len(),
+        })
+    }
+
+    /// Returns the run's records as they'd be with `report` in them, if `report` breaks any of
+    /// the ones set by the reports so far.
+    pub fn records(&self, report: &Report) -> Option<MissionRecords> {
+        let records = break_records(self.records.clone()?, report);
+        (!records.broken.is_empty()).then_some(records)
+    }
+
+    pub fn len(&self) -> usize {
+        self.reports.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.reports.is_empty()
+    }
+}
+
+fn first_records(report: &Report) -> Option<MissionRecords> {
+    Some(MissionRecords {
+        lowest: report.lowest_temps.first()?.clone(),
+        highest: report.highest_temps.first()?.clone(),
+        largest_difference: report.largest_temp_difference.clone()?,
+        broken: Vec::new(),
+    })
+}
+
+/// Replaces whichever of `records` the report beats, noting the ones it broke.
+fn break_records(mut records: MissionRecords, report: &Report) -> MissionRecords {
+    records.broken.clear();
+
+    if let Some(lowest) = report.lowest_temps.first() {
+        if lowest.temperature < records.lowest.temperature {
+            records.broken.push(BrokenRecord {
+                record: RecordKind::Lowest,
+                previous: records.lowest.temperature.fahrenheit(),
+            });
+            records.lowest = lowest.clone();
+        }
+    }
+
+    if let Some(highest) = report.highest_temps.first() {
+        if highest.temperature > records.highest.temperature {
+            records.broken.push(BrokenRecord {
+                record: RecordKind::Highest,
+                previous: records.highest.temperature.fahrenheit(),
+            });
+            records.highest = highest.clone();
+        }
+    }
+
+    if let Some(difference) = &report.largest_temp_difference {
+        if difference.difference > records.largest_difference.difference {
+            records.broken.push(BrokenRecord {
+                record: RecordKind::LargestDifference,
+                previous: records.largest_difference.difference,
+            });
+            records.largest_difference = difference.clone();
+        }
+    }
+
+    records
+}
+
+// The mean, lowest & highest temps and largest difference, in degrees Fahrenheit. `None` if the
+// report didn't have the readings for them
+fn trend_values(report: &Report) -> Option<[f64; 4]> {
+    let mean = report.summary.as_ref()?.mean.fahrenheit();
+    let first = |recordings: &[Recording]| {
+        recordings
+            .first()
+            .map_or(mean, |x| x.temperature.fahrenheit())
+    };
+
+    Some([
+        mean,
+        first(&report.lowest_temps),
+        first(&report.highest_temps),
+        report.largest_temp_difference.as_ref()?.difference,
+    ])
+}
+
+fn trend_change(current: [f64; 4], earlier: [f64; 4]) -> TrendChange {
+    TrendChange {
+        mean: current[0] - earlier[0],
+        lowest: current[1] - earlier[1],
+        highest: current[2] - earlier[2],
+        largest_difference: current[3] - earlier[3],
+    }
+}
@@ -0,0 +1,154 @@
+//! Swapping sensor modules in and out while a simulation runs. The rover has one slot per
+//! configured sensor. Retiring a sensor ends its thread and leaves its slot empty, and adding one
+//! starts a new sensor thread in an empty slot, sending on the same queue the old one did. A
+//! sensor only owes readings while it's in its slot, so the reports don't count the time a slot
+//! was empty as missed readings.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+
+use super::SensorSender;
+
+/// Starts a sensor thread in a slot, sending on the slot's queue until it's told to retire.
+pub(crate) type InstallSensor =
+    dyn Fn(usize, SensorSender, Arc<AtomicBool>) -> JoinHandle<()> + Send + Sync;
+
+/// Adds and retires a `TemperatureSimulation`'s sensors without needing access to it, e.g. from
+/// a thread reading commands. Nothing can be swapped before the simulation starts or once it's
+/// been stopped.
+#[derive(Clone)]
+pub struct HotSwapHandle {
+    slots: Arc<SensorSlots>,
+}
+
+impl HotSwapHandle {
+    pub(crate) fn new(slots: Arc<SensorSlots>) -> HotSwapHandle {
+        HotSwapHandle { slots }
+    }
+
+    /// Starts a new sensor in the first empty slot, which takes its first reading when the next
+    /// one is due. Returns the new sensor's ID, or `None` if every slot has a sensor in it.
+    pub fn add(&self) -> Option<usize> {
+        let mut state = self.slots.lock();
+        let sensor_id = state.empty.iter().position(Option::is_some)?;
+        state.install(sensor_id).then_some(sensor_id)
+    }
+
+    /// Starts a new sensor in `sensor_id`'s slot. Returns false if the slot isn't empty.
+    pub fn add_at(&self, sensor_id: usize) -> bool {
+        self.slots.lock().install(sensor_id)
+    }
+
+    /// Ends a sensor's thread once it's finished with the reading it's taking, leaving its slot
+    /// empty. Returns false if there's no sensor in the slot or it's already being retired.
+    pub fn retire(&self, sensor_id: usize) -> bool {
+        let state = self.slots.lock();
+        if state.closed || state.empty.get(sensor_id).is_none_or(Option::is_some) {
+            return false;
+        }
+
+        !state.retiring[sensor_id].swap(true, Ordering::SeqCst)
+    }
+
+    /// The IDs of the slots with a sensor in them that isn't being retired.
+    pub fn installed(&self) -> Vec<usize> {
+        let state = self.slots.lock();
+
+        (0..state.empty.len())
+            .filter(|&x| state.empty[x].is_none() && !state.retiring[x].load(Ordering::SeqCst))
+            .collect()
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct SensorSlots {
+    state: Mutex<SlotState>,
+}
+
+#[derive(Default)]
+struct SlotState {
+    // Set once the simulation starts
+    install: Option<Arc<InstallSensor>>,
+
+    // Tells the sensor in each slot to finish
+    retiring: Vec<Arc<AtomicBool>>,
+
+    // The queue of each empty slot, kept for whichever sensor goes in it next. Holding on to it
+    // means the report thread doesn't see the queue close, so a rover whose sensors have all been
+    // retired keeps running, reporting hours without any readings, until it's stopped or reaches
+    // its last report
+    empty: Vec<Option<SensorSender>>,
+
+    // Every sensor thread, including the ones that have been retired
+    handles: Vec<JoinHandle<()>>,
+
+    // Set once the simulation's been stopped
+    closed: bool,
+}
+
+impl SlotState {
+    fn install(&mut self, sensor_id: usize) -> bool {
+        if self.closed {
+            return false;
+        }
+        let Some(install) = self.install.clone() else {
+            return false;
+        };
+        let Some(sender) = self.empty.get_mut(sensor_id).and_then(Option::take) else {
+            return false;
+        };
+
+        self.retiring[sensor_id].store(false, Ordering::SeqCst);
+        let handle = install(sensor_id, sender, self.retiring[sensor_id].clone());
+        self.handles.push(handle);
+        true
+    }
+}
+
+impl SensorSlots {
+    fn lock(&self) -> MutexGuard<'_, SlotState> {
+        self.state.lock().unwrap()
+    }
+
+    /// Sets up `sensor_count` slots, each with a sensor in it, that new sensors are started in
+    /// with `install`.
+    pub(crate) fn open(&self, sensor_count: usize, install: Arc<InstallSensor>) {
+        let mut state = self.lock();
+        state.install = Some(install);
+        state.retiring = (0..sensor_count)
+            .map(|_| Arc::new(AtomicBool::new(false)))
+            .collect();
+        state.empty = (0..sensor_count).map(|_| None).collect();
+    }
+
+    /// The flag that tells the sensor in `sensor_id`'s slot to finish.
+    pub(crate) fn retiring(&self, sensor_id: usize) -> Arc<AtomicBool> {
+        self.lock().retiring[sensor_id].clone()
+    }
+
+    pub(crate) fn push_handle(&self, handle: JoinHandle<()>) {
+        self.lock().handles.push(handle);
+    }
+
+    /// Called by a retired sensor's thread as it finishes, handing back its slot's queue.
+    pub(crate) fn vacate(&self, sensor_id: usize, sender: SensorSender) {
+        let mut state = self.lock();
+        if !state.closed {
+            state.empty[sensor_id] = Some(sender);
+        }
+    }
+
+    /// Takes one of the sensor threads to join, if there are any left.
+    pub(crate) fn next_handle(&self) -> Option<JoinHandle<()>> {
+        self.lock().handles.pop()
+    }
+
+    /// Stops any more sensors being added and lets go of the empty slots' queues, so the report
+    /// thread sees them close once the running sensors have finished.
+    pub(crate) fn close(&self) {
+        let mut state = self.lock();
+        state.closed = true;
+        state.empty.iter_mut().for_each(|x| *x = None);
+    }
+}
@@ -0,0 +1,145 @@
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::thread::spawn;
+
+use serde::Serialize;
+use tiny_http::{Header, Method, Response, Server};
+
+use super::{Metrics, Recording, Report, ReportHistory, ReportSink};
+
+/// Serves the reports generated so far as JSON over HTTP, so a running simulation can be
+/// inspected from somewhere else:
+///
+/// - `GET /report/latest` returns the most recent report
+/// - `GET /report/<hour>` returns an array of the reports whose period starts during simulated
+///   hour `hour` (counting from 0), which is a single report with the default hourly interval
+/// - `GET /metrics` returns the simulation's `Metrics` for Prometheus to scrape
+///
+/// Requests are answered on a background thread that stops when the sink is dropped.
+pub struct HttpSink {
+    server: Arc<Server>,
+    state: Arc<Mutex<State>>,
+    address: Option<SocketAddr>,
+}
+
+/// Everything the server answers requests from.
+#[derive(Default)]
+struct State {
+    history: ReportHistory,
+    metrics: Metrics,
+}
+
+impl HttpSink {
+    /// Starts listening on `address`, e.g. `127.0.0.1:8080`. Port 0 picks a free port, which
+    /// `local_addr` then returns.
+    pub fn bind(address: impl ToSocketAddrs) -> io::Result<HttpSink> {
+        let server = Arc::new(Server::http(address).map_err(io::Error::other)?);
+        let address = server.server_addr().to_ip();
+        let state = Arc::new(Mutex::new(State::default()));
+
+        let local_server = server.clone();
+        let local_state = state.clone();
+        spawn(move || {
+            // Ends once `unblock` is called
+            for request in local_server.incoming_requests() {
+                let (status, content_type, body) = {
+                    let state = local_state.lock().unwrap();
+                    route(&state, request.method(), request.url())
+                };
+
+                let content_type =
+                    Header::from_bytes(&b"Content-Type"[..], content_type.as_bytes()).unwrap();
+                let response = Response::from_string(body)
+                    .with_status_code(status)
+                    .with_header(content_type);
+
+                if let Err(err) = request.respond(response) {
+                    eprintln!("Couldn't send an HTTP response: {}", err);
+                }
+            }
+        });
+
+        Ok(HttpSink {
+            server,
+            state,
+            address,
+        })
+    }
+
+    /// The address the server is listening on.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.address
+    }
+}
+
+impl ReportSink for HttpSink {
+    fn handle_report(&mut self, report: &Report) {
+        let mut state = self.state.lock().unwrap();
+        state.metrics.record_report(report);
+        state.history.push(report.clone());
+    }
+
+    fn handle_recording(&mut self, recording: &Recording) {
+        self.state.lock().unwrap().metrics.record_reading(recording);
+    }
+}
+
+impl Drop for HttpSink {
+    fn drop(&mut self) {
+        self.server.unblock();
+    }
+}
+
+const JSON: &str = "application/json";
+
+// The Prometheus text exposition format
+const METRICS: &str = "text/plain; version=0.0.4";
+
+/// Works out the status code, content type and body for a request.
+fn route(state: &State, method: &Method, url: &str) -> (u16, &'static str, String) {
+    let history = &state.history;
+
+    if *method != Method::Get {
+        return error(405, "only GET requests are supported");
+    }
+
+    // Query strings aren't used for anything
+    let path = url.split('?').next().unwrap_or_default();
+    let segments: Vec<&str> = path.split('/').filter(|x| !x.is_empty()).collect();
+
+    match segments.as_slice() {
+        ["report", "latest"] => match history.latest() {
+            Some(report) => json(200, report),
+            None => error(404, "no report has been generated yet"),
+        },
+        ["report", hour] => match hour.parse::<u64>() {
+            Ok(hour) => {
+                let reports = history.reports_for_hour(hour);
+                if reports.is_empty() {
+                    error(404, &format!("there's no report for hour {}", hour))
+                } else {
+                    json(200, &reports)
+                }
+            }
+            Err(_) => error(400, &format!("`{}` isn't an hour", hour)),
+        },
+        ["metrics"] => (200, METRICS, state.metrics.render()),
+        _ => error(404, "expected /report/latest, /report/<hour> or /metrics"),
+    }
+}
+
+fn json(status: u16, value: &impl Serialize) -> (u16, &'static str, String) {
+    match serde_json::to_string_pretty(value) {
+        Ok(body) => (status, JSON, body),
+        Err(err) => error(500, &err.to_string()),
+    }
+}
+
+fn error(status: u16, message: &str) -> (u16, &'static str, String) {
+    (
+        status,
+        JSON,
+        serde_json::json!({ "error": message }).to_string(),
+    )
+}
@@ -0,0 +1,221 @@
+use std::fmt::Write as _;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+
+#[cfg(feature = "influx")]
+use std::time::Duration;
+
+#[cfg(feature = "influx")]
+use ureq::http::Uri;
+#[cfg(feature = "influx")]
+use ureq::Agent;
+
+use super::{Recording, Report, ReportSink};
+
+/// How much line protocol is held before it's written out, even if no report is due.
+const FLUSH_BYTES: usize = 64 * 1024;
+
+/// Writes every reading and a summary of every report in InfluxDB line protocol, so a run can be
+/// loaded into InfluxDB (or anything else that reads line protocol, like Telegraf) and graphed
+/// in Grafana.
+///
+/// Readings are written as the `temperature` measurement with the sensor as a tag and the
+/// temperature in degrees Fahrenheit and the sequence number as fields, and reports as the
+/// `report` measurement at the end of their period. Timestamps are nanoseconds since the Unix
+/// epoch. Lines are written out in batches, whenever a report is generated and when the sink is
+/// dropped.
+pub struct InfluxSink {
+    target: Target,
+    pending: String,
+}
+
+enum Target {
+    File {
+        path: PathBuf,
+        writer: BufWriter<File>,
+    },
+
+    #[cfg(feature = "influx")]
+    Http {
+        agent: Agent,
+        uri: Uri,
+        token: Option<String>,
+    },
+}
+
+impl InfluxSink {
+    /// Appends the line protocol to a file.
+    pub fn file(path: impl Into<PathBuf>) -> io::Result<InfluxSink> {
+        let path = path.into();
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+
+        Ok(InfluxSink::with_target(Target::File {
+            path,
+            writer: BufWriter::new(file),
+        }))
+    }
+
+    /// POSTs each batch to an InfluxDB write endpoint, e.g.
+    /// `http://localhost:8086/api/v2/write?org=mars&bucket=rover&precision=ns`. `token` is sent
+    /// as the `Authorization: Token` header when there is one. Fails if `url` isn't an http or
+    /// https URL.
+    #[cfg(feature = "influx")]
+    pub fn http(url: &str, token: Option<String>) -> io::Result<InfluxSink> {
+        let uri: Uri = url
+            .parse()
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+        if !matches!(uri.scheme_str(), Some("http" | "https")) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the URL has to start with http:// or https://",
+            ));
+        }
+
+        let agent: Agent = Agent::config_builder()
+            .timeout_global(Some(Duration::from_secs(10)))
+            .build()
+            .into();
+
+        Ok(InfluxSink::with_target(Target::Http { agent, uri, token }))
+    }
+
+    fn with_target(target: Target) -> InfluxSink {
+        InfluxSink {
+            target,
+            pending: String::new(),
+        }
+    }
+
+    /// Writes out every pending line. A batch that can't be written is dropped rather than
+    /// retried, so a database that's down doesn't make the sink hold on to the whole run.
+    fn flush(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+
+        let lines = std::mem::take(&mut self.pending);
+        match &mut self.target {
+            Target::File { path, writer } => {
+                let result = writer
+                    .write_all(lines.as_bytes())
+                    .and_then(|_| writer.flush());
+                if let Err(err) = result {
+                    eprintln!(
+                        "Couldn't write line protocol to {}: {}",
+                        path.display(),
+                        err
+                    );
+                }
+            }
+
+            #[cfg(feature = "influx")]
+            Target::Http { agent, uri, token } => {
+                let mut request = agent
+                    .post(&*uri)
+                    .header("Content-Type", "text/plain; charset=utf-8");
+                if let Some(token) = token {
+                    request = request.header("Authorization", format!("Token {}", token));
+                }
+
+                if let Err(err) = request.send(lines.as_bytes()) {
+                    eprintln!("Couldn't write line protocol to InfluxDB: {}", err);
+                }
+            }
+        }
+
+        // The buffer would otherwise stay as big as the biggest batch so far
+        self.pending = lines;
+        self.pending.clear();
+        self.pending.shrink_to(FLUSH_BYTES * 2);
+    }
+}
+
+impl ReportSink for InfluxSink {
+    fn handle_recording(&mut self, recording: &Recording) {
+        let _ = writeln!(
+            self.pending,
+            "temperature,sensor={} fahrenheit={},sequence={}i {}",
+            recording.sensor_id,
+            recording.temperature.fahrenheit(),
+            recording.sequence,
+            recording
+                .wall_time()
+                .timestamp_nanos_opt()
+                .unwrap_or_default()
+        );
+
+        if self.pending.len() >= FLUSH_BYTES {
+            self.flush();
+        }
+    }
+
+    fn handle_report(&mut self, report: &Report) {
+        let _ = write!(self.pending, "report");
+        if let Some(rover) = &report.rover {
+            let _ = write!(self.pending, ",rover={}", escape_tag(rover));
+        }
+
+        let _ = write!(
+            self.pending,
+            " index={}i,partial={},alerts={}i,\
+             dropped={}i,late={}i,missed={}i,suspect_sensors={}i,stalled_sensors={}i",
+            report.index,
+            report.partial,
+            report.alert_count,
+            report.dropped_readings,
+            report.late_readings,
+            report.missed_readings,
+            report.suspect_sensors.len(),
+            report.stalled_sensors.len()
+        );
+        if let Some(summary) = &report.summary {
+            let _ = write!(
+                self.pending,
+                ",mean={},std_dev={}",
+                summary.mean.fahrenheit(),
+                summary.std_dev
+            );
+        }
+        if let Some(largest) = &report.largest_temp_difference {
+            let _ = write!(self.pending, ",largest_difference={}", largest.difference);
+        }
+        if let Some(lowest) = report.lowest_temps.first() {
+            let _ = write!(self.pending, ",lowest={}", lowest.temperature.fahrenheit());
+        }
+        if let Some(highest) = report.highest_temps.first() {
+            let _ = write!(
+                self.pending,
+                ",highest={}",
+                highest.temperature.fahrenheit()
+            );
+        }
+
+        let _ = writeln!(
+            self.pending,
+            " {}",
+            report.period_end.timestamp_nanos_opt().unwrap_or_default()
+        );
+
+        self.flush();
+    }
+}
+
+impl Drop for InfluxSink {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+/// Tag values can't have unescaped commas, spaces or equals signs.
+fn escape_tag(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, ',' | ' ' | '=') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
@@ -0,0 +1,104 @@
+use std::time::Duration;
+
+use rdkafka::error::{KafkaError, RDKafkaErrorCode};
+use rdkafka::producer::{BaseRecord, DefaultProducerContext, Producer, ThreadedProducer};
+use rdkafka::ClientConfig;
+
+use super::{Recording, Report, ReportSink};
+
+/// Topic every reading is produced to, keyed by sensor ID.
+pub const READINGS_TOPIC: &str = "temperature-readings";
+
+/// Topic every report is produced to, keyed by report number.
+pub const REPORTS_TOPIC: &str = "temperature-reports";
+
+/// How long dropping the sink waits for queued messages to be delivered.
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Produces every reading to `temperature-readings` and every report to `temperature-reports` on
+/// a Kafka cluster, so the simulation can act as a load generator for a streaming pipeline.
+///
+/// Readings are sent as `{"sensor_id", "temperature", "sequence", "time"}` with the temperature
+/// in Fahrenheit and keyed by sensor ID, so each sensor's readings stay in order within their
+/// partition. Reports are sent as the same JSON the `JsonSink` writes. Messages are batched and
+/// delivered by librdkafka on its own thread; ones that don't fit in its queue (e.g. while the
+/// brokers are unreachable) are dropped rather than holding up the other sinks.
+pub struct KafkaSink {
+    producer: ThreadedProducer<DefaultProducerContext>,
+    failed: bool,
+}
+
+impl KafkaSink {
+    /// Creates a producer for the brokers in `bootstrap_servers` (`host:port`, comma separated).
+    /// The connection is made in the background, so this only fails if the producer can't be
+    /// set up at all.
+    pub fn connect(bootstrap_servers: &str) -> Result<KafkaSink, KafkaError> {
+        let producer = ClientConfig::new()
+            .set("bootstrap.servers", bootstrap_servers)
+            .set(
+                "client.id",
+                format!("assignment3-temperature-{}", std::process::id()),
+            )
+            .create()?;
+
+        Ok(KafkaSink {
+            producer,
+            failed: false,
+        })
+    }
+
+    fn produce(&mut self, topic: &str, key: &str, payload: &[u8]) {
+        let record = BaseRecord::to(topic).key(key).payload(payload);
+
+        // Only the first failure is printed, every reading would fail the same way
+        match self.producer.send(record) {
+            Ok(()) => self.failed = false,
+            Err((err, _)) if !self.failed => {
+                match err.rdkafka_error_code() {
+                    Some(RDKafkaErrorCode::QueueFull) => {
+                        eprintln!("Kafka producer queue is full, dropping messages")
+                    }
+                    _ => eprintln!("Couldn't queue a Kafka message: {}", err),
+                }
+                self.failed = true;
+            }
+            Err(_) => {}
+        }
+    }
+}
+
+impl ReportSink for KafkaSink {
+    fn handle_report(&mut self, report: &Report) {
+        match serde_json::to_vec(report) {
+            Ok(payload) => {
+                let key = (report.index + 1).to_string();
+                self.produce(REPORTS_TOPIC, &key, &payload);
+            }
+            Err(err) => eprintln!("Couldn't serialize report {}: {}", report.index + 1, err),
+        }
+    }
+
+    fn handle_recording(&mut self, recording: &Recording) {
+        let payload = serde_json::json!({
+            "sensor_id": recording.sensor_id,
+            "temperature": recording.temperature.fahrenheit(),
+            "sequence": recording.sequence,
+            "time": recording.wall_time(),
+        });
+
+        self.produce(
+            READINGS_TOPIC,
+            &recording.sensor_id.to_string(),
+            payload.to_string().as_bytes(),
+        );
+    }
+}
+
+impl Drop for KafkaSink {
+    /// Gives whatever is still queued a chance to be delivered.
+    fn drop(&mut self) {
+        if let Err(err) = self.producer.flush(FLUSH_TIMEOUT) {
+            eprintln!("Couldn't deliver every Kafka message: {}", err);
+        }
+    }
+}
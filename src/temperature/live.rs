@@ -0,0 +1,135 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Instant;
+
+use super::schedule::SensorSchedules;
+use super::{Recording, Report, ReportSink, SimulationConfig};
+
+/// The latest of what a running simulation has produced, for answering questions about it (e.g.
+/// from a command line) while it runs: the lowest & highest reading of the current period so far,
+/// each sensor's last reading and the last report.
+///
+/// It finds out about readings & reports through its sink, which has to be added to the
+/// simulation before it's started. Like every sink the sink runs on its own thread, so asking
+/// never holds up the report thread.
+#[derive(Clone)]
+pub struct LiveState {
+    state: Arc<Mutex<LiveStats>>,
+    config: SimulationConfig,
+}
+
+struct LiveStats {
+    latest: Vec<Option<Recording>>,
+
+    // The period the readings were due in and its lowest & highest reading so far
+    period: Option<(u64, Recording, Recording)>,
+
+    last_report: Option<Report>,
+}
+
+/// Feeds a `LiveState` with everything the simulation produces.
+pub struct LiveSink {
+    state: Arc<Mutex<LiveStats>>,
+    config: SimulationConfig,
+    schedules: Option<SensorSchedules>,
+    start: Option<Instant>,
+}
+
+impl LiveState {
+    pub fn new(config: &SimulationConfig) -> LiveState {
+        let state = LiveStats {
+            latest: vec![None; config.sensor_count],
+            period: None,
+            last_report: None,
+        };
+
+        LiveState {
+            state: Arc::new(Mutex::new(state)),
+            config: config.clone(),
+        }
+    }
+
+    pub fn sink(&self) -> LiveSink {
+        LiveSink {
+            state: self.state.clone(),
+            config: self.config.clone(),
+            schedules: None,
+            start: None,
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, LiveStats> {
+        self.state.lock().unwrap()
+    }
+
+    /// The lowest reading due in the current period that's been received so far.
+    pub fn lowest(&self) -> Option<Recording> {
+        self.lock()
+            .period
+            .as_ref()
+            .map(|(_, lowest, _)| lowest.clone())
+    }
+
+    /// The highest reading due in the current period that's been received so far.
+    pub fn highest(&self) -> Option<Recording> {
+        self.lock()
+            .period
+            .as_ref()
+            .map(|(_, _, highest)| highest.clone())
+    }
+
+    /// The last reading received from `sensor_id`, `None` if it hasn't sent one yet or there's
+    /// no such sensor.
+    pub fn latest(&self, sensor_id: usize) -> Option<Recording> {
+        self.lock().latest.get(sensor_id).cloned().flatten()
+    }
+
+    pub fn last_report(&self) -> Option<Report> {
+        self.lock().last_report.clone()
+    }
+}
+
+impl LiveSink {
+    // Which period a reading counts for, the same as the report thread works it out
+    fn period(&self, recording: &Recording) -> u64 {
+        let (Some(schedules), Some(start)) = (&self.schedules, self.start) else {
+            return 0;
+        };
+
+        let due = schedules.due(recording).saturating_duration_since(start);
+        (due.as_nanos() / self.config.scaled_report_interval().as_nanos().max(1)) as u64
+    }
+}
+
+impl ReportSink for LiveSink {
+    fn handle_start(&mut self, start: Instant) {
+        self.start = Some(start);
+        self.schedules = Some(SensorSchedules::new(start, &self.config));
+    }
+
+    fn handle_report(&mut self, report: &Report) {
+        self.state.lock().unwrap().last_report = Some(report.clone());
+    }
+
+    fn handle_recording(&mut self, recording: &Recording) {
+        let period = self.period(recording);
+        let mut state = self.state.lock().unwrap();
+
+        if let Some(latest) = state.latest.get_mut(recording.sensor_id) {
+            *latest = Some(recording.clone());
+        }
+
+        // Stragglers from the period that just ended don't count for the current one
+        match &mut state.period {
+            Some((current, lowest, highest)) if *current == period => {
+                if recording.temperature < lowest.temperature {
+                    *lowest = recording.clone();
+                }
+                if recording.temperature > highest.temperature {
+                    *highest = recording.clone();
+                }
+            }
+            Some((current, _, _)) if *current > period => {}
+            _ => state.period = Some((period, recording.clone(), recording.clone())),
+        }
+    }
+}
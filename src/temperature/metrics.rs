@@ -0,0 +1,138 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use super::{Recording, Report};
+
+/// Upper bounds of the report generation time histogram's buckets, in seconds.
+const GENERATION_BUCKETS: [f64; 10] = [
+    0.00001, 0.00005, 0.0001, 0.0005, 0.001, 0.005, 0.01, 0.05, 0.1, 0.5,
+];
+
+/// Telemetry about a running simulation, rendered in the Prometheus text format.
+///
+/// Readings are counted as they're received. Dropped readings are only counted once a report
+/// covering them has been generated, since that's when the report thread collects them from the
+/// sensors.
+#[derive(Clone, Debug, Default)]
+pub struct Metrics {
+    // Keyed by sensor ID
+    latest_temperature: BTreeMap<usize, f64>,
+    readings_received: BTreeMap<usize, u64>,
+    readings_dropped: BTreeMap<usize, u64>,
+    readings_late: BTreeMap<usize, u64>,
+    readings_missed: BTreeMap<usize, u64>,
+
+    reports_generated: u64,
+
+    // Cumulative count for each of `GENERATION_BUCKETS`, like Prometheus expects
+    generation_buckets: [u64; GENERATION_BUCKETS.len()],
+    generation_sum: f64,
+}
+
+impl Metrics {
+    pub fn new() -> Metrics {
+        Metrics::default()
+    }
+
+    pub fn record_reading(&mut self, recording: &Recording) {
+        self.latest_temperature
+            .insert(recording.sensor_id, recording.temperature.fahrenheit());
+        *self
+            .readings_received
+            .entry(recording.sensor_id)
+            .or_default() += 1;
+    }
+
+    pub fn record_report(&mut self, report: &Report) {
+        for sensor in &report.sensor_stats {
+            *self.readings_dropped.entry(sensor.sensor_id).or_default() += sensor.dropped as u64;
+            *self.readings_late.entry(sensor.sensor_id).or_default() += sensor.late as u64;
+            *self.readings_missed.entry(sensor.sensor_id).or_default() += sensor.missed as u64;
+        }
+
+        self.reports_generated += 1;
+        self.generation_sum += report.generation_seconds;
+        for (count, bound) in self.generation_buckets.iter_mut().zip(GENERATION_BUCKETS) {
+            if report.generation_seconds <= bound {
+                *count += 1;
+            }
+        }
+    }
+
+    /// Renders every metric in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+
+        write_per_sensor(
+            &mut out,
+            "temperature_sensor_latest_fahrenheit",
+            "gauge",
+            "The last temperature each sensor read, in degrees Fahrenheit",
+            &self.latest_temperature,
+        );
+        write_per_sensor(
+            &mut out,
+            "temperature_readings_received_total",
+            "counter",
+            "Readings the report thread has received from each sensor",
+            &self.readings_received,
+        );
+        write_per_sensor(
+            &mut out,
+            "temperature_readings_dropped_total",
+            "counter",
+            "Readings thrown away because a sensor's queue was full",
+            &self.readings_dropped,
+        );
+        write_per_sensor(
+            &mut out,
+            "temperature_readings_late_total",
+            "counter",
+            "Readings that arrived after their period's report was generated",
+            &self.readings_late,
+        );
+        write_per_sensor(
+            &mut out,
+            "temperature_readings_missed_total",
+            "counter",
+            "Readings a sensor was due to take that never made it into a report",
+            &self.readings_missed,
+        );
+
+        let name = "temperature_report_generation_seconds";
+        writeln!(
+            out,
+            "# HELP {} How long building each report took, in seconds",
+            name
+        )
+        .unwrap();
+        writeln!(out, "# TYPE {} histogram", name).unwrap();
+        for (count, bound) in self.generation_buckets.iter().zip(GENERATION_BUCKETS) {
+            writeln!(out, "{}_bucket{{le=\"{}\"}} {}", name, bound, count).unwrap();
+        }
+        writeln!(
+            out,
+            "{}_bucket{{le=\"+Inf\"}} {}",
+            name, self.reports_generated
+        )
+        .unwrap();
+        writeln!(out, "{}_sum {}", name, self.generation_sum).unwrap();
+        writeln!(out, "{}_count {}", name, self.reports_generated).unwrap();
+
+        out
+    }
+}
+
+fn write_per_sensor<T: std::fmt::Display>(
+    out: &mut String,
+    name: &str,
+    kind: &str,
+    help: &str,
+    values: &BTreeMap<usize, T>,
+) {
+    writeln!(out, "# HELP {} {}", name, help).unwrap();
+    writeln!(out, "# TYPE {} {}", name, kind).unwrap();
+    for (sensor_id, value) in values {
+        writeln!(out, "{}{{sensor=\"{}\"}} {}", name, sensor_id, value).unwrap();
+    }
+}
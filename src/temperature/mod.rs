@@ -0,0 +1,154 @@
+mod accumulator;
+pub mod affinity;
+mod anomaly;
+#[cfg(feature = "archive")]
+mod archive;
+#[cfg(feature = "async")]
+mod async_simulation;
+mod barrier;
+mod broadcast;
+mod callbacks;
+#[cfg(feature = "charts")]
+mod chart;
+mod clock;
+mod config;
+#[cfg(feature = "tui")]
+mod dashboard;
+mod decimation;
+mod document;
+mod error;
+mod extremes;
+mod faults;
+mod fleet;
+mod histogram;
+mod history;
+mod hot_swap;
+#[cfg(feature = "http")]
+mod http;
+mod influx;
+#[cfg(feature = "kafka")]
+mod kafka;
+mod live;
+#[cfg(feature = "http")]
+mod metrics;
+mod model;
+#[cfg(feature = "mqtt")]
+mod mqtt;
+mod out_dir;
+mod output;
+#[cfg(feature = "parquet")]
+mod parquet;
+mod pause;
+mod quantiles;
+mod recording;
+mod reorder;
+mod replay;
+mod report;
+mod report_builder;
+mod ring;
+mod rules;
+mod run_summary;
+mod schedule;
+#[cfg(all(feature = "serial", unix))]
+mod serial;
+#[cfg(all(feature = "multiprocess", unix))]
+mod shared_ring;
+mod simulation;
+mod skew;
+mod slots;
+#[cfg(unix)]
+mod socket;
+mod source;
+mod spatial;
+mod speed;
+mod spill;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod suspect;
+pub mod timestamp;
+mod transport;
+mod units;
+mod validation;
+mod watchdog;
+#[cfg(feature = "webhook")]
+mod webhook;
+
+pub use accumulator::ReportAccumulator;
+#[cfg(feature = "parallel")]
+pub use accumulator::PARALLEL_CHUNK_SIZE;
+pub use affinity::AffinityConfig;
+pub use anomaly::{Alert, AlertKind, AnomalyConfig, AnomalyDetector, PriorityConfig};
+#[cfg(feature = "archive")]
+pub use archive::ArchiveSink;
+#[cfg(feature = "async")]
+pub use async_simulation::AsyncTemperatureSimulation;
+#[cfg(feature = "charts")]
+pub use chart::ChartSink;
+pub use clock::{Clock, SystemClock, VirtualClock};
+pub use config::{ConfigError, SimulationConfig};
+#[cfg(feature = "tui")]
+pub use dashboard::{Dashboard, DashboardSink};
+pub use decimation::DecimationConfig;
+pub use document::{DocumentFormat, DocumentSink};
+pub use error::SimulationError;
+pub use extremes::Extremes;
+pub use faults::{FaultKind, FaultySource, SensorFaultConfig};
+pub use fleet::{rover_name, DisplayFleetSummary, Fleet, FleetSummary, RoverSummary};
+pub use histogram::{Histogram, HistogramBucket};
+pub use history::ReportHistory;
+pub use hot_swap::HotSwapHandle;
+#[cfg(feature = "http")]
+pub use http::HttpSink;
+pub use influx::InfluxSink;
+#[cfg(feature = "kafka")]
+pub use kafka::{KafkaSink, READINGS_TOPIC, REPORTS_TOPIC};
+pub use live::{LiveSink, LiveState};
+#[cfg(feature = "http")]
+pub use metrics::Metrics;
+pub use model::{
+    DiurnalModel, GaussianModel, ModelConfig, SensorModelConfig, TemperatureModel, UniformModel,
+};
+#[cfg(feature = "mqtt")]
+pub use mqtt::{MqttSink, REPORT_TOPIC};
+pub use out_dir::OutDirSink;
+pub use output::{ConsoleSink, CsvRecordingSink, JsonSink, ReportSink};
+#[cfg(feature = "parquet")]
+pub use parquet::ParquetSink;
+pub use pause::PauseHandle;
+pub use quantiles::{QuantileConfig, QuantileEstimator, TDigest};
+pub use recording::Recording;
+pub use replay::{RecordSink, RecordedRun};
+pub use report::{
+    find_largest_temp_difference, BrokenRecord, DisplayReport, MissionRecords, Percentile,
+    QueueLatency, RecordKind, RepeatRun, Report, ReportTrend, SensorStats, SummaryStats,
+    TemperatureDifference, TrendChange,
+};
+pub use report_builder::{ReportBuilder, ReportSection};
+pub use rules::{AlertRule, AlertRuleConfig};
+pub use run_summary::{DisplayRunSummary, RunSummary};
+pub use schedule::SensorSamplingConfig;
+#[cfg(all(feature = "serial", unix))]
+pub use serial::{SerialPort, SerialSource};
+#[cfg(all(feature = "multiprocess", unix))]
+pub use shared_ring::{run_sensor_process, SharedRingSender, SharedRingSource, SharedRings};
+pub use simulation::{ReportTrigger, StopHandle, TemperatureSimulation};
+pub use skew::ClockSkewConfig;
+#[cfg(unix)]
+pub use socket::SocketSink;
+pub use source::{FileSource, LineFormat, LineSource, RandomSource, SensorSource, StdinSource};
+pub use spatial::{SensorPositionConfig, SpatialField, SpatialModel};
+pub use speed::SpeedHandle;
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteSink;
+pub use suspect::{find_suspect_sensors, SuspectConfig, SuspectReason, SuspectSensor};
+pub use timestamp::WALL_TIME_FORMAT;
+pub use transport::{sensor_channels, OverflowPolicy, SensorReceiver, SensorSender, Transport};
+pub use units::{DisplayTemperature, Temperature, TemperatureError, TemperatureUnit};
+pub use validation::RejectReason;
+pub use watchdog::{StallReason, StalledSensor, WatchdogConfig};
+#[cfg(feature = "webhook")]
+pub use webhook::{WebhookFormat, WebhookSink};
+
+pub const ONE_HOUR_MS: u64 = 3600000;
+pub const ONE_MINUTE_MS: u64 = 60000;
+pub const SPEEDUP_FACTOR: u64 = 250;
@@ -0,0 +1,264 @@
+use std::f64::consts::TAU;
+
+use rand::rngs::StdRng;
+use rand::Rng;
+use rand_distr::{Distribution, Normal, NormalError};
+use serde::Deserialize;
+
+use super::{SimulationConfig, SpatialField, SpatialModel};
+
+/// Decides what temperature a sensor reads at a given moment of simulated time. Temperatures are
+/// in degrees Fahrenheit.
+pub trait TemperatureModel: Send {
+    /// Produces a temperature for a reading taken `elapsed_minutes` simulated minutes after the
+    /// sensor started.
+    fn sample(&mut self, rng: &mut StdRng, elapsed_minutes: f64) -> f64;
+}
+
+/// A normal distribution for a model's noise. `rand_distr` takes a negative standard deviation
+/// as a mirror image of the positive one, but the config doesn't allow it, so neither do the
+/// models.
+pub(super) fn normal(mean: f64, std_dev: f64) -> Result<Normal<f64>, NormalError> {
+    if std_dev < 0.0 {
+        return Err(NormalError::BadVariance);
+    }
+
+    Normal::new(mean, std_dev)
+}
+
+/// Every temperature in the range is equally likely, which is what the sensors did originally.
+pub struct UniformModel {
+    pub min: f64,
+    pub max: f64,
+}
+
+impl TemperatureModel for UniformModel {
+    fn sample(&mut self, rng: &mut StdRng, _elapsed_minutes: f64) -> f64 {
+        rng.gen_range(self.min..=self.max)
+    }
+}
+
+/// Readings hover around a fixed baseline with normally distributed noise.
+pub struct GaussianModel {
+    noise: Normal<f64>,
+}
+
+impl GaussianModel {
+    /// Fails if `std_dev` is negative, infinite or NaN.
+    pub fn new(baseline: f64, std_dev: f64) -> Result<GaussianModel, NormalError> {
+        Ok(GaussianModel {
+            noise: normal(baseline, std_dev)?,
+        })
+    }
+}
+
+impl TemperatureModel for GaussianModel {
+    fn sample(&mut self, rng: &mut StdRng, _elapsed_minutes: f64) -> f64 {
+        self.noise.sample(rng)
+    }
+}
+
+/// A day/night cycle. The temperature follows a sine wave around `mean`, coldest at the start
+/// of each period, plus optional normally distributed noise.
+pub struct DiurnalModel {
+    pub mean: f64,
+    pub amplitude: f64,
+    pub period_minutes: f64,
+    noise: Normal<f64>,
+}
+
+impl DiurnalModel {
+    /// Fails if `noise` is negative, infinite or NaN.
+    pub fn new(
+        mean: f64,
+        amplitude: f64,
+        period_minutes: f64,
+        noise: f64,
+    ) -> Result<DiurnalModel, NormalError> {
+        Ok(DiurnalModel {
+            mean,
+            amplitude,
+            period_minutes,
+            noise: normal(0.0, noise)?,
+        })
+    }
+}
+
+impl TemperatureModel for DiurnalModel {
+    fn sample(&mut self, rng: &mut StdRng, elapsed_minutes: f64) -> f64 {
+        let phase = TAU * elapsed_minutes / self.period_minutes;
+
+        self.mean - self.amplitude * phase.cos() + self.noise.sample(rng)
+    }
+}
+
+/// Which `TemperatureModel` a sensor uses, as written in the config file, e.g.
+/// `model = { kind = "gaussian", baseline = -40, std_dev = 10 }`.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase", deny_unknown_fields)]
+pub enum ModelConfig {
+    /// Uniformly random between `min_temperature` and `max_temperature`
+    #[default]
+    Uniform,
+
+    Gaussian {
+        baseline: f64,
+        std_dev: f64,
+    },
+
+    Diurnal {
+        mean: f64,
+        amplitude: f64,
+
+        /// Length of one day in simulated minutes. Defaults to a Martian sol
+        #[serde(default = "default_period_minutes")]
+        period_minutes: f64,
+
+        /// Standard deviation of the noise added on top of the cycle
+        #[serde(default)]
+        noise: f64,
+    },
+
+    /// A temperature field shared by every sensor, read at each sensor's position on the rover
+    Spatial {
+        mean: f64,
+
+        /// Standard deviation of the field
+        amplitude: f64,
+
+        /// Meters apart two sensors can be and still read much the same temperature
+        #[serde(default = "default_length_scale")]
+        length_scale: f64,
+
+        /// Simulated minutes it takes the field to change much
+        #[serde(default = "default_time_scale_minutes")]
+        time_scale_minutes: f64,
+
+        /// Standard deviation of the noise each sensor adds on its own
+        #[serde(default)]
+        noise: f64,
+    },
+}
+
+fn default_period_minutes() -> f64 {
+    1477.0
+}
+
+fn default_length_scale() -> f64 {
+    1.0
+}
+
+fn default_time_scale_minutes() -> f64 {
+    60.0
+}
+
+impl ModelConfig {
+    /// Creates the model for one sensor. Every sensor with the same spatial settings reads the
+    /// same field.
+    ///
+    /// Panics if `invalid_parameter` would find a problem, which `SimulationConfig::validate`
+    /// checks for.
+    pub fn build(&self, config: &SimulationConfig, sensor_id: usize) -> Box<dyn TemperatureModel> {
+        match *self {
+            ModelConfig::Uniform => Box::new(UniformModel {
+                min: config.min_temperature,
+                max: config.max_temperature,
+            }),
+            ModelConfig::Gaussian { baseline, std_dev } => {
+                Box::new(GaussianModel::new(baseline, std_dev).expect("invalid `std_dev`"))
+            }
+            ModelConfig::Diurnal {
+                mean,
+                amplitude,
+                period_minutes,
+                noise,
+            } => Box::new(
+                DiurnalModel::new(mean, amplitude, period_minutes, noise).expect("invalid `noise`"),
+            ),
+            ModelConfig::Spatial {
+                mean,
+                amplitude,
+                length_scale,
+                time_scale_minutes,
+                noise,
+            } => {
+                let field = SpatialField::new(
+                    &mut config.field_rng(),
+                    mean,
+                    amplitude,
+                    length_scale,
+                    time_scale_minutes,
+                );
+                Box::new(
+                    SpatialModel::new(field, config.position_for(sensor_id), noise)
+                        .expect("invalid `noise`"),
+                )
+            }
+        }
+    }
+
+    /// Returns the name of the first parameter that isn't usable along with what's wrong with it.
+    pub fn invalid_parameter(&self) -> Option<(&'static str, &'static str)> {
+        match *self {
+            ModelConfig::Uniform => None,
+            ModelConfig::Gaussian { baseline, std_dev } => {
+                if !baseline.is_finite() {
+                    Some(("baseline", "must be a finite number"))
+                } else if !(std_dev.is_finite() && std_dev >= 0.0) {
+                    Some(("std_dev", "must be a non-negative number"))
+                } else {
+                    None
+                }
+            }
+            ModelConfig::Diurnal {
+                mean,
+                amplitude,
+                period_minutes,
+                noise,
+            } => {
+                if !mean.is_finite() {
+                    Some(("mean", "must be a finite number"))
+                } else if !amplitude.is_finite() {
+                    Some(("amplitude", "must be a finite number"))
+                } else if !(period_minutes.is_finite() && period_minutes > 0.0) {
+                    Some(("period_minutes", "must be a positive number"))
+                } else if !(noise.is_finite() && noise >= 0.0) {
+                    Some(("noise", "must be a non-negative number"))
+                } else {
+                    None
+                }
+            }
+            ModelConfig::Spatial {
+                mean,
+                amplitude,
+                length_scale,
+                time_scale_minutes,
+                noise,
+            } => {
+                if !mean.is_finite() {
+                    Some(("mean", "must be a finite number"))
+                } else if !(amplitude.is_finite() && amplitude >= 0.0) {
+                    Some(("amplitude", "must be a non-negative number"))
+                } else if !(length_scale.is_finite() && length_scale > 0.0) {
+                    Some(("length_scale", "must be a positive number"))
+                } else if !(time_scale_minutes.is_finite() && time_scale_minutes > 0.0) {
+                    Some(("time_scale_minutes", "must be a positive number"))
+                } else if !(noise.is_finite() && noise >= 0.0) {
+                    Some(("noise", "must be a non-negative number"))
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+/// Overrides the model for one sensor, e.g.
+/// `[[sensor_model]]` with `sensor = 3` and the same keys as `model`.
+#[derive(Clone, Debug, Deserialize)]
+pub struct SensorModelConfig {
+    pub sensor: usize,
+
+    #[serde(flatten)]
+    pub model: ModelConfig,
+}
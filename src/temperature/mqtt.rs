@@ -0,0 +1,119 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{sleep, spawn, JoinHandle};
+use std::time::Duration;
+
+use rumqttc::{Client, Event, MqttOptions, Outgoing, QoS};
+
+use super::{Recording, Report, ReportSink};
+
+/// Topic every report is published to.
+pub const REPORT_TOPIC: &str = "reports/hourly";
+
+/// How many publishes can be waiting to go out before new ones are dropped.
+const QUEUE_CAPACITY: usize = 1024;
+
+/// Publishes every reading to `sensors/<id>/temperature` and every report to `reports/hourly`
+/// on an MQTT broker, so the simulation can feed home automation or IoT dashboards.
+///
+/// Readings are sent as `{"sensor_id", "temperature", "time"}` with the temperature in
+/// Fahrenheit, and reports as the same JSON the `JsonSink` writes. The latest report is retained
+/// by the broker so new subscribers get it straight away. Publishes that don't fit in the queue
+/// (e.g. while the broker is unreachable) are dropped rather than holding up the other sinks.
+pub struct MqttSink {
+    client: Client,
+    closing: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+    failed: bool,
+}
+
+impl MqttSink {
+    /// Connects to the broker at `host:port` in the background, reconnecting whenever the
+    /// connection drops.
+    pub fn connect(host: &str, port: u16) -> MqttSink {
+        let client_id = format!("assignment3-temperature-{}", std::process::id());
+        let mut options = MqttOptions::new(client_id, host, port);
+        options.set_keep_alive(Duration::from_secs(30));
+
+        let (client, mut connection) = Client::new(options, QUEUE_CAPACITY);
+        let closing = Arc::new(AtomicBool::new(false));
+        let local_closing = closing.clone();
+
+        // The connection only makes progress while it's iterated over
+        let handle = spawn(move || {
+            let mut connected = true;
+
+            for event in connection.iter() {
+                match event {
+                    Ok(Event::Outgoing(Outgoing::Disconnect)) => return,
+                    Ok(_) => connected = true,
+                    // Nothing queued will go out if the broker can't be reached
+                    Err(_) if local_closing.load(Ordering::SeqCst) => return,
+                    Err(err) => {
+                        if connected {
+                            eprintln!("MQTT connection error: {}, retrying", err);
+                            connected = false;
+                        }
+
+                        sleep(Duration::from_secs(1));
+                    }
+                }
+            }
+        });
+
+        MqttSink {
+            client,
+            closing,
+            handle: Some(handle),
+            failed: false,
+        }
+    }
+
+    fn publish(&mut self, topic: String, qos: QoS, retain: bool, payload: Vec<u8>) {
+        // Only the first failure is printed, every reading would fail the same way
+        if let Err(err) = self.client.try_publish(topic, qos, retain, payload) {
+            if !self.failed {
+                eprintln!("Couldn't queue an MQTT publish: {}", err);
+                self.failed = true;
+            }
+        } else {
+            self.failed = false;
+        }
+    }
+}
+
+impl ReportSink for MqttSink {
+    fn handle_report(&mut self, report: &Report) {
+        match serde_json::to_vec(report) {
+            Ok(payload) => self.publish(REPORT_TOPIC.to_string(), QoS::AtLeastOnce, true, payload),
+            Err(err) => eprintln!("Couldn't serialize report {}: {}", report.index + 1, err),
+        }
+    }
+
+    fn handle_recording(&mut self, recording: &Recording) {
+        let payload = serde_json::json!({
+            "sensor_id": recording.sensor_id,
+            "temperature": recording.temperature.fahrenheit(),
+            "time": recording.wall_time(),
+        });
+
+        self.publish(
+            format!("sensors/{}/temperature", recording.sensor_id),
+            QoS::AtMostOnce,
+            false,
+            payload.to_string().into_bytes(),
+        );
+    }
+}
+
+impl Drop for MqttSink {
+    /// Sends whatever is still queued before disconnecting.
+    fn drop(&mut self) {
+        self.closing.store(true, Ordering::SeqCst);
+        let _ = self.client.try_disconnect();
+
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::mem;
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use std::time::Instant;
+
+use serde::Serialize;
+
+use super::schedule::SensorSchedules;
+use super::{
+    timestamp, Recording, Report, ReportSink, RunSummary, SimulationConfig, TemperatureUnit,
+};
+
+/// Writes everything a long run produces to a directory instead of the console, one set of files
+/// per report (so per simulated hour, unless the report interval's been changed):
+/// `report-hour-001.txt` with the report as it would have been printed, `report-hour-001.json`
+/// with it as JSON, and `readings-hour-001.csv` with the raw readings it was worked out from
+/// (the columns `sensor_id,sequence,timestamp,temperature`, as in the `ArchiveSink`). The run's
+/// summary goes in `summary.txt` and `summary.json`.
+///
+/// The raw readings are by far the biggest part, so only the latest `retain` hours of them are
+/// kept if there's a limit, and older files are deleted as new ones are written. The reports are
+/// always kept.
+pub struct OutDirSink {
+    dir: PathBuf,
+    unit: TemperatureUnit,
+    retain: Option<NonZeroUsize>,
+    config: SimulationConfig,
+    schedules: Option<SensorSchedules>,
+    start: Option<Instant>,
+    readings: Vec<Recording>,
+}
+
+impl OutDirSink {
+    /// Creates the output directory if it doesn't exist yet. `retain` is how many hours of raw
+    /// readings to keep, or `None` to keep them all.
+    pub fn new(
+        dir: impl Into<PathBuf>,
+        config: &SimulationConfig,
+        retain: Option<NonZeroUsize>,
+    ) -> io::Result<OutDirSink> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+
+        Ok(OutDirSink {
+            dir,
+            unit: config.unit,
+            retain,
+            config: config.clone(),
+            schedules: None,
+            start: None,
+            readings: Vec::new(),
+        })
+    }
+
+    /// Takes the readings that were due before the end of the `index`th report's period, or
+    /// every reading if the report is partial.
+    fn take_period(&mut self, index: usize, partial: bool) -> Vec<Recording> {
+        let (Some(schedules), Some(start), false) = (&self.schedules, self.start, partial) else {
+            return mem::take(&mut self.readings);
+        };
+
+        let period_end = start + self.config.scaled_report_interval() * (index as u32 + 1);
+        schedules.take_due_before(&mut self.readings, period_end)
+    }
+
+    /// Deletes the raw readings that have fallen out of the retention limit now the `number`th
+    /// hour's have been written.
+    fn rotate(&self, number: usize) {
+        let Some(retain) = self.retain else {
+            return;
+        };
+        let Some(expired) = number.checked_sub(retain.get()) else {
+            return;
+        };
+
+        let path = self.dir.join(readings_file(expired));
+        match fs::remove_file(&path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => {
+                eprintln!("Couldn't delete {}: {}", path.display(), err);
+            }
+            _ => {}
+        }
+    }
+
+    fn write(&self, name: &str, write: impl FnOnce(&mut BufWriter<File>) -> io::Result<()>) {
+        let path = self.dir.join(name);
+        let result = File::create(&path).and_then(|file| {
+            let mut writer = BufWriter::new(file);
+            write(&mut writer)?;
+            writer.flush()
+        });
+
+        if let Err(err) = result {
+            eprintln!("Couldn't write {}: {}", path.display(), err);
+        }
+    }
+}
+
+fn readings_file(number: usize) -> String {
+    format!("readings-hour-{:03}.csv", number)
+}
+
+fn write_json(writer: &mut impl Write, value: &impl Serialize) -> io::Result<()> {
+    serde_json::to_writer_pretty(&mut *writer, value)?;
+    writeln!(writer)
+}
+
+fn write_readings(writer: &mut impl Write, readings: &[Recording]) -> io::Result<()> {
+    writeln!(writer, "sensor_id,sequence,timestamp,temperature")?;
+
+    for recording in readings {
+        writeln!(
+            writer,
+            "{},{},{},{}",
+            recording.sensor_id,
+            recording.sequence,
+            timestamp::to_nanos(recording.timestamp),
+            recording.temperature.fahrenheit()
+        )?;
+    }
+
+    Ok(())
+}
+
+impl ReportSink for OutDirSink {
+    fn handle_start(&mut self, start: Instant) {
+        self.start = Some(start);
+        self.schedules = Some(SensorSchedules::new(start, &self.config));
+    }
+
+    fn handle_recording(&mut self, recording: &Recording) {
+        self.readings.push(recording.clone());
+    }
+
+    fn handle_report(&mut self, report: &Report) {
+        let number = report.index + 1;
+        let readings = self.take_period(report.index, report.partial);
+
+        self.write(&format!("report-hour-{:03}.txt", number), |writer| {
+            write!(writer, "{}", report.display(self.unit))
+        });
+        self.write(&format!("report-hour-{:03}.json", number), |writer| {
+            write_json(writer, report)
+        });
+        self.write(&readings_file(number), |writer| {
+            write_readings(writer, &readings)
+        });
+        self.rotate(number);
+
+        // The buffer would otherwise stay as big as the busiest period so far
+        self.readings.shrink_to(self.readings.len() * 2);
+    }
+
+    fn handle_summary(&mut self, summary: &RunSummary) {
+        self.write("summary.txt", |writer| {
+            write!(writer, "{}", summary.display(self.unit))
+        });
+        self.write("summary.json", |writer| write_json(writer, summary));
+    }
+}
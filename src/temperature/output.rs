@@ -0,0 +1,203 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::time::Instant;
+
+use serde::Serialize;
+
+use super::{timestamp, Alert, AlertKind, Recording, Report, RunSummary, TemperatureUnit};
+
+/// Receives every report the simulation generates, and optionally every recording as the report
+/// thread receives it. Each sink runs on its own output thread with its own queue, so a slow sink
+/// never holds up the report thread or the other sinks.
+pub trait ReportSink: Send {
+    /// Called once before anything else, with the time the first period starts at.
+    fn handle_start(&mut self, _start: Instant) {}
+
+    fn handle_report(&mut self, report: &Report);
+
+    /// Called with a report that was asked for partway through its period. It isn't one of the
+    /// run's reports, so only sinks that show it somewhere need to handle it.
+    fn handle_on_demand_report(&mut self, _report: &Report) {}
+
+    fn handle_recording(&mut self, _recording: &Recording) {}
+
+    /// Called as soon as a reading raises an alert, without waiting for the report.
+    fn handle_alert(&mut self, _alert: &Alert) {}
+
+    /// Called once after the last report, with a summary of the whole run.
+    fn handle_summary(&mut self, _summary: &RunSummary) {}
+}
+
+impl<S: ReportSink + ?Sized> ReportSink for Box<S> {
+    fn handle_start(&mut self, start: Instant) {
+        (**self).handle_start(start)
+    }
+    fn handle_report(&mut self, report: &Report) {
+        (**self).handle_report(report)
+    }
+
+    fn handle_on_demand_report(&mut self, report: &Report) {
+        (**self).handle_on_demand_report(report)
+    }
+
+    fn handle_recording(&mut self, recording: &Recording) {
+        (**self).handle_recording(recording)
+    }
+
+    fn handle_alert(&mut self, alert: &Alert) {
+        (**self).handle_alert(alert)
+    }
+
+    fn handle_summary(&mut self, summary: &RunSummary) {
+        (**self).handle_summary(summary)
+    }
+}
+
+/// Prints each report to stdout in the given unit.
+pub struct ConsoleSink {
+    unit: TemperatureUnit,
+}
+
+impl ConsoleSink {
+    pub fn new(unit: TemperatureUnit) -> ConsoleSink {
+        ConsoleSink { unit }
+    }
+}
+
+impl ReportSink for ConsoleSink {
+    fn handle_report(&mut self, report: &Report) {
+        println!("\nA new report has been generated\n");
+        print!("{}", report.display(self.unit));
+    }
+
+    fn handle_on_demand_report(&mut self, report: &Report) {
+        println!("\nAn on-demand report has been generated\n");
+        print!("{}", report.display(self.unit));
+    }
+
+    fn handle_alert(&mut self, alert: &Alert) {
+        let compared_with = match alert.kind {
+            AlertKind::Spike => "from its baseline of",
+            AlertKind::Extreme => "past the limit of",
+            AlertKind::Rule => "compared with",
+        };
+        let rule = match &alert.rule {
+            Some(rule) => format!(" ({})", rule),
+            None => String::new(),
+        };
+
+        println!(
+            "ALERT{}: sensor {} recorded {}, {:+.1}{} {} {}",
+            rule,
+            alert.recording.sensor_id,
+            alert.recording.temperature.display(self.unit),
+            self.unit.convert_difference(alert.deviation),
+            self.unit.symbol(),
+            compared_with,
+            alert.baseline.display(self.unit)
+        );
+    }
+
+    fn handle_summary(&mut self, summary: &RunSummary) {
+        println!();
+        print!("{}", summary.display(self.unit));
+    }
+}
+
+/// Writes each report to its own pretty printed JSON file in a directory, named
+/// `report-001.json`, `report-002.json` and so on, and the run's summary to `summary.json`.
+pub struct JsonSink {
+    dir: PathBuf,
+}
+
+impl JsonSink {
+    /// Creates the output directory if it doesn't exist yet.
+    pub fn new(dir: impl Into<PathBuf>) -> io::Result<JsonSink> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+
+        Ok(JsonSink { dir })
+    }
+
+    fn write_json(&self, path: &PathBuf, value: &impl Serialize) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut writer, value)?;
+        writeln!(writer)?;
+        writer.flush()
+    }
+}
+
+impl ReportSink for JsonSink {
+    fn handle_report(&mut self, report: &Report) {
+        let path = self
+            .dir
+            .join(format!("report-{:03}.json", report.index + 1));
+
+        if let Err(err) = self.write_json(&path, report) {
+            eprintln!("Couldn't write report to {}: {}", path.display(), err);
+        }
+    }
+
+    fn handle_summary(&mut self, summary: &RunSummary) {
+        let path = self.dir.join("summary.json");
+
+        if let Err(err) = self.write_json(&path, summary) {
+            eprintln!("Couldn't write run summary to {}: {}", path.display(), err);
+        }
+    }
+}
+
+/// Appends every raw recording to a CSV file with the columns `sensor_id,timestamp,temperature`.
+/// Timestamps are nanoseconds since `timestamp::process_epoch()` and temperatures are in
+/// Fahrenheit. Writes are buffered and flushed whenever a report is generated.
+pub struct CsvRecordingSink {
+    path: PathBuf,
+    writer: BufWriter<File>,
+}
+
+impl CsvRecordingSink {
+    /// Opens the file for appending, writing the header if the file is new or empty.
+    pub fn new(path: impl Into<PathBuf>) -> io::Result<CsvRecordingSink> {
+        let path = path.into();
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let is_empty = file.metadata()?.len() == 0;
+
+        let mut writer = BufWriter::new(file);
+        if is_empty {
+            writeln!(writer, "sensor_id,timestamp,temperature")?;
+        }
+
+        Ok(CsvRecordingSink { path, writer })
+    }
+}
+
+impl ReportSink for CsvRecordingSink {
+    fn handle_report(&mut self, _report: &Report) {
+        if let Err(err) = self.writer.flush() {
+            eprintln!(
+                "Couldn't write recordings to {}: {}",
+                self.path.display(),
+                err
+            );
+        }
+    }
+
+    fn handle_recording(&mut self, recording: &Recording) {
+        let result = writeln!(
+            self.writer,
+            "{},{},{}",
+            recording.sensor_id,
+            timestamp::to_nanos(recording.timestamp),
+            recording.temperature.fahrenheit()
+        );
+
+        if let Err(err) = result {
+            eprintln!(
+                "Couldn't write recordings to {}: {}",
+                self.path.display(),
+                err
+            );
+        }
+    }
+}
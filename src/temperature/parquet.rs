@@ -0,0 +1,168 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+
+use parquet::basic::Compression;
+use parquet::data_type::{DoubleType, Int32Type, Int64Type};
+use parquet::errors::ParquetError;
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::parser::parse_message_type;
+
+use super::schedule::SensorSchedules;
+use super::{Recording, Report, ReportSink, RunSummary, SimulationConfig};
+
+const SCHEMA: &str = "
+    message reading {
+        required int32 sensor_id;
+        required int64 sequence;
+        required int64 time (TIMESTAMP(MICROS, true));
+        required double temperature;
+    }
+";
+
+/// Writes the raw readings to Parquet files partitioned by the simulated hour they were due in,
+/// `hour=0/part-0.parquet` for the first hour and so on, so a run can be loaded straight into
+/// pandas or DuckDB (e.g. `read_parquet('dir/*/*.parquet', hive_partitioning = true)`). Each file
+/// has the columns `sensor_id`, `sequence`, `time` (the wall clock time, in UTC) and
+/// `temperature` (in Fahrenheit). An hour is written once the report covering the end of it has
+/// been, and its readings let go of, the same as the `ArchiveSink`.
+pub struct ParquetSink {
+    dir: PathBuf,
+    config: SimulationConfig,
+    schedules: Option<SensorSchedules>,
+    start: Option<Instant>,
+    hours: BTreeMap<u64, Vec<Recording>>,
+
+    // How many files have been written for each hour. Normally one, but a reading that turns up
+    // after its hour's been written goes in a file of its own rather than replacing it
+    parts: BTreeMap<u64, usize>,
+}
+
+impl ParquetSink {
+    /// Creates the output directory if it doesn't exist yet.
+    pub fn new(dir: impl Into<PathBuf>, config: &SimulationConfig) -> io::Result<ParquetSink> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+
+        Ok(ParquetSink {
+            dir,
+            config: config.clone(),
+            schedules: None,
+            start: None,
+            hours: BTreeMap::new(),
+            parts: BTreeMap::new(),
+        })
+    }
+
+    /// The simulated hour `recording` was due in, counting from the start of the run.
+    fn hour(&self, recording: &Recording) -> u64 {
+        let (Some(schedules), Some(start)) = (&self.schedules, self.start) else {
+            return 0;
+        };
+
+        let since_start = schedules.due(recording).saturating_duration_since(start);
+        (since_start.as_secs_f64() / self.config.scaled_minutes(60).as_secs_f64()) as u64
+    }
+
+    /// Writes out every hour before `end_hour`, or every hour there's a reading for if there's
+    /// no end.
+    fn write_hours(&mut self, end_hour: Option<u64>) {
+        let hours = match end_hour {
+            Some(end_hour) => {
+                let later = self.hours.split_off(&end_hour);
+                mem::replace(&mut self.hours, later)
+            }
+            None => mem::take(&mut self.hours),
+        };
+
+        for (hour, readings) in hours {
+            let part = self.parts.entry(hour).or_default();
+            let dir = self.dir.join(format!("hour={}", hour));
+            let path = dir.join(format!("part-{}.parquet", part));
+            *part += 1;
+
+            let written = fs::create_dir_all(&dir)
+                .map_err(ParquetError::from)
+                .and_then(|_| write_readings(&path, &readings));
+            if let Err(err) = written {
+                eprintln!("Couldn't write readings to {}: {}", path.display(), err);
+            }
+        }
+    }
+}
+
+fn write_readings(path: &Path, readings: &[Recording]) -> Result<(), ParquetError> {
+    let schema = Arc::new(parse_message_type(SCHEMA)?);
+    let properties = Arc::new(
+        WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build(),
+    );
+    let mut writer = SerializedFileWriter::new(File::create(path)?, schema, properties)?;
+
+    let sensor_ids: Vec<i32> = readings.iter().map(|x| x.sensor_id as i32).collect();
+    let sequences: Vec<i64> = readings.iter().map(|x| x.sequence as i64).collect();
+    let times: Vec<i64> = readings
+        .iter()
+        .map(|x| x.wall_time().timestamp_micros())
+        .collect();
+    let temperatures: Vec<f64> = readings
+        .iter()
+        .map(|x| x.temperature.fahrenheit())
+        .collect();
+
+    let mut row_group = writer.next_row_group()?;
+    let mut index = 0;
+    while let Some(mut column) = row_group.next_column()? {
+        match index {
+            0 => column
+                .typed::<Int32Type>()
+                .write_batch(&sensor_ids, None, None)?,
+            1 => column
+                .typed::<Int64Type>()
+                .write_batch(&sequences, None, None)?,
+            2 => column
+                .typed::<Int64Type>()
+                .write_batch(&times, None, None)?,
+            _ => column
+                .typed::<DoubleType>()
+                .write_batch(&temperatures, None, None)?,
+        };
+        column.close()?;
+        index += 1;
+    }
+    row_group.close()?;
+
+    writer.close()?;
+    Ok(())
+}
+
+impl ReportSink for ParquetSink {
+    fn handle_start(&mut self, start: Instant) {
+        self.start = Some(start);
+        self.schedules = Some(SensorSchedules::new(start, &self.config));
+    }
+
+    fn handle_recording(&mut self, recording: &Recording) {
+        let hour = self.hour(recording);
+        self.hours.entry(hour).or_default().push(recording.clone());
+    }
+
+    fn handle_report(&mut self, report: &Report) {
+        if report.partial {
+            self.write_hours(None);
+        } else {
+            let end_minute = report.start_minute + self.config.report_interval_minutes;
+            self.write_hours(Some(end_minute / 60));
+        }
+    }
+
+    fn handle_summary(&mut self, _summary: &RunSummary) {
+        self.write_hours(None);
+    }
+}
@@ -0,0 +1,161 @@
+//! Pausing a simulation stops time for it. The sensor and report threads see the time through a
+//! `PausableClock`, which leaves out however long the simulation has been paused, so a period
+//! that was paused partway through still covers a whole hour of running time and its report
+//! comes out the same as if it never had been.
+
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use super::Clock;
+
+/// Pauses and resumes a `TemperatureSimulation` without needing access to it, e.g. from a thread
+/// reading commands. A simulation can be paused before it's started, in which case it starts
+/// paused.
+#[derive(Clone)]
+pub struct PauseHandle {
+    pause: Arc<Pause>,
+}
+
+impl PauseHandle {
+    pub(crate) fn new(pause: Arc<Pause>) -> PauseHandle {
+        PauseHandle { pause }
+    }
+
+    /// Holds every sensor at its next reading and the report thread at the end of its period
+    /// until `resume` is called. Returns false if the simulation was already paused or has been
+    /// stopped.
+    pub fn pause(&self) -> bool {
+        self.pause.pause()
+    }
+
+    /// Returns false if the simulation wasn't paused.
+    pub fn resume(&self) -> bool {
+        self.pause.resume()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.pause.lock().paused
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Pause {
+    state: Mutex<PauseState>,
+    resumed: Condvar,
+}
+
+#[derive(Default)]
+struct PauseState {
+    paused: bool,
+
+    // A stopped simulation can't be paused, since its threads have to be able to finish
+    stopped: bool,
+
+    // Set once the simulation starts, pausing before then doesn't take up any time
+    clock: Option<Arc<dyn Clock>>,
+
+    // When the current pause started, by the clock the simulation was given
+    paused_since: Option<Instant>,
+
+    // How long every earlier pause took altogether
+    paused_for: Duration,
+}
+
+impl Pause {
+    fn lock(&self) -> MutexGuard<'_, PauseState> {
+        self.state.lock().unwrap()
+    }
+
+    /// Starts measuring pauses by `clock`. A simulation that was paused before it started is
+    /// paused from now.
+    pub(crate) fn attach(&self, clock: Arc<dyn Clock>) {
+        let mut state = self.lock();
+        if state.paused {
+            state.paused_since = Some(clock.now());
+        }
+        state.clock = Some(clock);
+    }
+
+    fn pause(&self) -> bool {
+        let mut state = self.lock();
+        if state.paused || state.stopped {
+            return false;
+        }
+
+        state.paused = true;
+        state.paused_since = state.clock.as_ref().map(|x| x.now());
+        true
+    }
+
+    fn resume(&self) -> bool {
+        let mut state = self.lock();
+        if !state.paused {
+            return false;
+        }
+
+        let since = state.paused_since.take();
+        if let (Some(clock), Some(since)) = (&state.clock, since) {
+            let paused = clock.now().saturating_duration_since(since);
+            state.paused_for += paused;
+        }
+        state.paused = false;
+        self.resumed.notify_all();
+        true
+    }
+
+    /// Resumes the simulation for good so its threads can see they've been told to stop.
+    pub(crate) fn stop(&self) {
+        self.resume();
+        self.lock().stopped = true;
+    }
+}
+
+/// The clock the simulation was given, minus the time it's spent paused. While the simulation
+/// is paused the time stands still and nothing that sleeps on the clock wakes up.
+pub(crate) struct PausableClock {
+    inner: Arc<dyn Clock>,
+    pause: Arc<Pause>,
+}
+
+impl PausableClock {
+    pub(crate) fn new(inner: Arc<dyn Clock>, pause: Arc<Pause>) -> PausableClock {
+        pause.attach(inner.clone());
+        PausableClock { inner, pause }
+    }
+}
+
+impl Clock for PausableClock {
+    fn now(&self) -> Instant {
+        let state = self.pause.lock();
+        let now = state.paused_since.unwrap_or_else(|| self.inner.now());
+        now - state.paused_for
+    }
+
+    /// A thread that's asleep when the simulation is paused wakes up when it would have, then
+    /// waits out the pause and sleeps for whatever's left.
+    fn sleep(&self, duration: Duration) {
+        let wake_at = self.now() + duration;
+
+        loop {
+            let mut state = self.pause.lock();
+            while state.paused {
+                state = self.pause.resumed.wait(state).unwrap();
+            }
+            drop(state);
+
+            let now = self.now();
+            if now >= wake_at {
+                return;
+            }
+            self.inner.sleep(wake_at - now);
+        }
+    }
+
+    fn add_participant(&self) {
+        self.inner.add_participant();
+    }
+
+    fn remove_participant(&self) {
+        self.inner.remove_participant();
+    }
+}
@@ -0,0 +1,267 @@
+use serde::Deserialize;
+
+/// How a report works out its median & percentiles, as written in the config file, e.g.
+/// `quantiles = { kind = "tdigest", compression = 100 }`.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase", deny_unknown_fields)]
+pub enum QuantileConfig {
+    /// Keeps every reading for the hour and sorts them, which is exact but memory grows with the
+    /// number of readings
+    #[default]
+    Exact,
+
+    /// Approximates the quantiles with a t-digest, which uses a fixed amount of memory
+    TDigest {
+        /// Higher values keep more centroids, trading memory for accuracy
+        #[serde(default = "default_compression")]
+        compression: f64,
+    },
+}
+
+fn default_compression() -> f64 {
+    100.0
+}
+
+impl QuantileConfig {
+    pub fn build(&self) -> QuantileEstimator {
+        match *self {
+            QuantileConfig::Exact => QuantileEstimator::Exact {
+                values: Vec::new(),
+                sorted: true,
+            },
+            QuantileConfig::TDigest { compression } => {
+                QuantileEstimator::TDigest(TDigest::new(compression))
+            }
+        }
+    }
+
+    /// Returns the name of the first parameter that isn't usable along with what's wrong with it.
+    pub fn invalid_parameter(&self) -> Option<(&'static str, &'static str)> {
+        match *self {
+            QuantileConfig::Exact => None,
+            QuantileConfig::TDigest { compression } => {
+                if !(compression.is_finite() && compression >= 1.0) {
+                    Some(("compression", "must be a number of at least 1"))
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+/// Collects readings (in degrees Fahrenheit) so quantiles can be estimated from them.
+#[derive(Clone, Debug)]
+pub enum QuantileEstimator {
+    Exact { values: Vec<f64>, sorted: bool },
+    TDigest(TDigest),
+}
+
+impl QuantileEstimator {
+    pub fn push(&mut self, value: f64) {
+        match self {
+            QuantileEstimator::Exact { values, sorted } => {
+                values.push(value);
+                *sorted = false;
+            }
+            QuantileEstimator::TDigest(digest) => digest.push(value),
+        }
+    }
+
+    /// Adds everything collected by `other`, which has to be the same kind of estimator.
+    pub fn merge(&mut self, other: QuantileEstimator) {
+        match (self, other) {
+            (
+                QuantileEstimator::Exact { values, sorted },
+                QuantileEstimator::Exact {
+                    values: other_values,
+                    ..
+                },
+            ) => {
+                values.extend(other_values);
+                *sorted = false;
+            }
+            (QuantileEstimator::TDigest(digest), QuantileEstimator::TDigest(other_digest)) => {
+                digest.merge(other_digest)
+            }
+            _ => panic!("can't merge different kinds of quantile estimators"),
+        }
+    }
+
+    /// Estimates the value below which `percentile` percent of the readings fall. Returns `None`
+    /// if nothing has been pushed.
+    pub fn percentile(&mut self, percentile: f64) -> Option<f64> {
+        match self {
+            QuantileEstimator::Exact { values, sorted } => {
+                if values.is_empty() {
+                    return None;
+                }
+
+                if !*sorted {
+                    values.sort_by(f64::total_cmp);
+                    *sorted = true;
+                }
+
+                // Linearly interpolates between the two closest ranks, so the 50th percentile of
+                // an even number of readings is the average of the middle two
+                let rank = percentile / 100.0 * (values.len() - 1) as f64;
+                let lower = rank.floor() as usize;
+                let upper = rank.ceil() as usize;
+
+                Some(values[lower] + (values[upper] - values[lower]) * (rank - lower as f64))
+            }
+            QuantileEstimator::TDigest(digest) => digest.quantile(percentile / 100.0),
+        }
+    }
+
+    /// Whether the percentiles are estimates rather than exact values.
+    pub fn is_approximate(&self) -> bool {
+        matches!(self, QuantileEstimator::TDigest(_))
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Centroid {
+    mean: f64,
+    weight: f64,
+}
+
+/// A merging t-digest. Readings are buffered and periodically merged into a sorted list of
+/// centroids, where centroids near the middle of the distribution can absorb many readings while
+/// the ones near the tails stay small. That keeps the extreme percentiles accurate while the
+/// memory used stays proportional to `compression` no matter how many readings come in.
+#[derive(Clone, Debug)]
+pub struct TDigest {
+    compression: f64,
+    centroids: Vec<Centroid>,
+    buffer: Vec<Centroid>,
+    count: f64,
+    min: f64,
+    max: f64,
+}
+
+impl TDigest {
+    pub fn new(compression: f64) -> TDigest {
+        TDigest {
+            compression,
+            centroids: Vec::new(),
+            buffer: Vec::new(),
+            count: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    pub fn push(&mut self, value: f64) {
+        self.add(Centroid {
+            mean: value,
+            weight: 1.0,
+        });
+    }
+
+    pub fn merge(&mut self, mut other: TDigest) {
+        other.compress();
+
+        for centroid in other.centroids {
+            self.add(centroid);
+        }
+
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+
+    /// Estimates the value below which a fraction `q` (between 0 and 1) of the readings fall.
+    pub fn quantile(&mut self, q: f64) -> Option<f64> {
+        self.compress();
+
+        let first = self.centroids.first()?;
+        let last = self.centroids.last()?;
+
+        let target = q * self.count;
+
+        // Each centroid's mean is treated as sitting at the middle of the readings it holds, and
+        // the min & max pin down the ends
+        if target <= first.weight / 2.0 {
+            return Some(interpolate(
+                self.min,
+                first.mean,
+                target / (first.weight / 2.0),
+            ));
+        }
+
+        if target >= self.count - last.weight / 2.0 {
+            let start = self.count - last.weight / 2.0;
+            return Some(interpolate(
+                last.mean,
+                self.max,
+                (target - start) / (last.weight / 2.0),
+            ));
+        }
+
+        let mut cumulative = first.weight / 2.0;
+        for pair in self.centroids.windows(2) {
+            let step = (pair[0].weight + pair[1].weight) / 2.0;
+
+            if target <= cumulative + step {
+                return Some(interpolate(
+                    pair[0].mean,
+                    pair[1].mean,
+                    (target - cumulative) / step,
+                ));
+            }
+
+            cumulative += step;
+        }
+
+        Some(last.mean)
+    }
+
+    fn add(&mut self, centroid: Centroid) {
+        self.count += centroid.weight;
+        self.min = self.min.min(centroid.mean);
+        self.max = self.max.max(centroid.mean);
+        self.buffer.push(centroid);
+
+        if self.buffer.len() as f64 >= self.compression * 5.0 {
+            self.compress();
+        }
+    }
+
+    // Merges the buffered readings into the centroids. A centroid covering the readings around
+    // quantile q can hold at most 4 * count * q * (1 - q) / compression readings.
+    fn compress(&mut self) {
+        if self.buffer.is_empty() {
+            return;
+        }
+
+        let mut all = std::mem::take(&mut self.buffer);
+        all.append(&mut self.centroids);
+        all.sort_by(|a, b| a.mean.total_cmp(&b.mean));
+
+        let mut merged = Vec::with_capacity(all.len());
+        let mut cumulative = 0.0;
+        let mut current = all[0];
+
+        for next in all.into_iter().skip(1) {
+            let proposed = current.weight + next.weight;
+            let q = (cumulative + proposed / 2.0) / self.count;
+            let limit = (4.0 * self.count * q * (1.0 - q) / self.compression).max(1.0);
+
+            if proposed <= limit {
+                current.mean += (next.mean - current.mean) * next.weight / proposed;
+                current.weight = proposed;
+            } else {
+                cumulative += current.weight;
+                merged.push(current);
+                current = next;
+            }
+        }
+
+        merged.push(current);
+        self.centroids = merged;
+    }
+}
+
+fn interpolate(from: f64, to: f64, fraction: f64) -> f64 {
+    from + (to - from) * fraction.clamp(0.0, 1.0)
+}
@@ -0,0 +1,52 @@
+use std::time::Instant;
+
+use chrono::{DateTime, Local};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::{timestamp, Temperature};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Recording {
+    /// Which sensor took the recording
+    pub sensor_id: usize,
+    pub temperature: Temperature,
+
+    /// Which of the sensor's sampling periods the recording was due in, counting from 0 at the
+    /// start of the simulation. Set by the sensor thread, so a gap means readings went missing
+    #[serde(default)]
+    pub sequence: u64,
+
+    /// Serialized as nanoseconds since `timestamp::process_epoch()`
+    #[serde(with = "super::timestamp")]
+    pub timestamp: Instant,
+}
+
+impl Recording {
+    /// Records the given temperature at the current time.
+    pub fn new(sensor_id: usize, temperature: Temperature) -> Recording {
+        // Pin the serialization epoch before the first timestamp is taken
+        timestamp::process_epoch();
+
+        Recording {
+            sensor_id,
+            temperature,
+            sequence: 0,
+            timestamp: Instant::now(),
+        }
+    }
+
+    /// Records a random temperature between `min` and `max` degrees Fahrenheit (inclusive) using
+    /// the given RNG.
+    pub fn random(rng: &mut impl Rng, sensor_id: usize, min: f64, max: f64) -> Recording {
+        Recording::new(
+            sensor_id,
+            Temperature::from_fahrenheit(rng.gen_range(min..=max)),
+        )
+    }
+
+    /// The local time the recording was taken at.
+    pub fn wall_time(&self) -> DateTime<Local> {
+        timestamp::wall_time(self.timestamp)
+    }
+}
@@ -0,0 +1,102 @@
+//! Readings from different sensors (or one sensor with jitter) don't always arrive in the order
+//! they were taken, but the largest difference scan and the anomaly checks expect them to. The
+//! report thread holds each reading for `reorder_window` after it was taken, so any taken before
+//! it that are still on their way can go ahead of it.
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::time::{Duration, Instant};
+
+use super::Recording;
+
+// A held recording, ordered by when it was taken and then by when it arrived. Without a window
+// only the arrival order counts
+struct Held {
+    taken: Option<Instant>,
+    arrival: u64,
+    recording: Recording,
+}
+
+impl PartialEq for Held {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Held {}
+
+impl PartialOrd for Held {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Held {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.taken, self.arrival).cmp(&(other.taken, other.arrival))
+    }
+}
+
+/// Holds readings until they're `window` old, and gives them back oldest first. A reading that
+/// arrives after a later one has already been given back goes out next, out of order, since
+/// there's no taking the later one back.
+pub(crate) struct ReorderBuffer {
+    window: Duration,
+    held: BinaryHeap<Reverse<Held>>,
+    arrivals: u64,
+    released_up_to: Option<Instant>,
+}
+
+impl ReorderBuffer {
+    pub(crate) fn new(window: Duration) -> ReorderBuffer {
+        ReorderBuffer {
+            window,
+            held: BinaryHeap::new(),
+            arrivals: 0,
+            released_up_to: None,
+        }
+    }
+
+    pub(crate) fn push(&mut self, recording: Recording) {
+        let taken = (!self.window.is_zero()).then_some(recording.timestamp);
+        if taken.is_some() && self.released_up_to.is_some_and(|x| recording.timestamp < x) {
+            tracing::debug!(
+                sensor_id = recording.sensor_id,
+                "reading arrived too late to reorder"
+            );
+        }
+        self.held.push(Reverse(Held {
+            taken,
+            arrival: self.arrivals,
+            recording,
+        }));
+        self.arrivals += 1;
+    }
+
+    /// Gives back the oldest reading if it's been held for the whole window by `now`.
+    pub(crate) fn pop_ready(&mut self, now: Instant) -> Option<Recording> {
+        // Without a window nothing's held, even a reading from a sensor whose clock is ahead
+        let ready = self.window.is_zero() || self.next_release()? <= now;
+        if ready {
+            self.pop()
+        } else {
+            None
+        }
+    }
+
+    /// Gives back the oldest reading however long it's been held, e.g. once nothing else can
+    /// arrive in time to go ahead of it.
+    pub(crate) fn pop(&mut self) -> Option<Recording> {
+        let Reverse(Held { recording, .. }) = self.held.pop()?;
+        let released_up_to = self.released_up_to.get_or_insert(recording.timestamp);
+        *released_up_to = (*released_up_to).max(recording.timestamp);
+        Some(recording)
+    }
+
+    /// When the oldest reading will have been held for the whole window.
+    pub(crate) fn next_release(&self) -> Option<Instant> {
+        self.held
+            .peek()
+            .map(|Reverse(oldest)| oldest.recording.timestamp + self.window)
+    }
+}
@@ -0,0 +1,432 @@
+//! Recording a run's readings so its reports can be reproduced offline. `RecordSink` writes every
+//! reading and where each report's period ended to a JSON lines log, with times measured from the
+//! start of the run. `RecordedRun` reads the log back and drives the report pipeline with it
+//! directly, without any sensor threads, so replaying a log always produces the same reports.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use super::anomaly::Alerts;
+use super::schedule::SensorSchedules;
+use super::{
+    timestamp, Recording, Report, ReportAccumulator, ReportHistory, ReportSink, RunSummary,
+    SensorSamplingConfig, SimulationConfig, StalledSensor, Temperature,
+};
+
+/// One line of a log.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum LogLine {
+    /// Always the first line. Readings' times are real time, so replaying them needs the same
+    /// speedup, and the reports need the same sensors, report interval & sampling periods
+    Run {
+        speedup: u64,
+        sensors: usize,
+        report_interval: u64,
+
+        /// In seconds. Logs from before readings had sequence numbers don't have it
+        #[serde(default)]
+        sampling_period: Option<f64>,
+
+        /// Sensors with a sampling period of their own
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        sensor_sampling: Vec<SensorSamplingConfig>,
+    },
+
+    Reading {
+        sensor_id: usize,
+        temperature: f64,
+
+        /// Logs from before readings had sequence numbers have none
+        #[serde(default)]
+        sequence: Option<u64>,
+
+        /// Nanoseconds since the run started
+        time: u64,
+    },
+
+    /// Written once the report was generated, which can be after some of the next period's
+    /// readings
+    Report {
+        index: usize,
+        partial: bool,
+
+        /// Nanoseconds since the run started
+        period_end: u64,
+
+        /// How many readings each sensor dropped during the period
+        dropped: Vec<usize>,
+
+        /// How many readings from earlier periods each sensor sent that arrived too late. They
+        /// aren't in the log
+        #[serde(default)]
+        late: Vec<usize>,
+
+        /// How many readings each sensor was due to take during the period that never arrived
+        #[serde(default)]
+        missed: Vec<usize>,
+
+        /// How many readings each sensor was due to take during the period, if it's known
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        expected: Vec<Option<usize>>,
+
+        /// The sequence numbers missing from each sensor's readings
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        gaps: Vec<Vec<Range<u64>>>,
+
+        /// Sensors the watchdog flagged during the period
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        stalled: Vec<StalledSensor>,
+    },
+}
+
+/// Writes every reading and report boundary to a log that `RecordedRun` can replay. Writes are
+/// buffered and flushed whenever a report is generated.
+pub struct RecordSink {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    start: Option<Instant>,
+}
+
+impl RecordSink {
+    /// Creates (or truncates) the log and writes the settings the readings depend on.
+    pub fn create(path: impl Into<PathBuf>, config: &SimulationConfig) -> io::Result<RecordSink> {
+        let path = path.into();
+        let mut sink = RecordSink {
+            writer: BufWriter::new(File::create(&path)?),
+            path,
+            start: None,
+        };
+
+        sink.write(&LogLine::Run {
+            speedup: config.speedup,
+            sensors: config.sensor_count,
+            report_interval: config.report_interval_minutes,
+            sampling_period: Some(config.sampling_period_seconds),
+            sensor_sampling: config.sensor_sampling.clone(),
+        })?;
+
+        Ok(sink)
+    }
+
+    fn write(&mut self, line: &LogLine) -> io::Result<()> {
+        serde_json::to_writer(&mut self.writer, line)?;
+        writeln!(self.writer)
+    }
+
+    fn since_start(&self, instant: Instant) -> u64 {
+        let start = self.start.unwrap_or(instant);
+        instant.saturating_duration_since(start).as_nanos() as u64
+    }
+
+    fn report_errors(&self, result: io::Result<()>) {
+        if let Err(err) = result {
+            eprintln!("Couldn't write the run to {}: {}", self.path.display(), err);
+        }
+    }
+}
+
+impl ReportSink for RecordSink {
+    fn handle_start(&mut self, start: Instant) {
+        self.start = Some(start);
+    }
+
+    fn handle_recording(&mut self, recording: &Recording) {
+        let line = LogLine::Reading {
+            sensor_id: recording.sensor_id,
+            temperature: recording.temperature.fahrenheit(),
+            sequence: Some(recording.sequence),
+            time: self.since_start(recording.timestamp),
+        };
+
+        let result = self.write(&line);
+        self.report_errors(result);
+    }
+
+    fn handle_report(&mut self, report: &Report) {
+        // The period's end is only kept as a wall time, which is converted back the same way
+        // `timestamp::wall_time` converted it
+        let start = self.start.map(timestamp::wall_time);
+        let period_end = start
+            .and_then(|start| (report.period_end - start).to_std().ok())
+            .map_or(0, |x| x.as_nanos() as u64);
+
+        let line = LogLine::Report {
+            index: report.index,
+            partial: report.partial,
+            period_end,
+            dropped: report.sensor_stats.iter().map(|x| x.dropped).collect(),
+            late: report.sensor_stats.iter().map(|x| x.late).collect(),
+            missed: report.sensor_stats.iter().map(|x| x.missed).collect(),
+            expected: report.sensor_stats.iter().map(|x| x.expected).collect(),
+            gaps: report.sensor_stats.iter().map(|x| x.gaps.clone()).collect(),
+            stalled: report.stalled_sensors.clone(),
+        };
+
+        let result = self.write(&line).and_then(|_| self.writer.flush());
+        self.report_errors(result);
+    }
+}
+
+/// A run read back from a log written by `RecordSink`.
+pub struct RecordedRun {
+    speedup: u64,
+    sensors: usize,
+    report_interval: u64,
+    sampling_period: Option<f64>,
+    sensor_sampling: Vec<SensorSamplingConfig>,
+
+    // Times are nanoseconds since the run started, followed by the sensor, temperature and
+    // sequence number
+    readings: Vec<(u64, usize, Temperature, Option<u64>)>,
+    reports: Vec<RecordedReport>,
+}
+
+struct RecordedReport {
+    index: usize,
+    partial: bool,
+    period_end: u64,
+    dropped: Vec<usize>,
+    late: Vec<usize>,
+    missed: Vec<usize>,
+    expected: Vec<Option<usize>>,
+    gaps: Vec<Vec<Range<u64>>>,
+    stalled: Vec<StalledSensor>,
+}
+
+impl RecordedRun {
+    pub fn open(path: impl AsRef<Path>) -> io::Result<RecordedRun> {
+        RecordedRun::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    pub fn from_reader(reader: impl BufRead) -> io::Result<RecordedRun> {
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+
+        let mut lines = reader.lines().enumerate();
+        let mut run = match lines.next() {
+            Some((_, line)) => match serde_json::from_str(&line?)? {
+                LogLine::Run {
+                    speedup,
+                    sensors,
+                    report_interval,
+                    sampling_period,
+                    sensor_sampling,
+                } => RecordedRun {
+                    speedup,
+                    sensors,
+                    report_interval,
+                    sampling_period,
+                    sensor_sampling,
+                    readings: Vec::new(),
+                    reports: Vec::new(),
+                },
+                _ => {
+                    return Err(invalid(
+                        "the log doesn't start with the run's settings".into(),
+                    ))
+                }
+            },
+            None => return Err(invalid("the log is empty".into())),
+        };
+
+        for (number, line) in lines {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let line = serde_json::from_str(&line)
+                .map_err(|err| invalid(format!("line {}: {}", number + 1, err)))?;
+
+            match line {
+                LogLine::Run { .. } => {
+                    return Err(invalid(format!(
+                        "line {}: the run's settings can only be on the first line",
+                        number + 1
+                    )))
+                }
+                LogLine::Reading {
+                    sensor_id,
+                    temperature,
+                    sequence,
+                    time,
+                } => {
+                    let temperature = Temperature::checked_fahrenheit(temperature)
+                        .map_err(|err| invalid(format!("line {}: {}", number + 1, err)))?;
+                    run.readings.push((time, sensor_id, temperature, sequence))
+                }
+                LogLine::Report {
+                    index,
+                    partial,
+                    period_end,
+                    dropped,
+                    late,
+                    missed,
+                    expected,
+                    gaps,
+                    stalled,
+                } => run.reports.push(RecordedReport {
+                    index,
+                    partial,
+                    period_end,
+                    dropped,
+                    late,
+                    missed,
+                    expected,
+                    gaps,
+                    stalled,
+                }),
+            }
+        }
+
+        // Readings stay in the order they were received, which is the order the report thread
+        // added them in. The next period's readings can be received before the last of this
+        // period's, so they're picked out by when they were taken
+        run.reports.sort_by_key(|x| x.index);
+
+        Ok(run)
+    }
+
+    /// `config` with the settings the recorded readings depend on put back to what they were
+    /// during the recorded run. Everything else about the reports can be changed.
+    pub fn config(&self, mut config: SimulationConfig) -> SimulationConfig {
+        config.speedup = self.speedup;
+        config.sensor_count = self.sensors;
+        config.report_interval_minutes = self.report_interval;
+        if let Some(sampling_period) = self.sampling_period {
+            config.sampling_period_seconds = sampling_period;
+        }
+        config.sensor_sampling = self.sensor_sampling.clone();
+        config
+    }
+
+    /// How many readings the log has.
+    pub fn len(&self) -> usize {
+        self.readings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.readings.is_empty()
+    }
+
+    /// Builds the recorded run's reports from its readings and hands everything to `sinks` the
+    /// way a running simulation would. Readings go in the report whose period they were due
+    /// in, and readings after the last recorded report are left out since the run never
+    /// reported on them. `config` should come from `config()`.
+    pub fn replay(
+        &self,
+        config: &SimulationConfig,
+        sinks: &mut [Box<dyn ReportSink>],
+    ) -> ReportHistory {
+        // Starting from the epoch makes the serialized timestamps the same on every replay
+        let start = timestamp::process_epoch();
+        let at = |nanos: u64| start + Duration::from_nanos(nanos);
+
+        // Readings from logs without sequence numbers count as due when they were taken
+        let schedules = SensorSchedules::new(start, config);
+        let due = |time: u64, sensor_id: usize, sequence: Option<u64>| {
+            sequence.map_or(time, |x| {
+                let schedule = schedules.sensor(sensor_id);
+                schedule.due(x).duration_since(start).as_nanos() as u64
+            })
+        };
+
+        for sink in sinks.iter_mut() {
+            sink.handle_start(start);
+        }
+
+        // Nanoseconds since the start
+        let warm_up_end = config.scaled_warm_up().as_nanos() as u64;
+
+        let mut history = ReportHistory::new();
+        let mut alerts = Alerts::new(config);
+        let mut period_start = 0;
+
+        for recorded in self.reports.iter() {
+            let mut accumulator = ReportAccumulator::new(config);
+            accumulator.start_period(at(period_start));
+
+            // A partial report's period ends when the run stopped, after every reading
+            let readings = self
+                .readings
+                .iter()
+                .filter(|(time, sensor_id, _, sequence)| {
+                    let due = due(*time, *sensor_id, *sequence);
+                    due >= period_start
+                        && (due < recorded.period_end
+                            || (recorded.partial && due == recorded.period_end))
+                });
+            for (time, sensor_id, temperature, sequence) in readings {
+                let recording = Recording {
+                    sensor_id: *sensor_id,
+                    temperature: *temperature,
+                    sequence: sequence
+                        .unwrap_or_else(|| schedules.sensor(*sensor_id).sequence_at(at(*time))),
+                    timestamp: at(*time),
+                };
+
+                for sink in sinks.iter_mut() {
+                    sink.handle_recording(&recording);
+                }
+                if due(*time, *sensor_id, *sequence) < warm_up_end {
+                    accumulator.push_warm_up(&recording);
+                    continue;
+                }
+                accumulator.push_decimated(&recording);
+
+                for alert in alerts.check(&recording) {
+                    accumulator.push_alert(&alert);
+                    for sink in sinks.iter_mut() {
+                        sink.handle_alert(&alert);
+                    }
+                }
+            }
+
+            for (sensor_id, count) in recorded.dropped.iter().enumerate() {
+                accumulator.push_dropped(sensor_id, *count);
+            }
+            for (sensor_id, count) in recorded.late.iter().enumerate() {
+                accumulator.push_late(sensor_id, *count);
+            }
+            for (sensor_id, count) in recorded.missed.iter().enumerate() {
+                accumulator.push_missed(sensor_id, *count);
+            }
+            for (sensor_id, expected) in recorded.expected.iter().enumerate() {
+                if let Some(expected) = expected {
+                    let gaps = recorded.gaps.get(sensor_id).map_or(&[][..], |x| x);
+                    accumulator.push_gaps(sensor_id, *expected, gaps);
+                }
+            }
+            for stalled in recorded.stalled.iter() {
+                accumulator.push_stalled(stalled);
+            }
+
+            if let Some(mut report) = accumulator.finish(recorded.index, config) {
+                report.partial = recorded.partial;
+                report.period_end = timestamp::wall_time(at(recorded.period_end));
+                report.trend = history.trend(&report);
+                report.records = history.records(&report);
+                history.push(report.clone());
+
+                for sink in sinks.iter_mut() {
+                    sink.handle_report(&report);
+                }
+            }
+
+            period_start = recorded.period_end;
+        }
+
+        // The log doesn't say how deep the queue got
+        let summary = RunSummary::from_reports(history.all_reports(), None);
+        for sink in sinks.iter_mut() {
+            sink.handle_summary(&summary);
+        }
+
+        history
+    }
+}
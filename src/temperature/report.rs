@@ -0,0 +1,903 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::Range;
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use super::anomaly::Alerts;
+#[cfg(feature = "parallel")]
+use super::PARALLEL_CHUNK_SIZE;
+use super::{
+    Histogram, QuantileEstimator, Recording, RejectReason, ReportAccumulator, SimulationConfig,
+    StallReason, StalledSensor, SuspectReason, SuspectSensor, Temperature, TemperatureUnit,
+    WALL_TIME_FORMAT,
+};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Report {
+    /// Position of the report in the run, starting at 0
+    pub index: usize,
+
+    /// Which rover generated the report, when several are running at once
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rover: Option<String>,
+
+    /// Simulated minute (since the simulation started) the report's period begins at
+    pub start_minute: u64,
+
+    /// Local times the report's period started & ended at. Reports generated from a batch of
+    /// recordings use the first & last recording's times
+    pub period_start: DateTime<Local>,
+    pub period_end: DateTime<Local>,
+
+    /// Set on the last report when the simulation stops (or the sensors go offline) before its
+    /// period is over
+    pub partial: bool,
+
+    /// Set on a report that was asked for partway through its period (see `ReportTrigger`). It
+    /// covers the period so far, and the period's regular report still follows
+    #[serde(default)]
+    pub on_demand: bool,
+
+    /// The `top_n` lowest & highest temps, from the most extreme in. There are fewer if the
+    /// period had fewer readings. Reports from before `top_n` was configurable used the old names
+    #[serde(alias = "top_five_lowest_temps")]
+    pub lowest_temps: Vec<Recording>,
+    #[serde(alias = "top_five_highest_temps")]
+    pub highest_temps: Vec<Recording>,
+
+    /// How many readings hit each of the lowest & highest temps, in the same order, when
+    /// `distinct_extremes` lists each temperature once. Empty otherwise
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lowest_temp_counts: Vec<usize>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub highest_temp_counts: Vec<usize>,
+
+    /// `None` if the period had fewer than two readings to compare, e.g. when every sensor
+    /// stalled
+    pub largest_temp_difference: Option<TemperatureDifference>,
+
+    /// How many simulated minutes into the period the largest difference's two recordings were
+    /// taken
+    pub largest_difference_minutes: Option<[f64; 2]>,
+
+    /// How many simulated minutes apart the largest difference's recordings could be. Reports
+    /// from before the window was configurable always used 10
+    #[serde(default = "default_difference_window_minutes")]
+    pub difference_window_minutes: u64,
+
+    /// Statistics over every reading in the report's period, whichever sensor it came from.
+    /// `None` if no readings were received during it
+    pub summary: Option<SummaryStats>,
+
+    /// How the readings are distributed, empty if `histogram_bucket_width` is 0
+    pub histogram: Histogram,
+
+    /// How many alerts were raised during the report's period, for spikes, extreme readings or
+    /// alert rules
+    pub alert_count: usize,
+
+    /// How many of the alerts each alert rule raised, by rule name. Rules that raised none are
+    /// left out
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub rule_alerts: BTreeMap<String, usize>,
+
+    /// How many readings were thrown away during the report's period because a sensor's queue
+    /// was full
+    pub dropped_readings: usize,
+
+    /// How many readings the sensors rejected as invalid during the report's period, by why.
+    /// They aren't in any report's statistics
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub rejected_readings: BTreeMap<RejectReason, usize>,
+
+    /// How many readings arrived during the report's period that were taken during an earlier
+    /// period, after that period's report had already been generated. They aren't in any report's
+    /// statistics
+    #[serde(default)]
+    pub late_readings: usize,
+
+    /// How many readings the sensors were due to take during the report's period that never made
+    /// it into the report, whether they were dropped, arrived late or were never taken
+    #[serde(default)]
+    pub missed_readings: usize,
+
+    /// How many readings were taken during the warm-up at the start of the run. They aren't in
+    /// any report's statistics
+    #[serde(default)]
+    pub warm_up_readings: usize,
+
+    /// How many readings decimation left out of the report's statistics
+    #[serde(default)]
+    pub decimated_readings: usize,
+
+    /// Sensors whose readings look faulty. Their readings are left out of the top temps and
+    /// the largest difference
+    pub suspect_sensors: Vec<SuspectSensor>,
+
+    /// Sensors the watchdog noticed had stopped sending readings during the report's period,
+    /// ordered by sensor ID
+    #[serde(default)]
+    pub stalled_sensors: Vec<StalledSensor>,
+
+    /// One entry per sensor, ordered by sensor ID. Sensors that didn't record anything during
+    /// the hour are included with no readings.
+    pub sensor_stats: Vec<SensorStats>,
+
+    /// How long the period's readings waited between being taken and the report thread using
+    /// them. `None` when that isn't known, e.g. for replayed reports
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub queue_latency: Option<QueueLatency>,
+
+    /// How long working the report out from the accumulated statistics took, in seconds
+    pub generation_seconds: f64,
+
+    /// How the report compares with the ones before it. `None` for the first report, and for
+    /// reports generated on their own from a batch of recordings
+    pub trend: Option<ReportTrend>,
+
+    /// The run's all-time records, when this report broke at least one of them. `None` otherwise,
+    /// and for the first report since there's nothing to break yet
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub records: Option<MissionRecords>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SensorStats {
+    pub sensor_id: usize,
+
+    /// How many recordings the sensor contributed to the report
+    pub readings: usize,
+
+    pub min: Option<Temperature>,
+    pub max: Option<Temperature>,
+    pub mean: Option<Temperature>,
+
+    /// Population standard deviation of the sensor's readings, in degrees Fahrenheit
+    pub std_dev: Option<f64>,
+
+    /// The longest run of identical readings from the sensor
+    pub longest_repeat: Option<RepeatRun>,
+
+    /// How many of the sensor's readings raised an alert
+    pub alerts: usize,
+
+    /// How many of the sensor's readings were thrown away because its queue was full
+    pub dropped: usize,
+
+    /// How many of the sensor's readings arrived after the report for the period they were taken
+    /// in had already been generated
+    #[serde(default)]
+    pub late: usize,
+
+    /// How many of the readings the sensor was due to take during the period never made it into
+    /// the report, worked out from the gaps in its sequence numbers
+    #[serde(default)]
+    pub missed: usize,
+
+    /// How many simulated seconds the sensor's clock was estimated to be ahead of the rover's
+    /// (behind if negative), when the report thread corrects for clock skew
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clock_offset: Option<f64>,
+
+    /// How many readings the sensor was due to take during the period. Only known for reports
+    /// made while the simulation is running
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected: Option<usize>,
+
+    /// The sequence numbers of the readings that are missing, as runs of consecutive readings
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub gaps: Vec<Range<u64>>,
+}
+
+/// A temperature a sensor read several times in a row.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RepeatRun {
+    pub temperature: Temperature,
+    pub readings: usize,
+}
+
+/// Statistics over a set of readings.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SummaryStats {
+    pub mean: Temperature,
+    pub median: Temperature,
+
+    /// Population standard deviation, in degrees Fahrenheit
+    pub std_dev: f64,
+
+    /// One entry per percentile in `SimulationConfig::percentiles`, in the same order
+    pub percentiles: Vec<Percentile>,
+
+    /// Whether the median & percentiles were estimated with a t-digest instead of worked out
+    /// exactly
+    pub approximate: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Percentile {
+    /// Between 0 and 100
+    pub percentile: f64,
+
+    pub temperature: Temperature,
+}
+
+impl SummaryStats {
+    /// Fills in the median & percentiles from `quantiles`. `mean` and `std_dev` are in degrees
+    /// Fahrenheit. Returns `None` if `quantiles` hasn't seen any readings.
+    pub fn new(
+        mean: f64,
+        std_dev: f64,
+        quantiles: &mut QuantileEstimator,
+        percentiles: &[f64],
+    ) -> Option<SummaryStats> {
+        let median = quantiles.percentile(50.0)?;
+
+        let percentiles = percentiles
+            .iter()
+            .map(|&percentile| Percentile {
+                percentile,
+                temperature: Temperature::from_fahrenheit(
+                    quantiles.percentile(percentile).unwrap_or(median),
+                ),
+            })
+            .collect();
+
+        Some(SummaryStats {
+            mean: Temperature::from_fahrenheit(mean),
+            median: Temperature::from_fahrenheit(median),
+            std_dev,
+            percentiles,
+            approximate: quantiles.is_approximate(),
+        })
+    }
+}
+
+/// Percentiles of how long readings waited in the queue, in real (not simulated) milliseconds.
+/// A p99 that keeps growing from one report to the next means the report thread isn't keeping
+/// up with the sensors.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct QueueLatency {
+    pub p50_ms: f64,
+    pub p99_ms: f64,
+}
+
+impl QueueLatency {
+    /// Returns `None` if `latencies` hasn't seen any readings.
+    pub fn new(latencies: &mut QuantileEstimator) -> Option<QueueLatency> {
+        Some(QueueLatency {
+            p50_ms: latencies.percentile(50.0)?,
+            p99_ms: latencies.percentile(99.0)?,
+        })
+    }
+}
+
+/// The two recordings within the comparison window whose temperatures are furthest apart.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TemperatureDifference {
+    /// The earlier of the two recordings
+    pub start: Recording,
+
+    /// The later of the two recordings
+    pub end: Recording,
+
+    /// How far apart the two temperatures are, in degrees Fahrenheit
+    pub difference: f64,
+}
+
+/// How a report compares with the reports generated before it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReportTrend {
+    /// Compared with the report right before this one
+    pub previous: TrendChange,
+
+    /// Compared with the average of every report before this one
+    pub average: TrendChange,
+
+    /// How many reports went into the average
+    pub reports_averaged: usize,
+}
+
+/// How much each headline number went up (or down, if negative) from an earlier report, in
+/// degrees Fahrenheit.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TrendChange {
+    pub mean: f64,
+
+    /// The lowest & highest temps recorded
+    pub lowest: f64,
+    pub highest: f64,
+
+    pub largest_difference: f64,
+}
+
+/// The most extreme readings and the largest difference of the whole run so far.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MissionRecords {
+    /// The lowest & highest temps recorded by any report
+    pub lowest: Recording,
+    pub highest: Recording,
+
+    /// The largest difference any report found
+    pub largest_difference: TemperatureDifference,
+
+    /// The records the report broke and what they were before
+    pub broken: Vec<BrokenRecord>,
+}
+
+/// A record a report broke.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BrokenRecord {
+    pub record: RecordKind,
+
+    /// What the record was before, in degrees Fahrenheit
+    pub previous: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordKind {
+    Lowest,
+    Highest,
+    LargestDifference,
+}
+
+impl MissionRecords {
+    /// What the record was before the report broke it, or `None` if it didn't.
+    pub fn previous(&self, record: RecordKind) -> Option<f64> {
+        self.broken
+            .iter()
+            .find(|x| x.record == record)
+            .map(|x| x.previous)
+    }
+}
+
+impl Report {
+    /// Compiles the `index`th report from all the recordings taken during its period. Returns
+    /// `None` if there aren't any recordings.
+    pub fn generate(
+        index: usize,
+        mut recordings: Vec<Recording>,
+        config: &SimulationConfig,
+    ) -> Option<Report> {
+        // The accumulator expects recordings in the order they were taken
+        recordings.sort_by_key(|x| x.timestamp);
+
+        #[cfg(feature = "parallel")]
+        let mut accumulator = if recordings.len() > PARALLEL_CHUNK_SIZE {
+            ReportAccumulator::from_recordings_parallel(&recordings, config)
+        } else {
+            ReportAccumulator::from_recordings(&recordings, config)
+        };
+
+        #[cfg(not(feature = "parallel"))]
+        let mut accumulator = ReportAccumulator::from_recordings(&recordings, config);
+
+        // Each alert depends on the readings before it, so the detector always runs in order
+        let mut alerts = Alerts::new(config);
+        for recording in recordings.iter() {
+            for alert in alerts.check(recording) {
+                accumulator.push_alert(&alert);
+            }
+        }
+
+        accumulator.finish(index, config)
+    }
+}
+
+impl Report {
+    /// The simulated hour (counting from 0) the report's period begins in.
+    pub fn hour(&self) -> u64 {
+        self.start_minute / 60
+    }
+
+    /// Formats the report with temperatures in the given unit. The plain `Display` impl uses
+    /// Fahrenheit.
+    pub fn display(&self, unit: TemperatureUnit) -> DisplayReport<'_> {
+        DisplayReport { report: self, unit }
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.display(TemperatureUnit::Fahrenheit).fmt(f)
+    }
+}
+
+pub struct DisplayReport<'a> {
+    report: &'a Report,
+    unit: TemperatureUnit,
+}
+
+impl fmt::Display for DisplayReport<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let report = self.report;
+        let unit = self.unit;
+
+        if let Some(rover) = &report.rover {
+            write!(f, "{}: ", rover)?;
+        }
+
+        writeln!(
+            f,
+            "Report {} (hour {}), {} to {}\n",
+            report.index + 1,
+            report.hour(),
+            report.period_start.format(WALL_TIME_FORMAT),
+            report.period_end.format(WALL_TIME_FORMAT)
+        )?;
+
+        if report.on_demand {
+            writeln!(f, "On-demand report, covering the period so far\n")?;
+        } else if report.partial {
+            writeln!(
+                f,
+                "Partial report, the simulation stopped before the period ended\n"
+            )?;
+        }
+
+        let extremes = [
+            ("lowest", &report.lowest_temps, &report.lowest_temp_counts),
+            (
+                "highest",
+                &report.highest_temps,
+                &report.highest_temp_counts,
+            ),
+        ];
+        for (label, recordings, counts) in extremes {
+            if recordings.is_empty() {
+                continue;
+            }
+
+            // Distinct temps come with how many readings hit each
+            if counts.is_empty() {
+                writeln!(f, "Top {} {} temps: ", recordings.len(), label)?;
+            } else {
+                writeln!(f, "Top {} {} distinct temps: ", recordings.len(), label)?;
+            }
+
+            for (index, recording) in recordings.iter().enumerate() {
+                write!(
+                    f,
+                    "{} (sensor {}",
+                    recording.temperature.display(unit),
+                    recording.sensor_id
+                )?;
+                match counts.get(index) {
+                    Some(1) => write!(f, ", 1 reading), ")?,
+                    Some(count) => write!(f, ", {} readings), ", count)?,
+                    None => write!(f, "), ")?,
+                }
+            }
+            writeln!(f, "\n")?;
+        }
+
+        match (
+            &report.largest_temp_difference,
+            report.largest_difference_minutes,
+        ) {
+            (Some(largest_difference), Some([start, end])) => {
+                writeln!(
+                    f,
+                    "Largest temperature difference within {} minutes: {:.1}{}, from {:.1} to {:.1} minutes into the period",
+                    report.difference_window_minutes,
+                    unit.convert_difference(largest_difference.difference),
+                    unit.symbol(),
+                    start,
+                    end
+                )?;
+                for (label, recording) in [
+                    ("Start", &largest_difference.start),
+                    ("End", &largest_difference.end),
+                ] {
+                    writeln!(
+                        f,
+                        "{}: sensor {} recorded {} at {}",
+                        label,
+                        recording.sensor_id,
+                        recording.temperature.display(unit),
+                        recording.wall_time().format(WALL_TIME_FORMAT)
+                    )?;
+                }
+            }
+            // Without any readings at all the summary below says so
+            _ if report.summary.is_some() => {
+                writeln!(f, "Not enough readings were received to compare")?
+            }
+            _ => {}
+        }
+
+        match &report.summary {
+            Some(summary) => {
+                writeln!(
+                    f,
+                    "\nMean {}, median {}{}, standard deviation {:.1}{}",
+                    summary.mean.display(unit),
+                    summary.median.display(unit),
+                    if summary.approximate {
+                        " (approx.)"
+                    } else {
+                        ""
+                    },
+                    unit.convert_difference(summary.std_dev),
+                    unit.symbol()
+                )?;
+
+                if !summary.percentiles.is_empty() {
+                    if summary.approximate {
+                        write!(f, "Percentiles (approx.): ")?;
+                    } else {
+                        write!(f, "Percentiles: ")?;
+                    }
+                    for percentile in summary.percentiles.iter() {
+                        write!(
+                            f,
+                            "p{} {}, ",
+                            percentile.percentile,
+                            percentile.temperature.display(unit)
+                        )?;
+                    }
+                    writeln!(f)?;
+                }
+            }
+            None => writeln!(f, "\nNo readings were received during this period")?,
+        }
+
+        if let Some(trend) = &report.trend {
+            writeln!(f)?;
+            write_trend_change(f, unit, "the previous report", &trend.previous)?;
+
+            // With only one earlier report the average is the same thing
+            if trend.reports_averaged > 1 {
+                let label = format!(
+                    "the average of the {} earlier reports",
+                    trend.reports_averaged
+                );
+                write_trend_change(f, unit, &label, &trend.average)?;
+            }
+        }
+
+        if let Some(records) = &report.records {
+            writeln!(f, "\nMission records: ")?;
+
+            // Only the records the report broke say what they were before
+            let new_record = |previous: Option<String>| match previous {
+                Some(previous) => format!(", new record (was {})", previous),
+                None => String::new(),
+            };
+            for (label, kind, recording) in [
+                ("Lowest", RecordKind::Lowest, &records.lowest),
+                ("Highest", RecordKind::Highest, &records.highest),
+            ] {
+                let previous = records
+                    .previous(kind)
+                    .map(|x| Temperature::from_fahrenheit(x).display(unit).to_string());
+                writeln!(
+                    f,
+                    "{} temp: {} (sensor {}){}",
+                    label,
+                    recording.temperature.display(unit),
+                    recording.sensor_id,
+                    new_record(previous)
+                )?;
+            }
+
+            let difference = |fahrenheit: f64| {
+                format!(
+                    "{:.1}{}",
+                    unit.convert_difference(fahrenheit),
+                    unit.symbol()
+                )
+            };
+            let largest_difference = &records.largest_difference;
+            let previous = records
+                .previous(RecordKind::LargestDifference)
+                .map(difference);
+            writeln!(
+                f,
+                "Largest difference: {} (sensor {} to sensor {}){}",
+                difference(largest_difference.difference),
+                largest_difference.start.sensor_id,
+                largest_difference.end.sensor_id,
+                new_record(previous)
+            )?;
+        }
+
+        if !report.histogram.is_empty() {
+            writeln!(f, "\nHistogram: ")?;
+
+            let histogram = &report.histogram;
+            let largest_count = histogram.buckets.iter().map(|x| x.count).max().unwrap_or(0);
+
+            for bucket in histogram.buckets.iter() {
+                // Bars are scaled so the fullest bucket is 40 characters wide
+                let bar_length = (bucket.count * 40).checked_div(largest_count).unwrap_or(0);
+
+                writeln!(
+                    f,
+                    "{:>9} to {:>9}: {:>5} {}",
+                    bucket.low.display(unit).to_string(),
+                    bucket.high.display(unit).to_string(),
+                    bucket.count,
+                    "#".repeat(bar_length)
+                )?;
+            }
+
+            if histogram.below > 0 || histogram.above > 0 {
+                writeln!(
+                    f,
+                    "{} below the range, {} above the range",
+                    histogram.below, histogram.above
+                )?;
+            }
+        }
+
+        if !report.suspect_sensors.is_empty() {
+            writeln!(
+                f,
+                "\nSuspect sensors (left out of the top temps and largest difference): "
+            )?;
+
+            for suspect in report.suspect_sensors.iter() {
+                match &suspect.reason {
+                    SuspectReason::Stuck {
+                        temperature,
+                        readings,
+                    } => writeln!(
+                        f,
+                        "Sensor {}: stuck at {} for {} readings",
+                        suspect.sensor_id,
+                        temperature.display(unit),
+                        readings
+                    )?,
+                    SuspectReason::DivergentMean { mean, typical } => writeln!(
+                        f,
+                        "Sensor {}: mean of {} is far from the typical {}",
+                        suspect.sensor_id,
+                        mean.display(unit),
+                        typical.display(unit)
+                    )?,
+                    SuspectReason::DivergentSpread { std_dev, typical } => writeln!(
+                        f,
+                        "Sensor {}: standard deviation of {:.1}{} is far above the typical {:.1}{}",
+                        suspect.sensor_id,
+                        unit.convert_difference(*std_dev),
+                        unit.symbol(),
+                        unit.convert_difference(*typical),
+                        unit.symbol()
+                    )?,
+                }
+            }
+        }
+
+        if !report.stalled_sensors.is_empty() {
+            writeln!(f, "\nSensors that stopped sending readings: ")?;
+
+            for stalled in report.stalled_sensors.iter() {
+                match &stalled.reason {
+                    StallReason::Silent { minutes } => writeln!(
+                        f,
+                        "Sensor {}: no readings for {:.1} minutes",
+                        stalled.sensor_id, minutes
+                    )?,
+                    StallReason::Panicked => {
+                        writeln!(f, "Sensor {}: its thread panicked", stalled.sensor_id)?
+                    }
+                    StallReason::Restarted => writeln!(
+                        f,
+                        "Sensor {}: its thread panicked and was restarted",
+                        stalled.sensor_id
+                    )?,
+                }
+            }
+        }
+
+        if report.alert_count > 0 {
+            writeln!(f, "\nAlerts raised: {}", report.alert_count)?;
+            for (rule, count) in report.rule_alerts.iter() {
+                writeln!(f, "{}: {}", rule, count)?;
+            }
+        }
+
+        if report.dropped_readings > 0 {
+            writeln!(
+                f,
+                "\nReadings dropped because a queue was full: {}",
+                report.dropped_readings
+            )?;
+        }
+
+        if !report.rejected_readings.is_empty() {
+            let reasons: Vec<String> = report
+                .rejected_readings
+                .iter()
+                .map(|(reason, count)| format!("{} {}", count, reason))
+                .collect();
+            writeln!(
+                f,
+                "\nReadings rejected as invalid: {} ({})",
+                report.rejected_readings.values().sum::<usize>(),
+                reasons.join(", ")
+            )?;
+        }
+
+        if report.late_readings > 0 {
+            writeln!(
+                f,
+                "\nReadings that arrived too late for an earlier report: {}",
+                report.late_readings
+            )?;
+        }
+
+        if report.warm_up_readings > 0 {
+            writeln!(
+                f,
+                "\nReadings left out while the sensors warmed up: {}",
+                report.warm_up_readings
+            )?;
+        }
+
+        if report.decimated_readings > 0 {
+            writeln!(
+                f,
+                "\nReadings left out by decimation: {}",
+                report.decimated_readings
+            )?;
+        }
+
+        if report.missed_readings > 0 {
+            writeln!(
+                f,
+                "\nReadings the sensors were due to take that never arrived: {}",
+                report.missed_readings
+            )?;
+        }
+
+        if let Some(latency) = &report.queue_latency {
+            writeln!(
+                f,
+                "\nQueue latency: p50 {:.3}ms, p99 {:.3}ms",
+                latency.p50_ms, latency.p99_ms
+            )?;
+        }
+
+        writeln!(f, "\nPer-sensor statistics: ")?;
+        for stats in report.sensor_stats.iter() {
+            match (stats.min, stats.max, stats.mean) {
+                (Some(min), Some(max), Some(mean)) => write!(
+                    f,
+                    "Sensor {}: {} readings, min {}, max {}, mean {}",
+                    stats.sensor_id,
+                    stats.readings,
+                    min.display(unit),
+                    max.display(unit),
+                    mean.display(unit)
+                )?,
+                _ => write!(f, "Sensor {}: no readings", stats.sensor_id)?,
+            }
+
+            if stats.alerts > 0 {
+                write!(f, ", {} alerts", stats.alerts)?;
+            }
+            if stats.dropped > 0 {
+                write!(f, ", {} dropped", stats.dropped)?;
+            }
+            if stats.late > 0 {
+                write!(f, ", {} late", stats.late)?;
+            }
+            if stats.missed > 0 {
+                write!(f, ", {} missed", stats.missed)?;
+            }
+            if let Some(offset) = stats.clock_offset {
+                write!(f, ", clock {:+.1}s off (corrected)", offset)?;
+            }
+            writeln!(f)?;
+        }
+
+        if report.sensor_stats.iter().any(|x| x.expected.is_some()) {
+            writeln!(f, "\nReadings expected & received: ")?;
+            writeln!(f, "Sensor  Expected  Received")?;
+
+            for stats in report.sensor_stats.iter() {
+                let Some(expected) = stats.expected else {
+                    continue;
+                };
+                let missing: u64 = stats.gaps.iter().map(|x| x.end - x.start).sum();
+                write!(
+                    f,
+                    "{:>6}  {:>8}  {:>8}",
+                    stats.sensor_id,
+                    expected,
+                    expected.saturating_sub(missing as usize)
+                )?;
+
+                // Gaps are what point to readings getting lost, so they're hard to miss
+                if !stats.gaps.is_empty() {
+                    let gaps: Vec<String> = stats
+                        .gaps
+                        .iter()
+                        .map(|x| match x.end - x.start {
+                            1 => format!("#{}", x.start),
+                            _ => format!("#{}-{}", x.start, x.end - 1),
+                        })
+                        .collect();
+                    write!(f, "  <-- GAP: missing {}", gaps.join(", "))?;
+                }
+                writeln!(f)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Compares every recording against every other recording. Skips the comparison if the recording isn't within
+// `difference_window_minutes`. The report thread uses `ReportAccumulator` instead, this is kept as a simple reference to check it
+// against.
+pub fn find_largest_temp_difference(
+    recordings: &[Recording],
+    config: &SimulationConfig,
+) -> Option<TemperatureDifference> {
+    let interval = config.scaled_difference_window();
+
+    let mut result: Option<TemperatureDifference> = None;
+
+    for (index, start_rec) in recordings.iter().enumerate() {
+        let start_time = start_rec.timestamp;
+
+        for end_rec in recordings.iter().skip(index + 1) {
+            let end_time = end_rec.timestamp;
+
+            // Skip comparison if this recording isn't within the window of the other
+            if end_time.duration_since(start_time) > interval {
+                break;
+            }
+
+            let current_diff = end_rec.temperature.difference(start_rec.temperature);
+
+            // Compare against the previous largest temperature difference
+            let is_larger = match &result {
+                Some(previous) => current_diff > previous.difference,
+                None => true,
+            };
+
+            if is_larger {
+                result = Some(TemperatureDifference {
+                    start: start_rec.clone(),
+                    end: end_rec.clone(),
+                    difference: current_diff,
+                });
+            }
+        }
+    }
+
+    result
+}
+
+fn default_difference_window_minutes() -> u64 {
+    10
+}
+
+fn write_trend_change(
+    f: &mut fmt::Formatter<'_>,
+    unit: TemperatureUnit,
+    label: &str,
+    change: &TrendChange,
+) -> fmt::Result {
+    let signed = |fahrenheit_difference: f64| {
+        // Rounded first so a tiny drop doesn't show up as -0.0 (adding 0.0 turns -0.0 into 0.0)
+        let difference = (unit.convert_difference(fahrenheit_difference) * 10.0).round() / 10.0;
+        format!("{:+.1}{}", difference + 0.0, unit.symbol())
+    };
+
+    writeln!(
+        f,
+        "Compared with {}: mean {}, lowest {}, highest {}, largest difference {}",
+        label,
+        signed(change.mean),
+        signed(change.lowest),
+        signed(change.highest),
+        signed(change.largest_difference)
+    )
+}
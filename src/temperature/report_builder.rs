@@ -0,0 +1,178 @@
+use std::str::FromStr;
+
+use super::{QuantileConfig, Recording, Report, SimulationConfig};
+
+/// A part of the report that can be left out, along with the work that goes into it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportSection {
+    /// The lowest & highest temps
+    TopTemps,
+
+    /// The percentiles listed after the median
+    Percentiles,
+
+    /// The histogram of every reading
+    Histogram,
+
+    /// Spotting faulty sensors, which means holding on to the period's readings
+    SuspectSensors,
+}
+
+impl FromStr for ReportSection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace('-', "_").as_str() {
+            "top" | "top_temps" => Ok(ReportSection::TopTemps),
+            "percentiles" => Ok(ReportSection::Percentiles),
+            "histogram" => Ok(ReportSection::Histogram),
+            "suspects" | "suspect_sensors" => Ok(ReportSection::SuspectSensors),
+            _ => Err(format!(
+                "unknown report section `{}`, expected top, percentiles, histogram or suspects",
+                s
+            )),
+        }
+    }
+}
+
+/// Picks which statistics go into each report, so the ones that aren't needed aren't worked out
+/// at all. Starts from the bare minimum (the mean, median, standard deviation and largest
+/// difference within 10 minutes, which every report has) and each method adds a section, e.g.
+///
+/// ```
+/// # use assignment3::temperature::{ReportBuilder, SimulationConfig};
+/// let mut config = SimulationConfig::default();
+/// ReportBuilder::new()
+///     .top_n(5)
+///     .largest_diff_window(10)
+///     .histogram(10.0)
+///     .apply(&mut config);
+/// ```
+///
+/// The sections are settings in `SimulationConfig` underneath, so `apply` fills them in for a
+/// simulation, and `from_config` starts from whatever a config already has. Mission records are
+/// only kept for the lowest & highest temps while the top temps are in the reports.
+#[derive(Clone, Debug)]
+pub struct ReportBuilder {
+    top_n: usize,
+    distinct_extremes: bool,
+    difference_window_minutes: u64,
+    percentiles: Vec<f64>,
+    quantiles: QuantileConfig,
+    histogram_bucket_width: f64,
+    suspect_sensors: bool,
+}
+
+impl Default for ReportBuilder {
+    fn default() -> Self {
+        ReportBuilder::new()
+    }
+}
+
+impl ReportBuilder {
+    pub fn new() -> ReportBuilder {
+        ReportBuilder {
+            top_n: 0,
+            distinct_extremes: false,
+            difference_window_minutes: SimulationConfig::default().difference_window_minutes,
+            percentiles: Vec::new(),
+            quantiles: QuantileConfig::default(),
+            histogram_bucket_width: 0.0,
+            suspect_sensors: false,
+        }
+    }
+
+    /// Starts from the sections `config` already has.
+    pub fn from_config(config: &SimulationConfig) -> ReportBuilder {
+        ReportBuilder {
+            top_n: config.top_n,
+            distinct_extremes: config.distinct_extremes,
+            difference_window_minutes: config.difference_window_minutes,
+            percentiles: config.percentiles.clone(),
+            quantiles: config.quantiles.clone(),
+            histogram_bucket_width: config.histogram_bucket_width,
+            suspect_sensors: config.suspect_sensors.enabled,
+        }
+    }
+
+    /// Lists the `n` lowest & highest temps.
+    pub fn top_n(mut self, n: usize) -> ReportBuilder {
+        self.top_n = n;
+        self
+    }
+
+    /// Lists the lowest & highest distinct temps, each with how many readings hit it, rather than
+    /// one entry per reading.
+    pub fn distinct_extremes(mut self) -> ReportBuilder {
+        self.distinct_extremes = true;
+        self
+    }
+
+    /// Finds the largest difference between readings taken within `minutes` simulated minutes of
+    /// each other.
+    pub fn largest_diff_window(mut self, minutes: u64) -> ReportBuilder {
+        self.difference_window_minutes = minutes;
+        self
+    }
+
+    /// Lists these percentiles (between 0 and 100) after the median.
+    pub fn percentiles(mut self, percentiles: &[f64]) -> ReportBuilder {
+        self.percentiles = percentiles.to_vec();
+        self
+    }
+
+    /// Works out the median & percentiles this way, e.g. with a t-digest so they take a fixed
+    /// amount of memory.
+    pub fn quantiles(mut self, quantiles: QuantileConfig) -> ReportBuilder {
+        self.quantiles = quantiles;
+        self
+    }
+
+    /// Adds a histogram with buckets `bucket_width` degrees Fahrenheit wide.
+    pub fn histogram(mut self, bucket_width: f64) -> ReportBuilder {
+        self.histogram_bucket_width = bucket_width;
+        self
+    }
+
+    /// Spots faulty sensors and leaves them out of the top temps and the largest difference.
+    pub fn suspect_sensors(mut self) -> ReportBuilder {
+        self.suspect_sensors = true;
+        self
+    }
+
+    /// Leaves a section out.
+    pub fn skip(mut self, section: ReportSection) -> ReportBuilder {
+        match section {
+            ReportSection::TopTemps => self.top_n = 0,
+            ReportSection::Percentiles => self.percentiles.clear(),
+            ReportSection::Histogram => self.histogram_bucket_width = 0.0,
+            ReportSection::SuspectSensors => self.suspect_sensors = false,
+        }
+        self
+    }
+
+    /// Sets the sections in `config`, leaving everything else as it is.
+    pub fn apply(&self, config: &mut SimulationConfig) {
+        config.top_n = self.top_n;
+        config.distinct_extremes = self.distinct_extremes;
+        config.difference_window_minutes = self.difference_window_minutes;
+        config.percentiles = self.percentiles.clone();
+        config.quantiles = self.quantiles.clone();
+        config.histogram_bucket_width = self.histogram_bucket_width;
+        config.suspect_sensors.enabled = self.suspect_sensors;
+    }
+
+    /// Compiles the `index`th report from a batch of recordings with just these sections, taking
+    /// everything else from `config`. Returns `None` if there aren't any recordings.
+    pub fn generate(
+        &self,
+        index: usize,
+        recordings: Vec<Recording>,
+        config: &SimulationConfig,
+    ) -> Option<Report> {
+        let mut config = config.clone();
+        self.apply(&mut config);
+
+        Report::generate(index, recordings, &config)
+    }
+}
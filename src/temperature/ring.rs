@@ -0,0 +1,383 @@
+//! A fixed-capacity, lock-free ring buffer with exactly one sender and one receiver. Each sensor
+//! thread gets its own ring to the report thread, so sending a reading never takes a lock or
+//! allocates.
+
+use std::cell::UnsafeCell;
+use std::mem::MaybeUninit;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{TryRecvError, TrySendError};
+use std::sync::Arc;
+
+struct Slot<T> {
+    // Which lap of the ring the slot is on. It equals the slot's index while the slot is free
+    // for the sender to write, and the index + 1 once it's been written and can be received
+    stamp: AtomicUsize,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+struct Ring<T> {
+    slots: Box<[Slot<T>]>,
+
+    // Both only ever count up (wrapping), the slot is the count modulo the capacity. `tail` is
+    // only written by the sender. `head` is normally only moved by the receiver, but the sender
+    // moves it too when it drops the oldest value, so it has to be claimed
+    head: AtomicUsize,
+    tail: AtomicUsize,
+
+    sender_alive: AtomicBool,
+    receiver_alive: AtomicBool,
+}
+
+// SAFETY: a slot's value is only written by the sender while its stamp says it's free, and only
+// read by whoever claimed it by moving `head` past it, so no two threads touch it at once
+unsafe impl<T: Send> Sync for Ring<T> {}
+
+impl<T> Ring<T> {
+    fn slot(&self, index: usize) -> &Slot<T> {
+        &self.slots[index % self.slots.len()]
+    }
+
+    /// Takes the oldest value out of the ring, or `None` if it's empty.
+    fn pop(&self) -> Option<T> {
+        let mut head = self.head.load(Ordering::Relaxed);
+
+        loop {
+            let slot = self.slot(head);
+            let stamp = slot.stamp.load(Ordering::Acquire);
+
+            if stamp == head.wrapping_add(1) {
+                match self.head.compare_exchange_weak(
+                    head,
+                    head.wrapping_add(1),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        // SAFETY: moving `head` past the slot claimed its value, and the
+                        // sender won't write it again until the stamp below frees it
+                        let value = unsafe { (*slot.value.get()).assume_init_read() };
+                        slot.stamp
+                            .store(head.wrapping_add(self.slots.len()), Ordering::Release);
+
+                        return Some(value);
+                    }
+                    Err(current) => head = current,
+                }
+            } else if stamp == head {
+                return None;
+            } else {
+                // Someone else claimed the slot first
+                head = self.head.load(Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+impl<T> Drop for Ring<T> {
+    fn drop(&mut self) {
+        while self.pop().is_some() {}
+    }
+}
+
+/// Creates a ring that holds up to `capacity` values. With a single slot the stamps for "written"
+/// and "free again" would be the same, so it needs at least 2.
+pub fn ring_channel<T>(capacity: usize) -> (RingSender<T>, RingReceiver<T>) {
+    assert!(capacity >= 2, "a ring needs room for at least 2 values");
+
+    let ring = Arc::new(Ring {
+        slots: (0..capacity)
+            .map(|index| Slot {
+                stamp: AtomicUsize::new(index),
+                value: UnsafeCell::new(MaybeUninit::uninit()),
+            })
+            .collect(),
+        head: AtomicUsize::new(0),
+        tail: AtomicUsize::new(0),
+        sender_alive: AtomicBool::new(true),
+        receiver_alive: AtomicBool::new(true),
+    });
+
+    (RingSender { ring: ring.clone() }, RingReceiver { ring })
+}
+
+pub struct RingSender<T> {
+    ring: Arc<Ring<T>>,
+}
+
+impl<T> RingSender<T> {
+    /// Adds a value to the ring. The value is handed back if the ring is full or the receiver
+    /// is gone.
+    pub fn try_send(&mut self, value: T) -> Result<(), TrySendError<T>> {
+        let ring = &self.ring;
+
+        if !ring.receiver_alive.load(Ordering::Acquire) {
+            return Err(TrySendError::Disconnected(value));
+        }
+
+        let tail = ring.tail.load(Ordering::Relaxed);
+        let slot = ring.slot(tail);
+        if slot.stamp.load(Ordering::Acquire) != tail {
+            return Err(TrySendError::Full(value));
+        }
+
+        // SAFETY: the stamp says the slot is free, and only this sender writes to free slots
+        unsafe { (*slot.value.get()).write(value) };
+        slot.stamp.store(tail.wrapping_add(1), Ordering::Release);
+        ring.tail.store(tail.wrapping_add(1), Ordering::Relaxed);
+
+        Ok(())
+    }
+
+    /// Takes the oldest value back out of the ring to make room, or `None` if the receiver got
+    /// to it first.
+    pub fn drop_oldest(&mut self) -> Option<T> {
+        self.ring.pop()
+    }
+}
+
+impl<T> Drop for RingSender<T> {
+    fn drop(&mut self) {
+        self.ring.sender_alive.store(false, Ordering::Release);
+    }
+}
+
+pub struct RingReceiver<T> {
+    ring: Arc<Ring<T>>,
+}
+
+impl<T> RingReceiver<T> {
+    /// Takes the oldest value out of the ring. Only reports `Disconnected` once the sender is
+    /// gone and everything it sent has been received.
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        // Checked before the ring is, so if the sender is gone every value it sent is seen
+        let disconnected = !self.ring.sender_alive.load(Ordering::Acquire);
+
+        match self.ring.pop() {
+            Some(value) => Ok(value),
+            None if disconnected => Err(TryRecvError::Disconnected),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+}
+
+impl<T> RingReceiver<T> {
+    /// Roughly how many values are waiting in the ring, since the sender may be adding one.
+    pub fn len(&self) -> usize {
+        let tail = self.ring.tail.load(Ordering::Relaxed);
+        let head = self.ring.head.load(Ordering::Relaxed);
+
+        tail.wrapping_sub(head).min(self.ring.slots.len())
+    }
+}
+
+impl<T> Drop for RingReceiver<T> {
+    fn drop(&mut self) {
+        self.ring.receiver_alive.store(false, Ordering::Release);
+    }
+}
+
+/// Receives from several rings, taking one value from each in turn so a busy sender can't
+/// starve the others.
+pub struct RoundRobinReceiver<T> {
+    receivers: Vec<RingReceiver<T>>,
+    next: usize,
+}
+
+impl<T> RoundRobinReceiver<T> {
+    pub fn new(receivers: Vec<RingReceiver<T>>) -> RoundRobinReceiver<T> {
+        RoundRobinReceiver { receivers, next: 0 }
+    }
+
+    /// Roughly how many values are waiting across every ring.
+    pub fn len(&self) -> usize {
+        self.receivers.iter().map(|x| x.len()).sum()
+    }
+
+    /// Only reports `Disconnected` once every ring's sender is gone and every ring is empty.
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        let mut checked = 0;
+
+        while checked < self.receivers.len() {
+            let index = self.next % self.receivers.len();
+
+            match self.receivers[index].try_recv() {
+                Ok(value) => {
+                    self.next = index + 1;
+                    return Ok(value);
+                }
+                Err(TryRecvError::Empty) => {
+                    self.next = index + 1;
+                    checked += 1;
+                }
+                // Rings that will never have anything in them again are dropped, the next one
+                // takes the same index
+                Err(TryRecvError::Disconnected) => {
+                    self.receivers.remove(index);
+                    self.next = index;
+                }
+            }
+        }
+
+        if self.receivers.is_empty() {
+            Err(TryRecvError::Disconnected)
+        } else {
+            Err(TryRecvError::Empty)
+        }
+    }
+
+    /// Moves up to `max` waiting values into `buffer`, taking one from each ring in turn like
+    /// `try_recv`, and returns how many it moved. Stops early once every ring is empty.
+    pub fn drain_into(&mut self, buffer: &mut Vec<T>, max: usize) -> usize {
+        let mut moved = 0;
+
+        while moved < max {
+            let before = moved;
+            for receiver in self.receivers.iter_mut() {
+                if moved == max {
+                    break;
+                }
+                if let Ok(value) = receiver.try_recv() {
+                    buffer.push(value);
+                    moved += 1;
+                }
+            }
+
+            if moved == before {
+                break;
+            }
+        }
+
+        moved
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn values_come_out_in_order_across_many_laps() {
+        let (mut sender, mut receiver) = ring_channel(4);
+
+        // Keeping it half full means head and tail both wrap past the capacity again and again
+        let mut next = 0;
+        for sent in 0..1_000 {
+            sender.try_send(sent).unwrap();
+            if sent % 2 == 1 {
+                assert_eq!(receiver.try_recv(), Ok(next));
+                assert_eq!(receiver.try_recv(), Ok(next + 1));
+                next += 2;
+            }
+        }
+
+        assert_eq!(receiver.try_recv(), Err(TryRecvError::Empty));
+        assert_eq!(receiver.len(), 0);
+    }
+
+    #[test]
+    fn full_ring_hands_the_value_back() {
+        let (mut sender, mut receiver) = ring_channel(3);
+        for value in 0..3 {
+            sender.try_send(value).unwrap();
+        }
+
+        assert_eq!(sender.try_send(3), Err(TrySendError::Full(3)));
+        assert_eq!(receiver.len(), 3);
+
+        // Making room frees exactly one slot
+        assert_eq!(receiver.try_recv(), Ok(0));
+        sender.try_send(3).unwrap();
+        assert_eq!(sender.try_send(4), Err(TrySendError::Full(4)));
+
+        // Dropping the oldest takes from the front, the same as receiving would
+        assert_eq!(sender.drop_oldest(), Some(1));
+        sender.try_send(4).unwrap();
+        let received: Vec<_> = std::iter::from_fn(|| receiver.try_recv().ok()).collect();
+        assert_eq!(received, vec![2, 3, 4]);
+        assert_eq!(sender.drop_oldest(), None);
+    }
+
+    #[test]
+    fn receiver_and_drop_oldest_never_both_take_a_value() {
+        const VALUES: usize = 200_000;
+
+        for _ in 0..10 {
+            let (mut sender, mut receiver) = ring_channel(2);
+
+            // A tiny ring keeps the sender dropping the same slot the receiver is taking from
+            let sending = thread::spawn(move || {
+                let mut dropped = Vec::new();
+                for value in 0..VALUES {
+                    let mut value = value;
+                    loop {
+                        match sender.try_send(value) {
+                            Ok(()) => break,
+                            Err(TrySendError::Full(returned)) => {
+                                value = returned;
+                                dropped.extend(sender.drop_oldest());
+                            }
+                            Err(TrySendError::Disconnected(_)) => unreachable!(),
+                        }
+                    }
+                }
+                dropped
+            });
+
+            let mut received = Vec::new();
+            loop {
+                match receiver.try_recv() {
+                    Ok(value) => received.push(value),
+                    Err(TryRecvError::Empty) => thread::yield_now(),
+                    Err(TryRecvError::Disconnected) => break,
+                }
+            }
+            let dropped = sending.join().unwrap();
+
+            // Every value went one way or the other, exactly once, and what was received is
+            // still in order
+            assert!(received.windows(2).all(|x| x[0] < x[1]));
+            assert!(dropped.windows(2).all(|x| x[0] < x[1]));
+            let all: HashSet<_> = received.iter().chain(dropped.iter()).collect();
+            assert_eq!(all.len(), VALUES);
+            assert_eq!(received.len() + dropped.len(), VALUES);
+        }
+    }
+
+    #[test]
+    fn receiver_sees_what_was_left_once_the_sender_is_gone() {
+        let (mut sender, mut receiver) = ring_channel(4);
+        sender.try_send(1).unwrap();
+        sender.try_send(2).unwrap();
+        assert_eq!(receiver.try_recv(), Ok(1));
+        drop(sender);
+
+        assert_eq!(receiver.try_recv(), Ok(2));
+        assert_eq!(receiver.try_recv(), Err(TryRecvError::Disconnected));
+        assert_eq!(receiver.try_recv(), Err(TryRecvError::Disconnected));
+    }
+
+    #[test]
+    fn sender_sees_the_receiver_is_gone() {
+        let (mut sender, receiver) = ring_channel(4);
+        drop(receiver);
+
+        assert_eq!(sender.try_send(1), Err(TrySendError::Disconnected(1)));
+    }
+
+    #[test]
+    fn values_left_in_the_ring_are_dropped_with_it() {
+        let value = Arc::new(());
+        let (mut sender, receiver) = ring_channel(4);
+        for _ in 0..3 {
+            sender.try_send(value.clone()).unwrap();
+        }
+        assert_eq!(Arc::strong_count(&value), 4);
+
+        drop(sender);
+        drop(receiver);
+        assert_eq!(Arc::strong_count(&value), 1);
+    }
+}
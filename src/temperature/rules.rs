@@ -0,0 +1,253 @@
+//! Alert rules written in the config file, for conditions that take more than one reading to
+//! notice, e.g. a sensor that's stayed hot for a few minutes or a big swing over an hour. Each
+//! rule fires once when its condition starts and again only after the condition has cleared.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+use super::{Alert, AlertKind, Recording, SimulationConfig, Temperature};
+
+/// A named alert rule, e.g. `[[alert_rule]]` with `name = "hot"`, `kind = "above"`,
+/// `degrees = 60` and `minutes = 3`.
+#[derive(Clone, Debug, Deserialize)]
+pub struct AlertRuleConfig {
+    /// Shown with every alert the rule raises and used to count them in reports
+    pub name: String,
+
+    #[serde(flatten)]
+    pub rule: AlertRule,
+}
+
+/// What an alert rule checks for. Temperatures are in degrees Fahrenheit.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase", deny_unknown_fields)]
+pub enum AlertRule {
+    /// A sensor's readings have all been above `degrees` for at least `minutes` simulated minutes
+    Above { degrees: f64, minutes: f64 },
+
+    /// A sensor's readings have all been below `degrees` for at least `minutes` simulated minutes
+    Below { degrees: f64, minutes: f64 },
+
+    /// The highest & lowest readings from any sensor within `minutes` simulated minutes of each
+    /// other are more than `degrees` apart
+    Swing { degrees: f64, minutes: f64 },
+}
+
+impl AlertRule {
+    /// Returns the name of the first parameter that isn't usable along with what's wrong with it.
+    pub fn invalid_parameter(&self) -> Option<(&'static str, &'static str)> {
+        match *self {
+            AlertRule::Above { degrees, .. } | AlertRule::Below { degrees, .. }
+                if !degrees.is_finite() =>
+            {
+                Some(("degrees", "must be a finite number"))
+            }
+            AlertRule::Swing { degrees, .. } if !(degrees.is_finite() && degrees >= 0.0) => {
+                Some(("degrees", "must be a non-negative number"))
+            }
+            AlertRule::Above { minutes, .. }
+            | AlertRule::Below { minutes, .. }
+            | AlertRule::Swing { minutes, .. }
+                if !(minutes.is_finite() && minutes >= 0.0) =>
+            {
+                Some(("minutes", "must be a non-negative number"))
+            }
+            _ => None,
+        }
+    }
+}
+
+// How long one sensor's readings have been past a rule's limit
+#[derive(Default)]
+struct Streak {
+    since: Option<Instant>,
+    fired: bool,
+}
+
+// Recent readings from every sensor, kept as sliding window minimums & maximums
+#[derive(Default)]
+struct Swing {
+    lowest: VecDeque<Recording>,
+    highest: VecDeque<Recording>,
+    fired: bool,
+}
+
+// What a rule keeps track of between readings
+enum Check {
+    Streaks {
+        degrees: f64,
+        above: bool,
+        streaks: HashMap<usize, Streak>,
+    },
+    Swing {
+        degrees: f64,
+        swing: Swing,
+    },
+}
+
+struct Rule {
+    name: String,
+    duration: Duration,
+    check: Check,
+}
+
+impl Rule {
+    fn check(&mut self, recording: &Recording) -> Option<Alert> {
+        let value = recording.temperature.fahrenheit();
+
+        match &mut self.check {
+            Check::Streaks {
+                degrees,
+                above,
+                streaks,
+            } => {
+                let degrees = *degrees;
+                let past_limit = if *above {
+                    value > degrees
+                } else {
+                    value < degrees
+                };
+                let streak = streaks.entry(recording.sensor_id).or_default();
+                if !past_limit {
+                    *streak = Streak::default();
+                    return None;
+                }
+
+                let since = *streak.since.get_or_insert(recording.timestamp);
+                if streak.fired
+                    || recording.timestamp.saturating_duration_since(since) < self.duration
+                {
+                    return None;
+                }
+                streak.fired = true;
+
+                Some(self.alert(recording, degrees))
+            }
+            Check::Swing { degrees, swing } => {
+                let degrees = *degrees;
+                swing.push(recording, self.duration);
+
+                let lowest = swing.lowest.front()?.temperature.fahrenheit();
+                let highest = swing.highest.front()?.temperature.fahrenheit();
+                if highest - lowest <= degrees {
+                    swing.fired = false;
+                    return None;
+                }
+                if swing.fired {
+                    return None;
+                }
+                swing.fired = true;
+
+                // Only a new lowest or highest reading can make the swing bigger, so the other
+                // end of the swing is what it's compared with
+                let other_end = if value == highest { lowest } else { highest };
+                Some(self.alert(recording, other_end))
+            }
+        }
+    }
+
+    fn alert(&self, recording: &Recording, baseline: f64) -> Alert {
+        tracing::debug!(
+            rule = %self.name,
+            sensor_id = recording.sensor_id,
+            "alert rule fired"
+        );
+
+        Alert {
+            recording: recording.clone(),
+            kind: AlertKind::Rule,
+            baseline: Temperature::from_fahrenheit(baseline),
+            deviation: recording.temperature.fahrenheit() - baseline,
+            rule: Some(self.name.clone()),
+        }
+    }
+}
+
+impl Swing {
+    fn push(&mut self, recording: &Recording, window: Duration) {
+        let value = recording.temperature.fahrenheit();
+
+        for queue in [&mut self.lowest, &mut self.highest] {
+            while queue.front().is_some_and(|x| {
+                recording.timestamp.saturating_duration_since(x.timestamp) > window
+            }) {
+                queue.pop_front();
+            }
+        }
+
+        while self
+            .lowest
+            .back()
+            .is_some_and(|x| x.temperature.fahrenheit() >= value)
+        {
+            self.lowest.pop_back();
+        }
+        self.lowest.push_back(recording.clone());
+
+        while self
+            .highest
+            .back()
+            .is_some_and(|x| x.temperature.fahrenheit() <= value)
+        {
+            self.highest.pop_back();
+        }
+        self.highest.push_back(recording.clone());
+    }
+}
+
+/// Checks every reading against the config's alert rules. Rules depend on the readings before
+/// them, so readings have to be checked in the order they were taken.
+pub(crate) struct RuleEngine {
+    rules: Vec<Rule>,
+}
+
+impl RuleEngine {
+    pub(crate) fn new(config: &SimulationConfig) -> RuleEngine {
+        let rules = config
+            .alert_rules
+            .iter()
+            .map(|x| {
+                let (minutes, check) = match x.rule {
+                    AlertRule::Above { degrees, minutes }
+                    | AlertRule::Below { degrees, minutes } => {
+                        let above = matches!(x.rule, AlertRule::Above { .. });
+                        let streaks = HashMap::new();
+                        (
+                            minutes,
+                            Check::Streaks {
+                                degrees,
+                                above,
+                                streaks,
+                            },
+                        )
+                    }
+                    AlertRule::Swing { degrees, minutes } => (
+                        minutes,
+                        Check::Swing {
+                            degrees,
+                            swing: Swing::default(),
+                        },
+                    ),
+                };
+
+                Rule {
+                    name: x.name.clone(),
+                    duration: config.scaled(Duration::from_secs_f64(minutes * 60.0)),
+                    check,
+                }
+            })
+            .collect();
+
+        RuleEngine { rules }
+    }
+
+    /// The alerts a reading sets off, one for each rule that fires.
+    pub(crate) fn check(&mut self, recording: &Recording) -> Vec<Alert> {
+        self.rules
+            .iter_mut()
+            .filter_map(|x| x.check(recording))
+            .collect()
+    }
+}
@@ -0,0 +1,162 @@
+//! What a whole run came to, worked out from its reports once it's finished.
+
+use std::fmt;
+
+use serde::Serialize;
+
+use super::{Recording, Report, Temperature, TemperatureUnit};
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RunSummary {
+    /// Which rover the run was, when several are running at once
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rover: Option<String>,
+
+    /// How many reports were generated, including a partial last one
+    pub reports: usize,
+
+    /// How many readings made it into a report
+    pub readings: usize,
+
+    /// How many readings each sensor contributed, indexed by sensor ID
+    pub sensor_readings: Vec<usize>,
+
+    /// Mean of every reading in every report
+    pub mean: Option<Temperature>,
+
+    /// The lowest & highest temps of the whole run
+    pub lowest: Option<Recording>,
+    pub highest: Option<Recording>,
+
+    /// How long working a report out took on average, in seconds
+    pub mean_generation_seconds: f64,
+
+    /// The most readings that were ever waiting for the report thread when it checked, or `None`
+    /// if the transport can't tell
+    pub peak_queue_depth: Option<usize>,
+}
+
+impl RunSummary {
+    pub fn from_reports(reports: &[Report], peak_queue_depth: Option<usize>) -> RunSummary {
+        let mut sensor_readings = Vec::new();
+        for report in reports.iter() {
+            for stats in report.sensor_stats.iter() {
+                if sensor_readings.len() <= stats.sensor_id {
+                    sensor_readings.resize(stats.sensor_id + 1, 0);
+                }
+                sensor_readings[stats.sensor_id] += stats.readings;
+            }
+        }
+
+        let readings: usize = sensor_readings.iter().sum();
+        let report_readings =
+            |report: &Report| -> usize { report.sensor_stats.iter().map(|x| x.readings).sum() };
+        let mean = (readings > 0).then(|| {
+            let total: f64 = reports
+                .iter()
+                .filter_map(|x| {
+                    let summary = x.summary.as_ref()?;
+                    Some(summary.mean.fahrenheit() * report_readings(x) as f64)
+                })
+                .sum();
+            Temperature::from_fahrenheit(total / readings as f64)
+        });
+
+        // Each report's top temps start with its most extreme one
+        let lowest = reports
+            .iter()
+            .filter_map(|x| x.lowest_temps.first())
+            .min_by(|a, b| {
+                a.temperature
+                    .fahrenheit()
+                    .total_cmp(&b.temperature.fahrenheit())
+            })
+            .cloned();
+        let highest = reports
+            .iter()
+            .filter_map(|x| x.highest_temps.first())
+            .max_by(|a, b| {
+                a.temperature
+                    .fahrenheit()
+                    .total_cmp(&b.temperature.fahrenheit())
+            })
+            .cloned();
+
+        let mean_generation_seconds = match reports.len() {
+            0 => 0.0,
+            count => reports.iter().map(|x| x.generation_seconds).sum::<f64>() / count as f64,
+        };
+
+        RunSummary {
+            rover: reports.first().and_then(|x| x.rover.clone()),
+            reports: reports.len(),
+            readings,
+            sensor_readings,
+            mean,
+            lowest,
+            highest,
+            mean_generation_seconds,
+            peak_queue_depth,
+        }
+    }
+
+    /// Formats the summary with temperatures in the given unit.
+    pub fn display(&self, unit: TemperatureUnit) -> DisplayRunSummary<'_> {
+        DisplayRunSummary {
+            summary: self,
+            unit,
+        }
+    }
+}
+
+pub struct DisplayRunSummary<'a> {
+    summary: &'a RunSummary,
+    unit: TemperatureUnit,
+}
+
+impl fmt::Display for DisplayRunSummary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let summary = self.summary;
+        let unit = self.unit;
+
+        if let Some(rover) = &summary.rover {
+            write!(f, "{}: ", rover)?;
+        }
+
+        writeln!(f, "Run summary\n")?;
+        writeln!(f, "Reports generated: {}", summary.reports)?;
+        writeln!(f, "Total readings: {}", summary.readings)?;
+
+        if let Some(mean) = summary.mean {
+            writeln!(f, "Mean: {}", mean.display(unit))?;
+        }
+        for (label, extreme) in [("Lowest", &summary.lowest), ("Highest", &summary.highest)] {
+            if let Some(recording) = extreme {
+                writeln!(
+                    f,
+                    "{}: {} (sensor {})",
+                    label,
+                    recording.temperature.display(unit),
+                    recording.sensor_id
+                )?;
+            }
+        }
+
+        writeln!(
+            f,
+            "Average report generation time: {:.3}ms",
+            summary.mean_generation_seconds * 1000.0
+        )?;
+        match summary.peak_queue_depth {
+            Some(depth) => writeln!(f, "Peak queue depth: {} readings", depth)?,
+            None => writeln!(f, "Peak queue depth: unknown")?,
+        }
+
+        writeln!(f, "\nReadings per sensor: ")?;
+        for (sensor_id, readings) in summary.sensor_readings.iter().enumerate() {
+            writeln!(f, "Sensor {}: {}", sensor_id, readings)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,174 @@
+//! Sensors take their readings on a fixed schedule, one every sampling period from the start of
+//! the simulation, and number each reading by the sampling period it was due in. The report
+//! thread works out from the same schedule how many readings each sensor owed every report, so
+//! gaps in the sequence numbers tell it exactly how many went missing. A sensor can have a
+//! sampling period of its own, in which case it has a schedule of its own too.
+
+use std::mem;
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::{Recording, SimulationConfig};
+
+/// Gives one sensor its own sampling period, e.g. `[[sensor_sampling]]` with `sensor = 3` and
+/// `period = 30`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SensorSamplingConfig {
+    pub sensor: usize,
+
+    /// How many simulated seconds the sensor waits between recordings
+    pub period: f64,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct SamplingSchedule {
+    start: Instant,
+
+    // The reading with sequence number `n` is due `n * step / per` real nanoseconds after the
+    // start. Steps are scaled the same way as the report interval, rather than scaling the
+    // sampling period on its own, so a period's last reading can't be rounded into the period
+    // before
+    step: u128,
+    per: u128,
+}
+
+impl SamplingSchedule {
+    /// The schedule `sensor_id` takes its readings on.
+    pub(crate) fn for_sensor(
+        start: Instant,
+        config: &SimulationConfig,
+        sensor_id: usize,
+    ) -> SamplingSchedule {
+        SamplingSchedule::new(start, config, config.sampling_period_for(sensor_id))
+    }
+
+    fn new(start: Instant, config: &SimulationConfig, period_seconds: f64) -> SamplingSchedule {
+        let period = Duration::from_secs_f64(period_seconds).as_nanos();
+        let interval = Duration::from_secs(config.report_interval_minutes * 60).as_nanos();
+
+        SamplingSchedule {
+            start,
+            step: (period * config.scaled_report_interval().as_nanos()).max(1),
+            per: interval.max(1),
+        }
+    }
+
+    /// The sequence number of the sampling period `at` falls in.
+    pub(crate) fn sequence_at(&self, at: Instant) -> u64 {
+        self.due_before_nanos(self.elapsed_nanos(at) + 1) - 1
+    }
+
+    /// The sequence number a sensor that's up to `next` gives the reading it takes at `now`. A
+    /// reading can be up to a sampling period late (e.g. from a timer that fires late) and keep
+    /// its place. A sensor that's fallen further behind (e.g. waiting for room in a full queue)
+    /// skips to the latest reading that's due instead of taking every one it missed at once,
+    /// which leaves a gap in its sequence numbers.
+    pub(crate) fn catch_up(&self, next: u64, now: Instant) -> u64 {
+        if now >= self.due(next + 2) {
+            self.sequence_at(now) - 1
+        } else {
+            next
+        }
+    }
+
+    /// When the first reading after `now` is due, for a sensor that has to wait before it tries
+    /// again (e.g. for room in its queue) without drifting off the schedule.
+    pub(crate) fn next_due(&self, now: Instant) -> Instant {
+        self.due(self.sequence_at(now) + 1)
+    }
+
+    /// When the reading with the given sequence number is due.
+    pub(crate) fn due(&self, sequence: u64) -> Instant {
+        self.start + Duration::from_nanos((sequence as u128 * self.step / self.per) as u64)
+    }
+
+    /// The sequence numbers of the readings due from `from` up to, but not including, `to`.
+    pub(crate) fn between(&self, from: Instant, to: Instant) -> Range<u64> {
+        self.due_before(from)..self.due_before(to)
+    }
+
+    /// How many readings are due before `at`.
+    fn due_before(&self, at: Instant) -> u64 {
+        self.due_before_nanos(self.elapsed_nanos(at))
+    }
+
+    fn due_before_nanos(&self, elapsed: u128) -> u64 {
+        (elapsed * self.per).div_ceil(self.step) as u64
+    }
+
+    fn elapsed_nanos(&self, at: Instant) -> u128 {
+        at.saturating_duration_since(self.start).as_nanos()
+    }
+}
+
+/// How late a sensor wakes up for each of its readings, picked at random up to
+/// `sampling_jitter`. Readings are still numbered by when they were due, so only their
+/// timestamps move.
+pub(crate) struct SamplingJitter {
+    rng: StdRng,
+    max: Duration,
+}
+
+impl SamplingJitter {
+    pub(crate) fn for_sensor(config: &SimulationConfig, sensor_id: usize) -> SamplingJitter {
+        SamplingJitter {
+            rng: config.jitter_rng(sensor_id),
+            max: config.scaled_sampling_jitter(),
+        }
+    }
+
+    pub(crate) fn delay(&mut self) -> Duration {
+        if self.max.is_zero() {
+            return Duration::ZERO;
+        }
+
+        self.max.mul_f64(self.rng.gen_range(0.0..=1.0))
+    }
+}
+
+/// Every sensor's schedule. Readings from a sensor the config doesn't have (e.g. one named in a
+/// file source) go by the default sampling period.
+#[derive(Clone, Debug)]
+pub(crate) struct SensorSchedules {
+    sensors: Vec<SamplingSchedule>,
+    default: SamplingSchedule,
+}
+
+impl SensorSchedules {
+    pub(crate) fn new(start: Instant, config: &SimulationConfig) -> SensorSchedules {
+        SensorSchedules {
+            sensors: (0..config.sensor_count)
+                .map(|sensor_id| SamplingSchedule::for_sensor(start, config, sensor_id))
+                .collect(),
+            default: SamplingSchedule::new(start, config, config.sampling_period_seconds),
+        }
+    }
+
+    pub(crate) fn sensor(&self, sensor_id: usize) -> &SamplingSchedule {
+        self.sensors.get(sensor_id).unwrap_or(&self.default)
+    }
+
+    /// When a recording was due, going by its sensor's schedule.
+    pub(crate) fn due(&self, recording: &Recording) -> Instant {
+        self.sensor(recording.sensor_id).due(recording.sequence)
+    }
+
+    /// Takes the recordings that were due before `end` out of `recordings`, leaving the later
+    /// ones behind.
+    pub(crate) fn take_due_before(
+        &self,
+        recordings: &mut Vec<Recording>,
+        end: Instant,
+    ) -> Vec<Recording> {
+        let (before, after): (Vec<_>, Vec<_>) = mem::take(recordings)
+            .into_iter()
+            .partition(|x| self.due(x) < end);
+        *recordings = after;
+        before
+    }
+}
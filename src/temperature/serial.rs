@@ -0,0 +1,86 @@
+//! Reading a real temperature sensor over a serial port, e.g. an Arduino printing one temperature
+//! per line. The port is read like any other line source, so real readings go through the same
+//! report pipeline as simulated ones.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufReader, Read};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+
+use nix::fcntl::OFlag;
+use nix::sys::termios::{self, BaudRate, ControlFlags, FlushArg, SetArg, SpecialCharacterIndices};
+
+use super::{LineFormat, LineSource};
+
+/// Reads temperatures (in degrees Fahrenheit) from a serial device, in the same formats as a file.
+pub type SerialSource = LineSource<BufReader<SerialPort>>;
+
+/// A serial device set up in raw mode, 8 data bits, no parity and 1 stop bit. Reads block until
+/// the device sends something.
+pub struct SerialPort {
+    file: File,
+}
+
+impl SerialPort {
+    pub fn open(path: impl AsRef<Path>, baud_rate: u32) -> io::Result<SerialPort> {
+        let speed = baud_rate_constant(baud_rate).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("unsupported baud rate {}", baud_rate),
+            )
+        })?;
+
+        // Without O_NOCTTY the device could become the process's controlling terminal
+        let file = OpenOptions::new()
+            .read(true)
+            .custom_flags(OFlag::O_NOCTTY.bits())
+            .open(path)?;
+
+        let mut settings = termios::tcgetattr(&file)?;
+        termios::cfmakeraw(&mut settings);
+        termios::cfsetspeed(&mut settings, speed)?;
+        settings.control_flags |= ControlFlags::CLOCAL | ControlFlags::CREAD;
+        settings.control_flags &= !(ControlFlags::CSTOPB | ControlFlags::PARENB);
+        settings.control_chars[SpecialCharacterIndices::VMIN as usize] = 1;
+        settings.control_chars[SpecialCharacterIndices::VTIME as usize] = 0;
+        termios::tcsetattr(&file, SetArg::TCSANOW, &settings)?;
+
+        // Whatever the device sent before the port was opened is stale
+        termios::tcflush(&file, FlushArg::TCIFLUSH)?;
+
+        Ok(SerialPort { file })
+    }
+}
+
+impl Read for SerialPort {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.file.read(buf)
+    }
+}
+
+impl SerialSource {
+    /// Opens a serial device at `baud_rate`, reading lines in `format`.
+    pub fn serial(
+        path: impl AsRef<Path>,
+        baud_rate: u32,
+        format: LineFormat,
+    ) -> io::Result<SerialSource> {
+        let port = SerialPort::open(path, baud_rate)?;
+        Ok(LineSource::new(BufReader::new(port), format))
+    }
+}
+
+fn baud_rate_constant(baud_rate: u32) -> Option<BaudRate> {
+    Some(match baud_rate {
+        1200 => BaudRate::B1200,
+        2400 => BaudRate::B2400,
+        4800 => BaudRate::B4800,
+        9600 => BaudRate::B9600,
+        19200 => BaudRate::B19200,
+        38400 => BaudRate::B38400,
+        57600 => BaudRate::B57600,
+        115200 => BaudRate::B115200,
+        230400 => BaudRate::B230400,
+        _ => return None,
+    })
+}
@@ -0,0 +1,408 @@
+//! Rings shared between processes. The report process creates a file holding one ring per
+//! sensor and every sensor process maps the same file into memory, so a reading is handed over
+//! by writing it into the mapping and moving the ring's tail, without a pipe or a socket in
+//! between. Each ring has exactly one writer (its sensor process) and one reader (the report
+//! process), so the atomic head and tail indices are all either side needs.
+
+use std::ffi::c_void;
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::mem::size_of;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use nix::sys::mman::{self, MapFlags, ProtFlags};
+
+use super::{RandomSource, Recording, SensorSource, SimulationConfig, Temperature};
+
+// Marks a file as holding shared rings, so a mapping of some other file is refused
+const MAGIC: u64 = u64::from_le_bytes(*b"TEMPRING");
+
+/// How often a side that's waiting on the other checks the ring again. There's nothing to
+/// block on across processes, so they poll.
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+// Everything in the file is an `AtomicU64`, so a freshly zeroed file is already valid and the
+// layout is the same in every process
+#[repr(C)]
+struct Header {
+    magic: AtomicU64,
+    sensor_count: AtomicU64,
+    capacity: AtomicU64,
+
+    // Set by the report process once it no longer wants readings
+    stopping: AtomicU64,
+}
+
+#[repr(C)]
+struct RingHeader {
+    // Both only ever count up, the slot is the count modulo the capacity. `head` is only
+    // written by the report process and `tail` only by the sensor process
+    head: AtomicU64,
+    tail: AtomicU64,
+
+    // Set once the sensor process has sent its last reading (or has exited)
+    closed: AtomicU64,
+}
+
+fn ring_size(capacity: usize) -> usize {
+    size_of::<RingHeader>() + capacity * size_of::<AtomicU64>()
+}
+
+/// How long a file holding `sensor_count` rings of `capacity` readings is, or `None` if that
+/// doesn't fit in a `usize`.
+fn file_len(sensor_count: usize, capacity: usize) -> Option<usize> {
+    capacity
+        .checked_mul(size_of::<AtomicU64>())
+        .and_then(|x| x.checked_add(size_of::<RingHeader>()))
+        .and_then(|x| x.checked_mul(sensor_count))
+        .and_then(|x| x.checked_add(size_of::<Header>()))
+}
+
+/// A file mapped into this process's memory, unmapped once every ring using it is gone.
+struct Mapping {
+    ptr: NonNull<c_void>,
+    len: usize,
+
+    // The report process's copy removes the file, the sensor processes' copies leave it
+    remove: Option<PathBuf>,
+}
+
+// SAFETY: the mapping is only ever accessed through atomics
+unsafe impl Send for Mapping {}
+unsafe impl Sync for Mapping {}
+
+impl Mapping {
+    fn map(file: &File, len: usize, remove: Option<PathBuf>) -> io::Result<Mapping> {
+        let length = NonZeroUsize::new(len)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "the file is empty"))?;
+
+        // SAFETY: a new shared mapping of a file doesn't alias anything in this process
+        let ptr = unsafe {
+            mman::mmap(
+                None,
+                length,
+                ProtFlags::PROT_READ | ProtFlags::PROT_WRITE,
+                MapFlags::MAP_SHARED,
+                file,
+                0,
+            )?
+        };
+
+        Ok(Mapping { ptr, len, remove })
+    }
+
+    /// The `AtomicU64` `index` words into the mapping.
+    fn word(&self, index: usize) -> &AtomicU64 {
+        assert!((index + 1) * size_of::<AtomicU64>() <= self.len);
+
+        // SAFETY: the mapping is page aligned, in bounds (checked above) and lives as long as
+        // `self`. Any bit pattern is a valid `AtomicU64`
+        unsafe { &*(self.ptr.as_ptr() as *const AtomicU64).add(index) }
+    }
+
+    fn header(&self) -> &Header {
+        assert!(size_of::<Header>() <= self.len);
+
+        // SAFETY: as for `word`
+        unsafe { &*(self.ptr.as_ptr() as *const Header) }
+    }
+
+    fn capacity(&self) -> usize {
+        self.header().capacity.load(Ordering::Relaxed) as usize
+    }
+
+    /// Index of the first word of `sensor_id`'s ring.
+    fn ring_start(&self, sensor_id: usize) -> usize {
+        (size_of::<Header>() + sensor_id * ring_size(self.capacity())) / size_of::<AtomicU64>()
+    }
+
+    fn ring(&self, sensor_id: usize) -> &RingHeader {
+        let start = self.ring_start(sensor_id);
+        assert!((start * size_of::<AtomicU64>()) + ring_size(self.capacity()) <= self.len);
+
+        // SAFETY: as for `word`, and the whole ring is in bounds (checked above)
+        unsafe { &*(self.word(start) as *const AtomicU64 as *const RingHeader) }
+    }
+
+    fn slot(&self, sensor_id: usize, index: u64) -> &AtomicU64 {
+        let capacity = self.capacity();
+        let header_words = size_of::<RingHeader>() / size_of::<AtomicU64>();
+
+        self.word(self.ring_start(sensor_id) + header_words + (index % capacity as u64) as usize)
+    }
+}
+
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        // SAFETY: nothing borrowed from the mapping outlives it
+        let _ = unsafe { mman::munmap(self.ptr, self.len) };
+
+        if let Some(path) = &self.remove {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// One ring per sensor in a memory-mapped file. Cloning gives another handle to the same
+/// mapping, and the file is removed once the process that created it drops its last handle.
+#[derive(Clone)]
+pub struct SharedRings {
+    mapping: Arc<Mapping>,
+}
+
+impl SharedRings {
+    /// Creates the file at `path` with a ring holding `capacity` readings for each of
+    /// `sensor_count` sensors. An existing file is overwritten.
+    pub fn create(
+        path: impl AsRef<Path>,
+        sensor_count: usize,
+        capacity: usize,
+    ) -> io::Result<SharedRings> {
+        let path = path.as_ref();
+        if capacity == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "a shared ring has to hold at least one reading",
+            ));
+        }
+        let len = file_len(sensor_count, capacity)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "shared rings too large"))?;
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        file.set_len(len as u64)?;
+
+        let mapping = Mapping::map(&file, len, Some(path.to_path_buf()))?;
+        let header = mapping.header();
+        header
+            .sensor_count
+            .store(sensor_count as u64, Ordering::Relaxed);
+        header.capacity.store(capacity as u64, Ordering::Relaxed);
+
+        // Written last, so a process that sees it also sees the sizes
+        header.magic.store(MAGIC, Ordering::Release);
+
+        Ok(SharedRings {
+            mapping: Arc::new(mapping),
+        })
+    }
+
+    /// Maps a file another process created with `create`.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<SharedRings> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        let len = file.metadata()?.len() as usize;
+
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "not a shared ring file");
+        if len < size_of::<Header>() {
+            return Err(invalid());
+        }
+
+        let mapping = Mapping::map(&file, len, None)?;
+        let header = mapping.header();
+        if header.magic.load(Ordering::Acquire) != MAGIC {
+            return Err(invalid());
+        }
+
+        // The sizes come from the file, so they're checked before anything is worked out from
+        // them: a capacity of 0 would leave nowhere to put a reading and sizes that overflow
+        // would let rings run past the end of the mapping
+        let sensor_count = usize::try_from(header.sensor_count.load(Ordering::Relaxed));
+        let capacity = usize::try_from(header.capacity.load(Ordering::Relaxed));
+        match (sensor_count, capacity) {
+            (Ok(sensor_count), Ok(capacity)) if capacity > 0 => {
+                if file_len(sensor_count, capacity).is_none_or(|x| len < x) {
+                    return Err(invalid());
+                }
+            }
+            _ => return Err(invalid()),
+        }
+
+        Ok(SharedRings {
+            mapping: Arc::new(mapping),
+        })
+    }
+
+    pub fn sensor_count(&self) -> usize {
+        self.mapping.header().sensor_count.load(Ordering::Relaxed) as usize
+    }
+
+    /// How many readings each ring holds.
+    pub fn capacity(&self) -> usize {
+        self.mapping.capacity()
+    }
+
+    /// Tells every sensor process to stop sending readings.
+    pub fn stop(&self) {
+        self.mapping.header().stopping.store(1, Ordering::Release);
+    }
+
+    pub fn is_stopping(&self) -> bool {
+        self.mapping.header().stopping.load(Ordering::Acquire) != 0
+    }
+
+    /// Marks a sensor's ring as finished, e.g. because its process exited without doing it.
+    /// Readings already in the ring can still be received.
+    pub fn close(&self, sensor_id: usize) {
+        self.mapping
+            .ring(sensor_id)
+            .closed
+            .store(1, Ordering::Release);
+    }
+
+    /// The sending end of a sensor's ring. Only one sender can be used per ring.
+    pub fn sender(&self, sensor_id: usize) -> SharedRingSender {
+        assert!(
+            sensor_id < self.sensor_count(),
+            "no ring for sensor {}",
+            sensor_id
+        );
+
+        SharedRingSender {
+            mapping: self.mapping.clone(),
+            sensor_id,
+        }
+    }
+
+    /// The receiving end of a sensor's ring, as a source for the sensor's thread in the report
+    /// process. Only one source can be used per ring.
+    pub fn source(&self, sensor_id: usize) -> SharedRingSource {
+        assert!(
+            sensor_id < self.sensor_count(),
+            "no ring for sensor {}",
+            sensor_id
+        );
+
+        SharedRingSource {
+            mapping: self.mapping.clone(),
+            sensor_id,
+        }
+    }
+}
+
+/// A sensor process's end of its ring. Closes the ring when it's dropped.
+pub struct SharedRingSender {
+    mapping: Arc<Mapping>,
+    sensor_id: usize,
+}
+
+impl SharedRingSender {
+    /// Adds a temperature to the ring. It's handed back if the ring is full.
+    pub fn try_send(&mut self, temperature: Temperature) -> Result<(), Temperature> {
+        let ring = self.mapping.ring(self.sensor_id);
+
+        let tail = ring.tail.load(Ordering::Relaxed);
+        let head = ring.head.load(Ordering::Acquire);
+        if tail.wrapping_sub(head) >= self.mapping.capacity() as u64 {
+            return Err(temperature);
+        }
+
+        self.mapping
+            .slot(self.sensor_id, tail)
+            .store(temperature.fahrenheit().to_bits(), Ordering::Relaxed);
+        ring.tail.store(tail.wrapping_add(1), Ordering::Release);
+
+        Ok(())
+    }
+}
+
+impl Drop for SharedRingSender {
+    fn drop(&mut self) {
+        self.mapping
+            .ring(self.sensor_id)
+            .closed
+            .store(1, Ordering::Release);
+    }
+}
+
+/// Reads a sensor process's readings out of its ring, waiting for the next one to be sent. Runs
+/// out once the ring is closed and empty.
+pub struct SharedRingSource {
+    mapping: Arc<Mapping>,
+    sensor_id: usize,
+}
+
+impl SharedRingSource {
+    fn try_recv(&mut self) -> Option<Temperature> {
+        let ring = self.mapping.ring(self.sensor_id);
+
+        let head = ring.head.load(Ordering::Relaxed);
+        if head == ring.tail.load(Ordering::Acquire) {
+            return None;
+        }
+
+        let bits = self
+            .mapping
+            .slot(self.sensor_id, head)
+            .load(Ordering::Relaxed);
+        ring.head.store(head.wrapping_add(1), Ordering::Release);
+
+        Some(Temperature::from_fahrenheit(f64::from_bits(bits)))
+    }
+}
+
+impl SensorSource for SharedRingSource {
+    fn next_reading(&mut self) -> Option<Recording> {
+        loop {
+            // Checked before the ring is, so every reading sent before it closed is seen
+            let closed = self
+                .mapping
+                .ring(self.sensor_id)
+                .closed
+                .load(Ordering::Acquire)
+                != 0;
+
+            match self.try_recv() {
+                Some(temperature) => return Some(Recording::new(self.sensor_id, temperature)),
+                None if closed => return None,
+                None => sleep(POLL_INTERVAL),
+            }
+        }
+    }
+}
+
+/// What a sensor process runs: takes a random reading every sampling period and sends it to the
+/// report process, until the report process stops it or goes away. A full ring is waited on.
+/// Faults are left to the report process, which treats the ring like any other source.
+pub fn run_sensor_process(rings: &SharedRings, sensor_id: usize, config: &SimulationConfig) {
+    let mut sender = rings.sender(sensor_id);
+    let mut source = RandomSource::for_sensor(config, sensor_id);
+    let period = config.scaled_sampling_period_for(sensor_id);
+
+    // A sensor process whose report process exited without stopping it is adopted by another
+    // process, so its parent changes
+    let parent = std::os::unix::process::parent_id();
+    let running = || !rings.is_stopping() && std::os::unix::process::parent_id() == parent;
+
+    // Readings are due a whole number of sampling periods after the start, like a sensor thread's
+    let start = Instant::now();
+    let mut readings_sent: u32 = 0;
+
+    while running() {
+        let Some(recording) = source.next_reading() else {
+            return;
+        };
+
+        let mut temperature = recording.temperature;
+        while let Err(x) = sender.try_send(temperature) {
+            if !running() {
+                return;
+            }
+            temperature = x;
+            sleep(POLL_INTERVAL);
+        }
+
+        readings_sent += 1;
+        let wake_at = start + period * readings_sent;
+        sleep(wake_at.saturating_duration_since(Instant::now()));
+    }
+}
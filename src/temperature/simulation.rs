@@ -0,0 +1,1270 @@
+use std::ops::Range;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, TryRecvError};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread::{spawn, JoinHandle};
+use std::time::{Duration, Instant};
+
+use super::anomaly::{Alerts, PRIORITY_CAPACITY};
+use super::barrier::{BarrierSeat, SamplingBarrier};
+use super::broadcast::{Broadcaster, OutputEvent};
+use super::callbacks::{OnAlert, OnReading, OnReport};
+use super::error::{self, SimulationError};
+use super::hot_swap::{InstallSensor, SensorSlots};
+use super::pause::{PausableClock, Pause};
+use super::reorder::ReorderBuffer;
+use super::schedule::{SamplingJitter, SensorSchedules};
+use super::skew::{self, SkewCorrector};
+use super::speed::{ScaledClock, Speed};
+use super::validation::{ReadingFilter, RejectionCounts};
+use super::watchdog::{SensorLiveness, Watchdog};
+use super::{
+    affinity, sensor_channels, timestamp, Alert, Clock, FaultySource, HotSwapHandle, PauseHandle,
+    RandomSource, Recording, Report, ReportAccumulator, ReportHistory, ReportSink, RunSummary,
+    SensorReceiver, SensorSender, SensorSource, SimulationConfig, SpeedHandle, SystemClock,
+};
+
+// Notes
+// 8 temperature reading threads
+// Must use shared memory to store & make the reports
+
+// Ideas
+// Every minute could be treated as a millisecond
+// Threads will do their operations and wait until it's time to do stuff again
+
+// - Sensor threads will record & push onto queue every minute
+
+// - Report thread will pull from the queue and add to the list until it's time
+// to make a report. Every iteration of the report thread loop will check to see
+// if it's time to make a report.
+
+// The temp sensor threads will be very simple. They'll generate a random number,
+// push it onto the queue, and wait for another minute.
+
+// There will be 1 report thread that receives message from
+// the sensors and adds them to the report.
+
+// Each sensor gets its own lock-free ring buffer (single producer, single consumer)
+// and the report thread takes readings from the rings in turn
+
+// A ring won't delay a sensor, sensors will just push onto their ring and continue
+
+// The ring will hold readings until the report thread is ready to read them again
+// so no readings will ever get lost. If a ring ever fills up the sensor waits for
+// room instead of dropping the reading (unless it's set to drop readings).
+
+// The rings can be swapped for a shared mpsc or crossbeam channel with `transport`
+
+// - Every temp reading for a given hour is folded into the report's statistics
+// as soon as it's received
+
+// - When its time for a report to be generated the statistics are taken as they
+// are and the next hour starts from scratch
+
+// - There are two sets of statistics. At the end of the hour the full one is
+// swapped for the empty one and handed to a builder thread to make the report
+// from, so the report thread never stops taking readings off the queues
+
+/// A frozen accumulator for the report builder thread to make a report from.
+struct ReportJob {
+    accumulator: ReportAccumulator,
+    index: usize,
+    period_start: Instant,
+    period_end: Instant,
+    partial: bool,
+
+    // A copy of the accumulator partway through its period, for a report that was asked for
+    on_demand: bool,
+}
+
+/// Runs the sensor threads and the report thread in the background. Reports generated so far
+/// can be retrieved with `all_reports()` or `reports_for_hour()` while the simulation is running
+/// or after it's stopped, and `subscribe_reports()` streams them to the caller as they're
+/// generated.
+pub struct TemperatureSimulation {
+    config: SimulationConfig,
+    name: Option<String>,
+    sources: Option<Vec<Box<dyn SensorSource>>>,
+    sinks: Vec<Box<dyn ReportSink>>,
+    running: Arc<AtomicBool>,
+
+    // Set by `start` and never cleared, so it stays set once the simulation has stopped
+    started: bool,
+
+    clock: Arc<dyn Clock>,
+    pause: Arc<Pause>,
+    speed: Arc<Speed>,
+    history: Arc<Mutex<ReportHistory>>,
+
+    // How many readings were waiting for the report thread when it last checked, `usize::MAX`
+    // if the transport can't tell
+    queue_depth: Arc<AtomicUsize>,
+
+    // The most readings that were ever waiting when the report thread checked
+    peak_queue_depth: Arc<AtomicUsize>,
+
+    // Set by a `ReportTrigger`, taken by the report thread
+    report_requested: Arc<AtomicBool>,
+
+    // Every sensor's slot and thread, including the ones added and retired along the way
+    slots: Arc<SensorSlots>,
+
+    report_handle: Option<JoinHandle<Result<(), SimulationError>>>,
+    output_handles: Vec<JoinHandle<()>>,
+}
+
+impl TemperatureSimulation {
+    pub fn new() -> TemperatureSimulation {
+        TemperatureSimulation::with_config(SimulationConfig::default())
+    }
+
+    pub fn with_config(config: SimulationConfig) -> TemperatureSimulation {
+        TemperatureSimulation {
+            config,
+            name: None,
+            sources: None,
+            sinks: Vec::new(),
+            running: Arc::new(AtomicBool::new(false)),
+            started: false,
+            clock: Arc::new(SystemClock),
+            pause: Arc::new(Pause::default()),
+            speed: Arc::new(Speed::default()),
+            history: Arc::new(Mutex::new(ReportHistory::new())),
+            queue_depth: Arc::new(AtomicUsize::new(usize::MAX)),
+            peak_queue_depth: Arc::new(AtomicUsize::new(0)),
+            report_requested: Arc::new(AtomicBool::new(false)),
+            slots: Arc::new(SensorSlots::default()),
+            report_handle: None,
+            output_handles: Vec::new(),
+        }
+    }
+
+    /// Creates a simulation with one sensor thread per source instead of the random sensors
+    /// described by the config. `config.sensor_count` is set to the number of sources.
+    pub fn with_sources(
+        mut config: SimulationConfig,
+        sources: Vec<Box<dyn SensorSource>>,
+    ) -> TemperatureSimulation {
+        config.sensor_count = sources.len();
+
+        let mut simulation = TemperatureSimulation::with_config(config);
+        simulation.sources = Some(sources);
+        simulation
+    }
+
+    /// Adds a sink that every generated report gets handed to. Sinks have to be added before
+    /// the simulation is started.
+    pub fn add_sink(&mut self, sink: impl ReportSink + 'static) {
+        self.sinks.push(Box::new(sink));
+    }
+
+    /// Calls `handler` with every report the simulation generates, on its own output thread
+    /// like any other sink. Has to be called before the simulation is started.
+    pub fn on_report(&mut self, handler: impl FnMut(&Report) + Send + 'static) {
+        self.add_sink(OnReport(handler));
+    }
+
+    /// Returns a channel that every report is sent down as it's generated, so the caller can
+    /// iterate over them (`for report in reports`) while the simulation runs. The iteration ends
+    /// once the simulation has finished and the last report has been taken. Fails once the
+    /// simulation has been started, since the reports already generated would be missing.
+    pub fn subscribe_reports(&mut self) -> Result<Receiver<Report>, SimulationError> {
+        if self.started {
+            return Err(SimulationError::AlreadyStarted);
+        }
+
+        let (sender, receiver) = mpsc::channel();
+        self.on_report(move |report| {
+            // Nobody's listening any more, which is fine
+            let _ = sender.send(report.clone());
+        });
+
+        Ok(receiver)
+    }
+
+    /// Calls `handler` with every reading as the report thread receives it. Has to be called
+    /// before the simulation is started.
+    pub fn on_reading(&mut self, handler: impl FnMut(&Recording) + Send + 'static) {
+        self.add_sink(OnReading(handler));
+    }
+
+    /// Calls `handler` with every alert as soon as it's raised. Has to be called before the
+    /// simulation is started.
+    pub fn on_alert(&mut self, handler: impl FnMut(&Alert) + Send + 'static) {
+        self.add_sink(OnAlert(handler));
+    }
+
+    /// Labels every report the simulation generates with `name`, e.g. which rover it came from.
+    /// The name has to be set before the simulation is started.
+    pub fn set_name(&mut self, name: impl Into<String>) {
+        self.name = Some(name.into());
+    }
+
+    /// Replaces the clock the sensor and report threads use, e.g. with a `VirtualClock` so a
+    /// simulated hour passes instantly. The clock has to be set before the simulation is started.
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    /// Spawns the sensor threads and the report thread. Does nothing if the simulation is
+    /// already running.
+    pub fn start(&mut self) {
+        if self.running.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        self.started = true;
+
+        let scaled_report_interval = self.config.scaled_report_interval();
+        let scaled_minute = self.config.scaled_minute();
+        let late_grace = self.config.scaled_late_grace();
+
+        // Every thread goes by the same clock, which runs at whatever speed the simulation has been
+        // set to and stops while it's paused
+        let scaled_clock = Arc::new(ScaledClock::new(
+            self.clock.clone(),
+            self.speed.clone(),
+            self.config.speedup,
+        ));
+        let clock: Arc<dyn Clock> = Arc::new(PausableClock::new(scaled_clock, self.pause.clone()));
+
+        // The report thread joins the clock before any sensor starts, otherwise a virtual clock
+        // could move forward while only the sensors are running
+        let report_participant = ClockParticipant::new(clock.clone());
+        let start_time = clock.now();
+
+        // Sources passed in by the caller can only be used once, after that the sensors fall
+        // back to random readings
+        let sources = sensor_sources(&self.config, self.sources.take());
+
+        // By default one ring per sensor carries its recordings to the report thread
+        let (temperature_senders, mut temperature_receiver) = sensor_channels(
+            self.config.transport,
+            sources.len(),
+            self.config.queue_capacity,
+            clock.clone(),
+        );
+        let queue_overflow = self.config.queue_overflow;
+        let high_frequency = self.config.high_frequency;
+        let rover = self.name.clone();
+        let core_count = affinity::core_count();
+
+        // Readings each sensor threw away because its queue was full, taken by the report thread
+        // whenever it makes a report
+        let sensor_count = sources.len();
+        let dropped_counts: Arc<Vec<AtomicUsize>> =
+            Arc::new((0..sensor_count).map(|_| AtomicUsize::new(0)).collect());
+
+        // Readings the sensors threw away as invalid, taken along with the dropped ones
+        let filter = ReadingFilter::new(&self.config);
+        let rejections = Arc::new(RejectionCounts::default());
+
+        // When each sensor last sent a reading, for the watchdog
+        let liveness = Arc::new(SensorLiveness::new(sensor_count, start_time));
+        let restart = self.config.watchdog.restart;
+        let schedules = SensorSchedules::new(start_time, &self.config);
+
+        // Alerts for extreme readings skip the sensors' queues so they're raised even when the
+        // report thread has fallen behind. The slots transport doesn't keep sequence numbers, so
+        // an alert couldn't be matched up with its reading and raised only once
+        let (priority_sender, priority_receiver) = mpsc::sync_channel::<Alert>(PRIORITY_CAPACITY);
+        let priority = self
+            .config
+            .priority
+            .clone()
+            .filter(|_| self.config.transport.keeps_sequence());
+
+        // With synchronized sampling no sensor takes a reading until every other one is ready to
+        let barrier = self
+            .config
+            .synchronized_sampling
+            .then(|| Arc::new(SamplingBarrier::new(sensor_count, clock.clone())));
+
+        // Starts a sensor's thread, which takes readings from `first_sequence` on until the
+        // simulation stops, the sensor goes offline or it's retired
+        let spawn_sensor = {
+            let config = self.config.clone();
+            let running = self.running.clone();
+            let slots = self.slots.clone();
+            let clock = clock.clone();
+            let schedules = schedules.clone();
+            let liveness = liveness.clone();
+            let dropped_counts = dropped_counts.clone();
+            let rejections = rejections.clone();
+            let priority_sender = priority_sender.clone();
+            let barrier = barrier.clone();
+            let rover = rover.clone();
+
+            move |sensor_id: usize,
+                  mut source: Box<dyn SensorSource>,
+                  mut local_sender: SensorSender,
+                  retiring: Arc<AtomicBool>,
+                  first_sequence: u64| {
+                let local_dropped_counts = dropped_counts.clone();
+                let local_rejections = rejections.clone();
+                let liveness = liveness.clone();
+                let local_priority = priority.clone().map(|x| (x, priority_sender.clone()));
+                let seat = barrier.clone().map(BarrierSeat);
+                let schedule = *schedules.sensor(sensor_id);
+                let mut jitter = SamplingJitter::for_sensor(&config, sensor_id);
+                let clock_offset = skew::clock_offset(&config, sensor_id);
+
+                let local_running = running.clone();
+                let slots = slots.clone();
+                let participant = ClockParticipant::new(clock.clone());
+                let core = config
+                    .affinity
+                    .as_ref()
+                    .map(|x| x.sensor_core(sensor_id, core_count));
+                let span = tracing::info_span!("sensor", rover = rover.as_deref(), sensor_id);
+
+                spawn(move || {
+                    let _span = span.entered();
+                    let clock = &participant.clock;
+                    let mut overslept = Duration::ZERO;
+                    let mut next_sequence = first_sequence;
+                    let mut wake_at = schedule.due(first_sequence);
+
+                    // The seat is given up once the thread ends, after it's no longer sampling but
+                    // before it stops being a participant on the clock
+                    let seat = seat;
+
+                    if let Some(core) = core {
+                        affinity::pin_current_thread(core);
+                    }
+
+                    // A panic (e.g. in the source) ends the sensor's thread unless the watchdog is set
+                    // to restart it, in which case it carries on with the same source and queue
+                    loop {
+                        let sensing = panic::catch_unwind(AssertUnwindSafe(|| {
+                            while local_running.load(Ordering::Relaxed)
+                                && !retiring.load(Ordering::Relaxed)
+                            {
+                                if let Some(seat) = &seat {
+                                    seat.0.wait();
+                                }
+
+                                // A source that has run out of readings is treated like a sensor going
+                                // offline. Invalid readings are skipped straight away
+                                let mut recording =
+                                    match filter.next_reading(source.as_mut(), &local_rejections) {
+                                        Some(recording) => recording,
+                                        None => return,
+                                    };
+
+                                // Readings are timestamped by the clock so they line up with the report
+                                // thread's idea of time, even when it's virtual
+                                recording.timestamp = clock.now();
+                                if high_frequency {
+                                    // A reading taken in a burst to catch up counts as taken when it
+                                    // was due, so none of the ones owed are skipped
+                                    recording.timestamp = recording.timestamp.min(wake_at);
+                                }
+                                liveness.reading(sensor_id, recording.timestamp);
+                                recording.sequence =
+                                    schedule.catch_up(next_sequence, recording.timestamp);
+                                next_sequence = recording.sequence + 1;
+
+                                // The sensor stamps the reading by its own clock, which can be off
+                                recording.timestamp =
+                                    skew::shift(recording.timestamp, clock_offset);
+
+                                // The alert goes first so it's waiting by the time the reading arrives
+                                if let Some((limits, sender)) = &local_priority {
+                                    if let Some(alert) = limits.check(&recording) {
+                                        let _ = sender.try_send(alert);
+                                    }
+                                }
+
+                                tracing::trace!(
+                                    temperature = recording.temperature.fahrenheit(),
+                                    overslept_ms = overslept.as_secs_f64() * 1000.0,
+                                    "reading produced"
+                                );
+
+                                // A full queue means the report thread has fallen behind. The report
+                                // thread dropping its end means it's finished, but not every transport
+                                // notices that, so the running flag is checked while blocked too
+                                let sent = local_sender.send(
+                                    recording,
+                                    queue_overflow,
+                                    || {
+                                        if !local_running.load(Ordering::Relaxed) {
+                                            return false;
+                                        }
+                                        let retry_at = schedule.next_due(clock.now());
+                                        clock
+                                            .sleep(retry_at.saturating_duration_since(clock.now()));
+                                        true
+                                    },
+                                    |dropped| {
+                                        // With a shared channel the dropped reading can be another
+                                        // sensor's
+                                        let counted = dropped.sensor_id.min(sensor_count - 1);
+                                        local_dropped_counts[counted]
+                                            .fetch_add(1, Ordering::Relaxed);
+                                        tracing::debug!(
+                                            dropped_sensor_id = dropped.sensor_id,
+                                            policy = ?queue_overflow,
+                                            "reading dropped"
+                                        );
+                                    },
+                                );
+                                if !sent {
+                                    return;
+                                }
+
+                                // Readings are due a fixed sampling period apart from the start, so a
+                                // sensor that oversleeps (or wakes up late on purpose, with jitter)
+                                // doesn't drift later and later
+                                wake_at = schedule.due(next_sequence) + jitter.delay();
+                                if !(high_frequency && wake_at <= clock.now()) {
+                                    clock.sleep(wake_at.saturating_duration_since(clock.now()));
+                                }
+                                overslept = clock.now().saturating_duration_since(wake_at);
+                            }
+                        }));
+
+                        match sensing {
+                            // A sensor that was told to stop doesn't owe any more readings, unlike
+                            // one that went offline
+                            Ok(()) => {
+                                let retired = retiring.load(Ordering::Relaxed);
+                                if retired || !local_running.load(Ordering::Relaxed) {
+                                    liveness.stopped(sensor_id, next_sequence);
+                                }
+
+                                // The slot's queue is kept for whichever sensor goes in it next
+                                if retired {
+                                    tracing::info!("sensor retired");
+                                    slots.vacate(sensor_id, local_sender);
+                                }
+                                return;
+                            }
+                            Err(payload) => {
+                                liveness.panicked(sensor_id, restart);
+                                if !restart {
+                                    panic::resume_unwind(payload);
+                                }
+
+                                // A source that panics every time shouldn't spin. It tries again when
+                                // the next reading is due, like it would have if it hadn't panicked
+                                let retry_at = schedule.next_due(clock.now());
+                                clock.sleep(retry_at.saturating_duration_since(clock.now()));
+                                wake_at = retry_at;
+                            }
+                        }
+                    }
+                })
+            }
+        };
+
+        // A sensor added while the simulation runs takes its first reading when the next one is
+        // due, from a random source of its own
+        let spawn_sensor = Arc::new(spawn_sensor);
+        let install: Arc<InstallSensor> = {
+            let spawn_sensor = spawn_sensor.clone();
+            let config = self.config.clone();
+            let clock = clock.clone();
+            let schedules = schedules.clone();
+            let liveness = liveness.clone();
+            let barrier = barrier.clone();
+
+            Arc::new(move |sensor_id, sender, retiring| {
+                let now = clock.now();
+                let first_sequence = schedules.sensor(sensor_id).sequence_at(now) + 1;
+                liveness.installed(sensor_id, first_sequence, now);
+                if let Some(barrier) = &barrier {
+                    barrier.join();
+                }
+                tracing::info!(sensor_id, first_sequence, "sensor added");
+
+                let source = Box::new(RandomSource::for_sensor(&config, sensor_id));
+                spawn_sensor(sensor_id, source, sender, retiring, first_sequence)
+            })
+        };
+
+        self.slots.open(sensor_count, install);
+        for (sensor_id, (source, sender)) in
+            sources.into_iter().zip(temperature_senders).enumerate()
+        {
+            let retiring = self.slots.retiring(sensor_id);
+            self.slots
+                .push_handle(spawn_sensor(sensor_id, source, sender, retiring, 0));
+        }
+
+        // Recordings and completed reports go to every sink, each on its own output thread
+        let (output_sender, workers) = Broadcaster::new(std::mem::take(&mut self.sinks));
+        for (sink_index, worker) in workers.into_iter().enumerate() {
+            let output_span =
+                tracing::info_span!("output", rover = self.name.as_deref(), sink_index);
+
+            self.output_handles.push(spawn(move || {
+                let _span = output_span.entered();
+                worker.run(start_time);
+            }));
+        }
+
+        let local_config = self.config.clone();
+        let local_name = self.name.clone();
+        let local_running = self.running.clone();
+        let local_pause = self.pause.clone();
+        let local_slots = self.slots.clone();
+        let local_history = self.history.clone();
+        let local_queue_depth = self.queue_depth.clone();
+        let local_peak_queue_depth = self.peak_queue_depth.clone();
+        let report_requested = self.report_requested.clone();
+        let mut watchdog = self
+            .config
+            .watchdog
+            .enabled
+            .then(|| Watchdog::new(liveness.clone(), &self.config, self.name.clone()));
+        let keeps_sequence = self.config.transport.keeps_sequence();
+
+        // The slots transport timestamps readings itself, so there's never any skew to correct
+        let mut skew_corrector = (self.config.clock_skew.correct && keeps_sequence)
+            .then(|| SkewCorrector::new(schedules.clone(), &self.config));
+
+        // The temperature receiving & report making process is done in a separate thread so the
+        // caller isn't blocked while the simulation runs.
+        let report_span = tracing::info_span!("report_thread", rover = self.name.as_deref());
+        let report_handle = spawn(move || {
+            let _span = report_span.entered();
+            let clock = &report_participant.clock;
+
+            if let Some(affinity) = &local_config.affinity {
+                affinity::pin_current_thread(affinity.report_core);
+            }
+
+            // Reports are built on their own thread from the accumulator that was just swapped
+            // out. `finish` leaves an empty accumulator behind, which is sent back so the two
+            // take turns
+            let (job_sender, job_receiver) = mpsc::channel::<ReportJob>();
+            let (spare_sender, spare_receiver) = mpsc::channel::<ReportAccumulator>();
+            let builder_config = local_config.clone();
+            let builder_name = local_name.clone();
+            let builder_output_sender = output_sender.clone();
+            let builder_history = local_history.clone();
+
+            let builder_span =
+                tracing::info_span!("report_builder", rover = builder_name.as_deref());
+            let builder_handle = spawn(move || {
+                let _span = builder_span.entered();
+
+                for mut job in job_receiver {
+                    let _report_span =
+                        tracing::debug_span!("report", index = job.index, partial = job.partial)
+                            .entered();
+                    tracing::debug!("report started");
+
+                    if let Some(mut report) = job.accumulator.finish(job.index, &builder_config) {
+                        tracing::debug!(
+                            generation_ms = report.generation_seconds * 1000.0,
+                            suspect_sensors = report.suspect_sensors.len(),
+                            "report finished"
+                        );
+
+                        report.partial = job.partial;
+                        report.on_demand = job.on_demand;
+                        report.period_end = timestamp::wall_time(job.period_end);
+                        report.rover = builder_name.clone();
+
+                        // An on-demand report isn't one of the run's reports, so it's left out of
+                        // the history and the trends of the reports after it
+                        if job.on_demand {
+                            let history = lock_history(&builder_history);
+                            report.trend = history.trend(&report);
+                            report.records = history.records(&report);
+                            drop(history);
+                            builder_output_sender
+                                .send(OutputEvent::OnDemandReport(Box::new(report)));
+                            continue;
+                        }
+
+                        let mut history = lock_history(&builder_history);
+                        report.trend = history.trend(&report);
+                        report.records = history.records(&report);
+                        history.push(report.clone());
+                        drop(history);
+
+                        // The output threads only stop once this thread and the report thread
+                        // drop their broadcasters
+                        builder_output_sender.send(OutputEvent::Report(Box::new(report)));
+                    }
+
+                    // The report thread is gone once the last job has been sent. An on-demand job's
+                    // accumulator was a copy, so there's already a spare for it
+                    if !job.on_demand {
+                        let _ = spare_sender.send(job.accumulator);
+                    }
+                }
+            });
+
+            // Periods are a fixed length from the start so reports don't drift later over time
+            let mut period_start = start_time;
+            let mut generate_next_report_at = period_start + scaled_report_interval;
+
+            // Every statistic is updated as recordings arrive so generating a report doesn't
+            // have to process the whole hour at once
+            let mut accumulator = ReportAccumulator::new(&local_config);
+            accumulator.start_period(period_start);
+            let mut alerts = Alerts::new(&local_config);
+            let mut reports_generated = 0;
+            let report_limit = local_config.report_limit();
+            let warm_up_end = start_time + local_config.scaled_warm_up();
+
+            let receive =
+                |recording: Recording, accumulator: &mut ReportAccumulator, alerts: &mut Alerts| {
+                    // How long the reading waited to be received is how far behind this thread is
+                    let latency = clock.now().saturating_duration_since(recording.timestamp);
+                    tracing::trace!(
+                        sensor_id = recording.sensor_id,
+                        lag_ms = latency.as_secs_f64() * 1000.0,
+                        "reading consumed"
+                    );
+                    output_sender.send(OutputEvent::Recording(recording.clone()));
+                    accumulator.push_latency(latency);
+
+                    // The sensors are still settling, so the reading is passed on but doesn't
+                    // count towards anything
+                    if schedules.due(&recording) < warm_up_end {
+                        accumulator.push_warm_up(&recording);
+                        return;
+                    }
+                    accumulator.push_decimated(&recording);
+
+                    // Alerts go out straight away instead of waiting for the report
+                    for alert in alerts.check(&recording) {
+                        accumulator.push_alert(&alert);
+                        output_sender.send(OutputEvent::Alert(alert));
+                    }
+                };
+
+            // Whatever the sensors owed the period and haven't sent by now counts as missed
+            let send_job = |mut job: ReportJob| {
+                count_missed(
+                    &mut job.accumulator,
+                    &schedules,
+                    &liveness,
+                    job.period_start..job.period_end,
+                );
+                job_sender
+                    .send(job)
+                    .map_err(|_| SimulationError::Disconnected("report builder"))
+            };
+
+            let take_discarded = |accumulator: &mut ReportAccumulator| {
+                for (sensor_id, count) in dropped_counts.iter().enumerate() {
+                    accumulator.push_dropped(sensor_id, count.swap(0, Ordering::Relaxed));
+                }
+                for (reason, count) in rejections.take() {
+                    accumulator.push_rejected(reason, count);
+                }
+            };
+
+            // Swaps in the empty accumulator, or a new one if the builder hasn't finished with it
+            let swap = |accumulator: &mut ReportAccumulator| {
+                let spare = spare_receiver
+                    .try_recv()
+                    .unwrap_or_else(|_| ReportAccumulator::new(&local_config));
+                std::mem::replace(accumulator, spare)
+            };
+
+            // A period's report waits `late_grace` after the period ends for readings that were
+            // taken before it ended but are still on their way. Readings are put in a period by
+            // when they were taken, not when they arrive
+            let mut closing: Option<(ReportJob, Instant)> = None;
+            let mut limit_reached = false;
+
+            // Alerts that went ahead of their readings count for the period the reading was due
+            // in, like the reading will
+            let raise_ahead = |accumulator: &mut ReportAccumulator,
+                               closing: &mut Option<(ReportJob, Instant)>,
+                               alerts: &mut Alerts| {
+                while let Ok(alert) = priority_receiver.try_recv() {
+                    tracing::debug!(sensor_id = alert.recording.sensor_id, "extreme reading");
+                    alerts.raise_ahead(&alert);
+
+                    match closing {
+                        Some((job, _)) if schedules.due(&alert.recording) < job.period_end => {
+                            job.accumulator.push_alert(&alert)
+                        }
+                        _ => accumulator.push_alert(&alert),
+                    }
+                    output_sender.send(OutputEvent::Alert(alert));
+                }
+            };
+
+            // Readings wait here until any taken before them have had `reorder_window` to arrive
+            let mut reorder = ReorderBuffer::new(local_config.scaled_reorder_window());
+
+            // Readings that are already queued are taken off in batches, into a buffer that's
+            // only allocated once
+            let ingest_batch = local_config.ingest_batch;
+            let mut batch = Vec::with_capacity(ingest_batch);
+
+            let mut arrive = |mut recording: Recording,
+                              accumulator: &mut ReportAccumulator,
+                              reorder: &mut ReorderBuffer| {
+                // Readings go in the period they were due in, so one that was taken a little late
+                // still counts for its own period
+                if !keeps_sequence {
+                    recording.sequence = schedules
+                        .sensor(recording.sensor_id)
+                        .sequence_at(recording.timestamp);
+                }
+                if let Some(offset) = skew_corrector
+                    .as_mut()
+                    .and_then(|x| x.correct(&mut recording))
+                {
+                    accumulator.push_clock_offset(recording.sensor_id, offset);
+                }
+                reorder.push(recording);
+            };
+
+            let route = |recording: Recording,
+                         accumulator: &mut ReportAccumulator,
+                         closing: &mut Option<(ReportJob, Instant)>,
+                         alerts: &mut Alerts,
+                         period_start: Instant| {
+                // However far behind the queue is, any alert that went ahead is raised first
+                raise_ahead(accumulator, closing, alerts);
+
+                let due = schedules.due(&recording);
+
+                match closing {
+                    Some((job, _)) if due < job.period_end => {
+                        receive(recording, &mut job.accumulator, alerts)
+                    }
+                    _ if due < period_start => {
+                        // Its period's report has already been generated
+                        tracing::debug!(sensor_id = recording.sensor_id, "late reading");
+                        accumulator.push_late(recording.sensor_id, 1);
+                    }
+                    _ => receive(recording, accumulator, alerts),
+                }
+            };
+
+            // The report thread keeps going until every sensor thread has stopped, so readings
+            // sent just before the simulation was stopped still make it into the final report.
+            // Sending only fails once the builder thread has gone away
+            let receive_readings = || -> Result<(), SimulationError> {
+                'receiving: loop {
+                    // Once the simulation's stopped the empty slots' queues are let go, otherwise
+                    // they'd never all close
+                    if !local_running.load(Ordering::Relaxed) {
+                        local_slots.close();
+                    }
+
+                    raise_ahead(&mut accumulator, &mut closing, &mut alerts);
+                    while let Some(recording) = reorder.pop_ready(clock.now()) {
+                        route(
+                            recording,
+                            &mut accumulator,
+                            &mut closing,
+                            &mut alerts,
+                            period_start,
+                        );
+                    }
+
+                    // Stalled sensors are flagged in the report for the period they stalled in,
+                    // and every one after that until they recover
+                    if let Some(watchdog) = &mut watchdog {
+                        for stalled in watchdog.check(clock.now()) {
+                            accumulator.push_stalled(&stalled);
+                        }
+                    }
+
+                    // A report on the period so far is made from a copy, so the period carries on
+                    // as if it had never been asked for. Discarded readings are only counted in
+                    // the period's regular report, and readings that could still be on their way
+                    // aren't counted as missed yet
+                    if report_requested.swap(false, Ordering::Relaxed) && !accumulator.is_empty() {
+                        tracing::debug!(index = reports_generated, "on-demand report requested");
+
+                        let now = clock.now();
+                        let mut snapshot = accumulator.clone();
+                        let owed_until =
+                            now.checked_sub(late_grace).unwrap_or(now).max(period_start);
+                        count_missed(
+                            &mut snapshot,
+                            &schedules,
+                            &liveness,
+                            period_start..owed_until,
+                        );
+
+                        job_sender
+                            .send(ReportJob {
+                                accumulator: snapshot,
+                                index: reports_generated,
+                                period_start,
+                                period_end: now,
+                                partial: false,
+                                on_demand: true,
+                            })
+                            .map_err(|_| SimulationError::Disconnected("report builder"))?;
+                    }
+
+                    if !limit_reached && closing.is_none() && clock.now() >= generate_next_report_at
+                    {
+                        take_discarded(&mut accumulator);
+                        tracing::debug!(index = reports_generated, "period ended");
+
+                        // Readings from the next period go into the other accumulator while this
+                        // one waits for stragglers
+                        let job = ReportJob {
+                            accumulator: swap(&mut accumulator),
+                            index: reports_generated,
+                            period_start,
+                            period_end: generate_next_report_at,
+                            partial: false,
+                            on_demand: false,
+                        };
+                        closing = Some((job, generate_next_report_at + late_grace));
+                        reports_generated += 1;
+
+                        // Once the run is long enough the sensors are told to stop. The receiver is
+                        // dropped when this thread ends, so any sensor that's mid-send stops too
+                        if report_limit == Some(reports_generated) {
+                            local_running.store(false, Ordering::SeqCst);
+                            local_pause.stop();
+                            limit_reached = true;
+                        }
+
+                        period_start = generate_next_report_at;
+                        accumulator.start_period(period_start);
+                        generate_next_report_at += scaled_report_interval;
+                    }
+
+                    let now = clock.now();
+                    if closing
+                        .as_ref()
+                        .is_some_and(|(_, close_at)| now >= *close_at)
+                    {
+                        // Whatever's already queued was sent before the grace period ran out.
+                        // The report can't wait for anything else to arrive, so nothing's held
+                        // back from it
+                        while temperature_receiver.drain_into(&mut batch, ingest_batch) > 0 {
+                            for recording in batch.drain(..) {
+                                arrive(recording, &mut accumulator, &mut reorder);
+                            }
+                        }
+                        while let Some(recording) = reorder.pop() {
+                            route(
+                                recording,
+                                &mut accumulator,
+                                &mut closing,
+                                &mut alerts,
+                                period_start,
+                            );
+                        }
+
+                        if let Some((job, _)) = closing.take() {
+                            // Everything recorded during the period goes to the builder thread
+                            send_job(job)?;
+                            if limit_reached {
+                                break 'receiving;
+                            }
+                        }
+                    }
+
+                    // This reporting thread shouldn't wait forever for a new recording.
+                    // If there's no new recording it checks again halfway through the next minute
+                    // (or when the period or its grace period ends) and sees if a report should be
+                    // generated
+                    let queued = temperature_receiver.queued();
+                    local_queue_depth.store(queued.unwrap_or(usize::MAX), Ordering::Relaxed);
+                    if let Some(queued) = queued {
+                        local_peak_queue_depth.fetch_max(queued, Ordering::Relaxed);
+                    }
+
+                    let now = clock.now();
+                    let mut wake_at =
+                        next_check_at(start_time, now, scaled_minute).min(generate_next_report_at);
+                    if let Some((_, close_at)) = &closing {
+                        wake_at = wake_at.min(*close_at);
+                    }
+                    if let Some(release_at) = reorder.next_release() {
+                        wake_at = wake_at.min(release_at);
+                    }
+                    let timeout = wake_at.saturating_duration_since(now);
+                    match receive_timeout(clock.as_ref(), &mut temperature_receiver, timeout) {
+                        Ok(recording) => {
+                            // Anything else that's already queued may have been taken before it,
+                            // so it all goes in before any are used
+                            arrive(recording, &mut accumulator, &mut reorder);
+                            while temperature_receiver.drain_into(&mut batch, ingest_batch) > 0 {
+                                for recording in batch.drain(..) {
+                                    arrive(recording, &mut accumulator, &mut reorder);
+                                }
+                            }
+                        }
+                        Err(RecvTimeoutError::Timeout) => {}
+                        Err(RecvTimeoutError::Disconnected) => {
+                            while let Some(recording) = reorder.pop() {
+                                route(
+                                    recording,
+                                    &mut accumulator,
+                                    &mut closing,
+                                    &mut alerts,
+                                    period_start,
+                                );
+                            }
+
+                            // Nothing else can arrive for the period that's waiting on stragglers
+                            if let Some((job, _)) = closing.take() {
+                                send_job(job)?;
+                            }
+
+                            // Every sensor has stopped, either because the simulation was stopped
+                            // or because they went offline (e.g. a file source ran out of
+                            // readings), so report on whatever was recorded during the unfinished
+                            // period
+                            if !limit_reached && !accumulator.is_empty() {
+                                take_discarded(&mut accumulator);
+                                send_job(ReportJob {
+                                    accumulator,
+                                    index: reports_generated,
+                                    period_start,
+                                    period_end: clock.now(),
+                                    partial: true,
+                                    on_demand: false,
+                                })?;
+                            }
+
+                            break 'receiving;
+                        }
+                    }
+                }
+
+                Ok(())
+            };
+            let received = receive_readings();
+
+            // Every report has to be published before the simulation counts as finished. If
+            // sending failed the builder's own error explains why
+            drop(job_sender);
+            let built = builder_handle
+                .join()
+                .map_err(|x| SimulationError::panicked("report builder", x));
+
+            // Every report is in the history by now, so the summary goes out after the last one
+            let peak_queue_depth = match local_queue_depth.load(Ordering::Relaxed) {
+                usize::MAX => None,
+                _ => Some(local_peak_queue_depth.load(Ordering::Relaxed)),
+            };
+            let summary = RunSummary::from_reports(
+                lock_history(&local_history).all_reports(),
+                peak_queue_depth,
+            );
+            output_sender.send(OutputEvent::Summary(Box::new(summary)));
+
+            error::first_cause([built, received])
+        });
+
+        self.report_handle = Some(report_handle);
+    }
+
+    /// Returns a handle that can stop the simulation from another thread, e.g. a Ctrl-C handler.
+    pub fn stop_handle(&self) -> StopHandle {
+        StopHandle::new(self.running.clone(), Some(self.pause.clone()))
+    }
+
+    /// Returns a handle that can pause and resume the simulation from another thread. Paused
+    /// time doesn't count towards any period, so reports come out as if there'd been no pause.
+    pub fn pause_handle(&self) -> PauseHandle {
+        PauseHandle::new(self.pause.clone())
+    }
+
+    /// Returns a handle that changes how fast the simulation runs from another thread. Each
+    /// report still covers the whole simulated period, it just takes more or less real time.
+    pub fn speed_handle(&self) -> SpeedHandle {
+        SpeedHandle::new(self.speed.clone(), self.config.speedup)
+    }
+
+    /// Returns a handle that adds and retires sensors from another thread, like swapping a
+    /// rover's sensor modules while it's running.
+    pub fn hot_swap_handle(&self) -> HotSwapHandle {
+        HotSwapHandle::new(self.slots.clone())
+    }
+
+    /// Returns a handle that asks for a report on the current period so far from another thread,
+    /// e.g. when a signal arrives.
+    pub fn report_trigger(&self) -> ReportTrigger {
+        ReportTrigger {
+            requested: self.report_requested.clone(),
+        }
+    }
+
+    /// Signals every thread to stop and waits for them to finish. The report thread publishes a
+    /// partial report for the unfinished period before it exits. Fails if a thread panicked or
+    /// stopped early, in which case some reports may be missing.
+    pub fn stop(&mut self) -> Result<(), SimulationError> {
+        self.running.store(false, Ordering::SeqCst);
+        self.pause.stop();
+        self.join()
+    }
+
+    /// Blocks until the report thread finishes, which only happens once the simulation is
+    /// stopped (e.g. through a `StopHandle`), every sensor goes offline, or the report thread runs
+    /// out of recordings to compare. Fails the same way as `stop`.
+    pub fn wait(&mut self) -> Result<(), SimulationError> {
+        let reported = match self.report_handle.take() {
+            Some(report_handle) => join_report_thread(report_handle),
+            None => Ok(()),
+        };
+
+        error::first_cause([reported, self.stop()])
+    }
+
+    /// Returns a copy of every report generated so far, oldest first.
+    pub fn all_reports(&self) -> Vec<Report> {
+        lock_history(&self.history).all_reports().to_vec()
+    }
+
+    /// Returns a copy of the reports for simulated hour `hour` (counting from 0).
+    pub fn reports_for_hour(&self, hour: u64) -> Vec<Report> {
+        let history = lock_history(&self.history);
+        history
+            .reports_for_hour(hour)
+            .into_iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Returns a copy of the whole report history.
+    pub fn history(&self) -> ReportHistory {
+        lock_history(&self.history).clone()
+    }
+
+    pub fn config(&self) -> &SimulationConfig {
+        &self.config
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    /// Whether the report thread has finished on its own (see `wait`) and published its last
+    /// report. Always false before the simulation is started and after it's been waited on.
+    pub fn is_finished(&self) -> bool {
+        self.report_handle
+            .as_ref()
+            .is_some_and(|report_handle| report_handle.is_finished())
+    }
+
+    /// Roughly how many readings were waiting for the report thread the last time it checked,
+    /// or `None` if the transport can't tell (`transport = "mpsc"`) or it hasn't checked yet.
+    pub fn queue_depth(&self) -> Option<usize> {
+        match self.queue_depth.load(Ordering::Relaxed) {
+            usize::MAX => None,
+            depth => Some(depth),
+        }
+    }
+
+    /// Summarises every report generated so far, along with the deepest the queue got. Once the
+    /// simulation has finished this is the summary the sinks were given.
+    pub fn summary(&self) -> RunSummary {
+        let peak_queue_depth = self
+            .queue_depth()
+            .map(|_| self.peak_queue_depth.load(Ordering::Relaxed));
+
+        RunSummary::from_reports(lock_history(&self.history).all_reports(), peak_queue_depth)
+    }
+
+    /// Waits for every thread, even after one of them has failed, so none are left running.
+    fn join(&mut self) -> Result<(), SimulationError> {
+        let mut results = Vec::new();
+
+        let join_sensors = |results: &mut Vec<_>| {
+            while let Some(sensor_handle) = self.slots.next_handle() {
+                results.push(
+                    sensor_handle
+                        .join()
+                        .map_err(|x| SimulationError::panicked("sensor", x)),
+                );
+            }
+        };
+
+        join_sensors(&mut results);
+
+        if let Some(report_handle) = self.report_handle.take() {
+            results.push(join_report_thread(report_handle));
+        }
+
+        // Any sensor that was added after the rest had finished, e.g. after every sensor had been
+        // retired, has been told to stop by now
+        self.slots.close();
+        join_sensors(&mut results);
+
+        for output_handle in self.output_handles.drain(..) {
+            results.push(
+                output_handle
+                    .join()
+                    .map_err(|x| SimulationError::panicked("output", x)),
+            );
+        }
+
+        error::first_cause(results)
+    }
+}
+
+fn join_report_thread(
+    report_handle: JoinHandle<Result<(), SimulationError>>,
+) -> Result<(), SimulationError> {
+    report_handle
+        .join()
+        .unwrap_or_else(|x| Err(SimulationError::panicked("report", x)))
+}
+
+/// Only a panicking report builder can poison the history, and every report in it is still
+/// complete since reports are pushed whole.
+pub(crate) fn lock_history(history: &Mutex<ReportHistory>) -> MutexGuard<'_, ReportHistory> {
+    history.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// The source each sensor thread reads from: the ones passed in, or random ones described by the
+/// config. Sensors with a fault configured get their source wrapped so the fault kicks in later.
+pub(crate) fn sensor_sources(
+    config: &SimulationConfig,
+    sources: Option<Vec<Box<dyn SensorSource>>>,
+) -> Vec<Box<dyn SensorSource>> {
+    let sources = sources.unwrap_or_else(|| {
+        (0..config.sensor_count)
+            .map(|sensor_id| {
+                Box::new(RandomSource::for_sensor(config, sensor_id)) as Box<dyn SensorSource>
+            })
+            .collect()
+    });
+
+    sources
+        .into_iter()
+        .enumerate()
+        .map(|(sensor_id, source)| match config.fault_for(sensor_id) {
+            Some(fault) => Box::new(FaultySource::new(
+                source,
+                fault,
+                config.fault_rng(sensor_id),
+                config,
+            )) as Box<dyn SensorSource>,
+            None => source,
+        })
+        .collect()
+}
+
+/// Counts the readings each sensor was due to take during `period` that never arrived. A sensor
+/// that was told to stop partway through only owed the readings due before then.
+pub(crate) fn count_missed(
+    accumulator: &mut ReportAccumulator,
+    schedules: &SensorSchedules,
+    liveness: &SensorLiveness,
+    period: Range<Instant>,
+) {
+    for sensor_id in 0..liveness.sensor_count() {
+        let sequences = schedules
+            .sensor(sensor_id)
+            .between(period.start, period.end);
+        accumulator.count_missed(sensor_id, liveness.owed(sensor_id, sequences));
+    }
+}
+
+/// Waits up to `timeout` for a recording. The rings can't be blocked on, so whatever the
+/// transport, when there's nothing to receive the report thread sleeps on the clock (which also
+/// lets a virtual clock move forward).
+fn receive_timeout(
+    clock: &dyn Clock,
+    receiver: &mut SensorReceiver,
+    timeout: Duration,
+) -> Result<Recording, RecvTimeoutError> {
+    match receiver.try_recv() {
+        Ok(recording) => Ok(recording),
+        Err(TryRecvError::Empty) => {
+            clock.sleep(timeout);
+            Err(RecvTimeoutError::Timeout)
+        }
+        Err(TryRecvError::Disconnected) => Err(RecvTimeoutError::Disconnected),
+    }
+}
+
+/// The next half-minute mark after `now`. Sensors read on the minute, so checking for readings
+/// halfway between means a sensor's slot (with `transport = "slots"`) is never sampled at the
+/// same moment it's written.
+fn next_check_at(start: Instant, now: Instant, minute: Duration) -> Instant {
+    let minutes = now.saturating_duration_since(start).as_secs_f64() / minute.as_secs_f64();
+
+    start + minute.mul_f64((minutes - 0.5).floor() + 1.5)
+}
+
+/// Registers a thread with the clock for as long as it runs.
+struct ClockParticipant {
+    clock: Arc<dyn Clock>,
+}
+
+impl ClockParticipant {
+    fn new(clock: Arc<dyn Clock>) -> ClockParticipant {
+        clock.add_participant();
+        ClockParticipant { clock }
+    }
+}
+
+impl Drop for ClockParticipant {
+    fn drop(&mut self) {
+        self.clock.remove_participant();
+    }
+}
+
+/// Stops a `TemperatureSimulation` without needing access to it. Stopping only signals the
+/// threads; whoever owns the simulation still has to `wait()` for them to finish. A paused
+/// simulation is resumed so its threads can finish.
+#[derive(Clone)]
+pub struct StopHandle {
+    running: Arc<AtomicBool>,
+    pause: Option<Arc<Pause>>,
+}
+
+impl StopHandle {
+    pub(crate) fn new(running: Arc<AtomicBool>, pause: Option<Arc<Pause>>) -> StopHandle {
+        StopHandle { running, pause }
+    }
+
+    pub fn stop(&self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(pause) = &self.pause {
+            pause.stop();
+        }
+    }
+
+    /// Whether `stop` has been called on this handle or the simulation.
+    pub fn is_stopped(&self) -> bool {
+        !self.running.load(Ordering::SeqCst)
+    }
+}
+
+/// Asks a `TemperatureSimulation` for a report on its current period so far, without needing
+/// access to it. The report is handed to every sink's `handle_on_demand_report` as soon as the
+/// report thread next wakes up, and the period carries on as normal, still getting its regular
+/// report when it ends. Nothing is reported if the period has no readings yet.
+#[derive(Clone)]
+pub struct ReportTrigger {
+    requested: Arc<AtomicBool>,
+}
+
+impl ReportTrigger {
+    pub fn trigger(&self) {
+        self.requested.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Default for TemperatureSimulation {
+    fn default() -> Self {
+        TemperatureSimulation::new()
+    }
+}
+
+impl Drop for TemperatureSimulation {
+    /// Stops the simulation if it's still running. Errors can't be returned from here, call
+    /// `stop` or `wait` first to see them.
+    fn drop(&mut self) {
+        let _ = self.stop();
+    }
+}
@@ -0,0 +1,98 @@
+//! Real sensors each keep their own time, and no two clocks agree exactly. With clock skew every
+//! sensor's clock is off from the rover's by a fixed amount, which moves the timestamps on its
+//! readings but not when they're taken. The report thread can work each sensor's offset out again
+//! since it knows when every reading was due: a reading is never taken before it's due, so the
+//! earliest any of a sensor's readings seems to be is its clock's offset.
+
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+use serde::Deserialize;
+
+use super::schedule::SensorSchedules;
+use super::{Recording, SimulationConfig};
+
+/// Settings for skewing the sensors' clocks, written as a `[clock_skew]` table in the config file.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClockSkewConfig {
+    /// Each sensor's clock is off by a random amount up to this many simulated seconds either way.
+    /// 0 leaves every clock right
+    pub max_offset: f64,
+
+    /// Whether the report thread works out each sensor's offset and takes it back out of the
+    /// sensor's timestamps before they're used
+    pub correct: bool,
+}
+
+/// How far a sensor's clock is ahead of the rover's (behind if negative), in real nanoseconds.
+pub(crate) fn clock_offset(config: &SimulationConfig, sensor_id: usize) -> i128 {
+    let max_offset = config.clock_skew.max_offset;
+    if max_offset == 0.0 {
+        return 0;
+    }
+
+    let offset = config
+        .skew_rng(sensor_id)
+        .gen_range(-max_offset..=max_offset);
+    let nanos = config
+        .scaled(Duration::from_secs_f64(offset.abs()))
+        .as_nanos() as i128;
+    if offset < 0.0 {
+        -nanos
+    } else {
+        nanos
+    }
+}
+
+/// `instant` moved `nanos` later, or earlier if negative.
+pub(crate) fn shift(instant: Instant, nanos: i128) -> Instant {
+    let by = Duration::from_nanos(nanos.unsigned_abs() as u64);
+    if nanos < 0 {
+        instant.checked_sub(by).unwrap_or(instant)
+    } else {
+        instant + by
+    }
+}
+
+// How much later `later` is than `earlier`, negative if it's earlier
+fn nanos_between(earlier: Instant, later: Instant) -> i128 {
+    match later.checked_duration_since(earlier) {
+        Some(duration) => duration.as_nanos() as i128,
+        None => -(earlier.duration_since(later).as_nanos() as i128),
+    }
+}
+
+/// Works out how far each sensor's clock is off from its readings and takes that back out of
+/// their timestamps. An estimate only ever gets closer to the true offset, from the reading that
+/// was taken the soonest after it was due.
+pub(crate) struct SkewCorrector {
+    schedules: SensorSchedules,
+    speedup: u64,
+
+    // Real nanoseconds each sensor's readings seem to be taken after they're due, at the least
+    estimates: Vec<Option<i128>>,
+}
+
+impl SkewCorrector {
+    pub(crate) fn new(schedules: SensorSchedules, config: &SimulationConfig) -> SkewCorrector {
+        SkewCorrector {
+            schedules,
+            speedup: config.speedup,
+            estimates: vec![None; config.sensor_count],
+        }
+    }
+
+    /// Takes the sensor's offset out of the reading's timestamp, and returns the offset in
+    /// simulated seconds. Readings from a sensor the config doesn't have are left alone.
+    pub(crate) fn correct(&mut self, recording: &mut Recording) -> Option<f64> {
+        let estimate = self.estimates.get_mut(recording.sensor_id)?;
+
+        let seems_late = nanos_between(self.schedules.due(recording), recording.timestamp);
+        let estimate = estimate.get_or_insert(seems_late);
+        *estimate = (*estimate).min(seems_late);
+
+        recording.timestamp = shift(recording.timestamp, -*estimate);
+        Some(*estimate as f64 / 1e9 * self.speedup as f64)
+    }
+}
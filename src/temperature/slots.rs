@@ -0,0 +1,117 @@
+//! The queue-free design: every sensor has one `AtomicI64` slot in a shared array holding the
+//! latest temperature it read, and the report thread samples the whole array. A slot only holds
+//! one reading, so a sensor that writes again before its slot was sampled either waits or
+//! overwrites it, depending on the overflow policy.
+
+use std::sync::atomic::{AtomicI64, AtomicUsize, Ordering};
+use std::sync::mpsc::{TryRecvError, TrySendError};
+use std::sync::Arc;
+
+use super::Temperature;
+
+// A NaN no sensor will ever read, marking a slot that has nothing in it. Temperatures are stored
+// as the bits of their `f64` value in degrees Fahrenheit
+const EMPTY: i64 = 0x7FF8_DEAD_0000_0001;
+
+struct SlotArray {
+    slots: Box<[AtomicI64]>,
+    senders_alive: AtomicUsize,
+}
+
+/// Creates a slot for each of `sensors` sensors. The senders are in sensor order.
+pub fn slot_channel(sensors: usize) -> (Vec<SlotSender>, SlotReceiver) {
+    let array = Arc::new(SlotArray {
+        slots: (0..sensors).map(|_| AtomicI64::new(EMPTY)).collect(),
+        senders_alive: AtomicUsize::new(sensors),
+    });
+
+    let senders = (0..sensors)
+        .map(|index| SlotSender {
+            array: array.clone(),
+            index,
+        })
+        .collect();
+
+    (senders, SlotReceiver { array, next: 0 })
+}
+
+fn decode(bits: i64) -> Temperature {
+    Temperature::from_fahrenheit(f64::from_bits(bits as u64))
+}
+
+pub struct SlotSender {
+    array: Arc<SlotArray>,
+    index: usize,
+}
+
+impl SlotSender {
+    /// Which slot (and so which sensor) the sender writes to.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Writes a temperature into the sensor's slot, unless the last one hasn't been sampled yet.
+    pub fn try_write(&mut self, temperature: Temperature) -> Result<(), TrySendError<Temperature>> {
+        let bits = temperature.fahrenheit().to_bits() as i64;
+
+        self.array.slots[self.index]
+            .compare_exchange(EMPTY, bits, Ordering::Release, Ordering::Relaxed)
+            .map(|_| ())
+            .map_err(|_| TrySendError::Full(temperature))
+    }
+
+    /// Empties the sensor's slot, returning the temperature that was never sampled.
+    pub fn take(&mut self) -> Option<Temperature> {
+        match self.array.slots[self.index].swap(EMPTY, Ordering::Acquire) {
+            EMPTY => None,
+            bits => Some(decode(bits)),
+        }
+    }
+}
+
+impl Drop for SlotSender {
+    fn drop(&mut self) {
+        self.array.senders_alive.fetch_sub(1, Ordering::Release);
+    }
+}
+
+pub struct SlotReceiver {
+    array: Arc<SlotArray>,
+    next: usize,
+}
+
+impl SlotReceiver {
+    /// How many slots have a temperature waiting to be sampled.
+    pub fn len(&self) -> usize {
+        self.array
+            .slots
+            .iter()
+            .filter(|x| x.load(Ordering::Relaxed) != EMPTY)
+            .count()
+    }
+
+    /// Samples the slots in turn, returning the next sensor that has a temperature waiting
+    /// along with its index. Only reports `Disconnected` once every sender is gone and every slot
+    /// is empty.
+    pub fn try_read(&mut self) -> Result<(usize, Temperature), TryRecvError> {
+        // Checked before the slots are, so if the senders are gone everything they wrote is seen
+        let disconnected = self.array.senders_alive.load(Ordering::Acquire) == 0;
+        let slots = &self.array.slots;
+
+        for offset in 0..slots.len() {
+            let index = (self.next + offset) % slots.len();
+
+            let bits = slots[index].swap(EMPTY, Ordering::Acquire);
+            if bits != EMPTY {
+                self.next = index + 1;
+                return Ok((index, decode(bits)));
+            }
+        }
+
+        if disconnected {
+            Err(TryRecvError::Disconnected)
+        } else {
+            Err(TryRecvError::Empty)
+        }
+    }
+}
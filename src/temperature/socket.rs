@@ -0,0 +1,102 @@
+use std::fs;
+use std::io::{self, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::spawn;
+use std::time::Duration;
+
+use super::{Recording, Report, ReportSink};
+
+/// How long a write to one client can take before the client is dropped, so a reader that's
+/// stopped reading can't hold up the sink.
+const WRITE_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Streams every reading as a line of JSON to every client connected to a Unix domain socket,
+/// so tools on the same machine (e.g. a separate visualizer) can tap the live feed without going
+/// through HTTP. Each line is `{"sensor_id", "temperature", "sequence", "time"}` with the
+/// temperature in Fahrenheit.
+///
+/// Clients can connect and disconnect at any time and get the readings from when they connect.
+/// One that doesn't keep up is disconnected rather than holding up the sink. The socket file is
+/// removed when the sink is dropped.
+pub struct SocketSink {
+    path: PathBuf,
+    clients: Arc<Mutex<Vec<UnixStream>>>,
+}
+
+impl SocketSink {
+    /// Starts listening on a socket at `path`. A socket file left behind by an earlier run is
+    /// replaced, any other file there is an error.
+    pub fn bind(path: impl Into<PathBuf>) -> io::Result<SocketSink> {
+        let path = path.into();
+        remove_stale_socket(&path)?;
+
+        let listener = UnixListener::bind(&path)?;
+        let clients = Arc::new(Mutex::new(Vec::new()));
+
+        // The thread is left blocked in `accept` when the sink is dropped, it goes away with the
+        // process
+        let local_clients = clients.clone();
+        spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else {
+                    continue;
+                };
+                if stream.set_write_timeout(Some(WRITE_TIMEOUT)).is_ok() {
+                    lock_clients(&local_clients).push(stream);
+                }
+            }
+        });
+
+        Ok(SocketSink { path, clients })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+fn remove_stale_socket(path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => fs::remove_file(path),
+        Ok(_) => Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            "a file that isn't a socket is already there",
+        )),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(err),
+    }
+}
+
+fn lock_clients(clients: &Mutex<Vec<UnixStream>>) -> std::sync::MutexGuard<'_, Vec<UnixStream>> {
+    clients.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl ReportSink for SocketSink {
+    fn handle_report(&mut self, _report: &Report) {}
+
+    fn handle_recording(&mut self, recording: &Recording) {
+        let mut line = serde_json::json!({
+            "sensor_id": recording.sensor_id,
+            "temperature": recording.temperature.fahrenheit(),
+            "sequence": recording.sequence,
+            "time": recording.wall_time(),
+        })
+        .to_string();
+        line.push('\n');
+
+        // A client that's gone away or fallen behind has had part of a line at most, so it's
+        // dropped rather than sent the rest of the stream out of step
+        lock_clients(&self.clients).retain_mut(|client| client.write_all(line.as_bytes()).is_ok());
+    }
+}
+
+impl Drop for SocketSink {
+    fn drop(&mut self) {
+        lock_clients(&self.clients).clear();
+        let _ = fs::remove_file(&self.path);
+    }
+}
@@ -0,0 +1,264 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Stdin};
+use std::path::Path;
+
+use rand::rngs::StdRng;
+use serde::Deserialize;
+
+use super::{Recording, SimulationConfig, Temperature, TemperatureModel};
+
+/// Where a sensor thread gets its readings from. Each sensor thread owns one source and calls
+/// `next_reading` once per sampling period.
+pub trait SensorSource: Send {
+    /// Returns the next reading, or `None` once the source has run out of readings.
+    fn next_reading(&mut self) -> Option<Recording>;
+
+    /// Returns how much input the source has skipped because it couldn't be parsed as a reading
+    /// since it was last asked. Sources that come up with their own readings never skip any.
+    fn take_malformed(&mut self) -> usize {
+        0
+    }
+}
+
+/// Generates temperatures from a `TemperatureModel`. Simulated time is tracked by counting
+/// readings, so a seeded source always produces the same sequence.
+pub struct RandomSource {
+    sensor_id: usize,
+    rng: StdRng,
+    model: Box<dyn TemperatureModel>,
+    min_temperature: f64,
+    max_temperature: f64,
+    sampling_period_minutes: f64,
+    readings_taken: u64,
+}
+
+impl RandomSource {
+    pub fn new(
+        sensor_id: usize,
+        rng: StdRng,
+        model: Box<dyn TemperatureModel>,
+        config: &SimulationConfig,
+    ) -> RandomSource {
+        RandomSource {
+            sensor_id,
+            rng,
+            model,
+            min_temperature: config.min_temperature,
+            max_temperature: config.max_temperature,
+            sampling_period_minutes: config.sampling_period_for(sensor_id) / 60.0,
+            readings_taken: 0,
+        }
+    }
+
+    /// Creates the source for a sensor using the RNG and model the config assigns to it.
+    pub fn for_sensor(config: &SimulationConfig, sensor_id: usize) -> RandomSource {
+        let model = config.model_for(sensor_id).build(config, sensor_id);
+
+        RandomSource::new(sensor_id, config.sensor_rng(sensor_id), model, config)
+    }
+}
+
+impl SensorSource for RandomSource {
+    fn next_reading(&mut self) -> Option<Recording> {
+        let elapsed_minutes = self.readings_taken as f64 * self.sampling_period_minutes;
+        self.readings_taken += 1;
+
+        // Models like the Gaussian one can wander outside of what the sensors are able to read
+        let temperature = self
+            .model
+            .sample(&mut self.rng, elapsed_minutes)
+            .clamp(self.min_temperature, self.max_temperature);
+
+        Some(Recording::new(
+            self.sensor_id,
+            Temperature::from_fahrenheit(temperature),
+        ))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineFormat {
+    /// Comma separated values. With a header the `temperature` and (optional) `sensor_id`
+    /// columns are used, otherwise the last column is the temperature.
+    Csv,
+
+    /// One JSON object per line with a `temperature` key and an optional `sensor_id` key
+    JsonLines,
+}
+
+impl LineFormat {
+    /// Picks the format from a file extension, defaulting to CSV.
+    pub fn from_path(path: &Path) -> LineFormat {
+        match path.extension().and_then(|x| x.to_str()) {
+            Some("jsonl") | Some("json") | Some("ndjson") => LineFormat::JsonLines,
+            _ => LineFormat::Csv,
+        }
+    }
+}
+
+/// Reads pre-recorded temperatures (in degrees Fahrenheit) one line at a time. Blank lines are
+/// skipped, and so are lines that can't be parsed (with a warning on stderr), which are counted as
+/// malformed. Lines without a sensor ID are attributed to `sensor_id`.
+pub struct LineSource<R> {
+    reader: R,
+    format: LineFormat,
+    sensor_id: usize,
+    csv_header: Option<CsvHeader>,
+    line_number: usize,
+    malformed: usize,
+}
+
+/// Column positions taken from a CSV header line
+struct CsvHeader {
+    temperature: usize,
+    sensor_id: Option<usize>,
+}
+
+pub type FileSource = LineSource<BufReader<File>>;
+pub type StdinSource = LineSource<BufReader<Stdin>>;
+
+#[derive(Deserialize)]
+struct JsonReading {
+    sensor_id: Option<usize>,
+    temperature: f64,
+}
+
+impl<R: BufRead + Send> LineSource<R> {
+    pub fn new(reader: R, format: LineFormat) -> LineSource<R> {
+        LineSource {
+            reader,
+            format,
+            sensor_id: 0,
+            csv_header: None,
+            line_number: 0,
+            malformed: 0,
+        }
+    }
+
+    /// Sets the sensor ID used for lines that don't specify one.
+    pub fn with_sensor_id(mut self, sensor_id: usize) -> LineSource<R> {
+        self.sensor_id = sensor_id;
+        self
+    }
+
+    pub fn into_reader(self) -> R {
+        self.reader
+    }
+
+    /// Returns the line's sensor ID (if it has one) and temperature.
+    fn parse_line(&mut self, line: &str) -> Option<(Option<usize>, f64)> {
+        let (sensor_id, temperature) = match self.format {
+            LineFormat::JsonLines => {
+                let reading = serde_json::from_str::<JsonReading>(line).ok()?;
+                (reading.sensor_id, reading.temperature)
+            }
+            LineFormat::Csv => {
+                let fields: Vec<&str> = line.split(',').map(|x| x.trim()).collect();
+
+                match &self.csv_header {
+                    Some(header) => {
+                        let sensor_id = match header.sensor_id {
+                            Some(column) => Some(fields.get(column)?.parse().ok()?),
+                            None => None,
+                        };
+
+                        (sensor_id, fields.get(header.temperature)?.parse().ok()?)
+                    }
+                    None => (None, fields.last()?.parse().ok()?),
+                }
+            }
+        };
+
+        Some((sensor_id, temperature))
+    }
+
+    /// Checks if a line is a CSV header and remembers which columns to read if it is.
+    fn parse_header(&mut self, line: &str) -> bool {
+        let fields: Vec<&str> = line.split(',').map(|x| x.trim()).collect();
+
+        let temperature = match fields.iter().position(|x| *x == "temperature") {
+            Some(index) => index,
+            None => return false,
+        };
+
+        let sensor_id = fields
+            .iter()
+            .position(|x| *x == "sensor_id" || *x == "sensor");
+
+        self.csv_header = Some(CsvHeader {
+            temperature,
+            sensor_id,
+        });
+
+        true
+    }
+}
+
+impl FileSource {
+    /// Opens a CSV or JSON lines file, picking the format from the extension.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<FileSource> {
+        let path = path.as_ref();
+        let file = File::open(path)?;
+
+        Ok(LineSource::new(
+            BufReader::new(file),
+            LineFormat::from_path(path),
+        ))
+    }
+}
+
+impl StdinSource {
+    pub fn stdin(format: LineFormat) -> StdinSource {
+        LineSource::new(BufReader::new(io::stdin()), format)
+    }
+}
+
+impl<R: BufRead + Send> SensorSource for LineSource<R> {
+    fn next_reading(&mut self) -> Option<Recording> {
+        let mut line = String::new();
+
+        loop {
+            line.clear();
+
+            // Treat read errors the same as the end of the input
+            if self.reader.read_line(&mut line).unwrap_or(0) == 0 {
+                return None;
+            }
+
+            self.line_number += 1;
+
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+
+            // The first line of a CSV might be a header
+            if self.format == LineFormat::Csv && self.line_number == 1 && self.parse_header(trimmed)
+            {
+                continue;
+            }
+
+            // Temperatures the sensors couldn't have read are left to the simulation to reject,
+            // so they're counted along with every other source's
+            match self.parse_line(trimmed) {
+                Some((sensor_id, temperature)) => {
+                    return Some(Recording::new(
+                        sensor_id.unwrap_or(self.sensor_id),
+                        Temperature::from_fahrenheit(temperature),
+                    ))
+                }
+                None => {
+                    eprintln!(
+                        "Skipping line {} that isn't a valid reading: {}",
+                        self.line_number, trimmed
+                    );
+                    self.malformed += 1;
+                }
+            }
+        }
+    }
+
+    fn take_malformed(&mut self) -> usize {
+        std::mem::take(&mut self.malformed)
+    }
+}
@@ -0,0 +1,115 @@
+//! Sensors on a real rover read the same air, so ones that are close together read similar
+//! temperatures. The spatial model gives every sensor a spot on the rover and reads a temperature
+//! field that's shared by every sensor, plus a little noise of the sensor's own.
+
+use std::f64::consts::TAU;
+
+use rand::rngs::StdRng;
+use rand::Rng;
+use rand_distr::{Distribution, Normal, NormalError};
+use serde::Deserialize;
+
+use super::model::normal;
+use super::TemperatureModel;
+
+// How many waves make up the field. More gives a smoother field that's closer to a Gaussian one
+const FIELD_COMPONENTS: usize = 64;
+
+/// Places one sensor on the rover, e.g. `[[sensor_position]]` with `sensor = 3`, `x = 0.5` and
+/// `y = -1.2`. Positions are in meters.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SensorPositionConfig {
+    pub sensor: usize,
+    pub x: f64,
+    pub y: f64,
+}
+
+// One cosine wave moving across the rover
+struct Wave {
+    x: f64,
+    y: f64,
+    minutes: f64,
+    phase: f64,
+}
+
+/// A temperature field that changes smoothly across the rover and over time. It's a sum of
+/// random waves, which makes readings `distance` meters apart correlate by about
+/// `exp(-distance² / (2 length_scale²))`. The same RNG always draws the same field.
+pub struct SpatialField {
+    mean: f64,
+    amplitude: f64,
+    waves: Vec<Wave>,
+}
+
+impl SpatialField {
+    pub fn new(
+        rng: &mut StdRng,
+        mean: f64,
+        amplitude: f64,
+        length_scale: f64,
+        time_scale_minutes: f64,
+    ) -> SpatialField {
+        let across = Normal::new(0.0, 1.0 / length_scale).unwrap();
+        let over_time = Normal::new(0.0, 1.0 / time_scale_minutes).unwrap();
+
+        let waves = (0..FIELD_COMPONENTS)
+            .map(|_| Wave {
+                x: across.sample(rng),
+                y: across.sample(rng),
+                minutes: over_time.sample(rng),
+                phase: rng.gen_range(0.0..TAU),
+            })
+            .collect();
+
+        SpatialField {
+            mean,
+            amplitude,
+            waves,
+        }
+    }
+
+    /// The temperature at `(x, y)` on the rover, `elapsed_minutes` simulated minutes after the
+    /// start.
+    pub fn at(&self, x: f64, y: f64, elapsed_minutes: f64) -> f64 {
+        let sum: f64 = self
+            .waves
+            .iter()
+            .map(|wave| {
+                (wave.x * x + wave.y * y + wave.minutes * elapsed_minutes + wave.phase).cos()
+            })
+            .sum();
+
+        self.mean + self.amplitude * (2.0 / self.waves.len() as f64).sqrt() * sum
+    }
+}
+
+/// Reads the shared field at the sensor's position, plus normally distributed noise.
+pub struct SpatialModel {
+    field: SpatialField,
+    x: f64,
+    y: f64,
+    noise: Normal<f64>,
+}
+
+impl SpatialModel {
+    /// Fails if `noise` is negative, infinite or NaN.
+    pub fn new(
+        field: SpatialField,
+        (x, y): (f64, f64),
+        noise: f64,
+    ) -> Result<SpatialModel, NormalError> {
+        Ok(SpatialModel {
+            field,
+            x,
+            y,
+            noise: normal(0.0, noise)?,
+        })
+    }
+}
+
+impl TemperatureModel for SpatialModel {
+    fn sample(&mut self, rng: &mut StdRng, elapsed_minutes: f64) -> f64 {
+        self.field.at(self.x, self.y, elapsed_minutes) + self.noise.sample(rng)
+    }
+}
@@ -0,0 +1,177 @@
+//! Changing a simulation's speed while it runs. The sensor and report threads see the time
+//! through a `ScaledClock`, which runs faster or slower than the clock the simulation was given,
+//! so a sped up run still has a whole hour of readings in each report, it just gets there sooner.
+
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use super::Clock;
+
+/// The longest a thread sleeps on the underlying clock at once while the speed can change, so a
+/// long sleep is cut short soon after the speed changes rather than running its old course.
+const SLEEP_SLICE: Duration = Duration::from_millis(50);
+
+/// Changes how fast a `TemperatureSimulation` runs without needing access to it, e.g. from a
+/// thread reading commands. The speed can be set before the simulation is started, in which case
+/// it starts at that speed.
+#[derive(Clone)]
+pub struct SpeedHandle {
+    speed: Arc<Speed>,
+}
+
+impl SpeedHandle {
+    pub(crate) fn new(speed: Arc<Speed>, configured: u64) -> SpeedHandle {
+        let mut state = speed.lock();
+        state.adjustable = true;
+        if state.clock.is_none() {
+            state.configured = configured as f64;
+        }
+        drop(state);
+
+        SpeedHandle { speed }
+    }
+
+    /// Runs the simulation `speedup` times faster than real time from now on, in place of the
+    /// configured speedup. Threads that are asleep wake up when they would have at the new
+    /// speed. Returns false if `speedup` isn't a positive number.
+    pub fn set_speedup(&self, speedup: f64) -> bool {
+        if !(speedup.is_finite() && speedup > 0.0) {
+            return false;
+        }
+
+        self.speed.set_speedup(speedup);
+        true
+    }
+
+    /// How many times faster than real time the simulation runs.
+    pub fn speedup(&self) -> f64 {
+        let state = self.speed.lock();
+        state.speedup.unwrap_or(state.configured)
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Speed {
+    state: Mutex<SpeedState>,
+}
+
+#[derive(Default)]
+struct SpeedState {
+    // The speedup the simulation was configured with, which the scaled durations are worked out
+    // from. Set once the simulation starts
+    configured: f64,
+
+    // The speedup asked for through a handle, if it's been changed
+    speedup: Option<f64>,
+
+    // Whether anything can change the speed, otherwise sleeps don't need to be cut up
+    adjustable: bool,
+
+    // Set once the simulation starts, changing the speed before then just sets where it starts
+    clock: Option<Arc<dyn Clock>>,
+
+    // The last time the speed changed, by the clock the simulation was given and by the scaled
+    // clock. The scaled time moves on from there at the current rate
+    changed_at: Option<(Instant, Instant)>,
+}
+
+impl SpeedState {
+    /// How much faster than the configured speedup the simulation runs.
+    fn rate(&self) -> f64 {
+        match self.speedup {
+            Some(speedup) if self.configured > 0.0 => speedup / self.configured,
+            _ => 1.0,
+        }
+    }
+
+    fn now(&self, inner: Instant) -> Instant {
+        match (self.speedup, self.changed_at) {
+            (Some(_), Some((inner_at, scaled_at))) => {
+                scaled_at
+                    + inner
+                        .saturating_duration_since(inner_at)
+                        .mul_f64(self.rate())
+            }
+            _ => inner,
+        }
+    }
+}
+
+impl Speed {
+    fn lock(&self) -> MutexGuard<'_, SpeedState> {
+        self.state.lock().unwrap()
+    }
+
+    /// Starts scaling `clock`, which runs at `configured` times real time, from now.
+    pub(crate) fn attach(&self, clock: Arc<dyn Clock>, configured: u64) {
+        let mut state = self.lock();
+        let now = clock.now();
+        state.configured = configured as f64;
+        state.changed_at = Some((now, now));
+        state.clock = Some(clock);
+    }
+
+    fn set_speedup(&self, speedup: f64) {
+        let mut state = self.lock();
+
+        // The scaled time carries on from wherever it's got to, so it never jumps
+        if let Some(clock) = state.clock.clone() {
+            let inner = clock.now();
+            let scaled = state.now(inner);
+            state.changed_at = Some((inner, scaled));
+        }
+        state.speedup = Some(speedup);
+    }
+}
+
+/// The clock the simulation was given, sped up or slowed down by however much the speed has
+/// been changed.
+pub(crate) struct ScaledClock {
+    inner: Arc<dyn Clock>,
+    speed: Arc<Speed>,
+}
+
+impl ScaledClock {
+    pub(crate) fn new(inner: Arc<dyn Clock>, speed: Arc<Speed>, configured: u64) -> ScaledClock {
+        speed.attach(inner.clone(), configured);
+        ScaledClock { inner, speed }
+    }
+}
+
+impl Clock for ScaledClock {
+    fn now(&self) -> Instant {
+        self.speed.lock().now(self.inner.now())
+    }
+
+    /// The sleep is measured in scaled time. While the speed can change it's taken a slice at a
+    /// time, so a thread that's asleep when it changes wakes up when it would have at the new
+    /// speed.
+    fn sleep(&self, duration: Duration) {
+        let wake_at = self.now() + duration;
+
+        loop {
+            let state = self.speed.lock();
+            let now = state.now(self.inner.now());
+            if now >= wake_at {
+                return;
+            }
+
+            let mut remaining = (wake_at - now).div_f64(state.rate());
+            if state.adjustable {
+                remaining = remaining.min(SLEEP_SLICE);
+            }
+            drop(state);
+
+            // Rounding can leave a sleep that's too short to get anywhere
+            self.inner.sleep(remaining.max(Duration::from_nanos(1)));
+        }
+    }
+
+    fn add_participant(&self) {
+        self.inner.add_participant();
+    }
+
+    fn remove_participant(&self) {
+        self.inner.remove_participant();
+    }
+}
@@ -0,0 +1,225 @@
+//! The recordings a report keeps so it can be worked out again without its suspect sensors. A
+//! period's worth of them is usually small, but a consumer that's fallen behind or a very short
+//! sampling period can make it grow without bound, so past a cap the oldest ones are written to
+//! a temporary file and read back, in order, when the report is made.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, Read, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use super::{Recording, Temperature};
+
+// Each recording is its sensor ID, sequence number, nanoseconds from the file's base instant and
+// temperature as 8 bytes each
+const RECORD_SIZE: usize = 32;
+
+// Tells apart the files of buffers in the same process
+static NEXT_FILE: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug)]
+pub(crate) struct RecordingBuffer {
+    // How many recordings are kept in memory before they're spilled. Unlimited when `None`
+    cap: Option<usize>,
+    memory: Vec<Recording>,
+
+    // Holds every recording that was pushed before the ones in memory
+    spill: Option<SpillFile>,
+}
+
+impl RecordingBuffer {
+    pub(crate) fn new(cap: Option<usize>) -> RecordingBuffer {
+        RecordingBuffer {
+            cap,
+            memory: Vec::new(),
+            spill: None,
+        }
+    }
+
+    pub(crate) fn push(&mut self, recording: Recording) {
+        self.memory.push(recording);
+
+        if self.cap.is_some_and(|cap| self.memory.len() > cap) {
+            self.spill_memory();
+        }
+    }
+
+    /// Adds every recording from `later`, which were all pushed after this buffer's.
+    #[cfg(feature = "parallel")]
+    pub(crate) fn append(&mut self, later: RecordingBuffer) {
+        for recording in later.into_recordings() {
+            self.push(recording);
+        }
+    }
+
+    /// Every recording in the order it was pushed, the spilled ones first.
+    pub(crate) fn into_recordings(mut self) -> impl Iterator<Item = Recording> {
+        let spilled = self.spill.take();
+        let memory = std::mem::take(&mut self.memory);
+
+        spilled
+            .into_iter()
+            .flat_map(SpillFile::into_recordings)
+            .chain(memory)
+    }
+
+    // A recording that can't be spilled is kept in memory rather than lost
+    fn spill_memory(&mut self) {
+        if self.spill.is_none() {
+            match SpillFile::create(self.memory[0].timestamp) {
+                Ok(spill) => self.spill = Some(spill),
+                Err(err) => {
+                    eprintln!("Couldn't create a file to spill recordings to: {}", err);
+                    self.cap = None;
+                    return;
+                }
+            }
+        }
+
+        if let Some(spill) = &mut self.spill {
+            match spill.write(&self.memory) {
+                Ok(()) => self.memory.clear(),
+                Err(err) => {
+                    eprintln!(
+                        "Couldn't spill recordings to {}: {}",
+                        spill.path.display(),
+                        err
+                    );
+                    self.cap = None;
+                }
+            }
+        }
+    }
+}
+
+impl Clone for RecordingBuffer {
+    fn clone(&self) -> Self {
+        let mut clone = RecordingBuffer::new(self.cap);
+        if let Some(spill) = &self.spill {
+            for recording in spill.read() {
+                clone.push(recording);
+            }
+        }
+
+        for recording in self.memory.iter() {
+            clone.push(recording.clone());
+        }
+
+        clone
+    }
+}
+
+/// A temporary file the oldest recordings are appended to. It's deleted when it's dropped.
+#[derive(Debug)]
+struct SpillFile {
+    path: PathBuf,
+    file: File,
+
+    // Timestamps are written relative to this, since an `Instant` can't be written as it is
+    base: Instant,
+}
+
+impl SpillFile {
+    fn create(base: Instant) -> io::Result<SpillFile> {
+        let path = std::env::temp_dir().join(format!(
+            "assignment3-recordings-{}-{}",
+            std::process::id(),
+            NEXT_FILE.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = OpenOptions::new()
+            .create_new(true)
+            .append(true)
+            .open(&path)?;
+
+        Ok(SpillFile { path, file, base })
+    }
+
+    fn write(&mut self, recordings: &[Recording]) -> io::Result<()> {
+        let mut bytes = Vec::with_capacity(recordings.len() * RECORD_SIZE);
+        for recording in recordings {
+            bytes.extend_from_slice(&(recording.sensor_id as u64).to_le_bytes());
+            bytes.extend_from_slice(&recording.sequence.to_le_bytes());
+            bytes.extend_from_slice(&self.offset(recording.timestamp).to_le_bytes());
+            bytes.extend_from_slice(&recording.temperature.fahrenheit().to_le_bytes());
+        }
+
+        self.file.write_all(&bytes)
+    }
+
+    fn offset(&self, timestamp: Instant) -> i64 {
+        match timestamp.checked_duration_since(self.base) {
+            Some(after) => after.as_nanos() as i64,
+            None => -(self.base.duration_since(timestamp).as_nanos() as i64),
+        }
+    }
+
+    /// Reads the recordings back one at a time. Stops early if the file can't be read, since
+    /// there's nothing better to do with the rest of a report than work it out without them.
+    fn read(&self) -> impl Iterator<Item = Recording> {
+        let path = self.path.clone();
+        let base = self.base;
+        let mut reader = match File::open(&path) {
+            Ok(file) => Some(BufReader::new(file)),
+            Err(err) => {
+                eprintln!(
+                    "Couldn't read spilled recordings from {}: {}",
+                    path.display(),
+                    err
+                );
+                None
+            }
+        };
+
+        std::iter::from_fn(move || {
+            let mut record = [0; RECORD_SIZE];
+            match reader.as_mut()?.read_exact(&mut record) {
+                Ok(()) => Some(decode(&record, base)),
+                Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => None,
+                Err(err) => {
+                    eprintln!(
+                        "Couldn't read spilled recordings from {}: {}",
+                        path.display(),
+                        err
+                    );
+                    None
+                }
+            }
+        })
+    }
+
+    /// Like `read`, but the file is deleted once every recording has been read.
+    fn into_recordings(self) -> impl Iterator<Item = Recording> {
+        let recordings = self.read();
+        let mut spill = Some(self);
+
+        recordings.chain(std::iter::from_fn(move || {
+            spill.take();
+            None
+        }))
+    }
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+fn decode(record: &[u8; RECORD_SIZE], base: Instant) -> Recording {
+    let field = |index: usize| {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&record[index * 8..index * 8 + 8]);
+        bytes
+    };
+
+    Recording {
+        sensor_id: u64::from_le_bytes(field(0)) as usize,
+        sequence: u64::from_le_bytes(field(1)),
+        timestamp: match i64::from_le_bytes(field(2)) {
+            offset if offset >= 0 => base + Duration::from_nanos(offset as u64),
+            offset => base - Duration::from_nanos(offset.unsigned_abs()),
+        },
+        temperature: Temperature::from_fahrenheit(f64::from_le_bytes(field(3))),
+    }
+}
@@ -0,0 +1,149 @@
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rusqlite::{params, Connection};
+
+use super::{timestamp, Recording, Report, ReportSink};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    id INTEGER PRIMARY KEY,
+    started_at INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS readings (
+    id INTEGER PRIMARY KEY,
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    sensor_id INTEGER NOT NULL,
+    timestamp_ns INTEGER NOT NULL,
+    temperature REAL NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS reports (
+    id INTEGER PRIMARY KEY,
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    report_number INTEGER NOT NULL,
+    lowest REAL,
+    highest REAL,
+    largest_difference REAL,
+    report_json TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS readings_by_sensor ON readings (run_id, sensor_id);
+";
+
+/// Stores every recording and report in a SQLite database. Each simulation run gets a row in
+/// `runs`, and the `readings` and `reports` tables point back at it. Temperatures are in
+/// Fahrenheit and the full report is also kept as JSON.
+///
+/// Recordings are inserted inside a transaction that's committed whenever a report is generated,
+/// so the database isn't hit with one commit per reading.
+pub struct SqliteSink {
+    connection: Connection,
+    run_id: i64,
+    in_transaction: bool,
+}
+
+impl SqliteSink {
+    /// Opens (or creates) the database and starts a new run.
+    pub fn open(path: impl AsRef<Path>) -> rusqlite::Result<SqliteSink> {
+        let connection = Connection::open(path)?;
+        connection.execute_batch(SCHEMA)?;
+
+        let started_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|x| x.as_secs() as i64)
+            .unwrap_or(0);
+
+        connection.execute("INSERT INTO runs (started_at) VALUES (?1)", [started_at])?;
+
+        Ok(SqliteSink {
+            run_id: connection.last_insert_rowid(),
+            connection,
+            in_transaction: false,
+        })
+    }
+
+    pub fn run_id(&self) -> i64 {
+        self.run_id
+    }
+
+    fn insert_recording(&mut self, recording: &Recording) -> rusqlite::Result<()> {
+        if !self.in_transaction {
+            self.connection.execute_batch("BEGIN")?;
+            self.in_transaction = true;
+        }
+
+        self.connection
+            .prepare_cached(
+                "INSERT INTO readings (run_id, sensor_id, timestamp_ns, temperature)
+                 VALUES (?1, ?2, ?3, ?4)",
+            )?
+            .execute(params![
+                self.run_id,
+                recording.sensor_id as i64,
+                timestamp::to_nanos(recording.timestamp) as i64,
+                recording.temperature.fahrenheit()
+            ])?;
+
+        Ok(())
+    }
+
+    fn insert_report(&mut self, report: &Report) -> rusqlite::Result<()> {
+        let report_json = serde_json::to_string(report)
+            .map_err(|err| rusqlite::Error::ToSqlConversionFailure(Box::new(err)))?;
+
+        self.connection.execute(
+            "INSERT INTO reports (run_id, report_number, lowest, highest, largest_difference, report_json)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                self.run_id,
+                report.index as i64 + 1,
+                report
+                    .lowest_temps
+                    .first()
+                    .map(|x| x.temperature.fahrenheit()),
+                report
+                    .highest_temps
+                    .first()
+                    .map(|x| x.temperature.fahrenheit()),
+                report.largest_temp_difference.as_ref().map(|x| x.difference),
+                report_json
+            ],
+        )?;
+
+        self.commit()
+    }
+
+    fn commit(&mut self) -> rusqlite::Result<()> {
+        if self.in_transaction {
+            self.in_transaction = false;
+            self.connection.execute_batch("COMMIT")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl ReportSink for SqliteSink {
+    fn handle_report(&mut self, report: &Report) {
+        if let Err(err) = self.insert_report(report) {
+            eprintln!("Couldn't store report in the database: {}", err);
+        }
+    }
+
+    fn handle_recording(&mut self, recording: &Recording) {
+        if let Err(err) = self.insert_recording(recording) {
+            eprintln!("Couldn't store recording in the database: {}", err);
+        }
+    }
+}
+
+impl Drop for SqliteSink {
+    fn drop(&mut self) {
+        // Keep readings from the last, unfinished hour
+        if let Err(err) = self.commit() {
+            eprintln!("Couldn't store recordings in the database: {}", err);
+        }
+    }
+}
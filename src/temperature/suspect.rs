@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+
+use super::{SensorStats, Temperature};
+
+/// Settings for spotting sensors whose readings can't be trusted, written as a
+/// `[suspect_sensors]` table in the config file.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SuspectConfig {
+    /// Suspect sensors are left out of the top temps and the largest difference. They're
+    /// still listed in the report
+    pub enabled: bool,
+
+    /// A sensor that reads exactly the same temperature this many times in a row is stuck
+    pub stuck_readings: usize,
+
+    /// How many degrees Fahrenheit a sensor's mean can be from the typical (median) sensor mean
+    pub max_mean_deviation: f64,
+
+    /// How many times the typical (median) sensor standard deviation a sensor's can be
+    pub max_spread_ratio: f64,
+}
+
+impl Default for SuspectConfig {
+    fn default() -> Self {
+        SuspectConfig {
+            enabled: true,
+            stuck_readings: 10,
+            max_mean_deviation: 40.0,
+            max_spread_ratio: 3.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SuspectSensor {
+    pub sensor_id: usize,
+    pub reason: SuspectReason,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SuspectReason {
+    /// Read the same temperature `readings` times in a row
+    Stuck {
+        temperature: Temperature,
+        readings: usize,
+    },
+
+    /// The sensor's mean is far from the other sensors'
+    DivergentMean {
+        mean: Temperature,
+        typical: Temperature,
+    },
+
+    /// The sensor's readings are spread out far more than the other sensors' (standard
+    /// deviations in degrees Fahrenheit)
+    DivergentSpread { std_dev: f64, typical: f64 },
+}
+
+/// Checks every sensor's statistics for signs it's faulty. Comparisons against the other sensors
+/// need at least 3 sensors with readings, so a lone sensor can only be flagged as stuck.
+pub fn find_suspect_sensors(stats: &[SensorStats], config: &SuspectConfig) -> Vec<SuspectSensor> {
+    let typical_mean = median(stats.iter().filter_map(|x| x.mean.map(|x| x.fahrenheit())));
+    let typical_std_dev = median(stats.iter().filter_map(|x| x.std_dev));
+
+    stats
+        .iter()
+        .filter_map(|sensor| {
+            let reason = suspect_reason(sensor, config, typical_mean, typical_std_dev)?;
+
+            Some(SuspectSensor {
+                sensor_id: sensor.sensor_id,
+                reason,
+            })
+        })
+        .collect()
+}
+
+fn suspect_reason(
+    sensor: &SensorStats,
+    config: &SuspectConfig,
+    typical_mean: Option<f64>,
+    typical_std_dev: Option<f64>,
+) -> Option<SuspectReason> {
+    if let Some(run) = &sensor.longest_repeat {
+        if config.stuck_readings > 0 && run.readings >= config.stuck_readings {
+            return Some(SuspectReason::Stuck {
+                temperature: run.temperature,
+                readings: run.readings,
+            });
+        }
+    }
+
+    let mean = sensor.mean?.fahrenheit();
+    if let Some(typical) = typical_mean {
+        if (mean - typical).abs() > config.max_mean_deviation {
+            return Some(SuspectReason::DivergentMean {
+                mean: Temperature::from_fahrenheit(mean),
+                typical: Temperature::from_fahrenheit(typical),
+            });
+        }
+    }
+
+    let std_dev = sensor.std_dev?;
+    if let Some(typical) = typical_std_dev {
+        if typical > 0.0 && std_dev > typical * config.max_spread_ratio {
+            return Some(SuspectReason::DivergentSpread { std_dev, typical });
+        }
+    }
+
+    None
+}
+
+// Returns `None` with fewer than 3 values, since there's nothing to compare against
+fn median(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let mut values: Vec<f64> = values.collect();
+    if values.len() < 3 {
+        return None;
+    }
+
+    values.sort_by(f64::total_cmp);
+
+    let middle = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        Some((values[middle - 1] + values[middle]) / 2.0)
+    } else {
+        Some(values[middle])
+    }
+}
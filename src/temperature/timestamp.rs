@@ -0,0 +1,62 @@
+//! `Instant`s can't be serialized directly, so recordings are serialized with their timestamp as
+//! the number of nanoseconds since a fixed point early in the process's life. Timestamps round
+//! trip exactly within one process, and across processes they still give the relative timing of
+//! the readings.
+//!
+//! The wall clock time is captured along with the epoch, so any `Instant` can also be shown as a
+//! readable time of day.
+
+use std::sync::OnceLock;
+use std::time::{Duration, Instant, SystemTime};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// How wall clock times are printed. Milliseconds are included since a simulated hour only takes
+/// a few real seconds.
+pub const WALL_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.3f";
+
+static PROCESS_EPOCH: OnceLock<(Instant, SystemTime)> = OnceLock::new();
+
+fn epoch() -> (Instant, SystemTime) {
+    *PROCESS_EPOCH.get_or_init(|| (Instant::now(), SystemTime::now()))
+}
+
+/// The instant serialized timestamps are measured from. It's fixed the first time it's called,
+/// which `Recording::new` makes sure happens before any recording is taken.
+pub fn process_epoch() -> Instant {
+    epoch().0
+}
+
+/// The local time `instant` happened at, going by the wall clock when the epoch was taken. Later
+/// changes to the system clock don't affect it. With a `VirtualClock` this is the simulated time
+/// rather than when the reading was really taken.
+pub fn wall_time(instant: Instant) -> DateTime<Local> {
+    let (epoch, epoch_wall_time) = epoch();
+
+    let wall_time = match instant.checked_duration_since(epoch) {
+        Some(since) => epoch_wall_time + since,
+        None => epoch_wall_time - epoch.duration_since(instant),
+    };
+
+    DateTime::from(wall_time)
+}
+
+/// Nanoseconds between the process epoch and `instant`.
+pub fn to_nanos(instant: Instant) -> u64 {
+    instant
+        .saturating_duration_since(process_epoch())
+        .as_nanos() as u64
+}
+
+pub fn from_nanos(nanos: u64) -> Instant {
+    process_epoch() + Duration::from_nanos(nanos)
+}
+
+pub fn serialize<S: Serializer>(instant: &Instant, serializer: S) -> Result<S::Ok, S::Error> {
+    to_nanos(*instant).serialize(serializer)
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Instant, D::Error> {
+    u64::deserialize(deserializer).map(from_nanos)
+}
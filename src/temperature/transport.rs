@@ -0,0 +1,344 @@
+//! The queues that carry readings from the sensor threads to the report thread. Which kind is
+//! used can be picked at runtime, so the backends can be compared on the same simulation.
+
+use std::str::FromStr;
+use std::sync::mpsc::{self, TryRecvError, TrySendError};
+use std::sync::Arc;
+
+use serde::Deserialize;
+
+use super::ring::{ring_channel, RingSender, RoundRobinReceiver};
+use super::slots::{slot_channel, SlotReceiver, SlotSender};
+use super::{Clock, Recording};
+
+/// Which kind of queue carries readings to the report thread.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Transport {
+    /// A lock-free ring per sensor, which the report thread takes from in turn
+    #[default]
+    Ring,
+
+    /// One bounded `std::sync::mpsc` channel shared by every sensor
+    Mpsc,
+
+    /// One bounded crossbeam-channel shared by every sensor. Needs the `crossbeam` feature
+    Crossbeam,
+
+    /// No queue, each sensor writes its latest temperature into its own atomic slot in a shared
+    /// array and the report thread samples the array. Readings are timestamped when they're
+    /// sampled and belong to the sensor whose slot they were in
+    Slots,
+}
+
+impl Transport {
+    /// Every transport that was compiled in.
+    pub fn available() -> Vec<Transport> {
+        [
+            Transport::Ring,
+            Transport::Mpsc,
+            Transport::Crossbeam,
+            Transport::Slots,
+        ]
+        .into_iter()
+        .filter(|x| x.is_available())
+        .collect()
+    }
+
+    pub fn is_available(self) -> bool {
+        self != Transport::Crossbeam || cfg!(feature = "crossbeam")
+    }
+
+    /// Whether a sensor can take the oldest reading back out of a full queue. A std receiver
+    /// can't be shared with the senders, so the mpsc transport can't.
+    pub fn can_drop_oldest(self) -> bool {
+        self != Transport::Mpsc
+    }
+
+    /// Whether readings keep the sequence numbers their sensors gave them. A slot only holds a
+    /// temperature, so sampled readings have to be numbered by when they were sampled instead.
+    pub fn keeps_sequence(self) -> bool {
+        self != Transport::Slots
+    }
+}
+
+impl FromStr for Transport {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ring" => Ok(Transport::Ring),
+            "mpsc" => Ok(Transport::Mpsc),
+            "crossbeam" => Ok(Transport::Crossbeam),
+            "slots" => Ok(Transport::Slots),
+            _ => Err(format!(
+                "unknown transport `{}`, expected ring, mpsc, crossbeam or slots",
+                s
+            )),
+        }
+    }
+}
+
+/// What a sensor does when its queue is full because the report thread has fallen behind.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverflowPolicy {
+    /// Wait for room, so no reading is lost but the sensor falls behind too
+    #[default]
+    Block,
+
+    /// Throw away the oldest reading in the queue to make room
+    DropOldest,
+
+    /// Throw away the new reading
+    DropNewest,
+}
+
+impl FromStr for OverflowPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace('-', "_").as_str() {
+            "block" => Ok(OverflowPolicy::Block),
+            "drop_oldest" => Ok(OverflowPolicy::DropOldest),
+            "drop_newest" => Ok(OverflowPolicy::DropNewest),
+            _ => Err(format!(
+                "unknown overflow policy `{}`, expected block, drop_oldest or drop_newest",
+                s
+            )),
+        }
+    }
+}
+
+/// Creates a sender for each of `senders` sensors and the receiver they all send to. Each
+/// sensor's ring holds `capacity` values, the shared channels hold `capacity` per sensor and the
+/// slots hold one reading each. `clock` timestamps readings sampled from the slots.
+///
+/// Panics if the transport wasn't compiled in.
+pub fn sensor_channels(
+    transport: Transport,
+    senders: usize,
+    capacity: usize,
+    clock: Arc<dyn Clock>,
+) -> (Vec<SensorSender>, SensorReceiver) {
+    match transport {
+        Transport::Ring => {
+            let (senders, receivers): (Vec<_>, Vec<_>) =
+                (0..senders).map(|_| ring_channel(capacity)).unzip();
+
+            (
+                senders
+                    .into_iter()
+                    .map(|x| SensorSender(SenderKind::Ring(x)))
+                    .collect(),
+                SensorReceiver(ReceiverKind::Ring(RoundRobinReceiver::new(receivers))),
+            )
+        }
+        Transport::Mpsc => {
+            let (sender, receiver) = mpsc::sync_channel(capacity * senders);
+
+            (
+                (0..senders)
+                    .map(|_| SensorSender(SenderKind::Mpsc(sender.clone())))
+                    .collect(),
+                SensorReceiver(ReceiverKind::Mpsc(receiver)),
+            )
+        }
+        #[cfg(feature = "crossbeam")]
+        Transport::Crossbeam => {
+            let (sender, receiver) = crossbeam_channel::bounded(capacity * senders);
+
+            (
+                (0..senders)
+                    .map(|_| SensorSender(SenderKind::Crossbeam(sender.clone(), receiver.clone())))
+                    .collect(),
+                SensorReceiver(ReceiverKind::Crossbeam(receiver)),
+            )
+        }
+        #[cfg(not(feature = "crossbeam"))]
+        Transport::Crossbeam => panic!("the crossbeam transport needs the `crossbeam` feature"),
+        Transport::Slots => {
+            let (senders, receiver) = slot_channel(senders);
+
+            (
+                senders
+                    .into_iter()
+                    .map(|x| SensorSender(SenderKind::Slot(x)))
+                    .collect(),
+                SensorReceiver(ReceiverKind::Slots(receiver, clock)),
+            )
+        }
+    }
+}
+
+/// One sensor's end of the transport.
+pub struct SensorSender(SenderKind);
+
+enum SenderKind {
+    Ring(RingSender<Recording>),
+    Mpsc(mpsc::SyncSender<Recording>),
+
+    // Crossbeam receivers can be cloned, so the sender keeps one to drop the oldest value with
+    #[cfg(feature = "crossbeam")]
+    Crossbeam(
+        crossbeam_channel::Sender<Recording>,
+        crossbeam_channel::Receiver<Recording>,
+    ),
+
+    Slot(SlotSender),
+}
+
+impl SensorSender {
+    /// Adds a reading to the queue. The reading is handed back if the queue is full or the
+    /// receiver is gone. Crossbeam and slot senders never see the receiver go.
+    pub fn try_send(&mut self, value: Recording) -> Result<(), TrySendError<Recording>> {
+        match &mut self.0 {
+            SenderKind::Ring(sender) => sender.try_send(value),
+            SenderKind::Mpsc(sender) => sender.try_send(value),
+            #[cfg(feature = "crossbeam")]
+            SenderKind::Crossbeam(sender, _) => sender.try_send(value).map_err(|err| match err {
+                crossbeam_channel::TrySendError::Full(x) => TrySendError::Full(x),
+                crossbeam_channel::TrySendError::Disconnected(x) => TrySendError::Disconnected(x),
+            }),
+            SenderKind::Slot(sender) => sender
+                .try_write(value.temperature)
+                .map_err(|_| TrySendError::Full(value)),
+        }
+    }
+
+    /// Takes the oldest value back out of the queue to make room. Returns `None` if the
+    /// receiver got to it first or the transport can't do it (see `Transport::can_drop_oldest`).
+    /// With a shared channel the oldest value might be another sensor's. A slot's reading comes
+    /// back without its timestamp, which is only known once a reading is sampled.
+    pub fn drop_oldest(&mut self) -> Option<Recording> {
+        match &mut self.0 {
+            SenderKind::Ring(sender) => sender.drop_oldest(),
+            SenderKind::Mpsc(_) => None,
+            #[cfg(feature = "crossbeam")]
+            SenderKind::Crossbeam(_, receiver) => receiver.try_recv().ok(),
+            SenderKind::Slot(sender) => {
+                let index = sender.index();
+                sender
+                    .take()
+                    .map(|temperature| Recording::new(index, temperature))
+            }
+        }
+    }
+
+    /// Adds a reading to the queue, doing what `policy` says whenever it's full. With `Block`,
+    /// `wait` is called each time the queue is full and should wait a while for room, returning
+    /// false to give up. Every reading thrown away to make room, or thrown away instead, is
+    /// passed to `dropped`.
+    ///
+    /// Returns false if the receiver is gone or `wait` gave up, in which case `value` wasn't
+    /// sent.
+    pub fn send(
+        &mut self,
+        mut value: Recording,
+        policy: OverflowPolicy,
+        mut wait: impl FnMut() -> bool,
+        mut dropped: impl FnMut(&Recording),
+    ) -> bool {
+        loop {
+            match (self.try_send(value), policy) {
+                (Ok(()), _) => return true,
+                (Err(TrySendError::Disconnected(_)), _) => return false,
+                (Err(TrySendError::Full(x)), OverflowPolicy::Block) => {
+                    if !wait() {
+                        return false;
+                    }
+                    value = x;
+                }
+                (Err(TrySendError::Full(x)), OverflowPolicy::DropOldest) => {
+                    // The receiver might take the oldest reading first, in which case there's
+                    // room without dropping anything. With a shared channel the oldest reading
+                    // can be another sensor's
+                    if let Some(oldest) = self.drop_oldest() {
+                        dropped(&oldest);
+                    }
+                    value = x;
+                }
+                (Err(TrySendError::Full(x)), OverflowPolicy::DropNewest) => {
+                    dropped(&x);
+                    return true;
+                }
+            }
+        }
+    }
+}
+
+/// The report thread's end of the transport.
+pub struct SensorReceiver(ReceiverKind);
+
+enum ReceiverKind {
+    Ring(RoundRobinReceiver<Recording>),
+    Mpsc(mpsc::Receiver<Recording>),
+    #[cfg(feature = "crossbeam")]
+    Crossbeam(crossbeam_channel::Receiver<Recording>),
+    Slots(SlotReceiver, Arc<dyn Clock>),
+}
+
+impl SensorReceiver {
+    /// Roughly how many readings are waiting to be received, or `None` for the mpsc transport,
+    /// which can't tell.
+    pub fn queued(&self) -> Option<usize> {
+        match &self.0 {
+            ReceiverKind::Ring(receiver) => Some(receiver.len()),
+            ReceiverKind::Mpsc(_) => None,
+            #[cfg(feature = "crossbeam")]
+            ReceiverKind::Crossbeam(receiver) => Some(receiver.len()),
+            ReceiverKind::Slots(receiver, _) => Some(receiver.len()),
+        }
+    }
+
+    /// Only reports `Disconnected` once every sender is gone and everything sent has been
+    /// received.
+    pub fn try_recv(&mut self) -> Result<Recording, TryRecvError> {
+        match &mut self.0 {
+            ReceiverKind::Ring(receiver) => receiver.try_recv(),
+            ReceiverKind::Mpsc(receiver) => receiver.try_recv(),
+            #[cfg(feature = "crossbeam")]
+            ReceiverKind::Crossbeam(receiver) => receiver.try_recv().map_err(|err| match err {
+                crossbeam_channel::TryRecvError::Empty => TryRecvError::Empty,
+                crossbeam_channel::TryRecvError::Disconnected => TryRecvError::Disconnected,
+            }),
+            ReceiverKind::Slots(receiver, clock) => {
+                let (sensor_id, temperature) = receiver.try_read()?;
+
+                Ok(Recording {
+                    sensor_id,
+                    temperature,
+                    sequence: 0,
+                    timestamp: clock.now(),
+                })
+            }
+        }
+    }
+
+    /// Moves up to `max` of the readings that are already waiting into `buffer` in one go and
+    /// returns how many it moved, so a backlog can be taken off the queues in batches rather
+    /// than a reading at a time. Never waits, and never reports the senders going away, that's
+    /// left to `try_recv`.
+    pub fn drain_into(&mut self, buffer: &mut Vec<Recording>, max: usize) -> usize {
+        let before = buffer.len();
+
+        match &mut self.0 {
+            ReceiverKind::Ring(receiver) => {
+                receiver.drain_into(buffer, max);
+            }
+            ReceiverKind::Mpsc(receiver) => buffer.extend(receiver.try_iter().take(max)),
+            #[cfg(feature = "crossbeam")]
+            ReceiverKind::Crossbeam(receiver) => buffer.extend(receiver.try_iter().take(max)),
+            ReceiverKind::Slots(..) => {
+                while buffer.len() - before < max {
+                    match self.try_recv() {
+                        Ok(recording) => buffer.push(recording),
+                        Err(_) => break,
+                    }
+                }
+            }
+        }
+
+        buffer.len() - before
+    }
+}
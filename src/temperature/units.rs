@@ -0,0 +1,192 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// A temperature reading. It's always stored in degrees Fahrenheit (the unit the assignment uses)
+/// and only converted to another unit when it's displayed.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Temperature(f64);
+
+impl Temperature {
+    /// The coldest temperature the rover's sensors can read, in degrees Fahrenheit
+    pub const MIN_FAHRENHEIT: f64 = -100.0;
+
+    /// The hottest temperature the rover's sensors can read, in degrees Fahrenheit
+    pub const MAX_FAHRENHEIT: f64 = 70.0;
+
+    pub fn new(value: f64, unit: TemperatureUnit) -> Temperature {
+        match unit {
+            TemperatureUnit::Fahrenheit => Temperature(value),
+            TemperatureUnit::Celsius => Temperature(value * 9.0 / 5.0 + 32.0),
+            TemperatureUnit::Kelvin => Temperature((value - 273.15) * 9.0 / 5.0 + 32.0),
+        }
+    }
+
+    pub fn from_fahrenheit(value: f64) -> Temperature {
+        Temperature(value)
+    }
+
+    /// Like `new`, but fails unless the temperature is one the rover's sensors could have read.
+    /// Readings from outside the simulation (files, stdin, recorded runs) go through this so a
+    /// bad value can't skew a report.
+    pub fn checked(value: f64, unit: TemperatureUnit) -> Result<Temperature, TemperatureError> {
+        if !value.is_finite() {
+            return Err(TemperatureError::NotFinite(value));
+        }
+
+        let temperature = Temperature::new(value, unit);
+        if !(Temperature::MIN_FAHRENHEIT..=Temperature::MAX_FAHRENHEIT).contains(&temperature.0) {
+            return Err(TemperatureError::OutOfRange(temperature));
+        }
+
+        Ok(temperature)
+    }
+
+    pub fn checked_fahrenheit(value: f64) -> Result<Temperature, TemperatureError> {
+        Temperature::checked(value, TemperatureUnit::Fahrenheit)
+    }
+
+    pub fn from_celsius(value: f64) -> Temperature {
+        Temperature::new(value, TemperatureUnit::Celsius)
+    }
+
+    pub fn from_kelvin(value: f64) -> Temperature {
+        Temperature::new(value, TemperatureUnit::Kelvin)
+    }
+
+    pub fn fahrenheit(self) -> f64 {
+        self.0
+    }
+
+    pub fn celsius(self) -> f64 {
+        self.in_unit(TemperatureUnit::Celsius)
+    }
+
+    pub fn kelvin(self) -> f64 {
+        self.in_unit(TemperatureUnit::Kelvin)
+    }
+
+    pub fn in_unit(self, unit: TemperatureUnit) -> f64 {
+        match unit {
+            TemperatureUnit::Fahrenheit => self.0,
+            TemperatureUnit::Celsius => (self.0 - 32.0) * 5.0 / 9.0,
+            TemperatureUnit::Kelvin => (self.0 - 32.0) * 5.0 / 9.0 + 273.15,
+        }
+    }
+
+    /// Difference between two temperatures in degrees Fahrenheit. Use
+    /// `TemperatureUnit::convert_difference` to display it in another unit.
+    pub fn difference(self, other: Temperature) -> f64 {
+        (self.0 - other.0).abs()
+    }
+
+    /// Formats the temperature in the given unit, e.g. `-12.2°C`.
+    pub fn display(self, unit: TemperatureUnit) -> DisplayTemperature {
+        DisplayTemperature {
+            temperature: self,
+            unit,
+        }
+    }
+}
+
+// Readings are never NaN, but `total_cmp` keeps sorting well defined even if one sneaks in
+impl PartialEq for Temperature {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Temperature {}
+
+impl PartialOrd for Temperature {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Temperature {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+/// Why a value couldn't be turned into a reading.
+#[derive(Clone, Copy, Debug, PartialEq, Error)]
+pub enum TemperatureError {
+    /// The value is NaN or infinite
+    #[error("{0} isn't a temperature")]
+    NotFinite(f64),
+
+    /// The value is colder or hotter than the rover's sensors can read
+    #[error(
+        "{:.1}°F is outside the range the rover's sensors can read ({:.1}°F to {:.1}°F)",
+        .0.fahrenheit(),
+        Temperature::MIN_FAHRENHEIT,
+        Temperature::MAX_FAHRENHEIT
+    )]
+    OutOfRange(Temperature),
+}
+
+pub struct DisplayTemperature {
+    temperature: Temperature,
+    unit: TemperatureUnit,
+}
+
+impl fmt::Display for DisplayTemperature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:.1}{}",
+            self.temperature.in_unit(self.unit),
+            self.unit.symbol()
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TemperatureUnit {
+    #[default]
+    Fahrenheit,
+    Celsius,
+    Kelvin,
+}
+
+impl TemperatureUnit {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            TemperatureUnit::Fahrenheit => "°F",
+            TemperatureUnit::Celsius => "°C",
+            TemperatureUnit::Kelvin => "K",
+        }
+    }
+
+    /// Converts a difference in degrees Fahrenheit into this unit. Unlike temperatures,
+    /// differences don't get an offset.
+    pub fn convert_difference(self, fahrenheit_difference: f64) -> f64 {
+        match self {
+            TemperatureUnit::Fahrenheit => fahrenheit_difference,
+            TemperatureUnit::Celsius | TemperatureUnit::Kelvin => fahrenheit_difference * 5.0 / 9.0,
+        }
+    }
+}
+
+impl FromStr for TemperatureUnit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "f" | "fahrenheit" => Ok(TemperatureUnit::Fahrenheit),
+            "c" | "celsius" => Ok(TemperatureUnit::Celsius),
+            "k" | "kelvin" => Ok(TemperatureUnit::Kelvin),
+            _ => Err(format!(
+                "unknown unit `{}`, expected fahrenheit, celsius or kelvin",
+                s
+            )),
+        }
+    }
+}
@@ -0,0 +1,118 @@
+//! Readings from outside the simulation (a file, stdin, ...) can be garbage. Every reading is
+//! checked before it's sent to the report thread, and ones that would throw off the statistics are
+//! thrown away and counted by why they were rejected.
+
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+use super::{Recording, SensorSource, SimulationConfig};
+
+/// Why a reading was thrown away instead of being used.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RejectReason {
+    /// The input couldn't be parsed as a reading
+    Malformed,
+
+    /// The temperature is NaN or infinite
+    NotANumber,
+
+    /// The temperature is outside `min_temperature` to `max_temperature`
+    OutOfRange,
+}
+
+impl RejectReason {
+    pub const ALL: [RejectReason; 3] = [
+        RejectReason::Malformed,
+        RejectReason::NotANumber,
+        RejectReason::OutOfRange,
+    ];
+}
+
+impl fmt::Display for RejectReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            RejectReason::Malformed => "malformed",
+            RejectReason::NotANumber => "not a number",
+            RejectReason::OutOfRange => "out of range",
+        })
+    }
+}
+
+/// Checks readings against what the config says the rover's sensors can produce.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ReadingFilter {
+    min_temperature: f64,
+    max_temperature: f64,
+}
+
+impl ReadingFilter {
+    pub(crate) fn new(config: &SimulationConfig) -> ReadingFilter {
+        ReadingFilter {
+            min_temperature: config.min_temperature,
+            max_temperature: config.max_temperature,
+        }
+    }
+
+    pub(crate) fn check(&self, recording: &Recording) -> Result<(), RejectReason> {
+        let value = recording.temperature.fahrenheit();
+
+        if !value.is_finite() {
+            Err(RejectReason::NotANumber)
+        } else if !(self.min_temperature..=self.max_temperature).contains(&value) {
+            Err(RejectReason::OutOfRange)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// The source's next reading that passes the checks, counting every one that doesn't along
+    /// with any input the source couldn't parse on the way. Returns `None` once the source has run
+    /// out of readings.
+    pub(crate) fn next_reading(
+        &self,
+        source: &mut dyn SensorSource,
+        rejections: &RejectionCounts,
+    ) -> Option<Recording> {
+        loop {
+            let recording = source.next_reading();
+            rejections.add(RejectReason::Malformed, source.take_malformed());
+            let recording = recording?;
+
+            match self.check(&recording) {
+                Ok(()) => return Some(recording),
+                Err(reason) => {
+                    eprintln!(
+                        "Skipping reading of {}°F from sensor {}: {}",
+                        recording.temperature.fahrenheit(),
+                        recording.sensor_id,
+                        reason
+                    );
+                    rejections.add(reason, 1);
+                }
+            }
+        }
+    }
+}
+
+/// Readings the sensors have rejected by reason, taken by the report thread whenever it makes a
+/// report.
+#[derive(Debug, Default)]
+pub(crate) struct RejectionCounts([AtomicUsize; RejectReason::ALL.len()]);
+
+impl RejectionCounts {
+    pub(crate) fn add(&self, reason: RejectReason, count: usize) {
+        if count > 0 {
+            self.0[reason as usize].fetch_add(count, Ordering::Relaxed);
+        }
+    }
+
+    /// The counts since they were last taken, leaving them at 0.
+    pub(crate) fn take(&self) -> impl Iterator<Item = (RejectReason, usize)> + '_ {
+        RejectReason::ALL
+            .into_iter()
+            .map(|reason| (reason, self.0[reason as usize].swap(0, Ordering::Relaxed)))
+    }
+}
@@ -0,0 +1,253 @@
+//! Notices sensors that have stopped sending readings, whether their thread is stuck (e.g. waiting
+//! on a full queue or a source that blocks), has finished (e.g. a dropout fault) or panicked.
+
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use super::SimulationConfig;
+
+/// Settings for the watchdog, written as a `[watchdog]` table in the config file.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WatchdogConfig {
+    /// Stalled sensors are logged and listed in the report
+    pub enabled: bool,
+
+    /// How many sampling periods a sensor can go without a reading before it counts as stalled
+    pub stall_periods: f64,
+
+    /// Whether a sensor whose thread panicked is started again on the same thread, with the same
+    /// source and queue. Without it the panic ends the sensor's thread
+    pub restart: bool,
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        WatchdogConfig {
+            enabled: true,
+            stall_periods: 2.0,
+            restart: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StalledSensor {
+    pub sensor_id: usize,
+    pub reason: StallReason,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StallReason {
+    /// Went `minutes` simulated minutes without sending a reading
+    Silent { minutes: f64 },
+
+    /// The sensor's thread panicked and wasn't restarted
+    Panicked,
+
+    /// The sensor's thread panicked and was started again
+    Restarted,
+}
+
+impl StallReason {
+    /// The reason that says the most about what went wrong with a sensor, for a sensor flagged
+    /// more than once in the same period.
+    pub fn worst(self, other: StallReason) -> StallReason {
+        match (self, other) {
+            (StallReason::Panicked, _) | (_, StallReason::Panicked) => StallReason::Panicked,
+            (StallReason::Restarted, _) | (_, StallReason::Restarted) => StallReason::Restarted,
+            (StallReason::Silent { minutes: a }, StallReason::Silent { minutes: b }) => {
+                StallReason::Silent { minutes: a.max(b) }
+            }
+        }
+    }
+}
+
+/// What the sensor threads tell the watchdog and the report thread about themselves.
+pub(crate) struct SensorLiveness {
+    start: Instant,
+
+    // Nanoseconds from `start` to each sensor's latest reading
+    last_reading: Vec<AtomicU64>,
+    panicked: Vec<AtomicBool>,
+    restarted: Vec<AtomicBool>,
+
+    // The sequence number each sensor would have sent next when it was told to stop, `u64::MAX`
+    // until then
+    stopped_at: Vec<AtomicU64>,
+
+    // The first sequence number each sensor owes, which is later than 0 for one that was added
+    // while the simulation was running
+    installed_at: Vec<AtomicU64>,
+}
+
+impl SensorLiveness {
+    pub(crate) fn new(sensor_count: usize, start: Instant) -> SensorLiveness {
+        SensorLiveness {
+            start,
+            last_reading: (0..sensor_count).map(|_| AtomicU64::new(0)).collect(),
+            panicked: (0..sensor_count).map(|_| AtomicBool::new(false)).collect(),
+            restarted: (0..sensor_count).map(|_| AtomicBool::new(false)).collect(),
+            stopped_at: (0..sensor_count)
+                .map(|_| AtomicU64::new(u64::MAX))
+                .collect(),
+            installed_at: (0..sensor_count).map(|_| AtomicU64::new(0)).collect(),
+        }
+    }
+
+    pub(crate) fn reading(&self, sensor_id: usize, at: Instant) {
+        let nanos = at.saturating_duration_since(self.start).as_nanos() as u64;
+        self.last_reading[sensor_id].store(nanos, Ordering::Relaxed);
+    }
+
+    pub(crate) fn panicked(&self, sensor_id: usize, restarting: bool) {
+        if restarting {
+            self.restarted[sensor_id].store(true, Ordering::Relaxed);
+        } else {
+            self.panicked[sensor_id].store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Marks a sensor as having stopped because the simulation was stopped, so it doesn't owe any
+    /// readings from `next_sequence` on. A sensor that goes offline any other way still does.
+    pub(crate) fn stopped(&self, sensor_id: usize, next_sequence: u64) {
+        self.stopped_at[sensor_id].store(next_sequence, Ordering::Relaxed);
+    }
+
+    /// Marks a new sensor as having been put in `sensor_id`'s slot at `at`, owing readings from
+    /// `first_sequence` on. If the slot's last sensor was retired during the same period, the
+    /// readings it owed before then aren't counted as missed any more.
+    pub(crate) fn installed(&self, sensor_id: usize, first_sequence: u64, at: Instant) {
+        self.installed_at[sensor_id].store(first_sequence, Ordering::Relaxed);
+        self.stopped_at[sensor_id].store(u64::MAX, Ordering::Relaxed);
+        self.panicked[sensor_id].store(false, Ordering::Relaxed);
+        self.reading(sensor_id, at);
+    }
+
+    /// The sequence numbers a sensor owes out of `sequences`.
+    pub(crate) fn owed(&self, sensor_id: usize, sequences: Range<u64>) -> Range<u64> {
+        let installed_at = self.installed_at[sensor_id].load(Ordering::Relaxed);
+        let stopped_at = self.stopped_at[sensor_id].load(Ordering::Relaxed);
+        let end = sequences.end.min(stopped_at);
+        sequences.start.max(installed_at).min(end)..end
+    }
+
+    pub(crate) fn sensor_count(&self) -> usize {
+        self.last_reading.len()
+    }
+}
+
+/// Checked by the report thread every time it wakes up. Sensors are logged when they stall and
+/// when they start sending readings again, and every check returns the sensors that are stalled
+/// right now so they're flagged in the report for the period it happened in.
+pub(crate) struct Watchdog {
+    liveness: Arc<SensorLiveness>,
+
+    // How long each sensor can go without a reading, going by its own sampling period
+    limits: Vec<Duration>,
+    speedup: u64,
+    name: Option<String>,
+
+    // Whether each sensor was stalled at the last check, so it's only logged once
+    stalled: Vec<bool>,
+}
+
+impl Watchdog {
+    pub(crate) fn new(
+        liveness: Arc<SensorLiveness>,
+        config: &SimulationConfig,
+        name: Option<String>,
+    ) -> Watchdog {
+        let sensor_count = liveness.sensor_count();
+
+        Watchdog {
+            liveness,
+            limits: (0..sensor_count)
+                .map(|sensor_id| {
+                    config
+                        .scaled_sampling_period_for(sensor_id)
+                        .mul_f64(config.watchdog.stall_periods)
+                })
+                .collect(),
+            speedup: config.speedup,
+            name,
+            stalled: vec![false; sensor_count],
+        }
+    }
+
+    pub(crate) fn check(&mut self, now: Instant) -> Vec<StalledSensor> {
+        let mut stalled_sensors = Vec::new();
+
+        for sensor_id in 0..self.stalled.len() {
+            let reason = self.reason(sensor_id, now);
+
+            match (&reason, self.stalled[sensor_id]) {
+                (Some(reason), false) => {
+                    tracing::warn!(sensor_id, ?reason, "sensor stalled");
+                    eprintln!(
+                        "{}Sensor {} {}",
+                        self.prefix(),
+                        sensor_id,
+                        match reason {
+                            StallReason::Silent { minutes } =>
+                                format!("hasn't sent a reading for {:.1} minutes", minutes),
+                            StallReason::Panicked => "panicked".to_string(),
+                            StallReason::Restarted => "panicked and was restarted".to_string(),
+                        }
+                    );
+                }
+                (None, true) => {
+                    tracing::info!(sensor_id, "sensor recovered");
+                    eprintln!(
+                        "{}Sensor {} is sending readings again",
+                        self.prefix(),
+                        sensor_id
+                    );
+                }
+                _ => {}
+            }
+
+            self.stalled[sensor_id] = reason.is_some();
+            if let Some(reason) = reason {
+                stalled_sensors.push(StalledSensor { sensor_id, reason });
+            }
+        }
+
+        stalled_sensors
+    }
+
+    fn reason(&self, sensor_id: usize, now: Instant) -> Option<StallReason> {
+        // A sensor that was told to stop (e.g. retired) isn't expected to send anything
+        if self.liveness.stopped_at[sensor_id].load(Ordering::Relaxed) != u64::MAX {
+            return None;
+        }
+
+        if self.liveness.panicked[sensor_id].load(Ordering::Relaxed) {
+            return Some(StallReason::Panicked);
+        }
+
+        // A restart is only reported once
+        if self.liveness.restarted[sensor_id].swap(false, Ordering::Relaxed) {
+            return Some(StallReason::Restarted);
+        }
+
+        let last_reading = self.liveness.start
+            + Duration::from_nanos(self.liveness.last_reading[sensor_id].load(Ordering::Relaxed));
+        let silent = now.saturating_duration_since(last_reading);
+
+        (silent > self.limits[sensor_id]).then(|| StallReason::Silent {
+            minutes: silent.as_secs_f64() * self.speedup as f64 / 60.0,
+        })
+    }
+
+    fn prefix(&self) -> String {
+        self.name
+            .as_ref()
+            .map_or_else(String::new, |name| format!("{}: ", name))
+    }
+}
@@ -0,0 +1,212 @@
+use std::io;
+use std::str::FromStr;
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::thread::{sleep, spawn, JoinHandle};
+use std::time::Duration;
+
+use ureq::http::Uri;
+use ureq::Agent;
+
+use super::{Report, ReportSink, TemperatureUnit};
+
+/// How many reports can be waiting to be posted before new ones are dropped.
+const QUEUE_CAPACITY: usize = 64;
+
+/// How many times posting a report is tried before it's given up on.
+const ATTEMPTS: u32 = 3;
+
+/// What the body of each POST is.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WebhookFormat {
+    /// The report as the same JSON the `JsonSink` writes, for a collector service
+    #[default]
+    Json,
+
+    /// A one-line summary of the report as a Slack incoming webhook message (`{"text": ...}`)
+    Slack,
+
+    /// A one-line summary of the report as a Discord webhook message (`{"content": ...}`)
+    Discord,
+}
+
+impl FromStr for WebhookFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(WebhookFormat::Json),
+            "slack" => Ok(WebhookFormat::Slack),
+            "discord" => Ok(WebhookFormat::Discord),
+            _ => Err(format!(
+                "unknown webhook format `{}`, expected json, slack or discord",
+                s
+            )),
+        }
+    }
+}
+
+/// POSTs every report to a URL as it's generated, so the simulation can be wired to a chat
+/// channel or a collector service.
+///
+/// Reports are posted from a background thread, so a slow or unreachable endpoint doesn't hold
+/// up the other sinks. A failed post is retried a couple of times before it's given up on, and
+/// reports that don't fit in the queue while the endpoint is struggling are dropped.
+pub struct WebhookSink {
+    format: WebhookFormat,
+    unit: TemperatureUnit,
+    sender: Option<SyncSender<(usize, Vec<u8>)>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl WebhookSink {
+    /// Starts the thread that posts to `url`. Fails if `url` isn't an http or https URL.
+    pub fn new(url: &str, format: WebhookFormat, unit: TemperatureUnit) -> io::Result<WebhookSink> {
+        let uri: Uri = url
+            .parse()
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+        if !matches!(uri.scheme_str(), Some("http" | "https")) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the URL has to start with http:// or https://",
+            ));
+        }
+
+        let agent: Agent = Agent::config_builder()
+            .timeout_global(Some(Duration::from_secs(10)))
+            .build()
+            .into();
+        let (sender, receiver) = mpsc::sync_channel(QUEUE_CAPACITY);
+        let handle = spawn(move || post_reports(agent, uri, receiver));
+
+        Ok(WebhookSink {
+            format,
+            unit,
+            sender: Some(sender),
+            handle: Some(handle),
+        })
+    }
+
+    fn body(&self, report: &Report) -> serde_json::Result<Vec<u8>> {
+        match self.format {
+            WebhookFormat::Json => serde_json::to_vec(report),
+            WebhookFormat::Slack => {
+                serde_json::to_vec(&serde_json::json!({ "text": summary(report, self.unit) }))
+            }
+            WebhookFormat::Discord => {
+                serde_json::to_vec(&serde_json::json!({ "content": summary(report, self.unit) }))
+            }
+        }
+    }
+}
+
+impl ReportSink for WebhookSink {
+    fn handle_report(&mut self, report: &Report) {
+        let body = match self.body(report) {
+            Ok(body) => body,
+            Err(err) => {
+                eprintln!("Couldn't serialize report {}: {}", report.index + 1, err);
+                return;
+            }
+        };
+
+        if let Some(sender) = &self.sender {
+            if let Err(TrySendError::Full(_)) = sender.try_send((report.index, body)) {
+                eprintln!(
+                    "Report {} wasn't posted, the webhook is too far behind",
+                    report.index + 1
+                );
+            }
+        }
+    }
+}
+
+impl Drop for WebhookSink {
+    /// Waits for the reports that are still queued to be posted.
+    fn drop(&mut self) {
+        self.sender.take();
+
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn post_reports(agent: Agent, uri: Uri, receiver: Receiver<(usize, Vec<u8>)>) {
+    for (index, body) in receiver {
+        for attempt in 1..=ATTEMPTS {
+            let posted = agent
+                .post(&uri)
+                .header("Content-Type", "application/json")
+                .send(&body[..]);
+
+            match posted {
+                Ok(_) => break,
+                // The endpoint turning the report down won't change by trying again
+                Err(ureq::Error::StatusCode(status))
+                    if (400..500).contains(&status) && status != 429 =>
+                {
+                    eprintln!(
+                        "Couldn't post report {} to the webhook: it answered {}",
+                        index + 1,
+                        status
+                    );
+                    break;
+                }
+                Err(err) if attempt == ATTEMPTS => {
+                    eprintln!("Couldn't post report {} to the webhook: {}", index + 1, err);
+                }
+                Err(_) => sleep(Duration::from_secs(attempt as u64)),
+            }
+        }
+    }
+}
+
+/// One line saying what a chat channel most needs to know about the report.
+fn summary(report: &Report, unit: TemperatureUnit) -> String {
+    let mut summary = format!("Report {} (hour {})", report.index + 1, report.hour());
+    if let Some(rover) = &report.rover {
+        summary += &format!(" from {}", rover);
+    }
+    if report.partial {
+        summary += " (partial)";
+    }
+
+    if let (Some(lowest), Some(highest)) =
+        (report.lowest_temps.first(), report.highest_temps.first())
+    {
+        summary += &format!(
+            ": lowest {}, highest {}",
+            lowest.temperature.display(unit),
+            highest.temperature.display(unit)
+        );
+        if let Some(largest) = &report.largest_temp_difference {
+            summary += &format!(
+                ", largest difference within {} minutes {:.1}{}",
+                report.difference_window_minutes,
+                unit.convert_difference(largest.difference),
+                unit.symbol()
+            );
+        }
+    } else {
+        summary += ": no readings received";
+    }
+
+    let mut problems = Vec::new();
+    if report.alert_count > 0 {
+        problems.push(format!("{} alerts", report.alert_count));
+    }
+    if !report.suspect_sensors.is_empty() {
+        problems.push(format!("{} suspect sensors", report.suspect_sensors.len()));
+    }
+    if !report.stalled_sensors.is_empty() {
+        problems.push(format!("{} stalled sensors", report.stalled_sensors.len()));
+    }
+    if report.missed_readings > 0 {
+        problems.push(format!("{} missed readings", report.missed_readings));
+    }
+    if !problems.is_empty() {
+        summary += &format!(". {}", problems.join(", "));
+    }
+
+    summary
+}
@@ -0,0 +1,107 @@
+//! Alert rules fire once when their condition starts, again once it's cleared and started again,
+//! and every alert they raise is counted in the report under the rule's name.
+
+use std::time::{Duration, Instant};
+
+use assignment3::temperature::{
+    AlertRule, AlertRuleConfig, Recording, Report, SimulationConfig, Temperature,
+};
+
+fn config() -> SimulationConfig {
+    let mut config = SimulationConfig {
+        sensor_count: 2,
+        alert_rules: vec![
+            AlertRuleConfig {
+                name: "hot".to_string(),
+                rule: AlertRule::Above {
+                    degrees: 60.0,
+                    minutes: 3.0,
+                },
+            },
+            AlertRuleConfig {
+                name: "swing".to_string(),
+                rule: AlertRule::Swing {
+                    degrees: 80.0,
+                    minutes: 60.0,
+                },
+            },
+        ],
+        ..SimulationConfig::default()
+    };
+    config.suspect_sensors.enabled = false;
+    config
+}
+
+/// An hour of readings from each sensor, one a minute, with the temperature for each minute.
+fn readings(config: &SimulationConfig, temperatures: [fn(u64) -> f64; 2]) -> Vec<Recording> {
+    let start = Instant::now();
+
+    (0..60)
+        .flat_map(|minute| {
+            temperatures
+                .iter()
+                .enumerate()
+                .map(move |(sensor_id, temperature)| Recording {
+                    sensor_id,
+                    temperature: Temperature::from_fahrenheit(temperature(minute)),
+                    sequence: minute,
+                    timestamp: start + config.scaled(Duration::from_secs(minute * 60)),
+                })
+        })
+        .collect()
+}
+
+#[test]
+fn rules_fire_once_per_streak() {
+    let config = config();
+
+    // Sensor 0 is hot for 5 minutes, cools off, then is hot for 4 more. Sensor 1 is only ever hot
+    // for 2 minutes at a time
+    let sensor_0 = |minute| match minute {
+        10..=14 | 30..=33 => 65.0,
+        _ => 20.0,
+    };
+    let sensor_1 = |minute| match minute {
+        40..=41 => 65.0,
+        _ => 0.0,
+    };
+    let report = Report::generate(0, readings(&config, [sensor_0, sensor_1]), &config).unwrap();
+
+    assert_eq!(report.rule_alerts.get("hot"), Some(&2));
+    assert_eq!(report.rule_alerts.get("swing"), None);
+    assert_eq!(report.sensor_stats[0].alerts, 2);
+    assert_eq!(report.sensor_stats[1].alerts, 0);
+    assert_eq!(report.alert_count, 2);
+}
+
+#[test]
+fn swing_across_sensors_fires_once() {
+    let config = config();
+
+    let sensor_0 = |minute| if minute >= 20 { 40.0 } else { 0.0 };
+    let sensor_1 = |_| -50.0;
+    let report = Report::generate(0, readings(&config, [sensor_0, sensor_1]), &config).unwrap();
+
+    assert_eq!(report.rule_alerts.get("swing"), Some(&1));
+    assert_eq!(report.sensor_stats[0].alerts, 1);
+    assert_eq!(report.alert_count, 1);
+}
+
+#[test]
+fn rules_sharing_a_name_are_rejected() {
+    let toml = r#"
+        [[alert_rule]]
+        name = "hot"
+        kind = "above"
+        degrees = 60
+        minutes = 3
+
+        [[alert_rule]]
+        name = "hot"
+        kind = "swing"
+        degrees = 80
+        minutes = 60
+    "#;
+    let err = SimulationConfig::from_toml_str(toml).unwrap_err();
+    assert!(err.to_string().contains("alert_rule[1].name"), "{}", err);
+}
@@ -0,0 +1,51 @@
+//! Closures registered with `on_report`, `on_reading` and `on_alert` see the same events a sink
+//! would.
+
+use std::sync::{Arc, Mutex};
+
+use assignment3::temperature::{AnomalyConfig, SimulationConfig, VirtualClock};
+use assignment3::TemperatureSimulation;
+
+#[test]
+fn callbacks_see_every_event() {
+    let config = SimulationConfig {
+        seed: Some(11),
+        hours: Some(2),
+        anomaly: Some(AnomalyConfig {
+            threshold: 20.0,
+            ..AnomalyConfig::default()
+        }),
+        ..SimulationConfig::default()
+    };
+
+    let mut simulation = TemperatureSimulation::with_config(config);
+    simulation.set_clock(Arc::new(VirtualClock::new()));
+
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let readings = Arc::new(Mutex::new(0));
+    let alerts = Arc::new(Mutex::new(0));
+
+    let seen = reports.clone();
+    simulation.on_report(move |report| seen.lock().unwrap().push(report.index));
+    let seen = readings.clone();
+    simulation.on_reading(move |_| *seen.lock().unwrap() += 1);
+    let seen = alerts.clone();
+    simulation.on_alert(move |_| *seen.lock().unwrap() += 1);
+
+    simulation.start();
+    simulation.wait().unwrap();
+
+    let generated = simulation.all_reports();
+    assert_eq!(*reports.lock().unwrap(), vec![0, 1]);
+
+    let in_reports: usize = generated
+        .iter()
+        .flat_map(|x| x.sensor_stats.iter())
+        .map(|x| x.readings)
+        .sum();
+    assert!(*readings.lock().unwrap() >= in_reports);
+
+    let alert_count: usize = generated.iter().map(|x| x.alert_count).sum();
+    assert!(alert_count > 0);
+    assert!(*alerts.lock().unwrap() >= alert_count);
+}
@@ -0,0 +1,90 @@
+//! Skewed sensor clocks move the readings' timestamps, and correcting for the skew puts them back
+//! to when the readings were taken.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use assignment3::temperature::{
+    ClockSkewConfig, Recording, Report, ReportSink, SimulationConfig, VirtualClock,
+};
+use assignment3::TemperatureSimulation;
+
+#[derive(Clone, Default)]
+struct RecordingSink {
+    start: Arc<Mutex<Option<Instant>>>,
+    recordings: Arc<Mutex<Vec<Recording>>>,
+}
+
+impl ReportSink for RecordingSink {
+    fn handle_start(&mut self, start: Instant) {
+        *self.start.lock().unwrap() = Some(start);
+    }
+
+    fn handle_recording(&mut self, recording: &Recording) {
+        self.recordings.lock().unwrap().push(recording.clone());
+    }
+
+    fn handle_report(&mut self, _report: &Report) {}
+}
+
+fn config(correct: bool) -> SimulationConfig {
+    SimulationConfig {
+        seed: Some(42),
+        hours: Some(1),
+        clock_skew: ClockSkewConfig {
+            max_offset: 5.0,
+            correct,
+        },
+        ..SimulationConfig::default()
+    }
+}
+
+/// Runs on a virtual clock, where every reading is taken right when it's due, and returns how far
+/// each reading's timestamp is from when it was due in simulated seconds.
+fn run(config: &SimulationConfig) -> (Report, Vec<f64>) {
+    let sink = RecordingSink::default();
+    let mut simulation = TemperatureSimulation::with_config(config.clone());
+    simulation.set_clock(Arc::new(VirtualClock::new()));
+    simulation.add_sink(sink.clone());
+
+    simulation.start();
+    simulation.wait().unwrap();
+
+    let start = sink.start.lock().unwrap().unwrap();
+    let offsets = sink
+        .recordings
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|recording| {
+            let due = start + config.scaled_sampling_period() * recording.sequence as u32;
+            let seconds = |x: Duration| x.as_secs_f64() * config.speedup as f64;
+            match recording.timestamp.checked_duration_since(due) {
+                Some(late) => seconds(late),
+                None => -seconds(due - recording.timestamp),
+            }
+        })
+        .collect();
+
+    (simulation.all_reports()[0].clone(), offsets)
+}
+
+#[test]
+fn skewed_timestamps_are_off_by_up_to_the_max_offset() {
+    let (report, offsets) = run(&config(false));
+
+    assert!(offsets.iter().all(|x| x.abs() <= 5.0 + 1e-6));
+    assert!(offsets.iter().any(|x| x.abs() > 1.0));
+    assert!(report.sensor_stats.iter().all(|x| x.clock_offset.is_none()));
+}
+
+#[test]
+fn corrected_timestamps_are_back_on_time() {
+    let (report, offsets) = run(&config(true));
+
+    assert!(offsets.iter().all(|x| x.abs() < 1e-3), "{:?}", offsets);
+    for stats in report.sensor_stats.iter() {
+        let offset = stats.clock_offset.unwrap();
+        assert!(offset.abs() <= 5.0 + 1e-6);
+    }
+}
@@ -0,0 +1,104 @@
+//! Decimation thins out the readings that go into a report without counting the rest as missed.
+
+use std::time::{Duration, Instant};
+
+use assignment3::temperature::{
+    DecimationConfig, Recording, ReportAccumulator, SimulationConfig, Temperature,
+};
+
+fn recording(sensor_id: usize, sequence: u64, fahrenheit: f64, timestamp: Instant) -> Recording {
+    Recording {
+        sensor_id,
+        temperature: Temperature::from_fahrenheit(fahrenheit),
+        sequence,
+        timestamp,
+    }
+}
+
+#[test]
+fn every_nth_reading_goes_into_the_report() {
+    let config = SimulationConfig {
+        sensor_count: 2,
+        top_n: 3,
+        decimation: DecimationConfig::Every { n: 10 },
+        ..SimulationConfig::default()
+    };
+    let start = Instant::now();
+    let mut accumulator = ReportAccumulator::new(&config);
+    accumulator.start_period(start);
+    for sequence in 0..60 {
+        for sensor_id in 0..2 {
+            let timestamp = start + Duration::from_millis(sequence);
+            accumulator.push_decimated(&recording(sensor_id, sequence, sequence as f64, timestamp));
+        }
+    }
+    for sensor_id in 0..2 {
+        accumulator.count_missed(sensor_id, 0..60);
+    }
+
+    let report = accumulator.finish(0, &config).unwrap();
+    assert_eq!(report.decimated_readings, 108);
+    assert_eq!(report.missed_readings, 0);
+    for stats in report.sensor_stats.iter() {
+        assert_eq!(stats.readings, 6);
+    }
+
+    // Only readings 0, 10, ..., 50 are left
+    assert_eq!(report.summary.as_ref().unwrap().mean.fahrenheit(), 25.0);
+    assert_eq!(report.highest_temps[0].temperature.fahrenheit(), 50.0);
+}
+
+#[test]
+fn min_max_keeps_each_minutes_extremes() {
+    let config = SimulationConfig {
+        sensor_count: 1,
+        top_n: 1,
+        decimation: DecimationConfig::MinMax,
+        ..SimulationConfig::default()
+    };
+    let minute = config.scaled_minute();
+    let start = Instant::now();
+    let mut accumulator = ReportAccumulator::new(&config);
+    accumulator.start_period(start);
+
+    // Ten readings a minute for three minutes, swinging further each minute
+    let mut sequence = 0;
+    for minute_index in 0..3u32 {
+        for step in 0..10u32 {
+            let timestamp = start + minute * minute_index + minute / 10 * step;
+            let swing = (minute_index + 1) as f64 * 10.0;
+            let fahrenheit = if step % 2 == 0 { swing } else { -swing } * step as f64 / 9.0;
+            accumulator.push_decimated(&recording(0, sequence, fahrenheit, timestamp));
+            sequence += 1;
+        }
+    }
+    accumulator.count_missed(0, 0..sequence);
+
+    let report = accumulator.finish(0, &config).unwrap();
+    assert_eq!(report.sensor_stats[0].readings, 6);
+    assert_eq!(report.decimated_readings, 24);
+    assert_eq!(report.missed_readings, 0);
+
+    // The whole period's extremes are among each minute's
+    let highest = 30.0 * 8.0 / 9.0;
+    let lowest = -30.0;
+    assert_eq!(report.highest_temps[0].temperature.fahrenheit(), highest);
+    assert_eq!(report.lowest_temps[0].temperature.fahrenheit(), lowest);
+}
+
+#[test]
+fn decimation_is_read_from_the_config_and_flags() {
+    let config =
+        SimulationConfig::from_toml_str("decimation = { kind = \"every\", n = 5 }").unwrap();
+    assert_eq!(config.decimation, DecimationConfig::Every { n: 5 });
+
+    let config = SimulationConfig::from_toml_str("decimation = { kind = \"min_max\" }").unwrap();
+    assert_eq!(config.decimation, DecimationConfig::MinMax);
+
+    assert!(SimulationConfig::from_toml_str("decimation = { kind = \"every\", n = 0 }").is_err());
+
+    assert_eq!("every:3".parse(), Ok(DecimationConfig::Every { n: 3 }));
+    assert_eq!("min-max".parse(), Ok(DecimationConfig::MinMax));
+    assert_eq!("off".parse(), Ok(DecimationConfig::Off));
+    assert!("every:lots".parse::<DecimationConfig>().is_err());
+}
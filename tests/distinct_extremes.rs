@@ -0,0 +1,87 @@
+//! With `distinct_extremes` the top lists hold distinct temperatures, each with how many readings
+//! hit it, even when most readings share a handful of values.
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use assignment3::temperature::{Recording, Report, SimulationConfig, Temperature};
+
+/// Readings to a tenth of a degree between -10°F and 10°F, so every temperature comes up many
+/// times. Enough of them that the `parallel` feature splits them into chunks.
+fn readings(config: &SimulationConfig) -> Vec<Recording> {
+    let start = Instant::now();
+
+    (0..10_000u64)
+        .map(|index| {
+            let tenths = (index * 7919 % 201) as f64 - 100.0;
+            let sensor_id = index as usize % config.sensor_count;
+            let mut recording =
+                Recording::new(sensor_id, Temperature::from_fahrenheit(tenths / 10.0));
+            recording.sequence = index / config.sensor_count as u64;
+            recording.timestamp = start + Duration::from_millis(index);
+            recording
+        })
+        .collect()
+}
+
+fn config(distinct_extremes: bool) -> SimulationConfig {
+    let mut config = SimulationConfig {
+        distinct_extremes,
+        ..SimulationConfig::default()
+    };
+    config.suspect_sensors.enabled = false;
+    config
+}
+
+#[test]
+fn distinct_extremes_are_counted() {
+    let config = config(true);
+    let readings = readings(&config);
+
+    let mut counts: BTreeMap<i64, usize> = BTreeMap::new();
+    for recording in readings.iter() {
+        *counts
+            .entry((recording.temperature.fahrenheit() * 10.0).round() as i64)
+            .or_default() += 1;
+    }
+
+    let report = Report::generate(0, readings, &config).unwrap();
+    let listed = |recordings: &[Recording]| -> Vec<i64> {
+        recordings
+            .iter()
+            .map(|x| (x.temperature.fahrenheit() * 10.0).round() as i64)
+            .collect()
+    };
+
+    let lowest: Vec<(i64, usize)> = counts.iter().take(5).map(|(t, c)| (*t, *c)).collect();
+    let highest: Vec<(i64, usize)> = counts.iter().rev().take(5).map(|(t, c)| (*t, *c)).collect();
+
+    assert_eq!(
+        listed(&report.lowest_temps),
+        lowest.iter().map(|x| x.0).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        report.lowest_temp_counts,
+        lowest.iter().map(|x| x.1).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        listed(&report.highest_temps),
+        highest.iter().map(|x| x.0).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        report.highest_temp_counts,
+        highest.iter().map(|x| x.1).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn repeated_temperatures_fill_the_list_without_the_option() {
+    let config = config(false);
+    let report = Report::generate(0, readings(&config), &config).unwrap();
+
+    assert!(report
+        .lowest_temps
+        .iter()
+        .all(|x| x.temperature.fahrenheit() == -10.0));
+    assert!(report.lowest_temp_counts.is_empty() && report.highest_temp_counts.is_empty());
+}
@@ -0,0 +1,87 @@
+//! Reports can be written out as Markdown or HTML documents with their lists as tables.
+
+use std::time::Instant;
+
+use assignment3::temperature::{
+    DocumentFormat, DocumentSink, Recording, Report, ReportSink, SimulationConfig, Temperature,
+    TemperatureUnit,
+};
+
+fn report() -> Report {
+    let config = SimulationConfig {
+        sensor_count: 2,
+        ..SimulationConfig::default()
+    };
+    let start = Instant::now();
+    let readings = (0..120u64)
+        .map(|index| {
+            let fahrenheit = (index * 37 % 170) as f64 - 100.0;
+            let mut recording =
+                Recording::new(index as usize % 2, Temperature::from_fahrenheit(fahrenheit));
+            recording.sequence = index / 2;
+            recording.timestamp = start + config.scaled_minute() * (index / 2) as u32;
+            recording
+        })
+        .collect();
+
+    let mut report = Report::generate(0, readings, &config).unwrap();
+    report.rover = Some("<rover-1>".to_string());
+    report
+}
+
+#[test]
+fn markdown_has_a_table_per_list() {
+    let report = report();
+    let markdown = report.to_document(TemperatureUnit::Fahrenheit, DocumentFormat::Markdown);
+
+    assert!(markdown.starts_with("# <rover-1>: Report 1 (hour 0)\n"));
+    assert!(markdown.contains("## Top 5 lowest temps\n\n| # | Temperature | Sensor | Time |\n|---|---|---|---|\n| 1 | -100.0°F |"));
+    assert!(markdown.contains("## Top 5 highest temps"));
+    assert!(markdown.contains("## Per-sensor statistics"));
+
+    // One row per sensor, after the header and the separator
+    let sensor_rows = markdown
+        .split("## Per-sensor statistics")
+        .nth(1)
+        .unwrap()
+        .lines()
+        .filter(|x| x.starts_with("| ") && !x.starts_with("| Sensor"))
+        .count();
+    assert_eq!(sensor_rows, 2);
+}
+
+#[test]
+fn html_is_escaped() {
+    let report = report();
+    let html = report.to_document(TemperatureUnit::Celsius, DocumentFormat::Html);
+
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<h1>&lt;rover-1&gt;: Report 1 (hour 0)</h1>"));
+    assert_eq!(html.matches("<table>").count(), 5);
+    assert!(html.contains("°C"));
+    assert!(html.trim_end().ends_with("</html>"));
+
+    assert_eq!("md".parse(), Ok(DocumentFormat::Markdown));
+    assert_eq!("HTML".parse(), Ok(DocumentFormat::Html));
+    assert!("pdf".parse::<DocumentFormat>().is_err());
+}
+
+#[test]
+fn sink_writes_a_file_per_report_and_format() {
+    let dir = std::env::temp_dir().join(format!("temperature-documents-{}", std::process::id()));
+    let mut sink = DocumentSink::new(&dir, TemperatureUnit::Fahrenheit, true).unwrap();
+    let mut report = report();
+    sink.handle_report(&report);
+    report.index = 1;
+    sink.handle_report(&report);
+
+    for name in [
+        "report-001.md",
+        "report-001.html",
+        "report-002.md",
+        "report-002.html",
+    ] {
+        assert!(dir.join(name).exists(), "{} is missing", name);
+    }
+    let _ = std::fs::remove_dir_all(&dir);
+}
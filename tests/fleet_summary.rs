@@ -0,0 +1,86 @@
+//! A fleet summary combines each rover's report for a period into the fleet's numbers, checked
+//! here against ones worked out by hand.
+
+use assignment3::temperature::{
+    timestamp, FleetSummary, Recording, Report, SimulationConfig, Temperature,
+};
+
+/// A report for `rover` where each of `sensors` reads `base`, `base + scale`, ...
+/// `base + 4 * scale` over and over for an hour, so its mean is `base + 2 * scale` and its
+/// largest difference `4 * scale`.
+fn report(rover: &str, sensors: usize, base: f64, scale: f64) -> Report {
+    let config = SimulationConfig::default();
+    let start = timestamp::process_epoch();
+    let scaled = |minute| config.scaled_minutes(minute);
+
+    let readings = (0..sensors)
+        .flat_map(|sensor_id| {
+            (0..60).map(move |minute| {
+                let temperature = base + (minute % 5) as f64 * scale;
+                let mut recording =
+                    Recording::new(sensor_id, Temperature::from_fahrenheit(temperature));
+                recording.timestamp = start + scaled(minute);
+                recording
+            })
+        })
+        .collect();
+
+    let mut report = Report::generate(0, readings, &config).unwrap();
+    report.rover = Some(rover.to_string());
+    report
+}
+
+#[test]
+fn summary_combines_every_rovers_report() {
+    let reports = [
+        report("rover-1", 1, -60.0, 1.0),
+        report("rover-2", 2, -20.0, 3.0),
+        report("rover-3", 1, 10.0, 2.0),
+    ];
+
+    let summary = FleetSummary::from_reports(&reports).unwrap();
+
+    // Rover 1 has 60 readings with a mean of -58, rover 2 120 with a mean of -14 and rover 3 60
+    // with a mean of 14, so together (60 * -58 + 120 * -14 + 60 * 14) / 240 = -18
+    assert_eq!(summary.readings, 240);
+    let mean = summary.mean.unwrap().fahrenheit();
+    assert!((mean - -18.0).abs() < 1e-9, "{}", mean);
+
+    let (rover, lowest) = summary.lowest.as_ref().unwrap();
+    assert_eq!(
+        (rover.as_str(), lowest.temperature.fahrenheit()),
+        ("rover-1", -60.0)
+    );
+    let (rover, highest) = summary.highest.as_ref().unwrap();
+    assert_eq!(
+        (rover.as_str(), highest.temperature.fahrenheit()),
+        ("rover-3", 18.0)
+    );
+    assert_eq!(
+        summary.largest_difference,
+        Some(("rover-2".to_string(), 12.0))
+    );
+
+    let rovers: Vec<_> = summary
+        .rovers
+        .iter()
+        .map(|x| (x.rover.as_str(), x.readings, x.largest_difference))
+        .collect();
+    assert_eq!(
+        rovers,
+        [
+            ("rover-1", 60, Some(4.0)),
+            ("rover-2", 120, Some(12.0)),
+            ("rover-3", 60, Some(8.0)),
+        ]
+    );
+    assert_eq!(
+        (summary.index, summary.hour, summary.partial),
+        (0, 0, false)
+    );
+}
+
+#[test]
+fn no_reports_means_no_summary() {
+    assert!(FleetSummary::from_reports(&[]).is_none());
+}
@@ -0,0 +1,77 @@
+//! Sensors sampling many times a second keep up, with the report thread taking the queued readings
+//! off in batches.
+
+use std::sync::Arc;
+
+use assignment3::temperature::{
+    sensor_channels, Recording, SimulationConfig, SystemClock, Temperature, Transport, VirtualClock,
+};
+use assignment3::TemperatureSimulation;
+
+#[test]
+fn queued_readings_are_drained_in_batches() {
+    for transport in [Transport::Ring, Transport::Mpsc] {
+        let (mut senders, mut receiver) = sensor_channels(transport, 2, 64, Arc::new(SystemClock));
+        for sequence in 0..10 {
+            for (sensor_id, sender) in senders.iter_mut().enumerate() {
+                let mut recording = Recording::new(sensor_id, Temperature::from_fahrenheit(0.0));
+                recording.sequence = sequence;
+                sender.try_send(recording).unwrap();
+            }
+        }
+
+        let mut batch = Vec::with_capacity(8);
+        assert_eq!(receiver.drain_into(&mut batch, 8), 8);
+        assert_eq!(receiver.drain_into(&mut batch, 100), 12);
+        assert_eq!(receiver.drain_into(&mut batch, 100), 0);
+        assert_eq!(batch.len(), 20);
+
+        // Each sensor's readings stay in order, and the rings are taken from in turn
+        for sensor_id in 0..2 {
+            let sequences: Vec<u64> = batch
+                .iter()
+                .filter(|x| x.sensor_id == sensor_id)
+                .map(|x| x.sequence)
+                .collect();
+            assert_eq!(sequences, (0..10).collect::<Vec<_>>(), "{:?}", transport);
+        }
+        if transport == Transport::Ring {
+            assert_eq!(batch[0].sensor_id, 0);
+            assert_eq!(batch[1].sensor_id, 1);
+        }
+    }
+}
+
+#[test]
+fn every_reading_is_kept_at_a_tenth_of_a_second() {
+    let config = SimulationConfig {
+        seed: Some(7),
+        hours: Some(1),
+        sensor_count: 2,
+        sampling_period_seconds: 0.1,
+        high_frequency: true,
+        ingest_batch: 256,
+        ..SimulationConfig::default()
+    };
+    let mut simulation = TemperatureSimulation::with_config(config.clone());
+    simulation.set_clock(Arc::new(VirtualClock::new()));
+
+    simulation.start();
+    simulation.wait().unwrap();
+
+    let report = &simulation.all_reports()[0];
+    assert_eq!(report.missed_readings, 0);
+    let readings: usize = report.sensor_stats.iter().map(|x| x.readings).sum();
+    assert_eq!(readings, config.readings_per_report());
+    assert_eq!(readings, 2 * 36_000);
+}
+
+#[test]
+fn ingest_batch_is_read_from_the_config() {
+    let config =
+        SimulationConfig::from_toml_str("high_frequency = true\ningest_batch = 4096").unwrap();
+    assert!(config.high_frequency);
+    assert_eq!(config.ingest_batch, 4096);
+
+    assert!(SimulationConfig::from_toml_str("ingest_batch = 0").is_err());
+}
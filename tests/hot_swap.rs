@@ -0,0 +1,109 @@
+//! Sensors can be retired and added while a simulation runs, and a sensor only owes readings
+//! while it's in its slot. A rover with every slot empty still reports its hours.
+
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use assignment3::temperature::SimulationConfig;
+use assignment3::TemperatureSimulation;
+
+fn config() -> SimulationConfig {
+    SimulationConfig {
+        seed: Some(42),
+        hours: Some(1),
+        speedup: 3_600,
+        ..SimulationConfig::default()
+    }
+}
+
+#[test]
+fn retired_sensor_stops_owing_readings() {
+    let mut simulation = TemperatureSimulation::with_config(config());
+    let swap = simulation.hot_swap_handle();
+
+    // Nothing to retire before the start
+    assert!(!swap.retire(2));
+
+    simulation.start();
+    sleep(Duration::from_millis(300));
+    assert!(swap.retire(2));
+    assert!(!swap.retire(2));
+    assert!(!swap.installed().contains(&2));
+    simulation.wait().unwrap();
+
+    let report = &simulation.all_reports()[0];
+    let retired = &report.sensor_stats[2];
+    assert!(retired.readings > 0 && retired.readings < 50);
+    assert_eq!(retired.missed, 0);
+    assert_eq!(report.missed_readings, 0);
+    for stats in report.sensor_stats.iter().filter(|x| x.sensor_id != 2) {
+        assert_eq!(stats.readings, 60);
+    }
+}
+
+#[test]
+fn sensor_can_be_added_to_an_empty_slot() {
+    let mut simulation = TemperatureSimulation::with_config(config());
+    let swap = simulation.hot_swap_handle();
+
+    simulation.start();
+
+    // Every slot has a sensor in it to begin with
+    assert_eq!(swap.installed(), (0..8).collect::<Vec<_>>());
+    assert_eq!(swap.add(), None);
+    assert!(!swap.retire(8));
+
+    sleep(Duration::from_millis(200));
+    assert!(swap.retire(5));
+
+    // The slot's only empty once the sensor's thread has finished with it
+    sleep(Duration::from_millis(200));
+    assert!(!swap.add_at(4));
+    assert_eq!(swap.add(), Some(5));
+    assert_eq!(swap.installed().len(), 8);
+    simulation.wait().unwrap();
+
+    let report = &simulation.all_reports()[0];
+    let swapped = &report.sensor_stats[5];
+    assert!(swapped.readings > 0 && swapped.readings < 60);
+    assert_eq!(swapped.missed, 0);
+
+    // No more can be added once the simulation's finished
+    assert!(!swap.retire(0));
+    assert_eq!(swap.add(), None);
+}
+
+#[test]
+fn rover_without_sensors_keeps_reporting_until_one_is_added() {
+    let config = SimulationConfig {
+        hours: Some(3),
+        ..config()
+    };
+    let mut simulation = TemperatureSimulation::with_config(config);
+    let swap = simulation.hot_swap_handle();
+
+    // Every sensor is out from early in the first hour until early in the third, so the second
+    // hour has no sensors at all
+    let started = Instant::now();
+    simulation.start();
+    sleep(Duration::from_millis(200));
+    for sensor_id in 0..8 {
+        assert!(swap.retire(sensor_id));
+    }
+    sleep(Duration::from_millis(2_200).saturating_sub(started.elapsed()));
+    assert_eq!(swap.add(), Some(0));
+    simulation.wait().unwrap();
+
+    let reports = simulation.all_reports();
+    assert_eq!(reports.len(), 3);
+
+    let empty = &reports[1];
+    assert!(empty.summary.is_none());
+    assert_eq!(empty.missed_readings, 0);
+    assert!(empty.sensor_stats.iter().all(|x| x.readings == 0));
+
+    let added = &reports[2].sensor_stats[0];
+    assert!(added.readings > 0 && added.readings < 60);
+    assert_eq!(added.missed, 0);
+    assert!(reports[2].summary.is_some());
+}
@@ -0,0 +1,66 @@
+//! The InfluxDB sink writes a line of line protocol for every reading and every report.
+
+use std::time::{Duration, Instant};
+
+use assignment3::temperature::{
+    InfluxSink, RandomSource, Recording, Report, ReportSink, SensorSource, SimulationConfig,
+};
+
+fn readings(config: &SimulationConfig) -> Vec<Recording> {
+    let start = Instant::now();
+
+    (0..config.sensor_count)
+        .flat_map(|sensor_id| {
+            let mut source = RandomSource::for_sensor(config, sensor_id);
+
+            (0..60).map(move |minute| {
+                let mut recording = source.next_reading().unwrap();
+                recording.sequence = minute;
+                recording.timestamp = start + Duration::from_millis(minute);
+                recording
+            })
+        })
+        .collect()
+}
+
+#[test]
+fn readings_and_reports_are_written_as_line_protocol() {
+    let config = SimulationConfig {
+        seed: Some(42),
+        ..SimulationConfig::default()
+    };
+    let path = std::env::temp_dir().join(format!("assignment3-influx-{}.txt", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let readings = readings(&config);
+    let mut report = Report::generate(0, readings.clone(), &config).unwrap();
+    report.rover = Some("rover 1".to_string());
+
+    let mut sink = InfluxSink::file(&path).unwrap();
+    for recording in readings.iter() {
+        sink.handle_recording(recording);
+    }
+    sink.handle_report(&report);
+    drop(sink);
+
+    let written = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let lines: Vec<&str> = written.lines().collect();
+
+    assert_eq!(lines.len(), readings.len() + 1);
+    assert_eq!(
+        lines[0],
+        format!(
+            "temperature,sensor=0 fahrenheit={},sequence=0i {}",
+            readings[0].temperature.fahrenheit(),
+            readings[0].wall_time().timestamp_nanos_opt().unwrap()
+        )
+    );
+
+    let report_line = lines.last().unwrap();
+    assert!(report_line.starts_with("report,rover=rover\\ 1 index=0i,partial=false,"));
+    assert!(report_line.ends_with(&format!(
+        " {}",
+        report.period_end.timestamp_nanos_opt().unwrap()
+    )));
+}
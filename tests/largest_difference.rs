@@ -0,0 +1,163 @@
+//! Checks the sliding-window search the report pipeline uses for the largest temperature
+//! difference against `find_largest_temp_difference`, which compares every pair of readings.
+
+use std::time::{Duration, Instant};
+
+use assignment3::temperature::{
+    find_largest_temp_difference, Recording, Report, SimulationConfig, Temperature,
+};
+use proptest::prelude::*;
+
+fn config(window_minutes: u64) -> SimulationConfig {
+    let mut config = SimulationConfig {
+        difference_window_minutes: window_minutes,
+        ..SimulationConfig::default()
+    };
+
+    // Suspect sensors are left out of the largest difference, which the brute force search
+    // doesn't know about
+    config.suspect_sensors.enabled = false;
+    config
+}
+
+/// Turns `(sensor, temperature, simulated seconds since the previous reading)` into recordings.
+/// A gap of 0 gives two readings taken at the same moment.
+fn recordings(readings: &[(usize, f64, u64)], config: &SimulationConfig) -> Vec<Recording> {
+    let start = Instant::now();
+    let mut elapsed = Duration::ZERO;
+
+    readings
+        .iter()
+        .map(|&(sensor_id, temperature, gap_seconds)| {
+            elapsed += Duration::from_secs(gap_seconds);
+
+            Recording {
+                sensor_id,
+                temperature: Temperature::from_fahrenheit(temperature),
+                sequence: 0,
+                timestamp: start + config.scaled(elapsed),
+            }
+        })
+        .collect()
+}
+
+fn readings() -> impl Strategy<Value = Vec<(usize, f64, u64)>> {
+    // Sensors read on the minute, so readings exactly a window apart come up often
+    let gap_seconds = prop_oneof![0..=900u64, (0..=15u64).prop_map(|x| x * 60)];
+
+    prop::collection::vec((0..8usize, -100.0..=70.0f64, gap_seconds), 0..300)
+}
+
+proptest! {
+    #[test]
+    fn matches_the_brute_force_search(readings in readings(), window in 1..=30u64) {
+        let config = config(window);
+        let recordings = recordings(&readings, &config);
+
+        let expected = find_largest_temp_difference(&recordings, &config);
+        let report = Report::generate(0, recordings, &config);
+        let largest = report.and_then(|x| x.largest_temp_difference);
+
+        prop_assert_eq!(
+            largest.map(|x| x.difference),
+            expected.map(|x| x.difference)
+        );
+    }
+
+    #[test]
+    fn the_difference_is_within_the_window(readings in readings(), window in 1..=30u64) {
+        let config = config(window);
+        let recordings = recordings(&readings, &config);
+
+        let report = Report::generate(0, recordings, &config);
+        if let Some((largest, [start_minute, end_minute])) = report
+            .as_ref()
+            .and_then(|x| Some((x.largest_temp_difference.as_ref()?, x.largest_difference_minutes?)))
+        {
+
+            prop_assert!(largest.start.timestamp <= largest.end.timestamp);
+            prop_assert!(
+                largest.end.timestamp - largest.start.timestamp
+                    <= config.scaled_difference_window()
+            );
+            prop_assert_eq!(
+                largest.difference,
+                largest.end.temperature.difference(largest.start.temperature)
+            );
+
+            prop_assert!(end_minute - start_minute <= window as f64 + 1e-6);
+        }
+    }
+
+    #[test]
+    fn readings_further_apart_than_the_window_are_never_compared(
+        temperatures in prop::collection::vec(-100.0..=70.0f64, 0..50),
+        window in 1..=30u64,
+    ) {
+        let config = config(window);
+        let gap = window * 60 + 1;
+        let readings: Vec<_> = temperatures.iter().map(|&x| (0, x, gap)).collect();
+
+        let recordings = recordings(&readings, &config);
+        prop_assert!(find_largest_temp_difference(&recordings, &config).is_none());
+        let report = Report::generate(0, recordings, &config);
+        prop_assert!(report.and_then(|x| x.largest_temp_difference).is_none());
+    }
+}
+
+#[test]
+fn no_readings_have_no_difference() {
+    let config = config(10);
+
+    assert!(find_largest_temp_difference(&[], &config).is_none());
+    assert!(Report::generate(0, Vec::new(), &config).is_none());
+}
+
+#[test]
+fn a_single_reading_has_no_difference() {
+    let config = config(10);
+    let recordings = recordings(&[(0, 20.0, 0)], &config);
+
+    assert!(find_largest_temp_difference(&recordings, &config).is_none());
+
+    // There's still a report, just without a difference
+    let report = Report::generate(0, recordings, &config).unwrap();
+    assert!(report.largest_temp_difference.is_none());
+    assert!(report.largest_difference_minutes.is_none());
+    assert_eq!(report.summary.unwrap().mean.fahrenheit(), 20.0);
+}
+
+#[test]
+fn readings_at_the_same_moment_are_compared() {
+    let config = config(10);
+    let recordings = recordings(&[(0, -50.0, 0), (1, 30.0, 0)], &config);
+
+    let expected = find_largest_temp_difference(&recordings, &config).unwrap();
+    let report = Report::generate(0, recordings, &config).unwrap();
+
+    assert_eq!(expected.difference, 80.0);
+    assert_eq!(report.largest_temp_difference.unwrap().difference, 80.0);
+}
+
+// Large batches are split into chunks that are searched in parallel and merged
+#[cfg(feature = "parallel")]
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(16))]
+
+    #[test]
+    fn matches_the_brute_force_search_across_chunks(
+        readings in prop::collection::vec(
+            (0..8usize, -100.0..=70.0f64, prop_oneof![0..=90u64, Just(60u64)]),
+            assignment3::temperature::PARALLEL_CHUNK_SIZE..3 * assignment3::temperature::PARALLEL_CHUNK_SIZE,
+        ),
+        window in 1..=30u64,
+    ) {
+        let config = config(window);
+        let recordings = recordings(&readings, &config);
+
+        let expected = find_largest_temp_difference(&recordings, &config).unwrap();
+        let report = Report::generate(0, recordings, &config).unwrap();
+
+        prop_assert_eq!(report.largest_temp_difference.unwrap().difference, expected.difference);
+    }
+}
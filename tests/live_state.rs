@@ -0,0 +1,42 @@
+//! The live state keeps the current period's extremes and each sensor's last reading as the
+//! readings arrive, without waiting for the report.
+
+use std::time::{Duration, Instant};
+
+use assignment3::temperature::{LiveState, Recording, ReportSink, SimulationConfig, Temperature};
+
+fn recording(sensor_id: usize, sequence: u64, fahrenheit: f64) -> Recording {
+    Recording {
+        sensor_id,
+        sequence,
+        timestamp: Instant::now(),
+        temperature: Temperature::from_fahrenheit(fahrenheit),
+    }
+}
+
+#[test]
+fn extremes_start_again_each_period() {
+    let config = SimulationConfig::default();
+    let live = LiveState::new(&config);
+    let mut sink = live.sink();
+    sink.handle_start(Instant::now() - Duration::from_secs(1));
+
+    assert_eq!(live.lowest(), None);
+    sink.handle_recording(&recording(0, 10, 20.0));
+    sink.handle_recording(&recording(1, 10, -40.0));
+    sink.handle_recording(&recording(2, 11, 65.0));
+    assert_eq!(live.lowest().unwrap().sensor_id, 1);
+    assert_eq!(live.highest().unwrap().sensor_id, 2);
+
+    // The first reading due in the second hour starts its extremes over, and a straggler from the
+    // first hour doesn't count for the second
+    sink.handle_recording(&recording(0, 60, 0.0));
+    sink.handle_recording(&recording(3, 59, -90.0));
+    assert_eq!(live.lowest().unwrap().sequence, 60);
+    assert_eq!(live.highest().unwrap().sequence, 60);
+
+    assert_eq!(live.latest(3).unwrap().sequence, 59);
+    assert_eq!(live.latest(4), None);
+    assert_eq!(live.latest(100), None);
+    assert!(live.last_report().is_none());
+}
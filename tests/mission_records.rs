@@ -0,0 +1,73 @@
+//! A report that breaks one of the run's all-time records says so, and one that doesn't leaves
+//! the records out.
+
+use std::time::{Duration, Instant};
+
+use assignment3::temperature::{
+    RecordKind, Recording, Report, ReportHistory, SimulationConfig, Temperature, TemperatureUnit,
+};
+
+/// A report on readings that alternate between `low` and `high`, a minute apart.
+fn report(index: usize, low: f64, high: f64, config: &SimulationConfig) -> Report {
+    let start = Instant::now();
+    let readings = (0..20u64)
+        .map(|sequence| {
+            let fahrenheit = if sequence % 2 == 0 { low } else { high };
+            let mut recording = Recording::new(0, Temperature::from_fahrenheit(fahrenheit));
+            recording.sequence = sequence;
+            recording.timestamp =
+                start + Duration::from_secs(60 * sequence) / config.speedup as u32;
+            recording
+        })
+        .collect();
+
+    Report::generate(index, readings, config).unwrap()
+}
+
+fn push(history: &mut ReportHistory, mut report: Report) -> Report {
+    report.records = history.records(&report);
+    history.push(report.clone());
+    report
+}
+
+#[test]
+fn broken_records_are_reported() {
+    let config = SimulationConfig {
+        sensor_count: 1,
+        ..SimulationConfig::default()
+    };
+    let mut history = ReportHistory::new();
+
+    // Nothing to break yet
+    let first = push(&mut history, report(0, -50.0, 20.0, &config));
+    assert!(first.records.is_none());
+
+    // Inside the first report's range
+    let second = push(&mut history, report(1, -40.0, 10.0, &config));
+    assert!(second.records.is_none());
+
+    // Colder than ever, but not hotter and not as big a swing
+    let third = push(&mut history, report(2, -60.0, 0.0, &config));
+    let records = third.records.as_ref().unwrap();
+    assert_eq!(records.lowest.temperature.fahrenheit(), -60.0);
+    assert_eq!(records.highest.temperature.fahrenheit(), 20.0);
+    assert_eq!(records.largest_difference.difference, 70.0);
+    assert_eq!(records.previous(RecordKind::Lowest), Some(-50.0));
+    assert_eq!(records.previous(RecordKind::Highest), None);
+    assert_eq!(records.previous(RecordKind::LargestDifference), None);
+
+    let text = third.display(TemperatureUnit::Fahrenheit).to_string();
+    assert!(text.contains("Mission records"));
+    assert!(text.contains("new record (was -50.0°F)"));
+
+    // Hotter and a bigger swing than ever, measured against the third report's records
+    let fourth = push(&mut history, report(3, -55.0, 30.0, &config));
+    let records = fourth.records.as_ref().unwrap();
+    assert_eq!(records.lowest.temperature.fahrenheit(), -60.0);
+    assert_eq!(records.previous(RecordKind::Highest), Some(20.0));
+    assert_eq!(records.previous(RecordKind::LargestDifference), Some(70.0));
+    assert_eq!(records.largest_difference.difference, 85.0);
+
+    let text = second.display(TemperatureUnit::Fahrenheit).to_string();
+    assert!(!text.contains("Mission records"));
+}
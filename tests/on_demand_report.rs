@@ -0,0 +1,70 @@
+//! A report asked for partway through a period covers the period so far, and the period's
+//! regular report comes out the same as if it had never been asked for.
+
+use std::sync::{Arc, Mutex};
+
+use assignment3::temperature::{Recording, Report, ReportSink, ReportTrigger, SimulationConfig};
+use assignment3::TemperatureSimulation;
+
+/// Asks for a report once it's seen `after` readings, and keeps the on-demand reports it's given.
+struct TriggerSink {
+    trigger: ReportTrigger,
+    after: usize,
+    seen: usize,
+    on_demand: Arc<Mutex<Vec<Report>>>,
+}
+
+impl ReportSink for TriggerSink {
+    fn handle_report(&mut self, _report: &Report) {}
+
+    fn handle_recording(&mut self, _recording: &Recording) {
+        self.seen += 1;
+        if self.seen == self.after {
+            self.trigger.trigger();
+        }
+    }
+
+    fn handle_on_demand_report(&mut self, report: &Report) {
+        self.on_demand.lock().unwrap().push(report.clone());
+    }
+}
+
+#[test]
+fn on_demand_report_covers_the_period_so_far() {
+    let config = SimulationConfig {
+        seed: Some(42),
+        hours: Some(1),
+        speedup: 14_400,
+        ..SimulationConfig::default()
+    };
+    let readings_per_hour = config.readings_per_report();
+
+    let mut simulation = TemperatureSimulation::with_config(config);
+    let on_demand = Arc::new(Mutex::new(Vec::new()));
+    simulation.add_sink(TriggerSink {
+        trigger: simulation.report_trigger(),
+        after: readings_per_hour / 2,
+        seen: 0,
+        on_demand: on_demand.clone(),
+    });
+
+    simulation.start();
+    simulation.wait().unwrap();
+
+    let on_demand = on_demand.lock().unwrap();
+    assert_eq!(on_demand.len(), 1);
+    let report = &on_demand[0];
+    assert!(report.on_demand && !report.partial);
+    assert_eq!(report.index, 0);
+
+    let so_far: usize = report.sensor_stats.iter().map(|x| x.readings).sum();
+    assert!(so_far >= readings_per_hour / 2 && so_far < readings_per_hour);
+
+    // The on-demand report isn't one of the run's reports
+    let reports = simulation.all_reports();
+    assert_eq!(reports.len(), 1);
+    assert!(!reports[0].on_demand);
+    let total: usize = reports[0].sensor_stats.iter().map(|x| x.readings).sum();
+    assert_eq!(total, readings_per_hour);
+    assert_eq!(reports[0].missed_readings, 0);
+}
@@ -0,0 +1,72 @@
+//! The `OutDirSink` writes every report and its raw readings to files, keeping only the latest
+//! hours of readings when there's a retention limit.
+
+use std::fs;
+use std::num::NonZeroUsize;
+use std::time::Instant;
+
+use assignment3::temperature::{
+    OutDirSink, Recording, Report, ReportSink, SimulationConfig, Temperature,
+};
+
+#[test]
+fn old_readings_are_rotated_out() {
+    let dir = std::env::temp_dir().join(format!("out-dir-sink-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+
+    let config = SimulationConfig {
+        sensor_count: 2,
+        ..SimulationConfig::default()
+    };
+    let start = Instant::now();
+    let mut sink = OutDirSink::new(&dir, &config, NonZeroUsize::new(2)).unwrap();
+    sink.handle_start(start);
+
+    // Four hours of readings, one a minute from each sensor
+    let readings: Vec<Recording> = (0..480u64)
+        .map(|index| {
+            let mut recording = Recording::new(
+                index as usize % 2,
+                Temperature::from_fahrenheit(index as f64 % 170.0 - 100.0),
+            );
+            recording.sequence = index / 2;
+            recording.timestamp = start + config.scaled_minute() * (index / 2) as u32;
+            recording
+        })
+        .collect();
+
+    for (index, period) in readings.chunks(120).enumerate() {
+        for recording in period {
+            sink.handle_recording(recording);
+        }
+
+        let report = Report::generate(index, period.to_vec(), &config).unwrap();
+        sink.handle_report(&report);
+    }
+
+    for hour in 1..=4 {
+        assert!(dir.join(format!("report-hour-{:03}.txt", hour)).exists());
+        assert!(dir.join(format!("report-hour-{:03}.json", hour)).exists());
+
+        let path = dir.join(format!("readings-hour-{:03}.csv", hour));
+        assert_eq!(path.exists(), hour > 2, "hour {}", hour);
+    }
+
+    // The header and the hour's readings from both sensors
+    let csv = fs::read_to_string(dir.join("readings-hour-004.csv")).unwrap();
+    assert_eq!(csv.lines().count(), 121);
+    assert!(csv.lines().skip(1).all(|x| {
+        let sequence: u64 = x.split(',').nth(1).unwrap().parse().unwrap();
+        (180..240).contains(&sequence)
+    }));
+
+    let text = fs::read_to_string(dir.join("report-hour-001.txt")).unwrap();
+    assert_eq!(
+        text,
+        Report::generate(0, readings[..120].to_vec(), &config)
+            .unwrap()
+            .to_string()
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+}
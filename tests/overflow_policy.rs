@@ -0,0 +1,107 @@
+//! What each `OverflowPolicy` does when a sensor's queue fills up because the report thread has
+//! stalled: which readings are still there for it once it catches up, and how many were counted
+//! as dropped.
+
+use std::sync::Arc;
+
+use assignment3::temperature::{
+    sensor_channels, OverflowPolicy, Recording, SensorReceiver, SensorSender, SystemClock,
+    Temperature, Transport,
+};
+
+const CAPACITY: usize = 2;
+const SENT: u64 = 6;
+
+fn channel() -> (SensorSender, SensorReceiver) {
+    let (mut senders, receiver) =
+        sensor_channels(Transport::Ring, 1, CAPACITY, Arc::new(SystemClock));
+    (senders.remove(0), receiver)
+}
+
+fn reading(sequence: u64) -> Recording {
+    let mut recording = Recording::new(0, Temperature::from_fahrenheit(-70.0));
+    recording.sequence = sequence;
+    recording
+}
+
+fn received(receiver: &mut SensorReceiver) -> Vec<u64> {
+    std::iter::from_fn(|| receiver.try_recv().ok())
+        .map(|x| x.sequence)
+        .collect()
+}
+
+/// Sends every reading with `policy` without the receiver taking any, returning the sequence
+/// numbers of the readings that were dropped.
+fn send_while_stalled(policy: OverflowPolicy) -> (Vec<u64>, SensorReceiver) {
+    let (mut sender, receiver) = channel();
+    let mut dropped = Vec::new();
+
+    for sequence in 0..SENT {
+        let sent = sender.send(
+            reading(sequence),
+            policy,
+            || panic!("only `Block` waits"),
+            |x| dropped.push(x.sequence),
+        );
+        assert!(sent);
+    }
+
+    (dropped, receiver)
+}
+
+#[test]
+fn drop_oldest_keeps_the_newest_readings() {
+    let (dropped, mut receiver) = send_while_stalled(OverflowPolicy::DropOldest);
+
+    assert_eq!(received(&mut receiver), vec![4, 5]);
+    assert_eq!(dropped, vec![0, 1, 2, 3]);
+}
+
+#[test]
+fn drop_newest_keeps_the_oldest_readings() {
+    let (dropped, mut receiver) = send_while_stalled(OverflowPolicy::DropNewest);
+
+    assert_eq!(received(&mut receiver), vec![0, 1]);
+    assert_eq!(dropped, vec![2, 3, 4, 5]);
+}
+
+#[test]
+fn block_waits_for_room_and_drops_nothing() {
+    let (mut sender, mut receiver) = channel();
+    let mut taken = Vec::new();
+    let mut waits = 0;
+
+    for sequence in 0..SENT {
+        let sent = sender.send(
+            reading(sequence),
+            OverflowPolicy::Block,
+            || {
+                // The report thread stays stalled for a few waits before taking a reading
+                waits += 1;
+                if waits % 3 == 0 {
+                    taken.push(receiver.try_recv().unwrap().sequence);
+                }
+                true
+            },
+            |_| panic!("`Block` never drops a reading"),
+        );
+        assert!(sent);
+    }
+
+    taken.extend(received(&mut receiver));
+    assert_eq!(taken, (0..SENT).collect::<Vec<_>>());
+    assert_eq!(waits, 3 * (SENT as usize - CAPACITY));
+}
+
+#[test]
+fn block_gives_up_when_told_to() {
+    let (mut sender, mut receiver) = channel();
+    for sequence in 0..CAPACITY as u64 {
+        assert!(sender.send(reading(sequence), OverflowPolicy::Block, || true, |_| {}));
+    }
+
+    let sent = sender.send(reading(2), OverflowPolicy::Block, || false, |_| {});
+
+    assert!(!sent);
+    assert_eq!(received(&mut receiver), vec![0, 1]);
+}
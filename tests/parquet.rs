@@ -0,0 +1,82 @@
+//! The `ParquetSink` writes each simulated hour's readings to its own partition.
+#![cfg(feature = "parquet")]
+
+use std::fs::{self, File};
+use std::path::Path;
+use std::time::Instant;
+
+use assignment3::temperature::{
+    ParquetSink, Recording, Report, ReportSink, SimulationConfig, Temperature,
+};
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use parquet::record::RowAccessor;
+
+fn read_sequences(path: &Path) -> Vec<(i32, i64)> {
+    let reader = SerializedFileReader::new(File::open(path).unwrap()).unwrap();
+
+    reader
+        .get_row_iter(None)
+        .unwrap()
+        .map(|row| {
+            let row = row.unwrap();
+            (row.get_int(0).unwrap(), row.get_long(1).unwrap())
+        })
+        .collect()
+}
+
+#[test]
+fn readings_are_partitioned_by_hour() {
+    let dir = std::env::temp_dir().join(format!("parquet-sink-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+
+    let config = SimulationConfig {
+        sensor_count: 2,
+        ..SimulationConfig::default()
+    };
+    let start = Instant::now();
+    let mut sink = ParquetSink::new(&dir, &config).unwrap();
+    sink.handle_start(start);
+
+    // Two and a half hours of readings, one a minute from each sensor
+    let readings: Vec<Recording> = (0..300u64)
+        .map(|index| {
+            let mut recording = Recording::new(
+                index as usize % 2,
+                Temperature::from_fahrenheit(index as f64 % 170.0 - 100.0),
+            );
+            recording.sequence = index / 2;
+            recording.timestamp = start + config.scaled_minute() * (index / 2) as u32;
+            recording
+        })
+        .collect();
+
+    for (index, period) in readings.chunks(120).enumerate() {
+        for recording in period {
+            sink.handle_recording(recording);
+        }
+
+        let mut report = Report::generate(index, period.to_vec(), &config).unwrap();
+        report.partial = period.len() < 120;
+        sink.handle_report(&report);
+
+        // Each hour is written once its report has been, and not before
+        let written = dir.join(format!("hour={}", index)).join("part-0.parquet");
+        assert!(written.exists(), "hour {} wasn't written", index);
+        assert!(!dir.join(format!("hour={}", index + 1)).exists());
+    }
+
+    for hour in 0..3 {
+        let rows = read_sequences(&dir.join(format!("hour={}", hour)).join("part-0.parquet"));
+        let expected = if hour < 2 { 120 } else { 60 };
+        assert_eq!(rows.len(), expected);
+        assert!(rows
+            .iter()
+            .all(|&(_, sequence)| sequence as u64 / 60 == hour));
+        assert_eq!(
+            rows.iter().filter(|&&(sensor, _)| sensor == 1).count(),
+            expected / 2
+        );
+    }
+
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,61 @@
+//! Pausing a simulation stops time for it, so the reports cover the same periods with the same
+//! readings as if it had never been paused, and a paused simulation can still be stopped.
+
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use assignment3::temperature::SimulationConfig;
+use assignment3::TemperatureSimulation;
+
+fn config() -> SimulationConfig {
+    SimulationConfig {
+        seed: Some(42),
+        hours: Some(2),
+        speedup: 14_400,
+        ..SimulationConfig::default()
+    }
+}
+
+#[test]
+fn paused_time_is_left_out_of_the_reports() {
+    let config = config();
+    let mut simulation = TemperatureSimulation::with_config(config.clone());
+    let pause = simulation.pause_handle();
+    let started = Instant::now();
+
+    simulation.start();
+    sleep(Duration::from_millis(30));
+    assert!(pause.pause());
+    assert!(!pause.pause());
+    sleep(Duration::from_millis(300));
+    assert!(pause.is_paused());
+    assert!(pause.resume());
+    simulation.wait().unwrap();
+
+    assert!(started.elapsed() >= Duration::from_millis(800));
+
+    let reports = simulation.all_reports();
+    assert_eq!(reports.len(), 2);
+    for report in reports.iter() {
+        assert_eq!(
+            (report.period_end - report.period_start).to_std().unwrap(),
+            config.scaled_report_interval()
+        );
+        assert_eq!(report.missed_readings, 0);
+    }
+}
+
+#[test]
+fn paused_simulation_can_be_stopped() {
+    let mut simulation = TemperatureSimulation::with_config(config());
+    let pause = simulation.pause_handle();
+
+    // Pausing before the start means nothing runs until it's resumed
+    assert!(pause.pause());
+    simulation.start();
+    sleep(Duration::from_millis(50));
+
+    simulation.stop().unwrap();
+    assert!(!pause.is_paused());
+    assert!(!pause.pause());
+}
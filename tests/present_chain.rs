@@ -0,0 +1,282 @@
+//! Every `ChainKind` keeps its presents in order and agrees on what's on the chain, alone and with
+//! several servants changing it at once.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use assignment3::presents::{
+    self, ChainKind, LazyChain, OptimisticChain, PresentChain, PresentsConfig,
+};
+
+fn for_each_kind(check: impl Fn(ChainKind, Arc<dyn PresentChain>)) {
+    for kind in ChainKind::available() {
+        check(kind, kind.build());
+    }
+}
+
+#[test]
+fn presents_come_off_in_order() {
+    for_each_kind(|kind, chain| {
+        for present in [5, 1, 4, 2, 3] {
+            assert!(chain.add(present), "{:?}", kind);
+        }
+
+        let taken: Vec<usize> = std::iter::from_fn(|| chain.remove_min()).collect();
+        assert_eq!(taken, vec![1, 2, 3, 4, 5], "{:?}", kind);
+        assert!(chain.is_empty(), "{:?}", kind);
+    });
+}
+
+#[test]
+fn a_present_is_only_added_once() {
+    for_each_kind(|kind, chain| {
+        assert!(chain.add(3), "{:?}", kind);
+        assert!(!chain.add(3), "{:?}", kind);
+        assert_eq!(chain.len(), 1, "{:?}", kind);
+    });
+}
+
+#[test]
+fn presents_can_be_removed_by_id() {
+    for_each_kind(|kind, chain| {
+        for present in 1..=5 {
+            chain.add(present);
+        }
+
+        assert!(chain.remove_by_id(3), "{:?}", kind);
+        assert!(!chain.remove_by_id(3), "{:?}", kind);
+        assert!(!chain.remove_by_id(9), "{:?}", kind);
+
+        assert!(!chain.contains(3), "{:?}", kind);
+        assert!(chain.contains(1) && chain.contains(5), "{:?}", kind);
+        assert_eq!(chain.len(), 4, "{:?}", kind);
+
+        assert!(chain.remove_by_id(1), "{:?}", kind);
+        assert_eq!(chain.remove_min(), Some(2), "{:?}", kind);
+    });
+}
+
+#[test]
+fn an_empty_chain_has_nothing_to_remove() {
+    for_each_kind(|kind, chain| {
+        assert!(chain.is_empty(), "{:?}", kind);
+        assert_eq!(chain.remove_min(), None, "{:?}", kind);
+        assert!(!chain.remove_by_id(1), "{:?}", kind);
+        assert!(!chain.contains(1), "{:?}", kind);
+    });
+}
+
+#[test]
+fn servants_adding_at_once_keep_the_chain_sorted() {
+    for_each_kind(|kind, chain| {
+        let handles: Vec<_> = (0..4)
+            .map(|servant| {
+                let chain = chain.clone();
+                // Each servant adds every fourth present, so they interleave along the chain
+                thread::spawn(move || {
+                    for present in (servant..400).step_by(4) {
+                        assert!(chain.add(present + 1));
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(chain.len(), 400, "{:?}", kind);
+        let taken: Vec<usize> = std::iter::from_fn(|| chain.remove_min()).collect();
+        assert_eq!(taken, (1..=400).collect::<Vec<_>>(), "{:?}", kind);
+    });
+}
+
+#[test]
+fn servants_removing_at_once_only_take_their_own_presents() {
+    for_each_kind(|kind, chain| {
+        let handles: Vec<_> = (0..4)
+            .map(|servant| {
+                let chain = chain.clone();
+                thread::spawn(move || {
+                    let presents: Vec<usize> = (servant..400).step_by(4).map(|x| x + 1).collect();
+
+                    for &present in presents.iter() {
+                        chain.add(present);
+                    }
+
+                    // Take the even presents back off while the other servants are still busy
+                    for &present in presents.iter().filter(|&&x| x % 2 == 0) {
+                        assert!(chain.remove_by_id(present));
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(chain.len(), 200, "{:?}", kind);
+        let taken: Vec<usize> = std::iter::from_fn(|| chain.remove_min()).collect();
+        assert_eq!(
+            taken,
+            (1..=400).step_by(2).collect::<Vec<_>>(),
+            "{:?}",
+            kind
+        );
+    });
+}
+
+#[test]
+fn servants_fighting_over_the_same_presents_agree_on_what_is_left() {
+    for_each_kind(|kind, chain| {
+        let added = Arc::new(AtomicUsize::new(0));
+        let removed = Arc::new(AtomicUsize::new(0));
+
+        let handles: Vec<_> = (0..4)
+            .map(|servant| {
+                let chain = chain.clone();
+                let added = added.clone();
+                let removed = removed.clone();
+                // Every servant keeps adding and removing the same few presents, so nodes are
+                // taken off from under the others all the time
+                thread::spawn(move || {
+                    for round in 0..2_000 {
+                        let present = (servant + round) % 8 + 1;
+
+                        if chain.add(present) {
+                            added.fetch_add(1, Ordering::Relaxed);
+                        }
+
+                        if chain.remove_by_id((present + 3) % 8 + 1) {
+                            removed.fetch_add(1, Ordering::Relaxed);
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let left = added.load(Ordering::Relaxed) - removed.load(Ordering::Relaxed);
+        assert_eq!(chain.len(), left, "{:?}", kind);
+
+        let taken: Vec<usize> = std::iter::from_fn(|| chain.remove_min()).collect();
+        assert_eq!(taken.len(), left, "{:?}", kind);
+        assert!(taken.windows(2).all(|x| x[0] < x[1]), "{:?}", kind);
+    });
+}
+
+#[test]
+fn an_optimistic_chain_finds_presents_while_others_are_taken_off() {
+    let chain = Arc::new(OptimisticChain::new());
+
+    for present in 1..=200 {
+        chain.add(present);
+    }
+
+    // Take the odd presents off while the even ones are looked for, so the lookups keep having to
+    // check their place again
+    let remover = {
+        let chain = chain.clone();
+        thread::spawn(move || {
+            for present in (1..=200).step_by(2) {
+                assert!(chain.remove_by_id(present));
+            }
+        })
+    };
+
+    for _ in 0..10 {
+        for present in (2..=200).step_by(2) {
+            assert!(chain.contains(present));
+        }
+    }
+
+    remover.join().unwrap();
+
+    assert_eq!(chain.len(), 100);
+    assert!(!chain.contains(1));
+    assert_eq!(chain.remove_min(), Some(2));
+}
+
+#[test]
+fn a_lazy_chain_stops_finding_a_present_once_it_is_taken_off() {
+    let chain = Arc::new(LazyChain::new());
+
+    for present in (2..=200).step_by(2) {
+        chain.add(present);
+    }
+
+    // Lookups for the even presents, which stay on the chain, run alongside a servant adding and
+    // taking off the odd ones between them
+    let checkers: Vec<_> = (0..2)
+        .map(|_| {
+            let chain = chain.clone();
+            thread::spawn(move || {
+                for _ in 0..20 {
+                    for present in (2..=200).step_by(2) {
+                        assert!(chain.contains(present));
+                    }
+                }
+            })
+        })
+        .collect();
+
+    for round in 0..20 {
+        for present in (1..200).step_by(2) {
+            assert!(chain.add(present), "round {}", round);
+            assert!(chain.contains(present));
+            assert!(chain.remove_by_id(present));
+            assert!(!chain.contains(present));
+        }
+    }
+
+    for checker in checkers {
+        checker.join().unwrap();
+    }
+
+    assert_eq!(chain.len(), 100);
+}
+
+#[test]
+fn every_kind_thanks_every_present() {
+    for kind in ChainKind::available() {
+        let summary = presents::run(PresentsConfig {
+            presents: 1_000,
+            servants: 4,
+            chain: kind,
+            seed: Some(3),
+        })
+        .unwrap();
+
+        assert_eq!(summary.thank_you_cards, 1_000, "{:?}", kind);
+    }
+}
+
+#[test]
+fn only_chains_that_were_compiled_in_are_available() {
+    assert!(ChainKind::available().contains(&ChainKind::Coarse));
+    assert!(ChainKind::available().contains(&ChainKind::HandOverHand));
+    assert!(ChainKind::available().contains(&ChainKind::Optimistic));
+    assert!(ChainKind::available().contains(&ChainKind::Lazy));
+    assert_eq!(
+        ChainKind::available().contains(&ChainKind::LockFree),
+        cfg!(feature = "lockfree")
+    );
+}
+
+#[test]
+fn chain_kinds_parse_by_name() {
+    assert_eq!("coarse".parse::<ChainKind>(), Ok(ChainKind::Coarse));
+    assert_eq!(
+        "hand_over_hand".parse::<ChainKind>(),
+        Ok(ChainKind::HandOverHand)
+    );
+    assert_eq!("lock_free".parse::<ChainKind>(), Ok(ChainKind::LockFree));
+    assert_eq!("optimistic".parse::<ChainKind>(), Ok(ChainKind::Optimistic));
+    assert_eq!("lazy".parse::<ChainKind>(), Ok(ChainKind::Lazy));
+    assert!("tree".parse::<ChainKind>().is_err());
+}
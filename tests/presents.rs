@@ -0,0 +1,71 @@
+//! `presents::run` thanks every guest exactly once, however many servants there are, and won't start
+//! without any presents or servants.
+
+use assignment3::presents::{self, ConfigError, PresentsConfig};
+
+#[test]
+fn every_present_gets_one_thank_you_card() {
+    let summary = presents::run(PresentsConfig {
+        presents: 2_000,
+        servants: 4,
+        seed: Some(7),
+        ..PresentsConfig::default()
+    })
+    .unwrap();
+
+    assert_eq!(summary.presents, 2_000);
+    assert_eq!(summary.thank_you_cards, 2_000);
+    assert_eq!(summary.servants.len(), 4);
+
+    // Each present is added by one servant and thanked for by one servant
+    let added: u64 = summary.servants.iter().map(|x| x.presents_added).sum();
+    let thanked: u64 = summary.servants.iter().map(|x| x.thank_you_cards).sum();
+    assert_eq!(added, 2_000);
+    assert_eq!(thanked, 2_000);
+
+    for (index, servant) in summary.servants.iter().enumerate() {
+        assert_eq!(servant.servant_id, index);
+    }
+}
+
+#[test]
+fn one_servant_does_all_the_work() {
+    let summary = presents::run(PresentsConfig {
+        presents: 500,
+        servants: 1,
+        seed: None,
+        ..PresentsConfig::default()
+    })
+    .unwrap();
+
+    assert_eq!(summary.thank_you_cards, 500);
+    assert_eq!(summary.servants[0].presents_added, 500);
+    assert_eq!(summary.servants[0].thank_you_cards, 500);
+}
+
+#[test]
+fn no_presents_or_no_servants_is_rejected() {
+    for (config, key) in [
+        (
+            PresentsConfig {
+                presents: 0,
+                ..PresentsConfig::default()
+            },
+            "presents",
+        ),
+        (
+            PresentsConfig {
+                servants: 0,
+                ..PresentsConfig::default()
+            },
+            "servants",
+        ),
+    ] {
+        let chain = config.chain.build();
+        match presents::run_with(config.clone(), chain) {
+            Err(ConfigError::Invalid { key: invalid, .. }) => assert_eq!(invalid, key),
+            other => panic!("expected `{}` to be rejected, got {:?}", key, other),
+        }
+        assert!(presents::run(config).is_err());
+    }
+}
@@ -0,0 +1,121 @@
+//! Readings past the priority limits raise an alert that goes ahead of the sensors' queues, and
+//! is still only raised and counted once when the reading itself arrives.
+
+use std::sync::{Arc, Mutex};
+
+use assignment3::temperature::{
+    Alert, AlertKind, PriorityConfig, Recording, Report, ReportSink, SensorSource,
+    SimulationConfig, Temperature, Transport, VirtualClock,
+};
+use assignment3::TemperatureSimulation;
+
+/// Reads a steady 20°F, apart from one reading that's `extreme` instead.
+struct SteadySource {
+    sensor_id: usize,
+    readings: usize,
+    extreme_at: Option<(usize, f64)>,
+}
+
+impl SensorSource for SteadySource {
+    fn next_reading(&mut self) -> Option<Recording> {
+        self.readings += 1;
+        let fahrenheit = match self.extreme_at {
+            Some((at, extreme)) if at == self.readings => extreme,
+            _ => 20.0,
+        };
+
+        Some(Recording::new(
+            self.sensor_id,
+            Temperature::from_fahrenheit(fahrenheit),
+        ))
+    }
+}
+
+#[derive(Clone, Default)]
+struct AlertSink {
+    alerts: Arc<Mutex<Vec<Alert>>>,
+}
+
+impl ReportSink for AlertSink {
+    fn handle_report(&mut self, _report: &Report) {}
+
+    fn handle_alert(&mut self, alert: &Alert) {
+        self.alerts.lock().unwrap().push(alert.clone());
+    }
+}
+
+/// Runs an hour where sensor 2's 30th reading is `extreme`, returning the alerts and reports.
+fn run_hour(config: SimulationConfig, extreme: f64) -> (Vec<Alert>, Vec<Report>) {
+    let sources = (0..config.sensor_count)
+        .map(|sensor_id| {
+            Box::new(SteadySource {
+                sensor_id,
+                readings: 0,
+                extreme_at: (sensor_id == 2).then_some((30, extreme)),
+            }) as Box<dyn SensorSource>
+        })
+        .collect();
+
+    let sink = AlertSink::default();
+    let mut simulation = TemperatureSimulation::with_sources(config, sources);
+    simulation.set_clock(Arc::new(VirtualClock::new()));
+    simulation.add_sink(sink.clone());
+    simulation.start();
+    simulation.wait().unwrap();
+
+    let alerts = sink.alerts.lock().unwrap().clone();
+    (alerts, simulation.all_reports())
+}
+
+fn config() -> SimulationConfig {
+    SimulationConfig {
+        seed: Some(42),
+        hours: Some(1),
+        priority: Some(PriorityConfig::default()),
+        ..SimulationConfig::default()
+    }
+}
+
+#[test]
+fn extreme_reading_is_alerted_once() {
+    let (alerts, reports) = run_hour(config(), 65.0);
+
+    assert_eq!(alerts.len(), 1);
+    assert_eq!(alerts[0].kind, AlertKind::Extreme);
+    assert_eq!(alerts[0].recording.sensor_id, 2);
+    assert_eq!(alerts[0].recording.sequence, 29);
+    assert_eq!(alerts[0].baseline.fahrenheit(), 60.0);
+    assert_eq!(alerts[0].deviation, 5.0);
+
+    assert_eq!(reports[0].alert_count, 1);
+    assert_eq!(reports[0].sensor_stats[2].alerts, 1);
+}
+
+#[test]
+fn cold_reading_is_alerted() {
+    let (alerts, _) = run_hour(config(), -95.0);
+
+    assert_eq!(alerts.len(), 1);
+    assert_eq!(alerts[0].baseline.fahrenheit(), -90.0);
+    assert_eq!(alerts[0].deviation, -5.0);
+}
+
+#[test]
+fn slots_alert_when_the_reading_arrives() {
+    let mut config = config();
+    config.transport = Transport::Slots;
+    let (alerts, reports) = run_hour(config, 65.0);
+
+    // The slots only hold each sensor's latest reading, so the extreme one may be overwritten
+    // before it's taken, but it can't be alerted twice
+    assert!(alerts.len() <= 1);
+    assert_eq!(reports[0].alert_count, alerts.len());
+}
+
+#[test]
+fn readings_within_the_limits_are_not_alerted() {
+    let (alerts, reports) = run_hour(config(), 55.0);
+
+    assert!(alerts.is_empty());
+    assert_eq!(reports[0].alert_count, 0);
+}
@@ -0,0 +1,118 @@
+//! The t-digest's percentiles stay close to the exact ones, including after digests are merged,
+//! and it behaves sensibly with no readings or only one.
+
+use assignment3::temperature::{QuantileConfig, QuantileEstimator, TDigest};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rand_distr::Normal;
+
+const PERCENTILES: [f64; 9] = [0.1, 1.0, 5.0, 25.0, 50.0, 75.0, 95.0, 99.0, 99.9];
+
+/// How far off, as a fraction of the readings, the rank of an estimate is allowed to be. With
+/// compression 100 a centroid near q holds at most 4q(1 - q)/100 of the readings, 1% at the
+/// median, and an estimate shouldn't be further off than half of one.
+const RANK_ERROR: f64 = 0.005;
+
+fn sample(seed: u64, count: usize) -> Vec<f64> {
+    let normal = Normal::new(-40.0, 20.0).unwrap();
+    StdRng::seed_from_u64(seed)
+        .sample_iter(normal)
+        .take(count)
+        .collect()
+}
+
+fn exact(values: &[f64]) -> QuantileEstimator {
+    let mut estimator = QuantileConfig::Exact.build();
+    for &value in values {
+        estimator.push(value);
+    }
+    estimator
+}
+
+/// The fraction of `sorted` that's below `value`.
+fn rank(sorted: &[f64], value: f64) -> f64 {
+    sorted.partition_point(|&x| x < value) as f64 / sorted.len() as f64
+}
+
+fn assert_close_to_exact(digest: &mut TDigest, values: &[f64]) {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let mut exact = exact(values);
+
+    for percentile in PERCENTILES {
+        let estimate = digest.quantile(percentile / 100.0).unwrap();
+        let expected = exact.percentile(percentile).unwrap();
+
+        let error = (rank(&sorted, estimate) - rank(&sorted, expected)).abs();
+        assert!(
+            error <= RANK_ERROR,
+            "p{} estimated as {} but is {}, {} of the readings apart",
+            percentile,
+            estimate,
+            expected,
+            error
+        );
+    }
+}
+
+#[test]
+fn percentiles_are_close_to_exact() {
+    let values = sample(1, 100_000);
+    let mut digest = TDigest::new(100.0);
+    for &value in values.iter() {
+        digest.push(value);
+    }
+
+    assert_close_to_exact(&mut digest, &values);
+}
+
+#[test]
+fn merged_digests_are_close_to_exact() {
+    let values = sample(2, 100_000);
+    let mut digests: Vec<_> = values
+        .chunks(10_000)
+        .map(|chunk| {
+            let mut digest = TDigest::new(100.0);
+            for &value in chunk {
+                digest.push(value);
+            }
+            digest
+        })
+        .collect();
+
+    let mut merged = digests.remove(0);
+    for digest in digests {
+        merged.merge(digest);
+    }
+
+    assert_close_to_exact(&mut merged, &values);
+
+    // The ends are pinned to the lowest and highest readings from any of the digests
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    assert_eq!(merged.quantile(0.0), Some(min));
+    assert_eq!(merged.quantile(1.0), Some(max));
+}
+
+#[test]
+fn empty_digest_has_no_percentiles() {
+    let mut digest = TDigest::new(100.0);
+    assert_eq!(digest.quantile(0.5), None);
+
+    // Merging in another empty digest doesn't change that
+    digest.merge(TDigest::new(100.0));
+    assert_eq!(digest.quantile(0.5), None);
+
+    let mut estimator = QuantileConfig::TDigest { compression: 100.0 }.build();
+    assert_eq!(estimator.percentile(50.0), None);
+}
+
+#[test]
+fn single_reading_is_every_percentile() {
+    let mut digest = TDigest::new(100.0);
+    digest.push(-12.5);
+
+    for percentile in PERCENTILES.into_iter().chain([0.0, 100.0]) {
+        assert_eq!(digest.quantile(percentile / 100.0), Some(-12.5));
+    }
+}
@@ -0,0 +1,52 @@
+//! Every report from a running simulation says how long its readings spent in the queue, and a
+//! report built from a batch of readings, which never went through one, doesn't.
+
+use std::time::{Duration, Instant};
+
+use assignment3::temperature::{RandomSource, Recording, Report, SensorSource, SimulationConfig};
+use assignment3::TemperatureSimulation;
+
+#[test]
+fn live_reports_have_queue_latency() {
+    let config = SimulationConfig {
+        seed: Some(3),
+        hours: Some(2),
+        speedup: 14_400,
+        ..SimulationConfig::default()
+    };
+
+    let mut simulation = TemperatureSimulation::with_config(config);
+    simulation.start();
+    simulation.wait().unwrap();
+
+    let reports = simulation.all_reports();
+    assert_eq!(reports.len(), 2);
+    for report in reports.iter() {
+        let latency = report.queue_latency.as_ref().unwrap();
+        assert!(latency.p50_ms >= 0.0);
+        assert!(latency.p50_ms <= latency.p99_ms);
+    }
+}
+
+#[test]
+fn batch_reports_leave_queue_latency_out() {
+    let config = SimulationConfig {
+        seed: Some(3),
+        ..SimulationConfig::default()
+    };
+    let start = Instant::now();
+
+    let readings: Vec<Recording> = (0..config.sensor_count)
+        .flat_map(|sensor_id| {
+            let mut source = RandomSource::for_sensor(&config, sensor_id);
+            (0..60).map(move |minute| {
+                let mut recording = source.next_reading().unwrap();
+                recording.timestamp = start + Duration::from_millis(minute);
+                recording
+            })
+        })
+        .collect();
+
+    let report = Report::generate(0, readings, &config).unwrap();
+    assert!(report.queue_latency.is_none());
+}
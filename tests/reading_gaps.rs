@@ -0,0 +1,125 @@
+//! Every report made while the simulation runs says how many readings each sensor was due to take
+//! and which of them never arrived, so lost readings stand out.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use assignment3::temperature::{
+    FaultKind, Recording, ReportAccumulator, SensorFaultConfig, SimulationConfig, Temperature,
+    VirtualClock,
+};
+use assignment3::TemperatureSimulation;
+
+fn recording(sensor_id: usize, sequence: u64, start: Instant) -> Recording {
+    Recording {
+        sensor_id,
+        temperature: Temperature::from_fahrenheit(sequence as f64),
+        sequence,
+        timestamp: start + Duration::from_millis(sequence * 100),
+    }
+}
+
+#[test]
+fn missing_sequence_numbers_are_reported_as_gaps() {
+    let config = SimulationConfig {
+        sensor_count: 2,
+        ..SimulationConfig::default()
+    };
+    let start = Instant::now();
+    let mut accumulator = ReportAccumulator::new(&config);
+    for sequence in 0..60 {
+        if !(10..13).contains(&sequence) && sequence != 40 {
+            accumulator.push(&recording(0, sequence, start));
+        }
+        accumulator.push(&recording(1, sequence, start));
+    }
+    for sensor_id in 0..2 {
+        accumulator.count_missed(sensor_id, 0..60);
+    }
+
+    let report = accumulator.finish(0, &config).unwrap();
+    let stats = &report.sensor_stats[0];
+    assert_eq!(stats.expected, Some(60));
+    assert_eq!(stats.gaps, vec![10..13, 40..41]);
+    assert_eq!(stats.missed, 4);
+    assert_eq!(report.sensor_stats[1].expected, Some(60));
+    assert!(report.sensor_stats[1].gaps.is_empty());
+
+    let text = report.to_string();
+    assert!(text.contains("<-- GAP: missing #10-12, #40"), "{}", text);
+    assert_eq!(text.matches("GAP").count(), 1, "{}", text);
+}
+
+#[test]
+fn period_with_every_reading_missed_is_still_reported() {
+    let config = SimulationConfig {
+        sensor_count: 2,
+        ..SimulationConfig::default()
+    };
+    let mut accumulator = ReportAccumulator::new(&config);
+    accumulator.start_period(Instant::now());
+    for sensor_id in 0..2 {
+        accumulator.count_missed(sensor_id, 60..120);
+    }
+
+    let report = accumulator.finish(1, &config).unwrap();
+    assert_eq!(report.missed_readings, 120);
+    assert!(report.summary.is_none());
+    for stats in report.sensor_stats.iter() {
+        assert_eq!(stats.readings, 0);
+        assert_eq!(stats.expected, Some(60));
+        assert_eq!(stats.gaps, vec![60..120]);
+        assert_eq!(stats.missed, 60);
+    }
+
+    let text = report.to_string();
+    assert!(text.contains("No readings were received"), "{}", text);
+    assert!(!text.contains("Not enough readings"), "{}", text);
+    assert!(
+        text.contains("     0        60         0  <-- GAP: missing #60-119"),
+        "{}",
+        text
+    );
+    assert_eq!(text.matches("GAP").count(), 2, "{}", text);
+}
+
+#[test]
+fn sensor_that_goes_quiet_has_a_gap_to_the_end_of_the_period() {
+    let mut config = SimulationConfig {
+        seed: Some(42),
+        hours: Some(1),
+        ..SimulationConfig::default()
+    };
+    config.sensor_faults.push(SensorFaultConfig {
+        sensor: 3,
+        fault: FaultKind::Dropout,
+        after_minutes: [20.0, 20.0],
+    });
+
+    let mut simulation = TemperatureSimulation::with_config(config);
+    simulation.set_clock(Arc::new(VirtualClock::new()));
+    simulation.start();
+    simulation.wait().unwrap();
+
+    let report = &simulation.all_reports()[0];
+    for stats in report.sensor_stats.iter() {
+        assert_eq!(stats.expected, Some(60));
+        match stats.sensor_id {
+            3 => assert_eq!(stats.gaps, vec![20..60]),
+            _ => assert!(stats.gaps.is_empty(), "{:?}", stats),
+        }
+    }
+}
+
+#[test]
+fn batch_reports_have_no_expected_counts() {
+    let config = SimulationConfig::default();
+    let start = Instant::now();
+    let recordings: Vec<Recording> = (0..10).map(|x| recording(0, x, start)).collect();
+
+    let report = ReportAccumulator::from_recordings(&recordings, &config)
+        .finish(0, &config)
+        .unwrap();
+    assert_eq!(report.sensor_stats[0].expected, None);
+    assert!(!report.to_string().contains("Readings expected"));
+}
@@ -0,0 +1,58 @@
+//! Invalid readings from an external source are thrown away before they reach the statistics, and
+//! each report counts them by why they were rejected.
+
+use std::io::Cursor;
+use std::sync::Arc;
+
+use assignment3::temperature::{
+    LineFormat, LineSource, RejectReason, SensorSource, SimulationConfig, VirtualClock,
+};
+use assignment3::TemperatureSimulation;
+
+#[test]
+fn invalid_readings_are_counted_by_reason() {
+    let mut input = String::from("abc\nNaN\n500\n-40\ninf\n-1000\n");
+    for minute in 0..100 {
+        input.push_str(&format!("{}\n", minute as f64 * 0.5 - 20.0));
+    }
+    let source = LineSource::new(Cursor::new(input), LineFormat::Csv);
+
+    let config = SimulationConfig {
+        hours: Some(1),
+        ..SimulationConfig::default()
+    };
+    let sources: Vec<Box<dyn SensorSource>> = vec![Box::new(source)];
+    let mut simulation = TemperatureSimulation::with_sources(config, sources);
+    simulation.set_clock(Arc::new(VirtualClock::new()));
+
+    simulation.start();
+    simulation.wait().unwrap();
+
+    let report = &simulation.all_reports()[0];
+    assert_eq!(
+        report.rejected_readings.get(&RejectReason::Malformed),
+        Some(&1)
+    );
+    assert_eq!(
+        report.rejected_readings.get(&RejectReason::NotANumber),
+        Some(&2)
+    );
+    assert_eq!(
+        report.rejected_readings.get(&RejectReason::OutOfRange),
+        Some(&2)
+    );
+
+    // Only valid readings made it into the statistics
+    assert_eq!(report.sensor_stats[0].readings, 60);
+    assert_eq!(report.lowest_temps[0].temperature.fahrenheit(), -40.0);
+    assert!(report
+        .summary
+        .as_ref()
+        .unwrap()
+        .mean
+        .fahrenheit()
+        .is_finite());
+    assert!(report
+        .to_string()
+        .contains("Readings rejected as invalid: 5 (1 malformed, 2 not a number, 2 out of range)"));
+}
@@ -0,0 +1,104 @@
+//! A seeded run recorded with `RecordSink` and replayed with `RecordedRun` gives back the same
+//! reports, and replaying the same log again gives exactly the same ones.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use assignment3::temperature::{RecordSink, RecordedRun, Report, SimulationConfig, VirtualClock};
+use assignment3::TemperatureSimulation;
+use serde_json::Value;
+
+fn log_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("assignment3-{}-{}.log", name, std::process::id()))
+}
+
+fn config() -> SimulationConfig {
+    SimulationConfig {
+        seed: Some(50),
+        hours: Some(3),
+        ..SimulationConfig::default()
+    }
+}
+
+/// Runs the seeded simulation while recording it to `path`, returning its reports.
+fn record(path: &PathBuf) -> Vec<Report> {
+    let mut simulation = TemperatureSimulation::with_config(config());
+    simulation.set_clock(Arc::new(VirtualClock::new()));
+    simulation.add_sink(RecordSink::create(path, simulation.config()).unwrap());
+    simulation.start();
+    simulation.wait().unwrap();
+
+    simulation.all_reports()
+}
+
+fn replay(path: &PathBuf) -> Vec<Report> {
+    let run = RecordedRun::open(path).unwrap();
+    let config = run.config(config());
+
+    run.replay(&config, &mut []).all_reports().to_vec()
+}
+
+/// A report as JSON without anything that depends on when the run happened or how fast it went:
+/// the times (a replay starts from a different instant), how long it took to generate and how
+/// long readings waited in a queue, which a replay doesn't have.
+fn without_times(report: &Report) -> Value {
+    fn strip(value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for key in [
+                    "timestamp",
+                    "period_start",
+                    "period_end",
+                    "generation_seconds",
+                    "queue_latency",
+                ] {
+                    map.remove(key);
+                }
+                map.values_mut().for_each(strip);
+            }
+            Value::Array(values) => values.iter_mut().for_each(strip),
+            _ => {}
+        }
+    }
+
+    let mut value = serde_json::to_value(report).unwrap();
+    strip(&mut value);
+    value
+}
+
+#[test]
+fn replay_gives_back_the_recorded_reports() {
+    let path = log_path("round-trip");
+    let recorded = record(&path);
+    let replayed = replay(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(recorded.len(), 3);
+    assert_eq!(replayed.len(), recorded.len());
+    for (replayed, recorded) in replayed.iter().zip(recorded.iter()) {
+        assert_eq!(without_times(replayed), without_times(recorded));
+    }
+}
+
+#[test]
+fn replaying_twice_gives_identical_reports() {
+    let path = log_path("twice");
+    record(&path);
+    let first = replay(&path);
+    let second = replay(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(first.len(), 3);
+    for (first, second) in first.iter().zip(second.iter()) {
+        assert_eq!(
+            Report {
+                generation_seconds: 0.0,
+                ..first.clone()
+            },
+            Report {
+                generation_seconds: 0.0,
+                ..second.clone()
+            }
+        );
+    }
+}
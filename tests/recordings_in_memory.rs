@@ -0,0 +1,78 @@
+//! Capping how many recordings a report keeps in memory spills the rest to a temporary file,
+//! which mustn't change the report or be left behind afterwards.
+
+use std::time::{Duration, Instant};
+
+use assignment3::temperature::{
+    RandomSource, Recording, Report, SensorSource, SimulationConfig, Temperature,
+};
+
+fn config() -> SimulationConfig {
+    SimulationConfig {
+        seed: Some(42),
+        ..SimulationConfig::default()
+    }
+}
+
+/// An hour of readings where sensor 0 is stuck, so it's left out of the extremes and the
+/// largest difference, which are worked out again from the kept recordings.
+fn readings(config: &SimulationConfig) -> Vec<Recording> {
+    let start = Instant::now();
+
+    (0..config.sensor_count)
+        .flat_map(|sensor_id| {
+            let mut source = RandomSource::for_sensor(config, sensor_id);
+
+            (0..60).map(move |minute| {
+                let mut recording = source.next_reading().unwrap();
+                if sensor_id == 0 {
+                    recording.temperature = Temperature::from_fahrenheit(-100.0);
+                }
+                recording.sequence = minute;
+                recording.timestamp = start + Duration::from_millis(minute);
+                recording
+            })
+        })
+        .collect()
+}
+
+fn spill_files() -> usize {
+    let prefix = format!("assignment3-recordings-{}-", std::process::id());
+
+    std::fs::read_dir(std::env::temp_dir())
+        .unwrap()
+        .filter(|x| {
+            x.as_ref()
+                .is_ok_and(|x| x.file_name().to_string_lossy().starts_with(&prefix))
+        })
+        .count()
+}
+
+#[test]
+fn spilled_recordings_give_the_same_report() {
+    let unlimited = config();
+    let mut capped = config();
+    capped.max_recordings_in_memory = Some(7);
+
+    let readings = readings(&unlimited);
+    let expected = Report::generate(0, readings.clone(), &unlimited).unwrap();
+    let report = Report::generate(0, readings, &capped).unwrap();
+
+    assert_eq!(report.suspect_sensors.len(), 1);
+    assert_eq!(report.suspect_sensors[0].sensor_id, 0);
+    assert!(report.lowest_temps.iter().all(|x| x.sensor_id != 0));
+
+    assert_eq!(report.lowest_temps, expected.lowest_temps);
+    assert_eq!(report.highest_temps, expected.highest_temps);
+    assert_eq!(
+        report.largest_temp_difference,
+        expected.largest_temp_difference
+    );
+    assert_eq!(spill_files(), 0);
+}
+
+#[test]
+fn zero_recordings_in_memory_is_rejected() {
+    let err = SimulationConfig::from_toml_str("recordings_in_memory = 0").unwrap_err();
+    assert!(err.to_string().contains("recordings_in_memory"), "{}", err);
+}
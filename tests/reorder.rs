@@ -0,0 +1,70 @@
+//! Readings that arrive out of the order they were taken are put back in order before they're
+//! used, as long as they're no later than the reorder window.
+
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use assignment3::temperature::{
+    ClockSkewConfig, Recording, Report, ReportSink, SimulationConfig, VirtualClock,
+};
+use assignment3::TemperatureSimulation;
+
+#[derive(Clone, Default)]
+struct RecordingSink {
+    timestamps: Arc<Mutex<Vec<Instant>>>,
+}
+
+impl ReportSink for RecordingSink {
+    fn handle_recording(&mut self, recording: &Recording) {
+        self.timestamps.lock().unwrap().push(recording.timestamp);
+    }
+
+    fn handle_report(&mut self, _report: &Report) {}
+}
+
+/// Sensors whose clocks are off (and not corrected) send readings that seem to have been taken
+/// up to 10 simulated seconds apart at the same time.
+fn run(reorder_window_seconds: f64) -> (Report, Vec<Instant>) {
+    let config = SimulationConfig {
+        seed: Some(42),
+        hours: Some(1),
+        clock_skew: ClockSkewConfig {
+            max_offset: 5.0,
+            correct: false,
+        },
+        reorder_window_seconds,
+        ..SimulationConfig::default()
+    };
+    let sink = RecordingSink::default();
+    let mut simulation = TemperatureSimulation::with_config(config);
+    simulation.set_clock(Arc::new(VirtualClock::new()));
+    simulation.add_sink(sink.clone());
+
+    simulation.start();
+    simulation.wait().unwrap();
+
+    let timestamps = sink.timestamps.lock().unwrap().clone();
+    (simulation.all_reports()[0].clone(), timestamps)
+}
+
+#[test]
+fn readings_arrive_out_of_order_without_a_window() {
+    let (_, timestamps) = run(0.0);
+
+    assert!(timestamps.windows(2).any(|x| x[1] < x[0]));
+}
+
+#[test]
+fn readings_are_used_in_the_order_they_were_taken() {
+    let (report, timestamps) = run(10.0);
+
+    assert!(timestamps.windows(2).all(|x| x[0] <= x[1]));
+    assert!(report.sensor_stats.iter().all(|x| x.readings == 60));
+    assert_eq!(report.missed_readings, 0);
+}
+
+#[test]
+fn window_longer_than_the_late_grace_is_rejected() {
+    let err = SimulationConfig::from_toml_str("late_grace = 10\nreorder_window = 20").unwrap_err();
+    assert!(err.to_string().contains("reorder_window"), "{}", err);
+}
@@ -0,0 +1,81 @@
+//! A `ReportBuilder` only puts the sections it's asked for into a report.
+
+use std::time::{Duration, Instant};
+
+use assignment3::temperature::{
+    Recording, ReportBuilder, ReportSection, SimulationConfig, Temperature, TemperatureUnit,
+};
+
+fn readings() -> Vec<Recording> {
+    let start = Instant::now();
+
+    (0..240u64)
+        .map(|index| {
+            let sensor_id = index as usize % 8;
+            let fahrenheit = (index * 37 % 170) as f64 - 100.0;
+            let mut recording = Recording::new(sensor_id, Temperature::from_fahrenheit(fahrenheit));
+            recording.sequence = index / 8;
+            recording.timestamp = start + Duration::from_millis(index * 30);
+            recording
+        })
+        .collect()
+}
+
+#[test]
+fn bare_report_leaves_the_optional_sections_out() {
+    let config = SimulationConfig::default();
+    let report = ReportBuilder::new()
+        .generate(0, readings(), &config)
+        .unwrap();
+
+    assert!(report.lowest_temps.is_empty() && report.highest_temps.is_empty());
+    assert!(report.summary.as_ref().unwrap().percentiles.is_empty());
+    assert!(report.histogram.is_empty());
+    assert!(report.suspect_sensors.is_empty());
+    assert!(report.largest_temp_difference.as_ref().unwrap().difference > 0.0);
+
+    let text = report.display(TemperatureUnit::Fahrenheit).to_string();
+    assert!(!text.contains("lowest temps"));
+    assert!(!text.contains("Histogram"));
+    assert!(text.contains("Largest temperature difference"));
+}
+
+#[test]
+fn sections_are_added_one_at_a_time() {
+    let config = SimulationConfig::default();
+    let report = ReportBuilder::new()
+        .top_n(3)
+        .largest_diff_window(1)
+        .percentiles(&[10.0, 90.0])
+        .histogram(20.0)
+        .generate(0, readings(), &config)
+        .unwrap();
+
+    assert_eq!(report.lowest_temps.len(), 3);
+    assert_eq!(report.highest_temps.len(), 3);
+    assert_eq!(report.difference_window_minutes, 1);
+    assert_eq!(report.summary.as_ref().unwrap().percentiles.len(), 2);
+    assert!(!report.histogram.is_empty());
+}
+
+#[test]
+fn skipped_sections_are_turned_off_in_the_config() {
+    let mut config = SimulationConfig::default();
+    ReportBuilder::from_config(&config)
+        .skip(ReportSection::TopTemps)
+        .skip(ReportSection::Histogram)
+        .skip(ReportSection::SuspectSensors)
+        .apply(&mut config);
+
+    assert_eq!(config.top_n, 0);
+    assert_eq!(config.histogram_bucket_width, 0.0);
+    assert!(!config.suspect_sensors.enabled);
+
+    // The rest are left as they were
+    assert_eq!(config.percentiles, SimulationConfig::default().percentiles);
+    assert!(config.validate().is_ok());
+
+    assert_eq!("top".parse(), Ok(ReportSection::TopTemps));
+    assert_eq!("suspect-sensors".parse(), Ok(ReportSection::SuspectSensors));
+    assert!("everything".parse::<ReportSection>().is_err());
+}
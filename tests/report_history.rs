@@ -0,0 +1,36 @@
+//! `reports_for_hour` picks out exactly the reports whose period starts during that simulated
+//! hour, however many of them there are.
+
+use std::sync::Arc;
+
+use assignment3::temperature::{SimulationConfig, VirtualClock};
+use assignment3::TemperatureSimulation;
+
+#[test]
+fn reports_for_hour_returns_that_hours_reports() {
+    let config = SimulationConfig {
+        seed: Some(15),
+        hours: Some(2),
+        report_interval_minutes: 20,
+        ..SimulationConfig::default()
+    };
+
+    let mut simulation = TemperatureSimulation::with_config(config);
+    simulation.set_clock(Arc::new(VirtualClock::new()));
+    simulation.start();
+    simulation.wait().unwrap();
+
+    let all = simulation.all_reports();
+    assert_eq!(all.len(), 6);
+
+    for hour in 0..2 {
+        let reports = simulation.reports_for_hour(hour);
+        let expected: Vec<_> = all.iter().filter(|x| x.hour() == hour).cloned().collect();
+
+        assert_eq!(reports, expected);
+        let start_minutes: Vec<_> = reports.iter().map(|x| x.start_minute).collect();
+        assert_eq!(start_minutes, [0, 20, 40].map(|x| hour * 60 + x));
+    }
+
+    assert!(simulation.reports_for_hour(2).is_empty());
+}
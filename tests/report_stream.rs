@@ -0,0 +1,81 @@
+//! Reports can be taken off `subscribe_reports()` as they're generated, and the stream ends with
+//! the simulation. Subscribing once it's started is refused, since earlier reports would be
+//! missing.
+
+use std::sync::Arc;
+
+use assignment3::temperature::{SimulationConfig, SimulationError, VirtualClock};
+use assignment3::TemperatureSimulation;
+
+#[test]
+fn every_report_comes_down_the_stream_in_order() {
+    let config = SimulationConfig {
+        seed: Some(9),
+        hours: Some(3),
+        ..SimulationConfig::default()
+    };
+
+    let mut simulation = TemperatureSimulation::with_config(config);
+    simulation.set_clock(Arc::new(VirtualClock::new()));
+    let reports = simulation.subscribe_reports().unwrap();
+
+    simulation.start();
+
+    // Ends by itself once the simulation has finished
+    let streamed: Vec<_> = reports.iter().collect();
+    simulation.wait().unwrap();
+
+    assert_eq!(streamed.len(), 3);
+    assert_eq!(streamed, simulation.all_reports());
+}
+
+#[test]
+fn subscribing_after_the_start_is_refused() {
+    let config = SimulationConfig {
+        seed: Some(9),
+        hours: Some(1),
+        ..SimulationConfig::default()
+    };
+
+    let mut simulation = TemperatureSimulation::with_config(config);
+    simulation.set_clock(Arc::new(VirtualClock::new()));
+    simulation.start();
+    assert!(matches!(
+        simulation.subscribe_reports(),
+        Err(SimulationError::AlreadyStarted)
+    ));
+
+    // Still refused once it's finished
+    simulation.wait().unwrap();
+    assert!(matches!(
+        simulation.subscribe_reports(),
+        Err(SimulationError::AlreadyStarted)
+    ));
+}
+
+#[cfg(feature = "async")]
+#[tokio::test(flavor = "multi_thread")]
+async fn async_stream_ends_with_the_simulation() {
+    use assignment3::temperature::AsyncTemperatureSimulation;
+
+    let config = SimulationConfig {
+        seed: Some(9),
+        hours: Some(2),
+        speedup: 36_000,
+        ..SimulationConfig::default()
+    };
+
+    let mut simulation = AsyncTemperatureSimulation::with_config(config);
+    let mut reports = simulation.subscribe_reports().unwrap();
+    simulation.run().await.unwrap();
+
+    let mut streamed = Vec::new();
+    while let Some(report) = reports.recv().await {
+        streamed.push(report);
+    }
+    assert_eq!(streamed, simulation.all_reports());
+    assert!(matches!(
+        simulation.subscribe_reports(),
+        Err(SimulationError::AlreadyStarted)
+    ));
+}
@@ -0,0 +1,94 @@
+//! The summary handed to the sinks at the end of a run adds up the run's reports.
+
+use std::sync::{Arc, Mutex};
+
+use assignment3::temperature::{Report, ReportSink, RunSummary, SimulationConfig, VirtualClock};
+use assignment3::TemperatureSimulation;
+
+struct SummarySink {
+    reports: usize,
+    summaries: Arc<Mutex<Vec<(usize, RunSummary)>>>,
+}
+
+impl ReportSink for SummarySink {
+    fn handle_report(&mut self, _report: &Report) {
+        self.reports += 1;
+    }
+
+    fn handle_summary(&mut self, summary: &RunSummary) {
+        self.summaries
+            .lock()
+            .unwrap()
+            .push((self.reports, summary.clone()));
+    }
+}
+
+#[test]
+fn summary_adds_up_the_reports() {
+    let config = SimulationConfig {
+        seed: Some(7),
+        hours: Some(2),
+        ..SimulationConfig::default()
+    };
+    let sensor_count = config.sensor_count;
+    let readings_per_hour = config.readings_per_report();
+
+    let mut simulation = TemperatureSimulation::with_config(config);
+    simulation.set_clock(Arc::new(VirtualClock::new()));
+    let summaries = Arc::new(Mutex::new(Vec::new()));
+    simulation.add_sink(SummarySink {
+        reports: 0,
+        summaries: summaries.clone(),
+    });
+
+    simulation.start();
+    simulation.wait().unwrap();
+
+    // Handed over once, after every report
+    let summaries = summaries.lock().unwrap();
+    assert_eq!(summaries.len(), 1);
+    let (reports_before, summary) = &summaries[0];
+    assert_eq!(*reports_before, 2);
+    assert_eq!(summary, &simulation.summary());
+
+    assert_eq!(summary.reports, 2);
+    assert_eq!(summary.readings, readings_per_hour * 2);
+    assert_eq!(summary.sensor_readings.len(), sensor_count);
+    assert_eq!(
+        summary.sensor_readings.iter().sum::<usize>(),
+        summary.readings
+    );
+
+    let reports = simulation.all_reports();
+    let lowest = reports
+        .iter()
+        .flat_map(|x| x.lowest_temps.iter())
+        .map(|x| x.temperature.fahrenheit())
+        .fold(f64::INFINITY, f64::min);
+    let highest = reports
+        .iter()
+        .flat_map(|x| x.highest_temps.iter())
+        .map(|x| x.temperature.fahrenheit())
+        .fold(f64::NEG_INFINITY, f64::max);
+    assert_eq!(
+        summary.lowest.as_ref().unwrap().temperature.fahrenheit(),
+        lowest
+    );
+    assert_eq!(
+        summary.highest.as_ref().unwrap().temperature.fahrenheit(),
+        highest
+    );
+
+    // The default transport can tell how deep its queue is
+    assert!(summary.peak_queue_depth.is_some());
+}
+
+#[test]
+fn empty_run_has_nothing_to_summarise() {
+    let summary = RunSummary::from_reports(&[], None);
+
+    assert_eq!(summary.reports, 0);
+    assert_eq!(summary.readings, 0);
+    assert!(summary.mean.is_none() && summary.lowest.is_none() && summary.highest.is_none());
+    assert_eq!(summary.mean_generation_seconds, 0.0);
+}
@@ -0,0 +1,71 @@
+//! With jitter each reading is taken a little after it was due, by a different amount every time,
+//! but still counts as the reading that was due.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use assignment3::temperature::{Recording, Report, ReportSink, SimulationConfig, VirtualClock};
+use assignment3::TemperatureSimulation;
+
+#[derive(Clone, Default)]
+struct RecordingSink {
+    start: Arc<Mutex<Option<Instant>>>,
+    recordings: Arc<Mutex<Vec<Recording>>>,
+}
+
+impl ReportSink for RecordingSink {
+    fn handle_start(&mut self, start: Instant) {
+        *self.start.lock().unwrap() = Some(start);
+    }
+
+    fn handle_recording(&mut self, recording: &Recording) {
+        self.recordings.lock().unwrap().push(recording.clone());
+    }
+
+    fn handle_report(&mut self, _report: &Report) {}
+}
+
+#[test]
+fn jittered_readings_keep_their_place() {
+    let config = SimulationConfig {
+        seed: Some(42),
+        hours: Some(1),
+        sampling_jitter_seconds: 20.0,
+        ..SimulationConfig::default()
+    };
+    let sink = RecordingSink::default();
+    let mut simulation = TemperatureSimulation::with_config(config.clone());
+    simulation.set_clock(Arc::new(VirtualClock::new()));
+    simulation.add_sink(sink.clone());
+
+    simulation.start();
+    simulation.wait().unwrap();
+
+    let report = &simulation.all_reports()[0];
+    assert!(report.sensor_stats.iter().all(|x| x.readings == 60));
+    assert_eq!(report.missed_readings, 0);
+
+    let start = sink.start.lock().unwrap().unwrap();
+    let jitter = config.scaled_sampling_jitter();
+    let mut late_by = Vec::new();
+    for recording in sink.recordings.lock().unwrap().iter() {
+        let due = start + config.scaled_sampling_period() * recording.sequence as u32;
+        let late = recording.timestamp.saturating_duration_since(due);
+
+        assert!(recording.timestamp >= due);
+        assert!(late <= jitter + Duration::from_micros(1), "{:?}", late);
+        late_by.push(late);
+    }
+
+    // How late a reading is gets picked again for every one of them
+    late_by.sort();
+    late_by.dedup();
+    assert!(late_by.len() > 60);
+}
+
+#[test]
+fn jitter_as_long_as_the_sampling_period_is_rejected() {
+    let err =
+        SimulationConfig::from_toml_str("sampling_period = 30\nsampling_jitter = 30").unwrap_err();
+    assert!(err.to_string().contains("sampling_jitter"), "{}", err);
+}
@@ -0,0 +1,85 @@
+//! Sensors with a sampling period of their own read on their own schedule, and a sensor that
+//! samples faster than the rest doesn't crowd them out of the top temps.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use assignment3::temperature::{
+    Recording, Report, SensorSamplingConfig, SimulationConfig, Temperature, VirtualClock,
+};
+use assignment3::TemperatureSimulation;
+
+fn config() -> SimulationConfig {
+    SimulationConfig {
+        seed: Some(42),
+        hours: Some(1),
+        sensor_sampling: vec![SensorSamplingConfig {
+            sensor: 0,
+            period: 30.0,
+        }],
+        ..SimulationConfig::default()
+    }
+}
+
+#[test]
+fn sensors_read_on_their_own_schedule() {
+    let mut simulation = TemperatureSimulation::with_config(config());
+    simulation.set_clock(Arc::new(VirtualClock::new()));
+
+    simulation.start();
+    simulation.wait().unwrap();
+
+    let report = &simulation.all_reports()[0];
+    assert_eq!(report.sensor_stats[0].readings, 120);
+    assert!(report.sensor_stats[1..].iter().all(|x| x.readings == 60));
+    assert_eq!(report.missed_readings, 0);
+    assert!(report.stalled_sensors.is_empty());
+}
+
+#[test]
+fn faster_sensor_gets_one_top_temp_per_sampling_period() {
+    let mut config = config();
+    config.suspect_sensors.enabled = false;
+    let start = Instant::now();
+
+    // Sensor 0 reads twice a minute and every one of its readings is lower than sensor 1's
+    let mut recordings = Vec::new();
+    for sequence in 0..120 {
+        recordings.push(Recording {
+            sensor_id: 0,
+            temperature: Temperature::from_fahrenheit(-100.0 + sequence as f64 * 0.1),
+            sequence,
+            timestamp: start + config.scaled(Duration::from_secs(sequence * 30)),
+        });
+    }
+    for sequence in 0..60 {
+        recordings.push(Recording {
+            sensor_id: 1,
+            temperature: Temperature::from_fahrenheit(sequence as f64),
+            sequence,
+            timestamp: start + config.scaled(Duration::from_secs(sequence * 60)),
+        });
+    }
+
+    let report = Report::generate(0, recordings, &config).unwrap();
+
+    let lowest: Vec<u64> = report.lowest_temps.iter().map(|x| x.sequence).collect();
+    assert!(report.lowest_temps.iter().all(|x| x.sensor_id == 0));
+    assert_eq!(lowest, [0, 2, 4, 6, 8]);
+
+    let highest: Vec<u64> = report.highest_temps.iter().map(|x| x.sequence).collect();
+    assert!(report.highest_temps.iter().all(|x| x.sensor_id == 1));
+    assert_eq!(highest, [59, 58, 57, 56, 55]);
+    assert_eq!(report.sensor_stats[0].readings, 120);
+}
+
+#[test]
+fn sampling_period_for_a_missing_sensor_is_rejected() {
+    let err = SimulationConfig::from_toml_str("[[sensor_sampling]]\nsensor = 8\nperiod = 30")
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("sensor_sampling[0].sensor"),
+        "{}",
+        err
+    );
+}
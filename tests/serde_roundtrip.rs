@@ -0,0 +1,40 @@
+//! Reports and recordings come back out of JSON exactly as they went in, so anything that reads
+//! the JSON sink's output or a recorded run sees the same values the simulation did.
+
+use std::sync::Arc;
+
+use assignment3::temperature::{Recording, Report, SimulationConfig, Temperature, VirtualClock};
+use assignment3::TemperatureSimulation;
+
+#[test]
+fn generated_report_round_trips() {
+    let config = SimulationConfig {
+        seed: Some(5),
+        hours: Some(2),
+        ..SimulationConfig::default()
+    };
+
+    let mut simulation = TemperatureSimulation::with_config(config);
+    simulation.set_clock(Arc::new(VirtualClock::new()));
+    simulation.start();
+    simulation.wait().unwrap();
+
+    let reports = simulation.all_reports();
+    assert_eq!(reports.len(), 2);
+    for report in reports {
+        let json = serde_json::to_string(&report).unwrap();
+        let parsed: Report = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, report);
+    }
+}
+
+#[test]
+fn recording_round_trips() {
+    let mut recording = Recording::new(3, Temperature::from_fahrenheit(-41.237));
+    recording.sequence = 17;
+
+    let json = serde_json::to_string(&recording).unwrap();
+    let parsed: Recording = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(parsed, recording);
+}
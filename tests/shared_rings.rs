@@ -0,0 +1,119 @@
+//! Readings sent through one mapping of a shared ring file come out of another mapping of the
+//! same file, the way they go from a sensor process to the report process.
+
+#![cfg(all(feature = "multiprocess", unix))]
+
+use std::path::PathBuf;
+
+use assignment3::temperature::{SensorSource, SharedRings, Temperature};
+
+fn ring_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("assignment3-{}-{}.rings", name, std::process::id()))
+}
+
+#[test]
+fn readings_cross_mappings_in_order() {
+    let path = ring_path("order");
+    let report = SharedRings::create(&path, 2, 4).unwrap();
+    let sensor = SharedRings::open(&path).unwrap();
+    assert_eq!(sensor.sensor_count(), 2);
+    assert_eq!(sensor.capacity(), 4);
+
+    let mut sender = sensor.sender(1);
+    for temperature in [-10.5, 20.0, 30.25] {
+        sender
+            .try_send(Temperature::from_fahrenheit(temperature))
+            .unwrap();
+    }
+    drop(sender);
+
+    let mut source = report.source(1);
+    let readings: Vec<(usize, f64)> = std::iter::from_fn(|| source.next_reading())
+        .map(|x| (x.sensor_id, x.temperature.fahrenheit()))
+        .collect();
+
+    // The sender closed the ring when it was dropped, so the source ran out after the last one
+    assert_eq!(readings, vec![(1, -10.5), (1, 20.0), (1, 30.25)]);
+}
+
+#[test]
+fn full_ring_hands_the_reading_back() {
+    let path = ring_path("full");
+    let rings = SharedRings::create(&path, 1, 2).unwrap();
+    let mut sender = rings.sender(0);
+
+    sender.try_send(Temperature::from_fahrenheit(1.0)).unwrap();
+    sender.try_send(Temperature::from_fahrenheit(2.0)).unwrap();
+    assert_eq!(
+        sender.try_send(Temperature::from_fahrenheit(3.0)),
+        Err(Temperature::from_fahrenheit(3.0))
+    );
+
+    // Taking one out makes room again
+    let mut source = rings.source(0);
+    assert_eq!(source.next_reading().unwrap().temperature.fahrenheit(), 1.0);
+    sender.try_send(Temperature::from_fahrenheit(3.0)).unwrap();
+}
+
+#[test]
+fn stopping_is_seen_by_every_mapping_and_the_file_is_removed() {
+    let path = ring_path("stop");
+    let report = SharedRings::create(&path, 1, 2).unwrap();
+    let sensor = SharedRings::open(&path).unwrap();
+
+    assert!(!sensor.is_stopping());
+    report.stop();
+    assert!(sensor.is_stopping());
+
+    // A ring closed by the report process (e.g. its sensor process died) runs out straight away
+    report.close(0);
+    assert!(report.source(0).next_reading().is_none());
+
+    drop(report);
+    assert!(!path.exists());
+}
+
+#[test]
+fn other_files_are_refused() {
+    let path = ring_path("other");
+    std::fs::write(&path, vec![0; 4096]).unwrap();
+
+    assert!(SharedRings::open(&path).is_err());
+    std::fs::remove_file(&path).unwrap();
+}
+
+/// A file with a shared ring header giving the sizes it claims to hold, but no rings.
+fn write_header(path: &PathBuf, sensor_count: u64, capacity: u64) {
+    let mut contents = b"TEMPRING".to_vec();
+    contents.extend(sensor_count.to_le_bytes());
+    contents.extend(capacity.to_le_bytes());
+    contents.resize(4096, 0);
+    std::fs::write(path, contents).unwrap();
+}
+
+#[test]
+fn headers_with_unusable_sizes_are_refused() {
+    let path = ring_path("sizes");
+
+    // No room for a reading, and sizes that overflow when the file's length is worked out
+    for (sensor_count, capacity) in [(2, 0), (u64::MAX, 4), (2, u64::MAX), (1 << 40, 1 << 30)] {
+        write_header(&path, sensor_count, capacity);
+
+        let err = SharedRings::open(&path).err().unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    // Sizes that fit in the file are fine
+    write_header(&path, 2, 4);
+    assert_eq!(SharedRings::open(&path).unwrap().capacity(), 4);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn rings_without_room_for_a_reading_are_not_created() {
+    let path = ring_path("empty");
+
+    let err = SharedRings::create(&path, 2, 0).err().unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(!path.exists());
+}
@@ -0,0 +1,47 @@
+//! Embedding the simulation: `start` runs it in the background, `stop` and `wait` finish it, and
+//! `all_reports` hands back a copy of the reports it's generated so far.
+
+use std::sync::Arc;
+
+use assignment3::temperature::{SimulationConfig, VirtualClock};
+use assignment3::TemperatureSimulation;
+
+fn simulation(hours: u64) -> TemperatureSimulation {
+    let config = SimulationConfig {
+        seed: Some(1),
+        hours: Some(hours),
+        ..SimulationConfig::default()
+    };
+
+    let mut simulation = TemperatureSimulation::with_config(config);
+    simulation.set_clock(Arc::new(VirtualClock::new()));
+    simulation
+}
+
+#[test]
+fn all_reports_returns_every_report_oldest_first() {
+    let mut simulation = simulation(3);
+    assert!(simulation.all_reports().is_empty());
+
+    simulation.start();
+    simulation.wait().unwrap();
+
+    let reports = simulation.all_reports();
+    let periods: Vec<_> = reports.iter().map(|x| (x.index, x.start_minute)).collect();
+    assert_eq!(periods, [(0, 0), (1, 60), (2, 120)]);
+    assert!(reports
+        .iter()
+        .all(|x| !x.partial && !x.highest_temps.is_empty()));
+
+    // A finished simulation can be stopped again, and its reports don't change
+    simulation.stop().unwrap();
+    assert_eq!(simulation.all_reports(), reports);
+}
+
+#[test]
+fn stopping_before_starting_does_nothing() {
+    let mut simulation = simulation(1);
+
+    simulation.stop().unwrap();
+    assert!(simulation.all_reports().is_empty());
+}
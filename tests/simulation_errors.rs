@@ -0,0 +1,103 @@
+//! A thread that panics partway through a run comes back as an error from `wait` instead of
+//! taking the caller down with it.
+
+use std::sync::{Arc, Mutex};
+
+use assignment3::temperature::{
+    Recording, Report, ReportSink, SimulationConfig, SimulationError, VirtualClock,
+};
+use assignment3::TemperatureSimulation;
+
+struct PanickingSink;
+
+impl ReportSink for PanickingSink {
+    fn handle_report(&mut self, _report: &Report) {
+        panic!("the sink broke");
+    }
+}
+
+#[test]
+fn panicking_sink_is_reported() {
+    let config = SimulationConfig {
+        seed: Some(42),
+        hours: Some(2),
+        ..SimulationConfig::default()
+    };
+
+    let mut simulation = TemperatureSimulation::with_config(config);
+    simulation.set_clock(Arc::new(VirtualClock::new()));
+    simulation.add_sink(PanickingSink);
+
+    simulation.start();
+    match simulation.wait() {
+        Err(SimulationError::Panicked { thread, message }) => {
+            assert_eq!(thread, "output");
+            assert_eq!(message, "the sink broke");
+        }
+        other => panic!("expected the output thread's panic, got {:?}", other),
+    }
+
+    // Reports are still kept even though the sink never saw them
+    assert!(!simulation.all_reports().is_empty());
+}
+
+/// Counts what it's handed, to check a broken sink doesn't take anything away from it.
+#[derive(Clone, Default)]
+struct CountingSink {
+    recordings: Arc<Mutex<usize>>,
+    reports: Arc<Mutex<Vec<usize>>>,
+}
+
+impl ReportSink for CountingSink {
+    fn handle_recording(&mut self, _recording: &Recording) {
+        *self.recordings.lock().unwrap() += 1;
+    }
+
+    fn handle_report(&mut self, report: &Report) {
+        self.reports.lock().unwrap().push(report.index);
+    }
+}
+
+#[test]
+fn panicking_sink_leaves_the_others_alone() {
+    let config = SimulationConfig {
+        seed: Some(42),
+        hours: Some(2),
+        ..SimulationConfig::default()
+    };
+    let counting = CountingSink::default();
+
+    let mut simulation = TemperatureSimulation::with_config(config);
+    simulation.set_clock(Arc::new(VirtualClock::new()));
+    simulation.add_sink(PanickingSink);
+    simulation.add_sink(counting.clone());
+
+    simulation.start();
+    assert!(simulation.wait().is_err());
+
+    let reports = simulation.all_reports();
+    let recorded: usize = reports
+        .iter()
+        .flat_map(|x| x.sensor_stats.iter())
+        .map(|x| x.readings)
+        .sum();
+    assert_eq!(*counting.reports.lock().unwrap(), vec![0, 1]);
+    // Readings for the period after the last one still reach the sinks, though no report has them
+    assert!(*counting.recordings.lock().unwrap() >= recorded);
+}
+
+#[test]
+fn finished_run_has_no_error() {
+    let config = SimulationConfig {
+        seed: Some(42),
+        hours: Some(1),
+        ..SimulationConfig::default()
+    };
+
+    let mut simulation = TemperatureSimulation::with_config(config);
+    simulation.set_clock(Arc::new(VirtualClock::new()));
+
+    simulation.start();
+    assert!(simulation.wait().is_ok());
+    assert!(simulation.stop().is_ok());
+}
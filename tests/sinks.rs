@@ -0,0 +1,94 @@
+//! What the file sinks write, read back from the files they wrote it to.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use assignment3::temperature::{
+    timestamp, CsvRecordingSink, JsonSink, RandomSource, Recording, Report, ReportSink, RunSummary,
+    SensorSource, SimulationConfig, Temperature,
+};
+use serde_json::Value;
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("assignment3-sinks-{}-{}", name, std::process::id()))
+}
+
+/// A report on an hour of seeded readings, taken a minute apart.
+fn report() -> Report {
+    let config = SimulationConfig {
+        seed: Some(12),
+        ..SimulationConfig::default()
+    };
+    // Serialized timestamps can't be from before the epoch they're measured from
+    let start = timestamp::process_epoch();
+
+    let readings: Vec<Recording> = (0..config.sensor_count)
+        .flat_map(|sensor_id| {
+            let mut source = RandomSource::for_sensor(&config, sensor_id);
+            (0..60).map(move |minute| {
+                let mut recording = source.next_reading().unwrap();
+                recording.timestamp = start + Duration::from_secs(minute * 60);
+                recording
+            })
+        })
+        .collect();
+
+    Report::generate(0, readings, &config).unwrap()
+}
+
+#[test]
+fn json_sink_writes_reports_that_parse_back() {
+    let dir = temp_path("json");
+    let mut sink = JsonSink::new(&dir).unwrap();
+    let report = report();
+
+    sink.handle_report(&report);
+    sink.handle_summary(&RunSummary::from_reports(
+        std::slice::from_ref(&report),
+        Some(3),
+    ));
+
+    let written = std::fs::read_to_string(dir.join("report-001.json")).unwrap();
+    let parsed: Report = serde_json::from_str(&written).unwrap();
+    assert_eq!(parsed, report);
+
+    let summary: Value =
+        serde_json::from_str(&std::fs::read_to_string(dir.join("summary.json")).unwrap()).unwrap();
+    assert_eq!(summary["reports"], 1);
+    assert_eq!(summary["readings"], 8 * 60);
+    assert_eq!(summary["peak_queue_depth"], 3);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn csv_sink_writes_a_header_then_a_row_per_reading() {
+    let path = temp_path("readings.csv");
+    let epoch = timestamp::process_epoch();
+    let reading = |sensor_id, temperature, millis| {
+        let mut recording = Recording::new(sensor_id, Temperature::from_fahrenheit(temperature));
+        recording.timestamp = epoch + Duration::from_millis(millis);
+        recording
+    };
+
+    let mut sink = CsvRecordingSink::new(&path).unwrap();
+    sink.handle_recording(&reading(0, -40.5, 1));
+    sink.handle_recording(&reading(3, 12.25, 2));
+    sink.handle_report(&report());
+
+    // Opening it again carries on from the end without another header
+    let mut sink = CsvRecordingSink::new(&path).unwrap();
+    sink.handle_recording(&reading(1, -100.0, 60_000));
+    sink.handle_report(&report());
+
+    let written = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(
+        written,
+        "sensor_id,timestamp,temperature\n\
+         0,1000000,-40.5\n\
+         3,2000000,12.25\n\
+         1,60000000000,-100\n"
+    );
+
+    std::fs::remove_file(&path).unwrap();
+}
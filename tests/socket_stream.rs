@@ -0,0 +1,79 @@
+//! Readings are streamed as newline-delimited JSON to every client of the Unix socket sink.
+#![cfg(unix)]
+
+use std::io::{BufRead, BufReader};
+use std::os::unix::net::UnixStream;
+use std::time::{Duration, Instant};
+
+use assignment3::temperature::{Recording, ReportSink, SocketSink, Temperature};
+
+fn recording(sensor_id: usize, sequence: u64) -> Recording {
+    Recording {
+        sensor_id,
+        temperature: Temperature::from_fahrenheit(-40.0 + sequence as f64),
+        sequence,
+        timestamp: Instant::now(),
+    }
+}
+
+#[test]
+fn readings_are_streamed_to_connected_clients() {
+    let path = std::env::temp_dir().join(format!("assignment3-{}.sock", std::process::id()));
+    let mut sink = SocketSink::bind(&path).unwrap();
+
+    let client = UnixStream::connect(&path).unwrap();
+    client
+        .set_read_timeout(Some(Duration::from_millis(50)))
+        .unwrap();
+    let mut client = BufReader::new(client);
+
+    // Clients are picked up in the background, so keep sending until the first line arrives
+    let mut line = String::new();
+    let mut sequence = 0;
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while line.is_empty() {
+        assert!(Instant::now() < deadline, "client never got a reading");
+        sink.handle_recording(&recording(3, sequence));
+        sequence += 1;
+        let _ = client.read_line(&mut line);
+    }
+
+    // Everything after that arrives in order, one reading per line
+    let first: serde_json::Value = serde_json::from_str(&line).unwrap();
+    let first_sequence = first["sequence"].as_u64().unwrap();
+    assert_eq!(first["sensor_id"], 3);
+    assert_eq!(
+        first["temperature"].as_f64().unwrap(),
+        -40.0 + first_sequence as f64
+    );
+    assert!(first["time"].is_string());
+
+    for next in sequence..sequence + 5 {
+        sink.handle_recording(&recording(3, next));
+    }
+    client
+        .get_ref()
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    for expected in first_sequence + 1..sequence + 5 {
+        line.clear();
+        client.read_line(&mut line).unwrap();
+        let reading: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(reading["sequence"].as_u64().unwrap(), expected);
+    }
+
+    // The socket file goes away with the sink
+    drop(sink);
+    assert!(!path.exists());
+}
+
+#[test]
+fn a_file_that_isnt_a_socket_is_left_alone() {
+    let path = std::env::temp_dir().join(format!("assignment3-{}.not-a-sock", std::process::id()));
+    std::fs::write(&path, "keep me").unwrap();
+
+    assert!(SocketSink::bind(&path).is_err());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "keep me");
+
+    std::fs::remove_file(&path).unwrap();
+}
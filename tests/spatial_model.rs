@@ -0,0 +1,86 @@
+//! With the spatial model, sensors close together on the rover read much the same temperatures
+//! and sensors far apart don't.
+
+use assignment3::temperature::{
+    ModelConfig, RandomSource, SensorPositionConfig, SensorSource, SimulationConfig,
+};
+
+fn config() -> SimulationConfig {
+    let position = |sensor, x| SensorPositionConfig { sensor, x, y: 0.0 };
+
+    SimulationConfig {
+        seed: Some(42),
+        sensor_count: 3,
+        model: ModelConfig::Spatial {
+            mean: -40.0,
+            amplitude: 20.0,
+            length_scale: 1.0,
+            time_scale_minutes: 60.0,
+            noise: 1.0,
+        },
+        sensor_positions: vec![position(0, 0.0), position(1, 0.1), position(2, 10.0)],
+        ..SimulationConfig::default()
+    }
+}
+
+/// A day of readings from one sensor.
+fn readings(config: &SimulationConfig, sensor_id: usize) -> Vec<f64> {
+    let mut source = RandomSource::for_sensor(config, sensor_id);
+
+    (0..1440)
+        .map(|_| source.next_reading().unwrap().temperature.fahrenheit())
+        .collect()
+}
+
+fn correlation(a: &[f64], b: &[f64]) -> f64 {
+    let mean = |x: &[f64]| x.iter().sum::<f64>() / x.len() as f64;
+    let (mean_a, mean_b) = (mean(a), mean(b));
+
+    let mut covariance = 0.0;
+    let mut variance_a = 0.0;
+    let mut variance_b = 0.0;
+    for (x, y) in a.iter().zip(b) {
+        covariance += (x - mean_a) * (y - mean_b);
+        variance_a += (x - mean_a).powi(2);
+        variance_b += (y - mean_b).powi(2);
+    }
+
+    covariance / (variance_a * variance_b).sqrt()
+}
+
+#[test]
+fn neighboring_sensors_correlate() {
+    let config = config();
+    let sensors: Vec<Vec<f64>> = (0..3).map(|x| readings(&config, x)).collect();
+
+    let near = correlation(&sensors[0], &sensors[1]);
+    let far = correlation(&sensors[0], &sensors[2]);
+    assert!(near > 0.95, "{}", near);
+    assert!(far.abs() < 0.5, "{}", far);
+}
+
+#[test]
+fn every_sensor_reads_the_same_field() {
+    let mut config = config();
+    config.model = ModelConfig::Spatial {
+        mean: -40.0,
+        amplitude: 20.0,
+        length_scale: 1.0,
+        time_scale_minutes: 60.0,
+        noise: 0.0,
+    };
+    config.sensor_positions[1].x = 0.0;
+
+    assert_eq!(readings(&config, 0), readings(&config, 1));
+}
+
+#[test]
+fn position_for_a_missing_sensor_is_rejected() {
+    let err = SimulationConfig::from_toml_str("[[sensor_position]]\nsensor = 8\nx = 1\ny = 2")
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("sensor_position[0].sensor"),
+        "{}",
+        err
+    );
+}
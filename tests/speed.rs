@@ -0,0 +1,60 @@
+//! Changing the speed while a simulation runs changes how long it takes, but each report still
+//! covers a whole simulated period.
+
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use assignment3::temperature::SimulationConfig;
+use assignment3::TemperatureSimulation;
+
+fn config() -> SimulationConfig {
+    SimulationConfig {
+        seed: Some(42),
+        hours: Some(2),
+        speedup: 1_440,
+        ..SimulationConfig::default()
+    }
+}
+
+#[test]
+fn sped_up_simulation_finishes_sooner() {
+    let config = config();
+    let mut simulation = TemperatureSimulation::with_config(config.clone());
+    let speed = simulation.speed_handle();
+    let started = Instant::now();
+
+    // Two hours take 10 seconds at the configured speed, and a fraction of a second at 20 times it
+    simulation.start();
+    sleep(Duration::from_millis(100));
+    assert!(speed.set_speedup(28_800.0));
+    simulation.wait().unwrap();
+
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert_eq!(speed.speedup(), 28_800.0);
+
+    let reports = simulation.all_reports();
+    assert_eq!(reports.len(), 2);
+    for report in reports.iter() {
+        assert_eq!(
+            (report.period_end - report.period_start).to_std().unwrap(),
+            config.scaled_report_interval()
+        );
+        assert!(report.sensor_stats.iter().all(|x| x.readings > 0));
+    }
+}
+
+#[test]
+fn speedup_has_to_be_positive() {
+    let simulation = TemperatureSimulation::with_config(config());
+    let speed = simulation.speed_handle();
+
+    assert_eq!(speed.speedup(), 1_440.0);
+    assert!(!speed.set_speedup(0.0));
+    assert!(!speed.set_speedup(-10.0));
+    assert!(!speed.set_speedup(f64::NAN));
+    assert_eq!(speed.speedup(), 1_440.0);
+
+    // Set before the start, it's where the simulation starts from
+    assert!(speed.set_speedup(3_600.0));
+    assert_eq!(speed.speedup(), 3_600.0);
+}
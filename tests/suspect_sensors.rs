@@ -0,0 +1,85 @@
+//! A sensor that's stuck or far noisier than the rest is flagged as suspect in the report, and
+//! the healthy sensors around it aren't.
+
+use std::sync::Arc;
+
+use assignment3::temperature::{
+    FaultKind, ModelConfig, Report, SensorFaultConfig, SimulationConfig, SuspectReason,
+    VirtualClock,
+};
+use assignment3::TemperatureSimulation;
+
+/// One hour of sensors that all read about the same, with `fault` from the start on `sensor`.
+fn run(fault: Option<(usize, FaultKind)>) -> Report {
+    let config = SimulationConfig {
+        seed: Some(25),
+        hours: Some(1),
+        model: ModelConfig::Gaussian {
+            baseline: -40.0,
+            std_dev: 2.0,
+        },
+        sensor_faults: fault
+            .into_iter()
+            .map(|(sensor, fault)| SensorFaultConfig {
+                sensor,
+                after_minutes: [0.0, 0.0],
+                fault,
+            })
+            .collect(),
+        ..SimulationConfig::default()
+    };
+
+    let mut simulation = TemperatureSimulation::with_config(config);
+    simulation.set_clock(Arc::new(VirtualClock::new()));
+    simulation.start();
+    simulation.wait().unwrap();
+
+    simulation.all_reports().remove(0)
+}
+
+#[test]
+fn stuck_sensor_is_flagged() {
+    let report = run(Some((2, FaultKind::Stuck)));
+
+    assert_eq!(
+        report.suspect_sensors.len(),
+        1,
+        "{:?}",
+        report.suspect_sensors
+    );
+    let suspect = &report.suspect_sensors[0];
+    assert_eq!(suspect.sensor_id, 2);
+    match suspect.reason {
+        SuspectReason::Stuck { readings, .. } => assert!(readings >= 10),
+        ref other => panic!("expected sensor 2 to be stuck, got {:?}", other),
+    }
+}
+
+#[test]
+fn noisy_sensor_is_flagged() {
+    let report = run(Some((5, FaultKind::Noise { std_dev: 20.0 })));
+
+    assert_eq!(
+        report.suspect_sensors.len(),
+        1,
+        "{:?}",
+        report.suspect_sensors
+    );
+    let suspect = &report.suspect_sensors[0];
+    assert_eq!(suspect.sensor_id, 5);
+    assert!(matches!(
+        suspect.reason,
+        SuspectReason::DivergentSpread { .. }
+    ));
+}
+
+#[test]
+fn healthy_sensors_are_not_flagged() {
+    let report = run(None);
+
+    assert!(
+        report.suspect_sensors.is_empty(),
+        "{:?}",
+        report.suspect_sensors
+    );
+}
@@ -0,0 +1,93 @@
+//! With synchronized sampling every sensor takes each reading at the same instant, and a sensor
+//! that goes offline doesn't hold the others up.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use assignment3::temperature::{
+    RandomSource, Recording, Report, ReportSink, SensorSource, SimulationConfig, VirtualClock,
+};
+use assignment3::TemperatureSimulation;
+
+fn config() -> SimulationConfig {
+    SimulationConfig {
+        seed: Some(42),
+        hours: Some(1),
+        synchronized_sampling: true,
+        ..SimulationConfig::default()
+    }
+}
+
+#[derive(Clone, Default)]
+struct RecordingSink(Arc<Mutex<Vec<Recording>>>);
+
+impl ReportSink for RecordingSink {
+    fn handle_recording(&mut self, recording: &Recording) {
+        self.0.lock().unwrap().push(recording.clone());
+    }
+
+    fn handle_report(&mut self, _report: &Report) {}
+}
+
+/// Runs out of readings after `limit` of them, like a sensor going offline.
+struct OfflineSource {
+    inner: RandomSource,
+    limit: usize,
+}
+
+impl SensorSource for OfflineSource {
+    fn next_reading(&mut self) -> Option<Recording> {
+        self.limit = self.limit.checked_sub(1)?;
+        self.inner.next_reading()
+    }
+}
+
+#[test]
+fn sensors_sample_at_the_same_instant() {
+    let sink = RecordingSink::default();
+    let mut simulation = TemperatureSimulation::with_config(config());
+    simulation.set_clock(Arc::new(VirtualClock::new()));
+    simulation.add_sink(sink.clone());
+
+    simulation.start();
+    simulation.wait().unwrap();
+
+    let reports = simulation.all_reports();
+    assert!(reports[0].sensor_stats.iter().all(|x| x.readings == 60));
+
+    let mut sampled_at: HashMap<u64, Vec<Instant>> = HashMap::new();
+    for recording in sink.0.lock().unwrap().iter() {
+        sampled_at
+            .entry(recording.sequence)
+            .or_default()
+            .push(recording.timestamp);
+    }
+    for timestamps in sampled_at.values() {
+        assert!(timestamps.iter().all(|x| *x == timestamps[0]));
+    }
+}
+
+#[test]
+fn offline_sensor_leaves_the_barrier() {
+    let config = config();
+    let sources: Vec<Box<dyn SensorSource>> = (0..config.sensor_count)
+        .map(|sensor_id| {
+            let inner = RandomSource::for_sensor(&config, sensor_id);
+            match sensor_id {
+                0 => Box::new(OfflineSource { inner, limit: 10 }) as Box<dyn SensorSource>,
+                _ => Box::new(inner),
+            }
+        })
+        .collect();
+
+    let mut simulation = TemperatureSimulation::with_sources(config, sources);
+    simulation.set_clock(Arc::new(VirtualClock::new()));
+
+    simulation.start();
+    simulation.wait().unwrap();
+
+    let report = &simulation.all_reports()[0];
+    assert_eq!(report.sensor_stats[0].readings, 10);
+    assert!(report.sensor_stats[1..].iter().all(|x| x.readings == 60));
+}
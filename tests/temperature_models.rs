@@ -0,0 +1,34 @@
+//! Models with noise that can't be sampled are refused when they're made, rather than panicking
+//! there or when a sensor first takes a reading.
+
+use assignment3::temperature::{
+    DiurnalModel, GaussianModel, SpatialField, SpatialModel, TemperatureModel,
+};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+#[test]
+fn unusable_noise_is_refused() {
+    let mut rng = StdRng::seed_from_u64(1);
+
+    for noise in [-1.0, f64::INFINITY, f64::NAN] {
+        assert!(GaussianModel::new(-40.0, noise).is_err());
+        assert!(DiurnalModel::new(-40.0, 20.0, 1477.0, noise).is_err());
+
+        let field = SpatialField::new(&mut rng, -40.0, 10.0, 1.0, 60.0);
+        assert!(SpatialModel::new(field, (0.0, 0.0), noise).is_err());
+    }
+}
+
+#[test]
+fn models_without_noise_read_exactly() {
+    let mut rng = StdRng::seed_from_u64(1);
+
+    let mut gaussian = GaussianModel::new(-40.0, 0.0).unwrap();
+    assert_eq!(gaussian.sample(&mut rng, 10.0), -40.0);
+
+    // Coldest at the start of the day and warmest halfway through it
+    let mut diurnal = DiurnalModel::new(-40.0, 20.0, 1440.0, 0.0).unwrap();
+    assert_eq!(diurnal.sample(&mut rng, 0.0), -60.0);
+    assert_eq!(diurnal.sample(&mut rng, 720.0), -20.0);
+}
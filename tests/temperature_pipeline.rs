@@ -0,0 +1,318 @@
+//! Runs a whole simulated hour on a virtual clock with seeded sensors and checks the report
+//! against the same readings worked out by hand, and checks the periods the reports cover, even
+//! the ones without any readings.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use assignment3::temperature::{
+    find_largest_temp_difference, Clock, RandomSource, Recording, Report, SensorSource,
+    SimulationConfig, VirtualClock,
+};
+use assignment3::TemperatureSimulation;
+
+const SEED: u64 = 42;
+
+fn config() -> SimulationConfig {
+    let mut config = SimulationConfig {
+        seed: Some(SEED),
+        hours: Some(1),
+        ..SimulationConfig::default()
+    };
+
+    // A suspect sensor is left out of the extremes, which the expected values don't account for
+    config.suspect_sensors.enabled = false;
+    config
+}
+
+/// Every reading the seeded sensors take during the first hour, timestamped the way the virtual
+/// clock timestamps them: exactly one sampling period apart from `start`.
+fn expected_readings(config: &SimulationConfig, start: Instant) -> Vec<Recording> {
+    let per_sensor = config.report_interval_minutes * 60 / config.sampling_period_seconds as u64;
+
+    let mut readings: Vec<Recording> = (0..config.sensor_count)
+        .flat_map(|sensor_id| {
+            let mut source = RandomSource::for_sensor(config, sensor_id);
+
+            (0..per_sensor).map(move |reading| {
+                let mut recording = source.next_reading().unwrap();
+                recording.timestamp = start + config.scaled_sampling_period() * reading as u32;
+                recording
+            })
+        })
+        .collect();
+
+    readings.sort_by_key(|x| x.timestamp);
+    readings
+}
+
+fn run_hour(config: &SimulationConfig) -> Vec<Report> {
+    let mut simulation = TemperatureSimulation::with_config(config.clone());
+    simulation.set_clock(Arc::new(VirtualClock::new()));
+
+    simulation.start();
+    simulation.wait().unwrap();
+    simulation.all_reports()
+}
+
+/// Temperature and sensor of each recording, which are what the report can be checked on since
+/// the virtual clock's start isn't known.
+fn values(recordings: &[Recording]) -> Vec<(usize, f64)> {
+    recordings
+        .iter()
+        .map(|x| (x.sensor_id, x.temperature.fahrenheit()))
+        .collect()
+}
+
+#[test]
+fn a_simulated_hour_produces_the_expected_report() {
+    let config = config();
+
+    let started = Instant::now();
+    let reports = run_hour(&config);
+    let took = started.elapsed();
+
+    assert_eq!(reports.len(), 1);
+    let report = &reports[0];
+    assert_eq!(report.index, 0);
+    assert!(!report.partial);
+    assert!(
+        took < Duration::from_secs(5),
+        "a virtual hour took {:?}",
+        took
+    );
+
+    let expected = expected_readings(&config, Instant::now());
+    assert_eq!(expected.len(), 480);
+
+    // Counts
+    assert_eq!(report.sensor_stats.len(), config.sensor_count);
+    for (sensor_id, stats) in report.sensor_stats.iter().enumerate() {
+        let readings: Vec<f64> = expected
+            .iter()
+            .filter(|x| x.sensor_id == sensor_id)
+            .map(|x| x.temperature.fahrenheit())
+            .collect();
+
+        assert_eq!(stats.sensor_id, sensor_id);
+        assert_eq!(stats.readings, 60);
+        assert_eq!(stats.dropped, 0);
+        assert_eq!(stats.missed, 0);
+        assert_eq!(
+            stats.min.unwrap().fahrenheit(),
+            readings.iter().copied().fold(f64::INFINITY, f64::min)
+        );
+        assert_eq!(
+            stats.max.unwrap().fahrenheit(),
+            readings.iter().copied().fold(f64::NEG_INFINITY, f64::max)
+        );
+    }
+
+    // Top 5
+    let mut by_temperature = expected.clone();
+    by_temperature.sort_by(|a, b| {
+        a.temperature
+            .fahrenheit()
+            .total_cmp(&b.temperature.fahrenheit())
+    });
+    let lowest = &by_temperature[..5];
+    let highest: Vec<Recording> = by_temperature.iter().rev().take(5).cloned().collect();
+
+    assert_eq!(values(&report.lowest_temps), values(lowest));
+    assert_eq!(values(&report.highest_temps), values(&highest));
+
+    // Mean, up to the order the readings were summed in
+    let mean = expected
+        .iter()
+        .map(|x| x.temperature.fahrenheit())
+        .sum::<f64>()
+        / expected.len() as f64;
+    assert!((report.summary.as_ref().unwrap().mean.fahrenheit() - mean).abs() < 1e-9);
+
+    // Largest difference, against the brute force search
+    let difference = find_largest_temp_difference(&expected, &config).unwrap();
+    let largest = report.largest_temp_difference.as_ref().unwrap();
+    assert_eq!(largest.difference, difference.difference);
+    assert_eq!(
+        values(&[largest.start.clone(), largest.end.clone()]),
+        values(&[difference.start, difference.end])
+    );
+
+    let window = config.difference_window_minutes as f64;
+    let [start_minute, end_minute] = report.largest_difference_minutes.unwrap();
+    assert!(start_minute <= end_minute && end_minute - start_minute <= window);
+}
+
+#[test]
+fn the_same_seed_produces_the_same_report() {
+    let config = config();
+
+    let first = run_hour(&config);
+    let second = run_hour(&config);
+
+    let strip = |report: &Report| {
+        (
+            values(&report.lowest_temps),
+            values(&report.highest_temps),
+            report
+                .largest_temp_difference
+                .as_ref()
+                .map(|x| x.difference),
+            report.largest_difference_minutes,
+            report.summary.clone(),
+        )
+    };
+
+    assert_eq!(strip(&first[0]), strip(&second[0]));
+}
+
+#[test]
+fn reports_cover_back_to_back_periods_on_the_real_clock() {
+    let config = SimulationConfig {
+        hours: Some(3),
+        speedup: 36_000,
+        ..config()
+    };
+
+    let mut simulation = TemperatureSimulation::with_config(config.clone());
+    simulation.start();
+    simulation.wait().unwrap();
+    let reports = simulation.all_reports();
+    assert_eq!(reports.len(), 3);
+
+    // However late the report thread wakes up, the Nth report covers exactly the Nth hour
+    let interval = config.scaled_report_interval();
+    for (index, report) in reports.iter().enumerate() {
+        assert_eq!(report.start_minute, index as u64 * 60);
+        assert_eq!(
+            (report.period_end - report.period_start).to_std().unwrap(),
+            interval
+        );
+    }
+    for pair in reports.windows(2) {
+        assert_eq!(pair[0].period_end, pair[1].period_start);
+    }
+}
+
+/// Stalls for `stall` on its `stall_at`th reading, then carries on.
+struct StallingSource {
+    inner: RandomSource,
+    clock: Arc<dyn Clock>,
+    readings: usize,
+    stall_at: usize,
+    stall: Duration,
+}
+
+impl SensorSource for StallingSource {
+    fn next_reading(&mut self) -> Option<Recording> {
+        self.readings += 1;
+        if self.readings == self.stall_at {
+            self.clock.sleep(self.stall);
+        }
+
+        self.inner.next_reading()
+    }
+}
+
+/// Three hours where every sensor stalls half an hour in for `minutes` simulated minutes.
+fn every_sensor_stalls(
+    config: SimulationConfig,
+    clock: Arc<dyn Clock>,
+    minutes: u64,
+) -> (SimulationConfig, Vec<Box<dyn SensorSource>>) {
+    let config = SimulationConfig {
+        hours: Some(3),
+        ..config
+    };
+    let stall = config.scaled(Duration::from_secs(minutes * 60));
+    let sources = (0..config.sensor_count)
+        .map(|sensor_id| {
+            Box::new(StallingSource {
+                inner: RandomSource::for_sensor(&config, sensor_id),
+                clock: clock.clone(),
+                readings: 0,
+                stall_at: 30,
+                stall,
+            }) as Box<dyn SensorSource>
+        })
+        .collect();
+
+    (config, sources)
+}
+
+/// The last two hours have no readings at all, since the sensors stall until after the end.
+fn assert_every_hour_is_reported(reports: &[Report]) {
+    assert_eq!(reports.len(), 3);
+    assert!(reports[0].summary.is_some());
+    for report in reports[1..].iter() {
+        assert!(!report.partial);
+        assert!(report.summary.is_none() && report.largest_temp_difference.is_none());
+        assert!(report.sensor_stats.iter().all(|x| x.readings == 0));
+
+        let text = report.to_string();
+        assert!(text.contains("No readings were received"), "{}", text);
+    }
+}
+
+#[test]
+fn hours_without_readings_are_still_reported() {
+    let clock = Arc::new(VirtualClock::new());
+    let (config, sources) = every_sensor_stalls(config(), clock.clone(), 200);
+
+    let mut simulation = TemperatureSimulation::with_sources(config, sources);
+    simulation.set_clock(clock);
+    simulation.start();
+    simulation.wait().unwrap();
+
+    assert_every_hour_is_reported(&simulation.all_reports());
+}
+
+#[test]
+fn run_carries_on_after_an_hour_without_readings() {
+    // Stalled for the whole of the second hour, and back taking readings a little way into the
+    // third
+    let clock = Arc::new(VirtualClock::new());
+    let (config, sources) = every_sensor_stalls(config(), clock.clone(), 100);
+
+    let mut simulation = TemperatureSimulation::with_sources(config, sources);
+    simulation.set_clock(clock);
+    simulation.start();
+    simulation.wait().unwrap();
+
+    let reports = simulation.all_reports();
+    assert_eq!(reports.len(), 3);
+    assert!(reports[0].summary.is_some() && reports[2].summary.is_some());
+    assert!(reports[2].sensor_stats.iter().all(|x| x.readings > 0));
+
+    let empty = &reports[1];
+    assert!(empty.summary.is_none());
+    assert!(empty.largest_temp_difference.is_none() && empty.largest_difference_minutes.is_none());
+    assert!(empty.to_string().contains("No readings were received"));
+
+    // What it couldn't work out is null in the JSON, and reads back the same
+    let json: serde_json::Value = serde_json::to_value(empty).unwrap();
+    for key in [
+        "summary",
+        "largest_temp_difference",
+        "largest_difference_minutes",
+    ] {
+        assert_eq!(json.get(key), Some(&serde_json::Value::Null), "{}", key);
+    }
+    assert_eq!(&serde_json::from_value::<Report>(json).unwrap(), empty);
+}
+
+#[cfg(feature = "async")]
+#[tokio::test(flavor = "multi_thread")]
+async fn hours_without_readings_are_still_reported_by_the_async_simulation() {
+    use assignment3::temperature::{AsyncTemperatureSimulation, SystemClock};
+
+    let config = SimulationConfig {
+        speedup: 36_000,
+        ..config()
+    };
+    let (config, sources) = every_sensor_stalls(config, Arc::new(SystemClock), 200);
+    let mut simulation = AsyncTemperatureSimulation::with_sources(config, sources);
+    simulation.run().await.unwrap();
+
+    assert_every_hour_is_reported(&simulation.all_reports());
+}
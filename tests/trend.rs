@@ -0,0 +1,92 @@
+//! Each report's trend says which way the mean, extremes and largest difference moved since the
+//! previous report and since the average of every report before it.
+
+use assignment3::temperature::{
+    timestamp, Recording, Report, ReportHistory, SimulationConfig, Temperature, TrendChange,
+};
+
+/// A report whose readings go `base`, `base + scale`, ... `base + 4 * scale` over and over, so
+/// its lowest is `base`, its highest `base + 4 * scale`, its mean `base + 2 * scale` and its
+/// largest difference `4 * scale`.
+fn report(index: usize, base: f64, scale: f64) -> Report {
+    let config = SimulationConfig::default();
+    let start = timestamp::process_epoch() + config.scaled_minutes(index as u64 * 60);
+
+    let readings = (0..60)
+        .map(|minute| {
+            let temperature = base + (minute % 5) as f64 * scale;
+            let mut recording = Recording::new(0, Temperature::from_fahrenheit(temperature));
+            recording.timestamp = start + config.scaled_minutes(minute);
+            recording
+        })
+        .collect();
+
+    Report::generate(index, readings, &config).unwrap()
+}
+
+/// The trend of each report after the first, worked out the way the simulation does as the
+/// reports come in.
+fn trends(hours: &[(f64, f64)]) -> Vec<(TrendChange, TrendChange)> {
+    let mut history = ReportHistory::new();
+    let mut trends = Vec::new();
+
+    for (index, &(base, scale)) in hours.iter().enumerate() {
+        let report = report(index, base, scale);
+        if let Some(trend) = history.trend(&report) {
+            trends.push((trend.previous, trend.average));
+        }
+        history.push(report);
+    }
+
+    trends
+}
+
+fn values(change: &TrendChange) -> [f64; 4] {
+    [
+        change.mean,
+        change.lowest,
+        change.highest,
+        change.largest_difference,
+    ]
+}
+
+fn directions(change: &TrendChange) -> [f64; 4] {
+    values(change).map(f64::signum)
+}
+
+/// The mean is worked out as the readings come in, so it can be a rounding error off.
+fn assert_change(change: &TrendChange, expected: [f64; 4]) {
+    for (value, expected) in values(change).into_iter().zip(expected) {
+        assert!((value - expected).abs() < 1e-9, "{:?}", change);
+    }
+}
+
+#[test]
+fn rising_hours_trend_up() {
+    let trends = trends(&[(-60.0, 1.0), (-40.0, 2.0), (-20.0, 3.0)]);
+    assert_eq!(trends.len(), 2);
+
+    for (previous, average) in trends.iter() {
+        assert_eq!(directions(previous), [1.0; 4]);
+        assert_eq!(directions(average), [1.0; 4]);
+    }
+
+    // The mean goes from -36 to -14, the lowest from -40 to -20, the highest from -32 to -8 and
+    // the largest difference from 8 to 12
+    assert_change(&trends[1].0, [22.0, 20.0, 24.0, 4.0]);
+}
+
+#[test]
+fn falling_hours_trend_down() {
+    let trends = trends(&[(-20.0, 3.0), (-40.0, 2.0), (-60.0, 1.0)]);
+    assert_eq!(trends.len(), 2);
+
+    for (previous, average) in trends.iter() {
+        assert_eq!(directions(previous), [-1.0; 4]);
+        assert_eq!(directions(average), [-1.0; 4]);
+    }
+
+    // Against the average of the first two hours: a mean of -25, lowest of -30, highest of -20
+    // and largest difference of 10
+    assert_change(&trends[1].1, [-33.0, -30.0, -36.0, -6.0]);
+}
@@ -0,0 +1,69 @@
+//! Readings taken during the warm-up reach the sinks but are left out of the first report, and
+//! don't count as missed.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use assignment3::temperature::{Recording, Report, ReportSink, SimulationConfig, VirtualClock};
+use assignment3::TemperatureSimulation;
+
+struct CountingSink(Arc<AtomicUsize>);
+
+impl ReportSink for CountingSink {
+    fn handle_report(&mut self, _report: &Report) {}
+
+    fn handle_recording(&mut self, _recording: &Recording) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[test]
+fn warm_up_readings_are_left_out_of_the_first_report() {
+    let config = SimulationConfig {
+        seed: Some(5),
+        hours: Some(2),
+        warm_up_minutes: 5.0,
+        ..SimulationConfig::default()
+    };
+    let readings_per_hour = config.readings_per_report();
+
+    let mut simulation = TemperatureSimulation::with_config(config.clone());
+    simulation.set_clock(Arc::new(VirtualClock::new()));
+    let received = Arc::new(AtomicUsize::new(0));
+    simulation.add_sink(CountingSink(received.clone()));
+
+    simulation.start();
+    simulation.wait().unwrap();
+
+    // Every sensor takes a reading a minute, so 5 minutes is 5 readings each
+    let warm_up = 5 * config.sensor_count;
+    let reports = simulation.all_reports();
+    assert_eq!(reports.len(), 2);
+
+    let first = &reports[0];
+    assert_eq!(first.warm_up_readings, warm_up);
+    assert_eq!(first.missed_readings, 0);
+    for stats in first.sensor_stats.iter() {
+        assert_eq!(stats.readings, 55);
+        assert!(stats.gaps.is_empty());
+    }
+
+    let second = &reports[1];
+    assert_eq!(second.warm_up_readings, 0);
+    let total: usize = second.sensor_stats.iter().map(|x| x.readings).sum();
+    assert_eq!(total, readings_per_hour);
+
+    // The sinks get the warm-up readings along with the rest (and possibly a few taken after the
+    // last period, before the sensors stopped)
+    assert!(received.load(Ordering::Relaxed) >= readings_per_hour * 2);
+}
+
+#[test]
+fn warm_up_has_to_end_before_the_first_report() {
+    let config = SimulationConfig {
+        warm_up_minutes: 60.0,
+        ..SimulationConfig::default()
+    };
+
+    assert!(config.validate().is_err());
+}
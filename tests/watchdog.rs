@@ -0,0 +1,204 @@
+//! The watchdog flags sensors that stop sending readings, whether they went quiet or panicked,
+//! and can restart a sensor that panicked. A period where every sensor stalled is still reported.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use assignment3::temperature::{
+    Clock, FaultKind, RandomSource, Recording, Report, SensorFaultConfig, SensorSource,
+    SimulationConfig, SimulationError, StallReason, VirtualClock,
+};
+use assignment3::TemperatureSimulation;
+
+fn config() -> SimulationConfig {
+    SimulationConfig {
+        seed: Some(42),
+        hours: Some(1),
+        ..SimulationConfig::default()
+    }
+}
+
+fn stall_reason(report: &Report, sensor_id: usize) -> Option<&StallReason> {
+    report
+        .stalled_sensors
+        .iter()
+        .find(|x| x.sensor_id == sensor_id)
+        .map(|x| &x.reason)
+}
+
+/// Panics once, on its `panic_at`th reading.
+struct PanickingSource {
+    inner: RandomSource,
+    readings: usize,
+    panic_at: usize,
+}
+
+impl SensorSource for PanickingSource {
+    fn next_reading(&mut self) -> Option<Recording> {
+        self.readings += 1;
+        if self.readings == self.panic_at {
+            panic!("the sensor broke");
+        }
+
+        self.inner.next_reading()
+    }
+}
+
+/// Every sensor reads normally, apart from sensor 0 which panics on its 10th reading.
+fn run_with_panicking_sensor(config: SimulationConfig) -> TemperatureSimulation {
+    let sources = (0..config.sensor_count)
+        .map(|sensor_id| {
+            let inner = RandomSource::for_sensor(&config, sensor_id);
+            let panic_at = if sensor_id == 0 { 10 } else { usize::MAX };
+
+            Box::new(PanickingSource {
+                inner,
+                readings: 0,
+                panic_at,
+            }) as Box<dyn SensorSource>
+        })
+        .collect();
+
+    let mut simulation = TemperatureSimulation::with_sources(config, sources);
+    simulation.set_clock(Arc::new(VirtualClock::new()));
+    simulation.start();
+    simulation
+}
+
+/// Stalls on the virtual clock for `stall` on its `stall_at`th reading, then carries on.
+struct StallingSource {
+    inner: RandomSource,
+    clock: Arc<VirtualClock>,
+    readings: usize,
+    stall_at: usize,
+    stall: Duration,
+}
+
+impl SensorSource for StallingSource {
+    fn next_reading(&mut self) -> Option<Recording> {
+        self.readings += 1;
+        if self.readings == self.stall_at {
+            self.clock.sleep(self.stall);
+        }
+
+        self.inner.next_reading()
+    }
+}
+
+#[test]
+fn silent_sensor_is_flagged() {
+    let mut config = config();
+    config.sensor_faults.push(SensorFaultConfig {
+        sensor: 3,
+        fault: FaultKind::Dropout,
+        after_minutes: [20.0, 20.0],
+    });
+
+    let mut simulation = TemperatureSimulation::with_config(config);
+    simulation.set_clock(Arc::new(VirtualClock::new()));
+    simulation.start();
+    simulation.wait().unwrap();
+
+    let reports = simulation.all_reports();
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].stalled_sensors.len(), 1);
+    match stall_reason(&reports[0], 3) {
+        Some(StallReason::Silent { minutes }) => assert!(*minutes >= 2.0, "{}", minutes),
+        other => panic!("expected sensor 3 to be silent, got {:?}", other),
+    }
+
+    // Everything it was due to send after it went quiet is missing
+    let stats = &reports[0].sensor_stats[3];
+    assert_eq!(stats.readings, 20);
+    assert_eq!(stats.missed, 40);
+    assert_eq!(reports[0].missed_readings, 40);
+}
+
+#[test]
+fn healthy_sensors_are_not_flagged() {
+    let mut simulation = TemperatureSimulation::with_config(config());
+    simulation.set_clock(Arc::new(VirtualClock::new()));
+    simulation.start();
+    simulation.wait().unwrap();
+
+    let reports = simulation.all_reports();
+    assert!(reports[0].stalled_sensors.is_empty());
+    assert_eq!(reports[0].missed_readings, 0);
+}
+
+#[test]
+fn panicked_sensor_is_flagged() {
+    let mut simulation = run_with_panicking_sensor(config());
+
+    match simulation.wait() {
+        Err(SimulationError::Panicked { thread, .. }) => assert_eq!(thread, "sensor"),
+        other => panic!("expected the sensor's panic, got {:?}", other),
+    }
+
+    let reports = simulation.all_reports();
+    assert_eq!(stall_reason(&reports[0], 0), Some(&StallReason::Panicked));
+    assert_eq!(reports[0].sensor_stats[0].readings, 9);
+    assert_eq!(reports[0].sensor_stats[0].missed, 51);
+}
+
+#[test]
+fn panicked_sensor_is_restarted() {
+    let mut config = config();
+    config.watchdog.restart = true;
+    let mut simulation = run_with_panicking_sensor(config);
+    simulation.wait().unwrap();
+
+    // The sensor starts again a sampling period later, still in time to take the reading it
+    // panicked on
+    let reports = simulation.all_reports();
+    assert_eq!(stall_reason(&reports[0], 0), Some(&StallReason::Restarted));
+    assert_eq!(reports[0].sensor_stats[0].readings, 60);
+    assert_eq!(reports[0].sensor_stats[0].missed, 0);
+}
+
+#[test]
+fn period_where_every_sensor_stalls_is_still_reported() {
+    let config = SimulationConfig {
+        hours: Some(2),
+        ..config()
+    };
+
+    // Every sensor stalls just before the end of the first hour until after the end of the second
+    let clock = Arc::new(VirtualClock::new());
+    let stall = config.scaled(Duration::from_secs(80 * 60));
+    let sources = (0..config.sensor_count)
+        .map(|sensor_id| {
+            Box::new(StallingSource {
+                inner: RandomSource::for_sensor(&config, sensor_id),
+                clock: clock.clone(),
+                readings: 0,
+                stall_at: 50,
+                stall,
+            }) as Box<dyn SensorSource>
+        })
+        .collect();
+
+    let mut simulation = TemperatureSimulation::with_sources(config.clone(), sources);
+    simulation.set_clock(clock);
+    simulation.start();
+    simulation.wait().unwrap();
+
+    let reports = simulation.all_reports();
+    assert_eq!(reports.len(), 2);
+
+    let report = &reports[1];
+    assert_eq!(report.stalled_sensors.len(), config.sensor_count);
+    for stats in report.sensor_stats.iter() {
+        assert!(
+            matches!(
+                stall_reason(report, stats.sensor_id),
+                Some(StallReason::Silent { .. })
+            ),
+            "sensor {} wasn't flagged",
+            stats.sensor_id
+        );
+        assert_eq!(stats.readings, 0);
+        assert_eq!(stats.missed, 60);
+    }
+    assert!(report.summary.is_none());
+}